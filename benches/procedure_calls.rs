@@ -0,0 +1,51 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use crawl::interpreter::Interpreter;
+use crawl::parser::{Parser, Statement};
+use crawl::scanner::Scanner;
+
+const CALLS: usize = 5_000;
+
+fn parse(source: &str) -> Vec<Statement> {
+    Parser::from_tokens(Scanner::new(source))
+        .map(|s| s.unwrap())
+        .collect()
+}
+
+fn procedure_calls(c: &mut Criterion) {
+    // No dice, so the benchmark measures the cost of calling into a procedure rather than
+    // rolling.
+    let mut source = String::from(
+        "procedure day\n\
+        \tif fact? \"party is lost\" => reminder \"find your way back to the road\"\n\
+        \tif fact? \"day has random encounter\" => set-fact \"encounter is hostile\"\n\
+        \tset-fact \"party is tired\"\n\
+        \treminder \"players must consume one day's worth of rations\"\n\
+        \treminder \"mark off one torch\"\n\
+        \tclear-fact \"party is tired\"\n\
+        end\n",
+    );
+    for _ in 0..CALLS {
+        source.push_str("day\n");
+    }
+    let ast = parse(&source);
+
+    c.bench_function("5000 procedure calls", |b| {
+        b.iter(|| Interpreter::new().interpret(ast.clone()))
+    });
+}
+
+fn rolls(c: &mut Criterion) {
+    let mut source = String::new();
+    for _ in 0..CALLS {
+        source.push_str("roll 2d6\n\t2-6 => reminder \"miss\"\n\t7-12 => reminder \"hit\"\nend\n");
+    }
+    let ast = parse(&source);
+
+    c.bench_function("5000 rolls", |b| {
+        b.iter(|| Interpreter::new().with_seed(0).interpret(ast.clone()))
+    });
+}
+
+criterion_group!(benches, procedure_calls, rolls);
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crawl::dice::{DicePool, DiceRoll, Die};
+use crawl::interpreter::Interpreter;
+use crawl::parser::{Parser, Statement};
+use crawl::scanner::Scanner;
+
+const TABLE_ROLLS: usize = 1_000;
+
+fn dice(c: &mut Criterion) {
+    let roll = DiceRoll::new(DicePool::new(vec![Die(6); 1_000_000]), 0);
+    let mut rng = StdRng::seed_from_u64(0);
+    c.bench_function("roll 1000000 dice", |b| b.iter(|| roll.roll_with(&mut rng)));
+}
+
+fn table_rolls(c: &mut Criterion) {
+    let mut source = String::from("load table \"examples/random-encounters.csv\"\n");
+    for _ in 0..TABLE_ROLLS {
+        source.push_str("roll on table \"examples/random-encounters.csv\"\n");
+    }
+    let ast: Vec<Statement> = Parser::from_tokens(Scanner::new(&source))
+        .map(Result::unwrap)
+        .collect();
+
+    c.bench_function("1000 table rolls", |b| {
+        b.iter(|| Interpreter::new().with_seed(0).interpret(ast.clone()))
+    });
+}
+
+criterion_group!(benches, dice, table_rolls);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+use crawl::parser::{Parser, Statement};
+use crawl::scanner::{Scanner, Token};
+
+const PROCEDURES: usize = 1_000;
+
+// A script with a thousand procedures, each a few lines of the kinds of statements scripts are
+// mostly made of.
+fn large_script() -> String {
+    let mut source = String::from("load table \"examples/weather.csv\"\n");
+    for n in 0..PROCEDURES {
+        let name = procedure_name(n);
+        source.push_str(&format!(
+            "procedure {name}\n\
+            \troll on table \"examples/weather.csv\"\n\
+            \tif roll 1-3 on 1d6 => set-fact \"party is lost\"\n\
+            \tif fact? \"party is lost\" => reminder \"find your way back to the road\"\n\
+            \tset-fact \"encounter distance {{}}\" % roll 1d6\n\
+            \troll 2d6 + 1\n\
+            \t\t2-6 => set-fact \"encounter is hostile\"\n\
+            \t\t7-13 => set-fact \"encounter is friendly\"\n\
+            \tend\n\
+            end\n\
+            {name}\n"
+        ));
+    }
+    source
+}
+
+// Identifiers can't have digits, so procedures are told apart by letters: `day-a`, `day-b`, ...
+fn procedure_name(mut n: usize) -> String {
+    let mut name = String::from("day-");
+    loop {
+        name.push(char::from(b'a' + (n % 26) as u8));
+        n /= 26;
+        if n == 0 {
+            return name;
+        }
+    }
+}
+
+fn scanning(c: &mut Criterion) {
+    let source = large_script();
+    let mut group = c.benchmark_group("scripts");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("scan 1000 procedures", |b| {
+        b.iter(|| Scanner::new(&source).tokens())
+    });
+    group.finish();
+}
+
+fn parsing(c: &mut Criterion) {
+    let source = large_script();
+    let tokens: Vec<Token> = Scanner::new(&source).map(Result::unwrap).collect();
+    c.bench_function("parse 1000 procedures", |b| {
+        b.iter(|| {
+            Parser::new(tokens.clone())
+                .map(Result::unwrap)
+                .collect::<Vec<Statement>>()
+        })
+    });
+}
+
+criterion_group!(benches, scanning, parsing);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use crawl::lang::Crawl;
+use crawl::output::BufferSink;
+use crawl::prompt::ScriptedPrompt;
+use crawl::storage::MemoryStorage;
+
+// Running anything, however malformed, gives errors rather than panicking. Tables and facts are
+// kept in memory, and `choose` is never answered, so nothing waits on a person or the disk.
+fuzz_target!(|source: &str| {
+    let mut crawl = Crawl::new()
+        .with_seed(0)
+        .with_output(Box::new(BufferSink::new()))
+        .with_prompt(Box::new(ScriptedPrompt::default()))
+        .with_storage(Box::new(MemoryStorage::default()));
+    crawl.execute(source);
+});
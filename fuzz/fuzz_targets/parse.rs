@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use crawl::parser::Parser;
+use crawl::scanner::Scanner;
+
+// Scanning and parsing anything, however malformed, gives errors rather than panicking.
+fuzz_target!(|source: &str| {
+    for _ in Parser::from_tokens(Scanner::new(source)) {}
+});
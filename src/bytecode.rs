@@ -0,0 +1,213 @@
+use crate::dice::{DiceRoll, RollSpec};
+use crate::facts::Fact;
+use crate::parser::{Antecedent, CrawlStr, MatchingRollArm, ModifiedRollSpecifier, Statement};
+use crate::scanner::Token;
+
+// Procedure bodies compiled once, when they're defined, into a flat list of instructions, so a
+// procedure called thousands of times over in a simulation doesn't walk, clone, and re-parse its
+// statements on every call. Facts are split up and rolls with fixed modifiers are built ahead of
+// time, and an `if` becomes a jump over its consequent. Statements that aren't worth compiling
+// are kept to be evaluated as they were parsed.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Instruction {
+    // Start the body statement at this index
+    Step(usize),
+    // Evaluate a statement as parsed
+    Evaluate(Statement),
+    // Evaluate a statement as parsed, as an `if`'s consequent
+    EvaluateConsequent(Statement),
+    Reminder(String),
+    SetFact {
+        fact: String,
+        parsed: Fact,
+    },
+    ClearFact {
+        fact: String,
+        parsed: Fact,
+    },
+    Roll(CompiledRoll),
+    MatchingRoll {
+        roll: CompiledRoll,
+        arms: Vec<MatchingRollArm>,
+        otherwise: Option<Box<Statement>>,
+    },
+    // If the condition doesn't hold, record the `if` and skip this many instructions
+    If {
+        condition: Condition,
+        skip: usize,
+    },
+    // Record the `if` the last record was the consequent of
+    EndIf,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    Fact { fact: String, parsed: Fact },
+    Roll { target: Token, roll: CompiledRoll },
+    // Evaluated as parsed
+    Antecedent(Antecedent),
+}
+
+/// A roll whose dice and modifier are known without evaluating anything.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledRoll {
+    // Kept to be checked against the dice limits when it's rolled
+    pub spec: RollSpec,
+    pub roll: DiceRoll,
+}
+
+pub fn compile(body: &[Statement]) -> Vec<Instruction> {
+    let mut code = Vec::new();
+    for (idx, statement) in body.iter().enumerate() {
+        code.push(Instruction::Step(idx));
+        compile_statement(statement, &mut code);
+    }
+    code
+}
+
+fn compile_statement(statement: &Statement, code: &mut Vec<Instruction>) {
+    let instruction = match statement {
+        Statement::IfThen {
+            antecedent,
+            consequent,
+        } => {
+            let condition = match antecedent {
+                Antecedent::CheckFact(fact) => match Fact::try_from(fact.as_str()) {
+                    Ok(parsed) => Condition::Fact {
+                        fact: fact.clone(),
+                        parsed,
+                    },
+                    Err(_) => Condition::Antecedent(antecedent.clone()),
+                },
+                Antecedent::DiceRoll {
+                    target,
+                    roll_specifier,
+                } => match compile_roll(roll_specifier) {
+                    Some(roll) => Condition::Roll {
+                        target: target.clone(),
+                        roll,
+                    },
+                    None => Condition::Antecedent(antecedent.clone()),
+                },
+                _ => Condition::Antecedent(antecedent.clone()),
+            };
+            let start = code.len();
+            code.push(Instruction::If { condition, skip: 0 });
+            match compile_simple(consequent) {
+                Some(instruction) => code.push(instruction),
+                None => code.push(Instruction::EvaluateConsequent((**consequent).clone())),
+            }
+            code.push(Instruction::EndIf);
+            let skip = code.len() - start - 1;
+            if let Instruction::If { skip: jump, .. } = &mut code[start] {
+                *jump = skip;
+            }
+            return;
+        }
+        Statement::MatchingRoll {
+            roll_specifier,
+            arms,
+            otherwise,
+        } => match compile_roll(roll_specifier) {
+            Some(roll) => Instruction::MatchingRoll {
+                roll,
+                arms: arms.clone(),
+                otherwise: otherwise.clone(),
+            },
+            None => Instruction::Evaluate(statement.clone()),
+        },
+        _ => compile_simple(statement).unwrap_or_else(|| Instruction::Evaluate(statement.clone())),
+    };
+    code.push(instruction);
+}
+
+// Statements that compile to a single instruction, which can also be consequents.
+fn compile_simple(statement: &Statement) -> Option<Instruction> {
+    let instruction = match statement {
+        Statement::Reminder(reminder) => Instruction::Reminder(reminder.clone()),
+        Statement::SetFact(CrawlStr::Str(fact)) => Instruction::SetFact {
+            fact: fact.clone(),
+            parsed: Fact::try_from(fact.as_str()).ok()?,
+        },
+        Statement::ClearFact(fact) => Instruction::ClearFact {
+            fact: fact.clone(),
+            parsed: Fact::try_from(fact.as_str()).ok()?,
+        },
+        Statement::NontargetedRoll(roll_specifier) => {
+            Instruction::Roll(compile_roll(roll_specifier)?)
+        }
+        _ => return None,
+    };
+    Some(instruction)
+}
+
+fn compile_roll(roll_specifier: &ModifiedRollSpecifier) -> Option<CompiledRoll> {
+    match roll_specifier.base_roll_specifier {
+        Token::RollSpecifier(spec) if roll_specifier.terms.is_empty() => Some(CompiledRoll {
+            spec,
+            roll: roll_specifier.try_into().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    fn compile_source(source: &str) -> Vec<Instruction> {
+        let body: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(Result::unwrap)
+            .collect();
+        compile(&body)
+    }
+
+    #[test]
+    fn jumps() {
+        let code = compile_source(
+            "if fact? \"party is lost\" => reminder \"forage\"\nset-fact \"party is tired\"\n",
+        );
+        assert_eq!(
+            code,
+            vec![
+                Instruction::Step(0),
+                Instruction::If {
+                    condition: Condition::Fact {
+                        fact: "party is lost".into(),
+                        parsed: Fact::try_from("party is lost".to_string()).unwrap(),
+                    },
+                    skip: 2,
+                },
+                Instruction::Reminder("forage".into()),
+                Instruction::EndIf,
+                Instruction::Step(1),
+                Instruction::SetFact {
+                    fact: "party is tired".into(),
+                    parsed: Fact::try_from("party is tired".to_string()).unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluated_as_parsed() {
+        let code = compile_source(
+            "let x = 2\nroll 1d6 + x\n\t1-8 => reminder \"hit\"\nend\nset-fact \"party is {}\" % x\n",
+        );
+        assert!(matches!(
+            code[1],
+            Instruction::Evaluate(Statement::Let { .. })
+        ));
+        assert!(matches!(
+            code[3],
+            Instruction::Evaluate(Statement::MatchingRoll { .. })
+        ));
+        assert!(matches!(
+            code[5],
+            Instruction::Evaluate(Statement::SetFact(_))
+        ));
+    }
+}
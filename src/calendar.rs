@@ -0,0 +1,206 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CrawlError;
+
+// Dates are kept as the number of days since the first day of the first month of year 1, and
+// read off a calendar: its months and how long each is, the days of its week, and how many days
+// its moon takes to wax and wane. The moon is new on the first day, and full halfway through
+// each cycle.
+
+/// The months, weekdays, and moon a date is read by. The twelve months and seven days of the
+/// Gregorian calendar, without leap years, by default, with a 28 day moon.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Calendar {
+    months: Vec<(String, i32)>,
+    weekdays: Vec<String>,
+    lunar_cycle: i32,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        let months = [
+            ("January", 31),
+            ("February", 28),
+            ("March", 31),
+            ("April", 30),
+            ("May", 31),
+            ("June", 30),
+            ("July", 31),
+            ("August", 31),
+            ("September", 30),
+            ("October", 31),
+            ("November", 30),
+            ("December", 31),
+        ];
+        let weekdays = [
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+            "Sunday",
+        ];
+        Calendar {
+            months: months
+                .into_iter()
+                .map(|(month, days)| (month.into(), days))
+                .collect(),
+            weekdays: weekdays.into_iter().map(Into::into).collect(),
+            lunar_cycle: 28,
+        }
+    }
+}
+
+/// A unit dates are advanced by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateUnit {
+    Days,
+    Weeks,
+}
+
+impl fmt::Display for DateUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateUnit::Days => write!(f, "days"),
+            DateUnit::Weeks => write!(f, "weeks"),
+        }
+    }
+}
+
+impl Calendar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `months`, each with how many days it has, in place of the calendar's months. A year
+    /// needs at least one month, and no more days than dates can count.
+    pub fn with_months(mut self, months: &[(&str, i32)]) -> Result<Self, CrawlError> {
+        let months: Vec<(String, i32)> = months
+            .iter()
+            .map(|(month, days)| (month.to_string(), (*days).max(1)))
+            .collect();
+        if months.is_empty() {
+            return Err(CrawlError::InterpreterError {
+                reason: "a calendar needs at least one month".into(),
+            });
+        }
+        if months
+            .iter()
+            .try_fold(0i32, |year, (_, days)| year.checked_add(*days))
+            .is_none()
+        {
+            return Err(CrawlError::InterpreterError {
+                reason: "a calendar's year has too many days".into(),
+            });
+        }
+        self.months = months;
+        Ok(self)
+    }
+
+    /// Use `weekdays` in place of the calendar's week, which is as long as there are days.
+    pub fn with_weekdays(mut self, weekdays: &[&str]) -> Self {
+        self.weekdays = weekdays.iter().map(|day| day.to_string()).collect();
+        self
+    }
+
+    /// Wax and wane the moon over `days` days.
+    pub fn with_lunar_cycle(mut self, days: i32) -> Self {
+        self.lunar_cycle = days.max(1);
+        self
+    }
+
+    /// How many days a `unit` is.
+    pub fn days_in(&self, unit: DateUnit) -> i32 {
+        match unit {
+            DateUnit::Days => 1,
+            DateUnit::Weeks => self.weekdays.len().max(1) as i32,
+        }
+    }
+
+    /// The date `days` days after the calendar's first day, like "Monday, 1 January, year 1".
+    pub fn format(&self, days: i32) -> String {
+        let (year, month, day) = self.month_day(days);
+        match self.weekday(days) {
+            Some(weekday) => format!("{weekday}, {day} {month}, year {year}"),
+            None => format!("{day} {month}, year {year}"),
+        }
+    }
+
+    /// Whether `days` days after the first day falls in the month or on the weekday called
+    /// `name`, or is a `full-moon` or `new-moon`.
+    pub fn is(&self, days: i32, name: &str) -> Result<bool, CrawlError> {
+        let moon = days.rem_euclid(self.lunar_cycle);
+        let is_named = |named: &str| named.eq_ignore_ascii_case(name);
+        match name {
+            "full-moon" => Ok(moon == self.lunar_cycle / 2),
+            "new-moon" => Ok(moon == 0),
+            _ if self.months.iter().any(|(month, _)| is_named(month)) => {
+                Ok(is_named(self.month_day(days).1))
+            }
+            _ if self.weekdays.iter().any(|weekday| is_named(weekday)) => {
+                Ok(self.weekday(days).is_some_and(is_named))
+            }
+            _ => Err(CrawlError::InterpreterError {
+                reason: format!("the calendar has no month or weekday called {name:?}"),
+            }),
+        }
+    }
+
+    // The year, month, and day of the month.
+    fn month_day(&self, days: i32) -> (i32, &str, i32) {
+        let year_length: i32 = self.months.iter().map(|(_, days)| days).sum();
+        let mut day = days.rem_euclid(year_length);
+        for (month, length) in &self.months {
+            if day < *length {
+                return (days.div_euclid(year_length) + 1, month, day + 1);
+            }
+            day -= length;
+        }
+        unreachable!("a day of the year is in one of its months")
+    }
+
+    fn weekday(&self, days: i32) -> Option<&str> {
+        let week = self.weekdays.len() as i32;
+        if week == 0 {
+            return None;
+        }
+        Some(&self.weekdays[days.rem_euclid(week) as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        let calendar = Calendar::new();
+        assert_eq!(calendar.format(0), "Monday, 1 January, year 1");
+        assert_eq!(calendar.format(31 + 28), "Thursday, 1 March, year 1");
+        assert_eq!(calendar.format(365), "Tuesday, 1 January, year 2");
+        let is = |days, name| calendar.is(days, name).unwrap();
+        assert!(is(0, "new-moon") && is(14, "full-moon") && !is(13, "full-moon"));
+        assert!(is(40, "february") && is(6, "Sunday") && !is(6, "March"));
+        assert!(calendar.is(0, "Moonday").is_err());
+
+        let harptos = Calendar::new()
+            .with_months(&[("Hammer", 30), ("Midwinter", 1), ("Alturiak", 30)])
+            .unwrap()
+            .with_weekdays(&[])
+            .with_lunar_cycle(30);
+        assert_eq!(harptos.format(30), "1 Midwinter, year 1");
+        assert_eq!(harptos.format(61), "1 Hammer, year 2");
+        assert!(harptos.is(45, "full-moon").unwrap());
+        assert_eq!(harptos.days_in(DateUnit::Weeks), 1);
+
+        assert!(Calendar::new().with_months(&[]).is_err());
+        assert!(
+            Calendar::new()
+                .with_months(&[("Long", i32::MAX), ("Longer", 1)])
+                .is_err()
+        );
+    }
+}
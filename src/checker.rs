@@ -0,0 +1,305 @@
+use core::fmt;
+use std::collections::HashSet;
+
+use crate::downtime;
+use crate::parser::{Amount, Statement};
+use crate::rooms;
+use crate::scenes;
+use crate::visitor::{Visitor, walk_amount, walk_statement};
+
+// Semantic checks run over a parsed program before it's interpreted. These don't stop
+// execution - they're reported so script authors can catch typos in procedure and table
+// names before a long procedure falls over halfway through a session.
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Diagnostic {
+    UndefinedProcedure(String),
+    UnloadedTable(String),
+    UnusedProcedure(String),
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::UndefinedProcedure(name) => {
+                write!(f, "procedure {name:?} is called but never defined")
+            }
+            Diagnostic::UnloadedTable(name) => {
+                write!(f, "table {name:?} is rolled on but never loaded")
+            }
+            Diagnostic::UnusedProcedure(name) => {
+                write!(f, "procedure {name:?} is defined but never called")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Checker {
+    defined_procedures: HashSet<String>,
+    called_procedures: HashSet<String>,
+    loaded_tables: HashSet<String>,
+    rolled_tables: HashSet<String>,
+    host_functions: HashSet<String>,
+    // Interpolating a variable, or calling a generator, looks like calling a procedure.
+    variables: HashSet<String>,
+    generators: HashSet<String>,
+    // Included files can define procedures and load tables the checker can't see.
+    includes: bool,
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Functions registered by the host application, which can be called without being
+    /// defined.
+    pub fn with_host_functions(mut self, names: impl IntoIterator<Item = String>) -> Self {
+        self.host_functions.extend(names);
+        self
+    }
+
+    /// Tables already loaded, by earlier scripts or the host application.
+    pub fn with_loaded_tables(mut self, names: impl IntoIterator<Item = String>) -> Self {
+        self.loaded_tables.extend(names);
+        self
+    }
+
+    pub fn check(mut self, statements: &[Statement]) -> Vec<Diagnostic> {
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+
+        let mut diagnostics = Vec::new();
+        if !self.includes {
+            for name in self.called_procedures.difference(&self.defined_procedures) {
+                if !self.host_functions.contains(name)
+                    && !self.variables.contains(name)
+                    && !self.generators.contains(name)
+                {
+                    diagnostics.push(Diagnostic::UndefinedProcedure(name.clone()));
+                }
+            }
+            for name in self.rolled_tables.difference(&self.loaded_tables) {
+                diagnostics.push(Diagnostic::UnloadedTable(name.clone()));
+            }
+        }
+        for name in self.defined_procedures.difference(&self.called_procedures) {
+            diagnostics.push(Diagnostic::UnusedProcedure(name.clone()));
+        }
+        // HashSet iteration order isn't stable, so sort for reproducible output.
+        diagnostics.sort();
+        diagnostics
+    }
+}
+
+impl Visitor for Checker {
+    fn visit_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Generator { name, .. } => {
+                self.generators.insert(name.clone());
+            }
+            Statement::Include { .. } => self.includes = true,
+            Statement::Let { name, .. } => {
+                self.variables.insert(name.clone());
+            }
+            Statement::LoadTable { table, .. } => {
+                self.loaded_tables.insert(table.clone());
+            }
+            Statement::MatchingTableRoll { table, .. }
+            | Statement::TableRoll { table, .. }
+            | Statement::PinTable { table, .. }
+            | Statement::AdvanceWeather(table)
+            | Statement::AdvanceFronts(table) => {
+                self.rolled_tables.insert(table.clone());
+            }
+            Statement::GenerateRoom => {
+                self.rolled_tables
+                    .extend(rooms::TABLES.iter().map(|table| table.to_string()));
+            }
+            Statement::SceneCheck => {
+                self.rolled_tables
+                    .extend(scenes::TABLES.iter().map(|table| table.to_string()));
+            }
+            Statement::Procedure { declaration, .. } => {
+                self.defined_procedures.insert(declaration.0.clone());
+            }
+            Statement::ProcedureCall { identifier, .. } => {
+                self.called_procedures.insert(identifier.clone());
+            }
+            Statement::Downtime { arms, .. } => {
+                // Activities can interpolate the character they're for.
+                self.variables.insert(downtime::CHARACTER.into());
+                for arm in arms {
+                    self.called_procedures
+                        .extend(arm.activities.iter().cloned());
+                }
+            }
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+
+    // Naming something in an amount calls it, unless it's a variable.
+    fn visit_amount(&mut self, amount: &Amount) {
+        if let Amount::Call(name) = amount {
+            self.called_procedures.insert(name.clone());
+        }
+        walk_amount(self, amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{Antecedent, CrawlStr, ProcedureDeclaration};
+
+    #[test]
+    fn host_function() {
+        let ast = vec![Statement::ProcedureCall {
+            identifier: "weather-api".into(),
+            arguments: Vec::new(),
+        }];
+        let checker = Checker::new().with_host_functions(["weather-api".to_string()]);
+        assert!(checker.check(&ast).is_empty());
+    }
+
+    #[test]
+    fn called_in_amount() {
+        let ast = vec![Statement::Let {
+            name: "dc".into(),
+            value: Amount::Call("travel-dc".into()),
+        }];
+        assert_eq!(
+            Checker::new().check(&ast),
+            vec![Diagnostic::UndefinedProcedure("travel-dc".into())]
+        );
+    }
+
+    #[test]
+    fn generator() {
+        let ast = vec![
+            Statement::Generator {
+                name: "npc".into(),
+                template: "{names}".into(),
+            },
+            Statement::ProcedureCall {
+                identifier: "npc".into(),
+                arguments: Vec::new(),
+            },
+        ];
+        assert!(Checker::new().check(&ast).is_empty());
+    }
+
+    #[test]
+    fn loaded_table() {
+        let ast = vec![Statement::TableRoll {
+            table: "weather".into(),
+            field: None,
+            tag: None,
+            excluding_seen: false,
+        }];
+        let checker = Checker::new().with_loaded_tables(["weather".to_string()]);
+        assert!(checker.check(&ast).is_empty());
+    }
+
+    #[test]
+    fn undefined_procedure() {
+        let ast = vec![Statement::ProcedureCall {
+            identifier: "missing".into(),
+            arguments: Vec::new(),
+        }];
+        assert_eq!(
+            Checker::new().check(&ast),
+            vec![Diagnostic::UndefinedProcedure("missing".into())]
+        );
+    }
+
+    #[test]
+    fn interpolated_variable() {
+        let ast = vec![
+            Statement::Let {
+                name: "damage".into(),
+                value: Amount::Fixed(3),
+            },
+            Statement::SetFact(CrawlStr::InterpolatedStr {
+                format_string: "hit for {}".into(),
+                expressions: vec![Statement::ProcedureCall {
+                    identifier: "damage".into(),
+                    arguments: Vec::new(),
+                }],
+            }),
+        ];
+        assert!(Checker::new().check(&ast).is_empty());
+    }
+
+    #[test]
+    fn include() {
+        let ast = vec![
+            Statement::Include {
+                path: "procedures/travel.crawl".into(),
+                namespace: None,
+            },
+            Statement::ProcedureCall {
+                identifier: "travel".into(),
+                arguments: Vec::new(),
+            },
+        ];
+        assert!(Checker::new().check(&ast).is_empty());
+    }
+
+    #[test]
+    fn unloaded_table() {
+        let ast = vec![Statement::IfThen {
+            antecedent: Antecedent::CheckFact("party is lost".into()),
+            consequent: Box::new(Statement::TableRoll {
+                table: "weather.csv".into(),
+                field: None,
+                tag: None,
+                excluding_seen: false,
+            }),
+        }];
+        assert_eq!(
+            Checker::new().check(&ast),
+            vec![Diagnostic::UnloadedTable("weather.csv".into())]
+        );
+    }
+
+    #[test]
+    fn unused_procedure() {
+        let ast = vec![Statement::Procedure {
+            declaration: ProcedureDeclaration("day".into()),
+            body: vec![Statement::Reminder("eat".into())].into(),
+        }];
+        assert_eq!(
+            Checker::new().check(&ast),
+            vec![Diagnostic::UnusedProcedure("day".into())]
+        );
+    }
+
+    #[test]
+    fn well_formed_program() {
+        let ast = vec![
+            Statement::LoadTable {
+                table: "weather.csv".into(),
+                no_clamp: false,
+            },
+            Statement::Procedure {
+                declaration: ProcedureDeclaration("day".into()),
+                body: vec![Statement::TableRoll {
+                    table: "weather.csv".into(),
+                    field: None,
+                    tag: None,
+                    excluding_seen: false,
+                }]
+                .into(),
+            },
+            Statement::ProcedureCall {
+                identifier: "day".into(),
+                arguments: Vec::new(),
+            },
+        ];
+        assert!(Checker::new().check(&ast).is_empty());
+    }
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// A Blades-style progress clock: some number of segments, filled in as a threat or project
+/// advances.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Clock {
+    segments: i32,
+    filled: i32,
+}
+
+impl Clock {
+    pub fn new(segments: i32) -> Self {
+        Clock {
+            segments,
+            filled: 0,
+        }
+    }
+
+    pub fn segments(&self) -> i32 {
+        self.segments
+    }
+
+    pub fn filled(&self) -> i32 {
+        self.filled
+    }
+
+    /// Fill in `by` more segments. A clock can't be filled past full, or emptied below zero.
+    pub fn advance(&mut self, by: i32) {
+        self.filled = self.filled.saturating_add(by).clamp(0, self.segments);
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.filled >= self.segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance() {
+        let mut clock = Clock::new(4);
+        clock.advance(3);
+        assert_eq!(clock.filled(), 3);
+        assert!(!clock.is_full());
+        clock.advance(3);
+        assert_eq!(clock.filled(), 4);
+        assert!(clock.is_full());
+        clock.advance(-6);
+        assert_eq!(clock.filled(), 0);
+        clock.advance(i32::MAX);
+        clock.advance(i32::MAX);
+        assert_eq!(clock.filled(), 4);
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+
+use crate::error::CrawlError;
+use crate::facts::{Fact, FactDatabase};
+use crate::interpreter::{Interpreter, StatementLocation};
+use crate::parser::{SourceMap, Statement};
+
+/// Called by the interpreter before it evaluates each top-level or procedure body statement. An
+/// error stops the statement from running, and is reported as its error.
+pub trait StepHook: Send {
+    fn before_statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        location: &StatementLocation,
+        statement: &Statement,
+    ) -> Result<(), CrawlError>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Breakpoint {
+    Procedure(String),
+    Line(usize),
+}
+
+impl From<&str> for Breakpoint {
+    fn from(value: &str) -> Self {
+        match value.parse::<usize>() {
+            Ok(line) => Breakpoint::Line(line),
+            Err(_) => Breakpoint::Procedure(value.into()),
+        }
+    }
+}
+
+const HELP: &str = "\
+commands:
+  step, s                         evaluate the next statement and pause again
+  continue, c                     run until the next breakpoint
+  break, b <procedure | line>     add a breakpoint
+  delete, d <procedure | line>    remove a breakpoint
+  where, w                        show the paused statement
+  facts, f                        show local and persistent facts
+  set-fact \"<fact>\"               set a local fact
+  clear-fact \"<fact>\"             clear a local fact
+  set-persistent-fact \"<fact>\"    set a persistent fact
+  clear-persistent-fact \"<fact>\"  clear a persistent fact
+  help, h                         show this message";
+
+/// An interactive debugger driven by line-based commands read from `input`. Execution starts
+/// paused on the first statement.
+pub struct Debugger<R, W> {
+    input: R,
+    output: W,
+    source_map: SourceMap,
+    breakpoints: HashSet<Breakpoint>,
+    stepping: bool,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    pub fn new(input: R, output: W, source_map: SourceMap) -> Self {
+        Debugger {
+            input,
+            output,
+            source_map,
+            breakpoints: HashSet::new(),
+            stepping: true,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.insert(breakpoint);
+    }
+
+    fn line(&self, location: &StatementLocation) -> Option<usize> {
+        match location {
+            StatementLocation::TopLevel(idx) => self.source_map.top_level.get(*idx),
+            StatementLocation::Procedure(name, idx) => self
+                .source_map
+                .procedures
+                .get(name)
+                .and_then(|lines| lines.get(*idx)),
+            // Only the program being run has a source map.
+            StatementLocation::Include(..) => None,
+        }
+        .copied()
+    }
+
+    fn should_pause(&self, location: &StatementLocation) -> bool {
+        if self.stepping {
+            return true;
+        }
+        // Procedure breakpoints pause on the first statement of the procedure's body.
+        if matches!(location, StatementLocation::Procedure(name, 0)
+            if self.breakpoints.contains(&Breakpoint::Procedure(name.clone())))
+        {
+            return true;
+        }
+        self.line(location)
+            .is_some_and(|line| self.breakpoints.contains(&Breakpoint::Line(line)))
+    }
+
+    fn describe(&self, location: &StatementLocation, statement: &Statement) -> String {
+        let line = match self.line(location) {
+            Some(line) => format!("line {line}"),
+            None => "unknown line".into(),
+        };
+        match location {
+            StatementLocation::TopLevel(_) => format!("{line}: {statement:?}"),
+            StatementLocation::Procedure(name, _) => {
+                format!("{line} (in {name}): {statement:?}")
+            }
+            StatementLocation::Include(path, idx) => {
+                format!("statement {} of {path}: {statement:?}", idx + 1)
+            }
+        }
+    }
+
+    fn prompt(
+        &mut self,
+        interpreter: &mut Interpreter,
+        location: &StatementLocation,
+        statement: &Statement,
+    ) -> io::Result<()> {
+        writeln!(
+            self.output,
+            "paused at {}",
+            self.describe(location, statement)
+        )?;
+        loop {
+            write!(self.output, "(debug) ")?;
+            self.output.flush()?;
+
+            let mut input = String::new();
+            if self.input.read_line(&mut input)? == 0 {
+                // Input is exhausted, so there's no one left to drive the debugger.
+                self.stepping = false;
+                self.breakpoints.clear();
+                return Ok(());
+            }
+
+            let (command, argument) = match input.trim().split_once(' ') {
+                Some((command, argument)) => (command, argument.trim()),
+                None => (input.trim(), ""),
+            };
+            match command {
+                "step" | "s" => {
+                    self.stepping = true;
+                    return Ok(());
+                }
+                "continue" | "c" => {
+                    self.stepping = false;
+                    return Ok(());
+                }
+                "break" | "b" => {
+                    self.add_breakpoint(argument.into());
+                    writeln!(self.output, "breakpoint set at {argument}")?;
+                }
+                "delete" | "d" => {
+                    self.breakpoints.remove(&argument.into());
+                    writeln!(self.output, "breakpoint removed from {argument}")?;
+                }
+                "where" | "w" => {
+                    writeln!(self.output, "{}", self.describe(location, statement))?;
+                }
+                "facts" | "f" => {
+                    writeln!(self.output, "local facts:")?;
+                    Self::write_facts(&mut self.output, &interpreter.local_facts)?;
+                    writeln!(self.output, "persistent facts:")?;
+                    Self::write_facts(&mut self.output, &interpreter.persistent_facts)?;
+                }
+                "set-fact" | "clear-fact" | "set-persistent-fact" | "clear-persistent-fact" => {
+                    let fact = match Fact::try_from(argument.trim_matches('"').to_string()) {
+                        Ok(fact) => fact,
+                        Err(error) => {
+                            writeln!(self.output, "{error}")?;
+                            continue;
+                        }
+                    };
+                    match command {
+                        "set-fact" => interpreter.local_facts.set(fact),
+                        "clear-fact" => interpreter.local_facts.clear(&fact),
+                        "set-persistent-fact" => interpreter.persistent_facts.set(fact),
+                        _ => interpreter.persistent_facts.clear(&fact),
+                    }
+                }
+                "help" | "h" => writeln!(self.output, "{HELP}")?,
+                "" => {}
+                _ => writeln!(self.output, "unknown command {command:?}, try help")?,
+            }
+        }
+    }
+
+    fn write_facts(output: &mut W, facts: &FactDatabase) -> io::Result<()> {
+        let mut facts: Vec<String> = facts.iter().map(|fact| fact.to_string()).collect();
+        facts.sort();
+        for fact in facts {
+            writeln!(output, "  {fact}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead + Send, W: Write + Send> StepHook for Debugger<R, W> {
+    fn before_statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        location: &StatementLocation,
+        statement: &Statement,
+    ) -> Result<(), CrawlError> {
+        if self.should_pause(location) {
+            self.prompt(interpreter, location, statement)
+                .map_err(|error| CrawlError::InterpreterError {
+                    reason: format!("failed to communicate with debugger ({error})"),
+                })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    // Lets a test read what the debugger wrote after the interpreter has taken ownership of it.
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn debug(source: &str, commands: &str) -> (Interpreter, String) {
+        let mut scanner = Scanner::new(source);
+        let toks = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
+        let mut parser = Parser::new(toks).with_lines(scanner.lines().to_vec());
+        let ast = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+
+        let output = SharedOutput::default();
+        let debugger = Debugger::new(
+            Cursor::new(commands.to_string()),
+            output.clone(),
+            parser.source_map().clone(),
+        );
+        let mut interpreter = Interpreter::new().with_step_hook(Box::new(debugger));
+        let _ = interpreter.interpret(ast);
+
+        let written = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        (interpreter, written)
+    }
+
+    const SOURCE: &str =
+        "procedure proc\n\treminder \"first\"\n\treminder \"second\"\nend\n\nproc\n";
+
+    #[test]
+    fn starts_paused() {
+        let (_, output) = debug(SOURCE, "c\n");
+        assert!(output.starts_with("paused at line 1:"));
+        assert_eq!(output.matches("paused at").count(), 1);
+    }
+
+    #[test]
+    fn procedure_breakpoint() {
+        let (_, output) = debug(SOURCE, "b proc\nc\nc\n");
+        assert!(output.contains("paused at line 2 (in proc): Reminder(\"first\")"));
+        assert_eq!(output.matches("paused at").count(), 2);
+    }
+
+    #[test]
+    fn line_breakpoint() {
+        let (_, output) = debug(SOURCE, "b 3\nc\nc\n");
+        assert!(output.contains("paused at line 3 (in proc): Reminder(\"second\")"));
+        assert_eq!(output.matches("paused at").count(), 2);
+    }
+
+    #[test]
+    fn step() {
+        let (_, output) = debug(SOURCE, "s\ns\ns\ns\n");
+        assert_eq!(output.matches("paused at").count(), 4);
+    }
+
+    #[test]
+    fn broken_output() {
+        struct BrokenPipe;
+
+        impl Write for BrokenPipe {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let debugger = Debugger::new(Cursor::new(String::new()), BrokenPipe, SourceMap::default());
+        let mut interpreter = Interpreter::new().with_step_hook(Box::new(debugger));
+        let values = interpreter.interpret(vec![Statement::Reminder("first".into())]);
+        assert!(matches!(
+            &values[0],
+            Err(CrawlError::InterpreterError { reason }) if reason.contains("debugger")
+        ));
+    }
+
+    #[test]
+    fn modify_facts() {
+        let (interpreter, output) =
+            debug(SOURCE, "set-persistent-fact \"party is lost\"\nfacts\nc\n");
+        assert!(output.contains("persistent facts:\n  party is lost\n"));
+        assert!(
+            interpreter
+                .persistent_facts
+                .check(&Fact::try_from(String::from("party is lost")).unwrap())
+        );
+    }
+}
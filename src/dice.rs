@@ -3,8 +3,86 @@ use crate::parser::ModifiedRollSpecifier;
 use crate::scanner::Token;
 use core::fmt;
 use rand::Rng;
-use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
+
+/// A roll specifier like `2d6`, parsed once by the scanner rather than on every roll.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollSpec {
+    pub n_dice: usize,
+    pub n_sides: i32,
+}
+
+impl RollSpec {
+    pub fn new(n_dice: usize, n_sides: i32) -> Self {
+        RollSpec { n_dice, n_sides }
+    }
+}
+
+impl FromStr for RollSpec {
+    type Err = CrawlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || CrawlError::ParserError { token: s.into() };
+        let (n_dice, n_sides) = s.split_once('d').ok_or_else(error)?;
+        let spec = RollSpec {
+            n_dice: n_dice.parse().map_err(|_| error())?,
+            n_sides: n_sides.parse().map_err(|_| error())?,
+        };
+        if spec.n_dice == 0 || spec.n_sides < 1 {
+            return Err(error());
+        }
+        Ok(spec)
+    }
+}
+
+pub const DEFAULT_MAX_DICE: usize = 1000;
+pub const DEFAULT_MAX_SIDES: i32 = 10_000;
+/// The most possible totals a roll's distribution is worked out for.
+pub const MAX_DISTRIBUTION_TOTALS: i64 = 100_000;
+
+/// The most dice a roll can have, and the most sides each can have, so a roll like
+/// `1000000d1000000` is refused rather than run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DiceLimits {
+    pub max_dice: usize,
+    pub max_sides: i32,
+}
+
+impl Default for DiceLimits {
+    fn default() -> Self {
+        DiceLimits {
+            max_dice: DEFAULT_MAX_DICE,
+            max_sides: DEFAULT_MAX_SIDES,
+        }
+    }
+}
+
+impl DiceLimits {
+    /// Whether `spec` can be rolled: it has at least one die, each with at least one side,
+    /// and is within the limits.
+    pub fn check(&self, spec: RollSpec) -> Result<(), CrawlError> {
+        let reason = if spec.n_dice == 0 {
+            format!("{spec} has no dice to roll")
+        } else if spec.n_sides < 1 {
+            format!("{spec} has dice without any sides")
+        } else if spec.n_dice > self.max_dice {
+            format!("{spec} rolls more than {} dice", self.max_dice)
+        } else if spec.n_sides > self.max_sides {
+            format!("{spec} rolls dice with more than {} sides", self.max_sides)
+        } else {
+            return Ok(());
+        };
+        Err(CrawlError::InterpreterError { reason })
+    }
+}
+
+impl fmt::Display for RollSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}d{}", self.n_dice, self.n_sides)
+    }
+}
 
 #[derive(Debug)]
 pub struct DieRollResult(i32);
@@ -18,9 +96,32 @@ impl fmt::Display for DieRollResult {
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Die(pub i32);
 
+// The Black Hack's usage dice, from largest to smallest.
+const USAGE_DICE: [i32; 6] = [20, 12, 10, 8, 6, 4];
+
+/// The die a usage die steps down to after a 1 or 2 is rolled on it, or `None` once it drops
+/// below a d4 and whatever it tracks is used up.
+pub fn step_down_usage_die(sides: i32) -> Option<i32> {
+    let idx = USAGE_DICE.iter().position(|&usage| usage == sides)?;
+    USAGE_DICE.get(idx + 1).copied()
+}
+
+pub fn is_usage_die(sides: i32) -> bool {
+    USAGE_DICE.contains(&sides)
+}
+
+/// The probability that a total from `distribution` satisfies `predicate`.
+pub fn probability(distribution: &HashMap<i64, f64>, predicate: impl Fn(i64) -> bool) -> f64 {
+    distribution
+        .iter()
+        .filter(|(total, _)| predicate(**total))
+        .map(|(_, probability)| probability)
+        .sum()
+}
+
 impl Die {
-    fn roll(&self) -> DieRollResult {
-        DieRollResult(rand::thread_rng().gen_range(1..=self.0))
+    fn roll_with<R: Rng + ?Sized>(&self, rng: &mut R) -> DieRollResult {
+        DieRollResult(rng.gen_range(1..=self.0))
     }
 }
 
@@ -29,7 +130,7 @@ pub struct DicePoolRollResult {
     pub results: Vec<DieRollResult>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DicePool {
     pub dice: Vec<Die>,
 }
@@ -39,24 +140,38 @@ impl DicePool {
         DicePool { dice }
     }
 
-    fn roll(&self) -> DicePoolRollResult {
+    fn roll_with<R: Rng + ?Sized>(&self, rng: &mut R) -> DicePoolRollResult {
         DicePoolRollResult {
-            results: self.dice.iter().map(Die::roll).collect(),
+            results: self.dice.iter().map(|die| die.roll_with(rng)).collect(),
         }
     }
+
+    /// Whether `faces` could have come up on the pool: one for each die, in range.
+    pub fn accepts_faces(&self, faces: &[i32]) -> bool {
+        faces.len() == self.dice.len()
+            && self
+                .dice
+                .iter()
+                .zip(faces)
+                .all(|(die, face)| (1..=die.0).contains(face))
+    }
 }
 
 impl fmt::Display for DicePool {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut counter: HashMap<&Die, usize> = HashMap::new();
+        // Count each size of die, in the order they first appear, so the output is stable.
+        let mut counts: Vec<(&Die, usize)> = Vec::new();
         for die in &self.dice {
-            *counter.entry(die).or_default() += 1;
-        }
-        let mut output = String::new();
-        for (die_size, count) in counter {
-            output.push_str(&format!("{}d{}", count, die_size.0));
+            match counts.iter_mut().find(|(size, _)| *size == die) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((die, 1)),
+            }
         }
-        write!(f, "{output}")
+        let groups: Vec<String> = counts
+            .iter()
+            .map(|(die, count)| format!("{count}d{}", die.0))
+            .collect();
+        write!(f, "{}", groups.join("+"))
     }
 }
 
@@ -64,7 +179,11 @@ impl fmt::Display for DicePool {
 pub struct DiceRollResult {
     pub pool_result: DicePoolRollResult,
     pub modifier: i32,
-    pub total: i32,
+    // Wider than the dice and modifier, so adding them up can't overflow
+    pub total: i64,
+    // Whether any die landed on its highest face, or on a 1, for crits and fumbles
+    pub natural_max: bool,
+    pub natural_min: bool,
 }
 
 impl fmt::Display for DiceRollResult {
@@ -74,16 +193,56 @@ impl fmt::Display for DiceRollResult {
 }
 
 impl DiceRollResult {
-    fn new(pool_result: DicePoolRollResult, modifier: i32, total: i32) -> Self {
+    fn new(pool: &DicePool, pool_result: DicePoolRollResult, modifier: i32, total: i64) -> Self {
+        let faces = || pool.dice.iter().zip(&pool_result.results);
         DiceRollResult {
+            natural_max: faces().any(|(die, face)| face.0 == die.0),
+            natural_min: faces().any(|(_, face)| face.0 == 1),
             pool_result,
             modifier,
             total,
         }
     }
+
+    /// The total, if it fits in the numbers scripts work with.
+    pub fn checked_total(&self) -> Result<i32, CrawlError> {
+        i32::try_from(self.total).map_err(|_| CrawlError::InterpreterError {
+            reason: format!("roll total {} is out of range", self.total),
+        })
+    }
 }
 
-#[derive(Debug)]
+/// What a roll came up as, detached from the dice that rolled it so it can be stored and
+/// compared later.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RollOutcome {
+    pub dice: String,
+    pub faces: Vec<i32>,
+    pub total: i64,
+}
+
+impl RollOutcome {
+    pub fn new(roll: &DiceRoll, result: &DiceRollResult) -> Self {
+        RollOutcome {
+            dice: format!("{}{:+}", roll.dice_pool, roll.modifier),
+            faces: result.pool_result.results.iter().map(|die| die.0).collect(),
+            total: result.total,
+        }
+    }
+}
+
+/// Dice and a fixed modifier, which can be used on their own, without a script:
+///
+/// ```
+/// use crawl::dice::DiceRoll;
+/// use rand::{SeedableRng, rngs::StdRng};
+///
+/// let roll: DiceRoll = "3d6+2".parse().unwrap();
+/// assert_eq!(roll.to_string(), "3d6+2");
+/// let total = roll.roll_with(&mut StdRng::seed_from_u64(7)).total;
+/// assert!((roll.min()..=roll.max()).contains(&total));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
 pub struct DiceRoll {
     pub dice_pool: DicePool,
     pub modifier: i32,
@@ -97,36 +256,175 @@ impl DiceRoll {
         }
     }
 
+    /// Parse a roll like `2d6+1`, refusing one outside `limits`.
+    pub fn parse_within(s: &str, limits: DiceLimits) -> Result<Self, CrawlError> {
+        let error = || CrawlError::ParserError { token: s.into() };
+        let (spec, modifier) = match s.find(['+', '-']) {
+            Some(idx) => {
+                let modifier: i32 = s[idx + 1..].trim().parse().map_err(|_| error())?;
+                let sign = if s[idx..].starts_with('-') { -1 } else { 1 };
+                (&s[..idx], sign * modifier)
+            }
+            None => (s, 0),
+        };
+        let spec: RollSpec = spec.trim().parse().map_err(|_| error())?;
+        limits.check(spec)?;
+        Ok(DiceRoll::new(
+            DicePool::new(vec![Die(spec.n_sides); spec.n_dice]),
+            modifier,
+        ))
+    }
+
+    /// Roll with the thread's random number generator.
     pub fn roll(&self) -> DiceRollResult {
-        let pool_result = self.dice_pool.roll();
-        let unmodified_total = pool_result.results.iter().fold(0, |acc, e| acc + e.0);
-        DiceRollResult::new(pool_result, self.modifier, unmodified_total + self.modifier)
+        self.roll_with(&mut rand::thread_rng())
+    }
+
+    /// Roll with `rng`, like a seeded one so rolls can be repeated.
+    pub fn roll_with<R: Rng + ?Sized>(&self, rng: &mut R) -> DiceRollResult {
+        self.result(self.dice_pool.roll_with(rng))
+    }
+
+    /// The result of the dice having come up as `faces`, like when they're rolled by hand.
+    pub fn with_faces(&self, faces: &[i32]) -> Result<DiceRollResult, CrawlError> {
+        if !self.dice_pool.accepts_faces(faces) {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("{faces:?} can't be rolled on {}", self.dice_pool),
+            });
+        }
+        Ok(self.result(DicePoolRollResult {
+            results: faces.iter().copied().map(DieRollResult).collect(),
+        }))
+    }
+
+    fn result(&self, pool_result: DicePoolRollResult) -> DiceRollResult {
+        let unmodified_total: i64 = pool_result.results.iter().map(|die| i64::from(die.0)).sum();
+        DiceRollResult::new(
+            &self.dice_pool,
+            pool_result,
+            self.modifier,
+            unmodified_total + i64::from(self.modifier),
+        )
+    }
+
+    /// The exact probability of rolling each possible total, found by convolving the
+    /// distribution of each die in the pool. Rolls with more than `MAX_DISTRIBUTION_TOTALS`
+    /// possible totals are refused, as working them out would take too long.
+    pub fn distribution(&self) -> Result<HashMap<i64, f64>, CrawlError> {
+        let (min, max) = (self.min(), self.max());
+        if max - min >= MAX_DISTRIBUTION_TOTALS {
+            return Err(CrawlError::InterpreterError {
+                reason: format!(
+                    "{self} has more than {MAX_DISTRIBUTION_TOTALS} totals to work out the odds of"
+                ),
+            });
+        }
+        // The probability of each total from the lowest up. Each die's faces are a window slid
+        // along the totals so far, adding the total entering it and taking away the one leaving.
+        let mut probabilities = vec![1.0];
+        for die in &self.dice_pool.dice {
+            let sides = die.0 as usize;
+            let face_probability = 1.0 / f64::from(die.0);
+            let mut next = Vec::with_capacity(probabilities.len() + sides - 1);
+            let mut window = 0.0;
+            for total in 0..probabilities.len() + sides - 1 {
+                window += probabilities.get(total).copied().unwrap_or(0.0);
+                if let Some(leaving) = total.checked_sub(sides) {
+                    window -= probabilities[leaving];
+                }
+                // Taking away can leave rounding error below zero where nothing's left.
+                next.push((window * face_probability).max(0.0));
+            }
+            probabilities = next;
+        }
+        Ok((min..).zip(probabilities).collect())
+    }
+
+    /// The probability that a roll's total satisfies `predicate`.
+    pub fn probability(&self, predicate: impl Fn(i64) -> bool) -> Result<f64, CrawlError> {
+        Ok(probability(&self.distribution()?, predicate))
+    }
+
+    /// Pearson's chi-squared statistic for how far the `counts` of each total rolled are from
+    /// the exact distribution, and its degrees of freedom. Totals expected fewer than 5 times
+    /// are pooled with their neighbours, as the test is unreliable for them.
+    pub fn chi_squared(&self, counts: &HashMap<i64, u64>) -> Result<(f64, usize), CrawlError> {
+        let samples = counts.values().sum::<u64>() as f64;
+        let mut distribution: Vec<(i64, f64)> = self.distribution()?.into_iter().collect();
+        distribution.sort_by_key(|(total, _)| *total);
+
+        // Expected and observed counts of each group of totals
+        let mut bins: Vec<(f64, f64)> = Vec::new();
+        let (mut expected, mut observed) = (0.0, 0.0);
+        for (total, probability) in distribution {
+            expected += probability * samples;
+            observed += counts.get(&total).copied().unwrap_or(0) as f64;
+            if expected >= 5.0 {
+                bins.push((expected, observed));
+                (expected, observed) = (0.0, 0.0);
+            }
+        }
+        match bins.last_mut() {
+            Some(last) => *last = (last.0 + expected, last.1 + observed),
+            None => bins.push((expected, observed)),
+        }
+
+        let statistic = bins
+            .iter()
+            .map(|(expected, observed)| (observed - expected).powi(2) / expected)
+            .sum();
+        Ok((statistic, bins.len() - 1))
+    }
+
+    pub fn min(&self) -> i64 {
+        self.dice_pool.dice.len() as i64 + i64::from(self.modifier)
+    }
+
+    pub fn max(&self) -> i64 {
+        self.dice_pool
+            .dice
+            .iter()
+            .map(|die| i64::from(die.0))
+            .sum::<i64>()
+            + i64::from(self.modifier)
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.dice_pool
+            .dice
+            .iter()
+            .map(|die| (die.0 + 1) as f64 / 2.0)
+            .sum::<f64>()
+            + self.modifier as f64
+    }
+}
+
+/// Parses a roll like `2d6`, `3d6+2`, or `1d20 - 1`, within the default limits.
+impl FromStr for DiceRoll {
+    type Err = CrawlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DiceRoll::parse_within(s, DiceLimits::default())
     }
 }
 
+impl fmt::Display for DiceRoll {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.dice_pool)?;
+        if self.modifier != 0 {
+            write!(f, "{:+}", self.modifier)?;
+        }
+        Ok(())
+    }
+}
+
+// Only the fixed modifier - modifier terms are looked up by the interpreter.
 impl TryFrom<&ModifiedRollSpecifier> for DiceRoll {
     type Error = CrawlError;
 
     fn try_from(value: &ModifiedRollSpecifier) -> Result<Self, Self::Error> {
-        if let Token::RollSpecifier(ref spec) = value.base_roll_specifier {
-            let re = Regex::new(r"(?<n_dice>\d+)*d(?<n_sides>\d+)").unwrap();
-            let captures = re
-                .captures(spec)
-                .ok_or(CrawlError::ParserError {
-                    token: format!("{value:?}"),
-                })
-                .expect("failed to parse roll specifier");
-
-            let n_dice = captures["n_dice"].parse().expect("failed to parse n_dice");
-            let n_sides = captures["n_sides"]
-                .parse()
-                .expect("failed to parse n_sides");
-
-            let mut dice = Vec::new();
-            for _ in 0..n_dice {
-                dice.push(Die(n_sides));
-            }
-
+        if let Token::RollSpecifier(spec) = value.base_roll_specifier {
+            let dice = vec![Die(spec.n_sides); spec.n_dice];
             Ok(DiceRoll::new(DicePool { dice }, value.modifier))
         } else {
             Err(CrawlError::ParserError {
@@ -143,3 +441,142 @@ impl TryFrom<ModifiedRollSpecifier> for DiceRoll {
         Self::try_from(&value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_d6_plus_3() -> DiceRoll {
+        DiceRoll::new(DicePool::new(vec![Die(6), Die(6)]), 3)
+    }
+
+    #[test]
+    fn with_faces() {
+        let roll = DiceRoll::new(DicePool::new(vec![Die(6), Die(6)]), 1);
+        assert_eq!(roll.with_faces(&[3, 4]).unwrap().total, 8);
+        assert!(roll.with_faces(&[3]).is_err());
+        assert!(roll.with_faces(&[3, 7]).is_err());
+        assert!(roll.with_faces(&[0, 1]).is_err());
+    }
+
+    #[test]
+    fn naturals() {
+        let roll = DiceRoll::new(DicePool::new(vec![Die(20), Die(6)]), 1);
+        let result = roll.with_faces(&[20, 3]).unwrap();
+        assert!(result.natural_max && !result.natural_min);
+        let result = roll.with_faces(&[4, 1]).unwrap();
+        assert!(!result.natural_max && result.natural_min);
+        // The modifier doesn't make a natural.
+        let result = roll.with_faces(&[19, 5]).unwrap();
+        assert!(!result.natural_max && !result.natural_min);
+    }
+
+    #[test]
+    fn chi_squared() {
+        let roll = DiceRoll::new(DicePool::new(vec![Die(4)]), 0);
+        let fair = HashMap::from([(1, 25), (2, 25), (3, 25), (4, 25)]);
+        assert_eq!(roll.chi_squared(&fair).unwrap(), (0.0, 3));
+        let loaded = HashMap::from([(1, 10), (2, 10), (3, 10), (4, 70)]);
+        assert_eq!(roll.chi_squared(&loaded).unwrap(), (108.0, 3));
+
+        // Each total is only expected 2.5 times, so they're pooled in pairs.
+        let (_, degrees_of_freedom) = roll.chi_squared(&HashMap::from([(1, 10)])).unwrap();
+        assert_eq!(degrees_of_freedom, 1);
+    }
+
+    #[test]
+    fn dice_roll_from_str() {
+        assert_eq!("2d6+3".parse::<DiceRoll>().unwrap(), two_d6_plus_3());
+        assert_eq!(" 2d6 + 3 ".parse::<DiceRoll>().unwrap(), two_d6_plus_3());
+        let roll: DiceRoll = "1d20-1".parse().unwrap();
+        assert_eq!(roll.modifier, -1);
+        assert_eq!(roll.to_string(), "1d20-1");
+        assert_eq!("4d8".parse::<DiceRoll>().unwrap().to_string(), "4d8");
+        assert!("2d6+".parse::<DiceRoll>().is_err());
+        assert!("0d6".parse::<DiceRoll>().is_err());
+        assert!("d6".parse::<DiceRoll>().is_err());
+    }
+
+    #[test]
+    fn dice_limits() {
+        let limits = DiceLimits {
+            max_dice: 10,
+            max_sides: 20,
+        };
+        assert!(limits.check(RollSpec::new(10, 20)).is_ok());
+        assert!(limits.check(RollSpec::new(11, 6)).is_err());
+        assert!(limits.check(RollSpec::new(1, 100)).is_err());
+        assert!(limits.check(RollSpec::new(0, 6)).is_err());
+        assert!(limits.check(RollSpec::new(2, 0)).is_err());
+        assert!("1000000d1000000".parse::<DiceRoll>().is_err());
+        assert!("0d6".parse::<RollSpec>().is_err());
+        assert!("2d0".parse::<RollSpec>().is_err());
+    }
+
+    #[test]
+    fn totals_dont_overflow() {
+        let roll = DiceRoll::new(DicePool::new(vec![Die(6)]), i32::MAX);
+        let result = roll.with_faces(&[6]).unwrap();
+        assert_eq!(result.total, i64::from(i32::MAX) + 6);
+        assert!(result.checked_total().is_err());
+    }
+
+    #[test]
+    fn dice_pool_display() {
+        let pool = DicePool::new(vec![Die(6), Die(8), Die(6)]);
+        assert_eq!(pool.to_string(), "2d6+1d8");
+    }
+
+    #[test]
+    fn usage_die() {
+        assert_eq!(step_down_usage_die(20), Some(12));
+        assert_eq!(step_down_usage_die(6), Some(4));
+        assert_eq!(step_down_usage_die(4), None);
+        assert!(!is_usage_die(7));
+    }
+
+    #[test]
+    fn roll_spec() {
+        let spec: RollSpec = "3d20".parse().unwrap();
+        assert_eq!(spec, RollSpec::new(3, 20));
+        assert_eq!(spec.to_string(), "3d20");
+        assert!("3d".parse::<RollSpec>().is_err());
+        assert!("d20".parse::<RollSpec>().is_err());
+    }
+
+    #[test]
+    fn distribution() {
+        let distribution = two_d6_plus_3().distribution().unwrap();
+        assert_eq!(distribution.len(), 11);
+        assert!((distribution[&5] - 1.0 / 36.0).abs() < 1e-9);
+        assert!((distribution[&10] - 6.0 / 36.0).abs() < 1e-9);
+        assert!((distribution.values().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // Totals past the largest i32 are still counted.
+        let roll: DiceRoll = "1d6+2147483647".parse().unwrap();
+        let distribution = roll.distribution().unwrap();
+        assert!((distribution[&(i64::from(i32::MAX) + 6)] - 1.0 / 6.0).abs() < 1e-9);
+        // Too many totals to work out is refused rather than left running.
+        assert!(
+            "1000d10000"
+                .parse::<DiceRoll>()
+                .unwrap()
+                .distribution()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn probability() {
+        let probability = two_d6_plus_3().probability(|total| total >= 9).unwrap();
+        assert!((probability - 26.0 / 36.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn min_max_mean() {
+        let roll = two_d6_plus_3();
+        assert_eq!(roll.min(), 5);
+        assert_eq!(roll.max(), 15);
+        assert_eq!(roll.mean(), 10.0);
+    }
+}
@@ -0,0 +1,19 @@
+use serde::Serialize;
+
+// Downtime runs each character's activities, which are procedures like `carousing` or
+// `training`, once a week for some number of weeks, in the order they're given, and then moves
+// the date on. While an activity runs, `{character}` in its strings is the character it's for,
+// so it can spend from their purse or set facts about them. What each character spent, and what
+// came of their activities, is gathered into a report.
+
+/// The character whose activity is running, interpolated as `{character}`.
+pub const CHARACTER: &str = "character";
+
+/// What a character spent over their downtime, in the fewest coins, and what their activities
+/// came to.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DowntimeReport {
+    pub character: String,
+    pub spent: String,
+    pub outcomes: Vec<String>,
+}
@@ -0,0 +1,617 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::parser::{
+    Amount, Antecedent, CrawlStr, ModifiedRollSpecifier, ModifierValue, Natural, ReturnValue,
+    Statement,
+};
+use crate::rooms;
+use crate::scanner::Token;
+use crate::scenes;
+
+// Narrates what a program would do without running it: nothing is rolled, and no facts,
+// clocks, or resources change. Every branch is described along with what it depends on, and
+// procedure calls are followed into the procedure's body, so a procedure's structure can be
+// reviewed before it's run for real.
+
+#[derive(Debug, Default)]
+pub struct DryRun {
+    procedures: HashMap<String, Arc<[Statement]>>,
+    // Procedures being narrated, so a recursive call isn't followed forever
+    calling: Vec<String>,
+    depth: usize,
+    lines: Vec<String>,
+}
+
+impl DryRun {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Procedures defined before the program, like those from earlier in a session, which
+    /// calls can be followed into.
+    pub fn with_procedures(
+        mut self,
+        procedures: impl IntoIterator<Item = (String, Arc<[Statement]>)>,
+    ) -> Self {
+        self.procedures.extend(procedures);
+        self
+    }
+
+    /// A line for each thing the program would do, indented under what it depends on.
+    pub fn narrate(mut self, statements: &[Statement]) -> Vec<String> {
+        for statement in statements {
+            self.narrate_statement(statement);
+        }
+        self.lines
+    }
+
+    fn narrate_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Choice { question, arms } => {
+                self.line(format!("ask {question:?}"));
+                for arm in arms {
+                    self.nested(format!("if {:?} is chosen", arm.option), &arm.consequent);
+                }
+            }
+            Statement::IfThen {
+                antecedent: condition,
+                consequent,
+            } => {
+                self.line(format!("check whether {}", antecedent(condition)));
+                self.nested("if so".into(), consequent);
+            }
+            Statement::MatchingRoll {
+                roll_specifier,
+                arms,
+                otherwise,
+            } => {
+                self.line(format!("roll {}", roll(roll_specifier)));
+                for arm in arms {
+                    let heading = match &arm.guard {
+                        Some(guard) => {
+                            format!("on {} if {}", target(&arm.target), antecedent(guard))
+                        }
+                        None => format!("on {}", target(&arm.target)),
+                    };
+                    self.nested(heading, &arm.consequent);
+                }
+                if let Some(otherwise) = otherwise {
+                    self.nested("otherwise".into(), otherwise);
+                }
+            }
+            Statement::Move {
+                name,
+                roll_specifier,
+                arms,
+                otherwise,
+            } => {
+                self.line(format!(
+                    "make move {name:?}, rolling {}",
+                    roll(roll_specifier)
+                ));
+                for arm in arms {
+                    let heading = match &arm.guard {
+                        Some(guard) => {
+                            format!("on {} if {}", target(&arm.target), antecedent(guard))
+                        }
+                        None => format!("on {}", target(&arm.target)),
+                    };
+                    self.nested(heading, &arm.consequent);
+                }
+                if let Some(otherwise) = otherwise {
+                    self.nested("otherwise".into(), otherwise);
+                }
+            }
+            Statement::Check {
+                roll_specifier,
+                dc,
+                arms,
+                otherwise,
+            } => {
+                self.line(format!(
+                    "roll {} against {}",
+                    roll(roll_specifier),
+                    amount(dc)
+                ));
+                for arm in arms {
+                    self.nested(format!("on a {}", arm.degree), &arm.consequent);
+                }
+                if let Some(otherwise) = otherwise {
+                    self.nested("otherwise".into(), otherwise);
+                }
+            }
+            Statement::Save {
+                statblock,
+                save,
+                arms,
+                otherwise,
+            } => {
+                self.line(format!("roll 1d20 for {statblock:?} to save vs {save:?}"));
+                for arm in arms {
+                    self.nested(format!("on a {}", arm.degree), &arm.consequent);
+                }
+                if let Some(otherwise) = otherwise {
+                    self.nested("otherwise".into(), otherwise);
+                }
+            }
+            Statement::MatchingFact {
+                fact,
+                arms,
+                otherwise,
+            } => {
+                self.line(format!("look up fact {fact:?}"));
+                for arm in arms {
+                    self.nested(format!("if it's {:?}", arm.pattern), &arm.consequent);
+                }
+                if let Some(otherwise) = otherwise {
+                    self.nested("otherwise".into(), otherwise);
+                }
+            }
+            Statement::MatchingTableRoll {
+                table,
+                tag,
+                excluding_seen,
+                arms,
+            } => {
+                self.line(format!(
+                    "roll on table {table:?}{}",
+                    table_filters(tag, *excluding_seen)
+                ));
+                for arm in arms {
+                    self.nested(format!("on {:?}", arm.pattern), &arm.consequent);
+                }
+            }
+            Statement::Procedure { declaration, body } => {
+                self.line(format!("define procedure {}", declaration.0));
+                self.procedures
+                    .insert(declaration.0.clone(), Arc::clone(body));
+            }
+            Statement::ProcedureCall {
+                identifier,
+                arguments,
+            } => {
+                self.line(describe(statement));
+                let body = match self.procedures.get(identifier) {
+                    Some(body) if arguments.is_empty() && !self.calling.contains(identifier) => {
+                        Arc::clone(body)
+                    }
+                    _ => return,
+                };
+                self.calling.push(identifier.clone());
+                self.depth += 1;
+                for statement in body.iter() {
+                    self.narrate_statement(statement);
+                }
+                self.depth -= 1;
+                self.calling.pop();
+            }
+            Statement::Downtime { arms, .. } => {
+                self.line(describe(statement));
+                self.depth += 1;
+                for arm in arms {
+                    self.line(format!(
+                        "each week, {:?} does {}",
+                        arm.character,
+                        arm.activities.join(", then ")
+                    ));
+                }
+                self.depth -= 1;
+            }
+            Statement::WithFacts(body) => {
+                self.line(describe(statement));
+                self.depth += 1;
+                for statement in body {
+                    self.narrate_statement(statement);
+                }
+                self.depth -= 1;
+            }
+            _ => self.line(describe(statement)),
+        }
+    }
+
+    fn nested(&mut self, heading: String, consequent: &Statement) {
+        self.depth += 1;
+        self.line(heading);
+        self.depth += 1;
+        self.narrate_statement(consequent);
+        self.depth -= 2;
+    }
+
+    fn line(&mut self, text: String) {
+        self.lines
+            .push(format!("{}{text}", "  ".repeat(self.depth)));
+    }
+}
+
+// A single statement on one line, as it would be narrated.
+fn describe(statement: &Statement) -> String {
+    match statement {
+        Statement::AddCombatant {
+            combatant,
+            roll_specifier,
+        } => format!(
+            "add combatant {combatant:?} with initiative {}",
+            roll(roll_specifier)
+        ),
+        Statement::AdvanceClock { clock, amount: by } => {
+            format!("advance clock {clock:?} by {}", amount(by))
+        }
+        Statement::AdvanceDate { amount: by, unit } => {
+            format!("advance the date by {} {unit}", amount(by))
+        }
+        Statement::AwardXp {
+            amount: xp,
+            multiplier,
+        } => format!("award {}{} xp", amount(xp), times(*multiplier)),
+        Statement::AwardTreasure {
+            amount: treasure,
+            multiplier,
+            coin,
+        } => format!(
+            "award {}{} {coin} in treasure",
+            amount(treasure),
+            times(*multiplier)
+        ),
+        Statement::ReportXp => "report the xp and treasure awarded".into(),
+        Statement::AdvanceTime(by) => format!("advance time by {} turns", amount(by)),
+        Statement::AdvanceTurn => "advance to the next combatant's turn".into(),
+        Statement::AdvanceWeather(table) => format!("advance the weather on table {table:?}"),
+        Statement::AddItem { item, owner } => format!("add item {item:?} to {owner:?}"),
+        Statement::ApplyModifier { modifier, rolls } => match rolls {
+            Some(name) => format!("apply {modifier:+} to rolls named {name:?}"),
+            None => format!("apply {modifier:+} to all rolls"),
+        },
+        Statement::Assert(condition) => format!("assert {}", antecedent(condition)),
+        Statement::AssertEqual { actual, expected } => {
+            format!("assert {} equals {}", value(actual), value(expected))
+        }
+        Statement::Campaign(campaign) => format!("switch to campaign {campaign:?}"),
+        Statement::ClearFact(fact) => format!("clear fact {fact:?}"),
+        Statement::ClearPersistentFact(fact) => format!("clear persistent fact {fact:?}"),
+        Statement::CreateClock { clock, segments } => {
+            format!("create clock {clock:?} with {segments} segments")
+        }
+        Statement::CreateFront { faction, segments } => {
+            format!("create a front for {faction:?} with {segments} segments")
+        }
+        Statement::AdvanceFronts(table) => {
+            format!("advance every faction's front on table {table:?}")
+        }
+        Statement::AddRumor { rumor, truth } => match truth {
+            Some(truth) => format!("add the rumor {rumor:?}, which is {truth}"),
+            None => format!("add the rumor {rumor:?}, its truth hidden"),
+        },
+        Statement::RevealRumor { rumor, truth } => match truth {
+            Some(truth) => format!("reveal the rumor {rumor:?} as {truth}"),
+            None => format!("reveal the rumor {rumor:?}"),
+        },
+        Statement::HearRumors(count) => format!("hear {} rumors", amount(count)),
+        Statement::ReportRumors => "report the rumors heard".into(),
+        Statement::Damage {
+            entity,
+            amount: damage,
+        } => format!("damage {entity:?} by {}", amount(damage)),
+        Statement::DefineRoll { name, roll: spec } => {
+            format!("define roll {name:?} as {}", roll(spec))
+        }
+        Statement::Generator { name, template } => {
+            format!("define generator {name} as {template:?}")
+        }
+        Statement::Include { path, namespace } => match namespace {
+            Some(namespace) => format!("include {path:?} as {namespace}"),
+            None => format!("include {path:?}"),
+        },
+        Statement::Let { name, value } => format!("let {name} = {}", amount(value)),
+        Statement::LoadTable { table, .. } => format!("load table {table:?}"),
+        Statement::LoadNames(names) => format!("load names {names:?}"),
+        Statement::LoadStatblocks(file) => format!("load statblocks {file:?}"),
+        Statement::LoadPrices(prices) => format!("load prices {prices:?}"),
+        Statement::Downtime { weeks, .. } => {
+            format!(
+                "spend {} weeks in downtime, then move the date on",
+                amount(weeks)
+            )
+        }
+        Statement::Price { item, settlement } => {
+            format!("look up the price of {item:?}{}", at_settlement(settlement))
+        }
+        Statement::Haggle { item, settlement } => {
+            format!("haggle over {item:?}{}", at_settlement(settlement))
+        }
+        Statement::NameRoll(names) => format!("roll a name from {names:?}"),
+        Statement::NontargetedRoll(spec) => format!("roll {}", roll(spec)),
+        Statement::LastRoll => "the last roll".into(),
+        Statement::CurrentDate => "the current date".into(),
+        Statement::ChaosFactor => "the chaos factor".into(),
+        Statement::AdjustChaos(by) if *by > 0 => "increase the chaos factor".into(),
+        Statement::AdjustChaos(_) => "decrease the chaos factor".into(),
+        Statement::PinTable {
+            table,
+            entry,
+            rolls,
+        } => format!("pin table {table:?} to {entry:?} for {rolls} rolls"),
+        Statement::SceneCheck => format!(
+            "roll 1d10 against the chaos factor, and if the scene's interrupted, on tables {:?} \
+             and {:?}",
+            scenes::EVENT_FOCUS,
+            scenes::EVENT_MEANING
+        ),
+        Statement::Oracle {
+            question,
+            likelihood,
+        } => format!("ask the oracle {question:?} ({likelihood})"),
+        Statement::ProcedureCall {
+            identifier,
+            arguments,
+        } if arguments.is_empty() => format!("call {identifier}"),
+        Statement::ProcedureCall {
+            identifier,
+            arguments,
+        } => format!("call {identifier} with {arguments:?}"),
+        Statement::RemoveCombatant(combatant) => format!("remove combatant {combatant:?}"),
+        Statement::SortCombatants => "sort combatants by initiative".into(),
+        Statement::RemoveModifier(rolls) => match rolls {
+            Some(name) => format!("remove the last modifier applied to rolls named {name:?}"),
+            None => "remove the last modifier applied".into(),
+        },
+        Statement::Reminder(reminder) => format!("remind {reminder:?}"),
+        Statement::ReportFacts(entity) => format!("report facts for {entity:?}"),
+        Statement::ReportInventory(owner) => format!("report inventory for {owner:?}"),
+        Statement::RemoveItem { item, owner } => format!("remove item {item:?} from {owner:?}"),
+        Statement::Return(returned) => format!("return {}", value(returned)),
+        Statement::SetFact(fact) => format!("set fact {}", crawl_str(fact)),
+        Statement::SetPersistentFact(fact) => format!("set persistent fact {fact:?}"),
+        Statement::AddMoney {
+            amount: added,
+            coin,
+            purse,
+        } => format!("add {} {coin} to {purse:?}", amount(added)),
+        Statement::SpendMoney {
+            amount: spent,
+            coin,
+            purse,
+        } => match purse {
+            Some(purse) => format!("spend {} {coin} from {purse:?}", amount(spent)),
+            None => format!("spend {} {coin}", amount(spent)),
+        },
+        Statement::SpendResource {
+            resource,
+            amount: spent,
+        } => format!("spend {} {resource:?}", amount(spent)),
+        Statement::Travel(terrain) => format!("travel through {terrain:?}"),
+        Statement::Light(light) => format!("light a {light}"),
+        Statement::MoraleCheck { group, morale } => {
+            format!(
+                "check {group:?}'s morale against {}",
+                modifier_value(morale)
+            )
+        }
+        Statement::Encounter(terrain) => format!("roll an encounter in {terrain:?}"),
+        Statement::GenerateRoom => format!(
+            "roll a room on tables {:?}, {:?}, and {:?}",
+            rooms::SHAPES,
+            rooms::EXITS,
+            rooms::CONTENTS
+        ),
+        Statement::TableRoll {
+            table,
+            field,
+            tag,
+            excluding_seen,
+        } => {
+            let filters = table_filters(tag, *excluding_seen);
+            match field {
+                Some(field) => format!("roll on table {table:?} for {field:?}{filters}"),
+                None => format!("roll on table {table:?}{filters}"),
+            }
+        }
+        Statement::TrackHitPoints {
+            entity,
+            amount: hit_points,
+        } => format!("track {entity:?} with {} hit points", amount(hit_points)),
+        Statement::TrackResource { resource, amount } => {
+            format!("track {resource:?} starting at {amount}")
+        }
+        Statement::UsageDie { resource, sides } => {
+            format!("track {resource:?} with a d{sides} usage die")
+        }
+        Statement::UsageRoll(resource) => format!("roll the usage die for {resource:?}"),
+        Statement::WithFacts(_) => "with temporary facts".into(),
+        Statement::Choice { question, .. } => format!("ask {question:?}"),
+        Statement::IfThen {
+            antecedent: condition,
+            consequent,
+        } => format!("if {}, {}", antecedent(condition), describe(consequent)),
+        Statement::MatchingRoll { roll_specifier, .. } => {
+            format!("roll {}", roll(roll_specifier))
+        }
+        Statement::Move {
+            name,
+            roll_specifier,
+            ..
+        } => format!("make move {name:?}, rolling {}", roll(roll_specifier)),
+        Statement::Check {
+            roll_specifier, dc, ..
+        } => format!("roll {} against {}", roll(roll_specifier), amount(dc)),
+        Statement::Save {
+            statblock, save, ..
+        } => format!("roll 1d20 for {statblock:?} to save vs {save:?}"),
+        Statement::MatchingTableRoll { table, .. } => format!("roll on table {table:?}"),
+        Statement::MatchingFact { fact, .. } => format!("look up fact {fact:?}"),
+        Statement::Procedure { declaration, .. } => {
+            format!("define procedure {}", declaration.0)
+        }
+        Statement::Rule(rule) => {
+            let conditions: Vec<String> = rule.conditions.iter().map(antecedent).collect();
+            format!(
+                "define rule deriving fact {:?} while {}",
+                rule.fact,
+                conditions.join(" and ")
+            )
+        }
+    }
+}
+
+fn crawl_str(s: &CrawlStr) -> String {
+    match s {
+        CrawlStr::Str(s) => format!("{s:?}"),
+        CrawlStr::InterpolatedStr {
+            format_string,
+            expressions,
+        } => {
+            let expressions: Vec<String> = expressions.iter().map(describe).collect();
+            format!("{format_string:?} with {}", expressions.join(", "))
+        }
+    }
+}
+
+fn value(value: &ReturnValue) -> String {
+    match value {
+        ReturnValue::Amount(value) => amount(value),
+        ReturnValue::Str(s) => crawl_str(s),
+    }
+}
+
+pub(crate) fn antecedent(antecedent: &Antecedent) -> String {
+    match antecedent {
+        Antecedent::CheckClockFull(clock) => format!("clock {clock:?} is full"),
+        Antecedent::CheckFact(fact) => format!("fact {fact:?} is set"),
+        Antecedent::CheckPersistentFact(fact) => format!("persistent fact {fact:?} is set"),
+        Antecedent::CheckResourceDepleted(resource) => format!("{resource:?} is depleted"),
+        Antecedent::CheckDead(entity) => format!("{entity:?} is dead"),
+        Antecedent::DateIs(date) => format!("the date is {date}"),
+        Antecedent::HasItem { item, owner } => format!("{owner:?} has {item:?}"),
+        Antecedent::InStock { item, settlement } => {
+            format!("{item:?} is in stock{}", at_settlement(settlement))
+        }
+        Antecedent::DiceRoll {
+            target: roll_target,
+            roll_specifier,
+        } => format!("{} rolls {}", roll(roll_specifier), target(roll_target)),
+        Antecedent::DegreeOfSuccess {
+            degree,
+            roll_specifier,
+            dc,
+        } => format!(
+            "{} against {} comes to a {degree}",
+            roll(roll_specifier),
+            amount(dc)
+        ),
+        Antecedent::NaturalRoll {
+            natural: Natural::Critical,
+            roll_specifier,
+        } => format!("{} rolls a die's highest face", roll(roll_specifier)),
+        Antecedent::NaturalRoll {
+            natural: Natural::Fumble,
+            roll_specifier,
+        } => format!("{} rolls a natural 1", roll(roll_specifier)),
+    }
+}
+
+fn amount(amount: &Amount) -> String {
+    match amount {
+        Amount::Fixed(n) => n.to_string(),
+        Amount::Roll(spec) => roll(spec),
+        Amount::Call(name) => name.clone(),
+    }
+}
+
+fn roll(spec: &ModifiedRollSpecifier) -> String {
+    let mut roll = match &spec.base_roll_specifier {
+        Token::RollSpecifier(dice) => dice.to_string(),
+        Token::Str(name) => format!("{name:?}"),
+        token => format!("{token:?}"),
+    };
+    if spec.modifier != 0 {
+        let sign = if spec.modifier < 0 { '-' } else { '+' };
+        roll.push_str(&format!(" {sign} {}", spec.modifier.abs()));
+    }
+    for term in &spec.terms {
+        let sign = if term.negated { '-' } else { '+' };
+        roll.push_str(&format!(" {sign} {}", modifier_value(&term.value)));
+        if let Some(condition) = &term.condition {
+            roll.push_str(&format!(" if {}", antecedent(condition)));
+        }
+    }
+    roll
+}
+
+fn times(multiplier: i32) -> String {
+    match multiplier {
+        1 => String::new(),
+        multiplier => format!(" x {multiplier}"),
+    }
+}
+
+fn at_settlement(settlement: &Option<String>) -> String {
+    match settlement {
+        Some(settlement) => format!(" at {settlement:?}"),
+        None => String::new(),
+    }
+}
+
+fn table_filters(tag: &Option<String>, excluding_seen: bool) -> String {
+    let mut filters = String::new();
+    if let Some(tag) = tag {
+        filters.push_str(&format!(" where tagged {tag:?}"));
+    }
+    if excluding_seen {
+        filters.push_str(", rerolling entries seen before");
+    }
+    filters
+}
+
+fn modifier_value(value: &ModifierValue) -> String {
+    match value {
+        ModifierValue::Fixed(n) => n.to_string(),
+        ModifierValue::Variable(name) => name.clone(),
+        ModifierValue::Fact(fact) => format!("fact {fact:?}"),
+        ModifierValue::Stat { statblock, stat } => format!("stat {statblock:?} {stat:?}"),
+    }
+}
+
+fn target(target: &Token) -> String {
+    match target {
+        Token::Num(n) => n.to_string(),
+        Token::NumRange(low, high) => format!("{low}-{high}"),
+        Token::OverOrEqual(n) => format!("{n}+"),
+        Token::UnderOrEqual(n) => format!("{n}-"),
+        Token::Identifier(name) => name.clone(),
+        token => format!("{token:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    fn parse(source: &str) -> Vec<Statement> {
+        let mut parser = Parser::from_tokens(Scanner::new(source));
+        parser.parse().into_iter().map(|s| s.unwrap()).collect()
+    }
+
+    #[test]
+    fn narrate() {
+        let ast = parse(
+            "procedure camp\n\tif fact? \"party is lost\" => set-fact \"party is hungry\"\n\troll on table \"weather\"\n\tcamp\nend\ncamp\nroll 1d6 + 1\n\t1-3 => reminder \"rain\"\nend\n",
+        );
+        assert_eq!(
+            DryRun::new().narrate(&ast),
+            vec![
+                "define procedure camp",
+                "call camp",
+                "  check whether fact \"party is lost\" is set",
+                "    if so",
+                "      set fact \"party is hungry\"",
+                "  roll on table \"weather\"",
+                "  call camp",
+                "roll 1d6 + 1",
+                "  on 1-3",
+                "    remind \"rain\"",
+            ]
+        );
+    }
+}
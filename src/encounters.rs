@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use crate::dice::DiceRoll;
+use crate::stocking::System;
+
+// An encounter rolls everything about meeting something at once: what's met, on the terrain's
+// encounter table, like "forest encounters"; how many, from the entry's `number appearing`
+// column; how they react; and how far off they are, which depends on the terrain.
+
+/// The encounter table column rolled for how many appear, like `2d4` or `{1d6}`.
+pub const NUMBER_APPEARING: &str = "number appearing";
+
+/// How far off encounters start: a roll, scaled, in some unit, like 2d6 × 10 feet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distance {
+    pub roll: DiceRoll,
+    pub scale: i32,
+    pub unit: String,
+}
+
+impl Distance {
+    pub fn new(roll: &str, scale: i32, unit: &str) -> Self {
+        Distance {
+            roll: roll.parse().expect("distance rolls are valid"),
+            scale,
+            unit: unit.into(),
+        }
+    }
+}
+
+/// How a system rolls encounters: the reaction roll and what its totals mean, and how far off
+/// encounters start in each terrain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Encounters {
+    reaction: DiceRoll,
+    // The highest total for each reaction, lowest first
+    reactions: Vec<(i32, String)>,
+    distances: HashMap<String, Distance>,
+    // For terrain without a distance of its own
+    distance: Distance,
+}
+
+impl Default for Encounters {
+    fn default() -> Self {
+        Self::new(System::Bx)
+    }
+}
+
+impl Encounters {
+    pub fn new(system: System) -> Self {
+        match system {
+            // A reaction on 2d6, meetings in the dungeon 2d6 × 10 feet off, and 4d6 × 10 yards
+            // off in the wilderness
+            System::Bx => Encounters {
+                reaction: "2d6".parse().unwrap(),
+                reactions: [
+                    (2, "hostile"),
+                    (5, "unfriendly"),
+                    (8, "uncertain"),
+                    (11, "indifferent"),
+                    (12, "friendly"),
+                ]
+                .into_iter()
+                .map(|(total, reaction)| (total, reaction.into()))
+                .collect(),
+                distances: HashMap::from([("dungeon".into(), Distance::new("2d6", 10, "feet"))]),
+                distance: Distance::new("4d6", 10, "yards"),
+            },
+        }
+    }
+
+    /// Roll reactions on `roll`, with each reaction given the highest total it's had on, lowest
+    /// first. Totals past the last are its reaction.
+    pub fn with_reactions(mut self, roll: DiceRoll, reactions: &[(i32, &str)]) -> Self {
+        self.reaction = roll;
+        self.reactions = reactions
+            .iter()
+            .map(|(total, reaction)| (*total, reaction.to_string()))
+            .collect();
+        self
+    }
+
+    pub fn with_distance(mut self, terrain: &str, distance: Distance) -> Self {
+        self.distances.insert(terrain.into(), distance);
+        self
+    }
+
+    pub fn reaction_roll(&self) -> &DiceRoll {
+        &self.reaction
+    }
+
+    /// What a reaction roll of `total` means.
+    pub fn reaction(&self, total: i32) -> &str {
+        self.reactions
+            .iter()
+            .find(|(highest, _)| total <= *highest)
+            .or(self.reactions.last())
+            .map_or("", |(_, reaction)| reaction.as_str())
+    }
+
+    pub fn distance(&self, terrain: &str) -> &Distance {
+        self.distances.get(terrain).unwrap_or(&self.distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reactions() {
+        let encounters = Encounters::default();
+        assert_eq!(encounters.reaction(2), "hostile");
+        assert_eq!(encounters.reaction(7), "uncertain");
+        assert_eq!(encounters.reaction(12), "friendly");
+        assert_eq!(encounters.distance("dungeon").unit, "feet");
+        assert_eq!(encounters.distance("forest").unit, "yards");
+
+        let encounters = encounters
+            .with_reactions("1d6".parse().unwrap(), &[(3, "hostile"), (6, "friendly")])
+            .with_distance("forest", Distance::new("1d6", 30, "feet"));
+        assert_eq!(encounters.reaction(9), "friendly");
+        assert_eq!(encounters.distance("forest").scale, 30);
+    }
+}
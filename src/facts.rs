@@ -1,4 +1,8 @@
-use std::collections::HashSet;
+use core::fmt;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::error::CrawlError;
 
@@ -6,43 +10,87 @@ use crate::error::CrawlError;
 // Right now a FactDatabase stores triples in a set -- might as well just store the strings.
 // Would be cool to be able to make datalog-like queries.
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+// Parts are shared rather than copied, as facts are cloned whenever local facts are scoped to a
+// procedure, and an entity is kept both in its facts and as the key they're indexed by.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Fact {
-    entity: String,
-    attribute: String,
-    value: String,
+    entity: Arc<str>,
+    attribute: Arc<str>,
+    value: Arc<str>,
+}
+
+impl Fact {
+    pub fn entity(&self) -> &str {
+        &self.entity
+    }
+
+    pub fn attribute(&self) -> &str {
+        &self.attribute
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for Fact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.entity, self.attribute, self.value)
+    }
 }
 
 impl TryFrom<String> for Fact {
     type Error = CrawlError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if let Some((entity, tail)) = value.split_once(' ') {
-            if let Some((attribute, val)) = tail.split_once(' ') {
-                Ok(Fact {
-                    entity: entity.into(),
-                    attribute: attribute.into(),
-                    value: val.into(),
-                })
-            } else {
-                Err(CrawlError::InterpreterError {
-                    reason: "couldn't convert to Fact".into(),
-                })
-            }
-        } else {
-            Err(CrawlError::InterpreterError {
-                reason: "couldn't convert to Fact".into(),
-            })
-        }
+        Fact::try_from(value.as_str())
+    }
+}
+
+impl TryFrom<&str> for Fact {
+    type Error = CrawlError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let error = || CrawlError::InterpreterError {
+            reason: format!(
+                "fact {value:?} isn't an entity, attribute, and value, like \"party is lost\""
+            ),
+        };
+        let (entity, tail) = value.split_once(' ').ok_or_else(error)?;
+        let (attribute, val) = tail.split_once(' ').ok_or_else(error)?;
+        Ok(Fact {
+            entity: entity.into(),
+            attribute: attribute.into(),
+            value: val.into(),
+        })
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredFacts", into = "StoredFacts")]
 pub struct FactDatabase {
-    // Making this a HashSet may prove too restrictive in the future. Right now, all we
-    // want to do is add and delete triples from a store. If we ever want to query by
-    // entities, attributes, or values, we'd want to change this.
-    pub facts: HashSet<Fact>,
+    // Indexed by entity, so everything about one can be found without scanning every fact
+    by_entity: HashMap<Arc<str>, HashSet<Fact>>,
+}
+
+// How a FactDatabase is saved, which doesn't depend on how it's indexed
+#[derive(Serialize, Deserialize)]
+struct StoredFacts {
+    facts: HashSet<Fact>,
+}
+
+impl From<StoredFacts> for FactDatabase {
+    fn from(stored: StoredFacts) -> Self {
+        FactDatabase::new(stored.facts)
+    }
+}
+
+impl From<FactDatabase> for StoredFacts {
+    fn from(db: FactDatabase) -> Self {
+        StoredFacts {
+            facts: db.by_entity.into_values().flatten().collect(),
+        }
+    }
 }
 
 impl Default for FactDatabase {
@@ -53,19 +101,64 @@ impl Default for FactDatabase {
 
 impl FactDatabase {
     pub fn new(facts: HashSet<Fact>) -> Self {
-        FactDatabase { facts }
+        let mut db = FactDatabase {
+            by_entity: HashMap::new(),
+        };
+        for fact in facts {
+            db.set(fact);
+        }
+        db
     }
 
     pub fn check(&self, fact: &Fact) -> bool {
-        self.facts.contains(fact)
+        self.by_entity
+            .get(&fact.entity)
+            .is_some_and(|facts| facts.contains(fact))
     }
 
     pub fn set(&mut self, fact: Fact) {
-        self.facts.insert(fact);
+        self.by_entity
+            .entry(Arc::clone(&fact.entity))
+            .or_default()
+            .insert(fact);
     }
 
     pub fn clear(&mut self, fact: &Fact) {
-        self.facts.remove(fact);
+        if let Some(facts) = self.by_entity.get_mut(&fact.entity) {
+            facts.remove(fact);
+            if facts.is_empty() {
+                self.by_entity.remove(&fact.entity);
+            }
+        }
+    }
+
+    /// Every fact, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Fact> {
+        self.by_entity.values().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_entity.values().map(HashSet::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_entity.is_empty()
+    }
+
+    /// Every fact about `entity`.
+    pub fn about<'a>(&'a self, entity: &str) -> impl Iterator<Item = &'a Fact> + 'a {
+        self.by_entity.get(entity).into_iter().flatten()
+    }
+
+    /// The values of every fact about `entity`'s `attribute`.
+    pub fn values<'a>(
+        &'a self,
+        entity: &'a str,
+        attribute: &'a str,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.about(entity)
+            .filter(move |fact| &*fact.attribute == attribute)
+            .map(|fact| &*fact.value)
     }
 }
 
@@ -84,4 +177,57 @@ mod tests {
             },
         )
     }
+
+    #[test]
+    fn values() {
+        let mut db = FactDatabase::default();
+        db.set(Fact::try_from(String::from("fighter str-mod 2")).unwrap());
+        db.set(Fact::try_from(String::from("fighter dex-mod 1")).unwrap());
+        assert_eq!(
+            db.values("fighter", "str-mod").collect::<Vec<_>>(),
+            vec!["2"]
+        );
+        assert_eq!(db.values("wizard", "str-mod").count(), 0);
+    }
+
+    #[test]
+    fn about() {
+        let mut db = FactDatabase::default();
+        let str_mod = Fact::try_from(String::from("fighter str-mod 2")).unwrap();
+        db.set(str_mod.clone());
+        db.set(Fact::try_from(String::from("wizard int-mod 3")).unwrap());
+        assert_eq!(db.about("fighter").collect::<Vec<_>>(), vec![&str_mod]);
+        assert_eq!(db.len(), 2);
+
+        db.clear(&str_mod);
+        assert_eq!(db.about("fighter").count(), 0);
+        assert!(!db.check(&str_mod));
+    }
+
+    #[test]
+    fn shared_parts() {
+        let mut db = FactDatabase::default();
+        let fact = Fact::try_from("party is lost").unwrap();
+        db.set(fact.clone());
+        let (entity, facts) = db.by_entity.iter().next().unwrap();
+        assert!(Arc::ptr_eq(entity, &fact.entity));
+        assert!(Arc::ptr_eq(
+            &facts.iter().next().unwrap().value,
+            &fact.value
+        ));
+    }
+
+    #[test]
+    fn stored_format() {
+        let db = FactDatabase::new(HashSet::from([Fact::try_from(String::from(
+            "party is lost",
+        ))
+        .unwrap()]));
+        let json = serde_json::to_string(&db).unwrap();
+        assert_eq!(
+            json,
+            r#"{"facts":[{"entity":"party","attribute":"is","value":"lost"}]}"#
+        );
+        assert_eq!(serde_json::from_str::<FactDatabase>(&json).unwrap(), db);
+    }
 }
@@ -0,0 +1,24 @@
+use serde::Serialize;
+
+// Factions' fronts are clocks kept for the campaign, and advanced between sessions with `advance
+// faction fronts`. That rolls once for each front on the "faction fronts" table, advancing it by
+// the entry's column named for the faction, if the table has one, or else by the entry. Either
+// can be a number or a roll, like `1d3`. Fronts that fill up are completed, and stop advancing.
+
+/// The table `advance faction fronts` rolls on, unless it's given another.
+pub const FRONTS_TABLE: &str = "faction fronts";
+
+/// How far a faction's front advanced, and how full it is now.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FrontAdvance {
+    pub faction: String,
+    pub by: i32,
+    pub filled: i32,
+    pub segments: i32,
+}
+
+impl FrontAdvance {
+    pub fn is_complete(&self) -> bool {
+        self.filled >= self.segments
+    }
+}
@@ -0,0 +1,114 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::scanner::{Scanner, Token};
+
+// What kind of thing each part of a script is, for syntax highlighting in editors and the REPL
+// without scanning it again. Parts that don't scan are left out, so a script is highlighted as
+// far as possible while it's being typed.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenCategory {
+    Keyword,
+    String,
+    // Dice, like `2d6` or `d8`
+    RollSpec,
+    Identifier,
+    // Numbers and ranges of them, like `1-3`
+    Number,
+    Operator,
+}
+
+/// A part of a script, by its byte range in the source.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SemanticToken {
+    pub span: Range<usize>,
+    pub category: TokenCategory,
+}
+
+/// The categorized parts of `source`, in order. Whitespace and line breaks aren't included.
+pub fn semantic_tokens(source: &str) -> Vec<SemanticToken> {
+    let mut scanner = Scanner::new(source);
+    let tokens = scanner.tokens();
+    tokens
+        .iter()
+        .zip(scanner.spans())
+        .filter_map(|(token, span)| {
+            let category = category(token.as_ref().ok()?)?;
+            Some(SemanticToken {
+                span: span.clone(),
+                category,
+            })
+        })
+        .collect()
+}
+
+fn category(token: &Token) -> Option<TokenCategory> {
+    let category = match token {
+        Token::Str(_) => TokenCategory::String,
+        Token::RollSpecifier(_) | Token::Die(_) => TokenCategory::RollSpec,
+        Token::Identifier(_) => TokenCategory::Identifier,
+        Token::Num(_)
+        | Token::NumRange(..)
+        | Token::OverOrEqual(_)
+        | Token::UnderOrEqual(_)
+        | Token::Version(..) => TokenCategory::Number,
+        Token::Arrow
+        | Token::Colon
+        | Token::Equals
+        | Token::Minus
+        | Token::Percent
+        | Token::Plus => TokenCategory::Operator,
+        Token::Eof | Token::Indent | Token::Newline => return None,
+        _ => TokenCategory::Keyword,
+    };
+    Some(category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories() {
+        let source = "roll 2d6 + 1\n\t7-9 => reminder \"close call\"\nend\ncamp\n";
+        let tokens: Vec<(&str, TokenCategory)> = semantic_tokens(source)
+            .into_iter()
+            .map(|token| (&source[token.span], token.category))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                ("roll", TokenCategory::Keyword),
+                ("2d6", TokenCategory::RollSpec),
+                ("+", TokenCategory::Operator),
+                ("1", TokenCategory::Number),
+                ("7-9", TokenCategory::Number),
+                ("=>", TokenCategory::Operator),
+                ("reminder", TokenCategory::Keyword),
+                ("\"close call\"", TokenCategory::String),
+                ("end", TokenCategory::Keyword),
+                ("camp", TokenCategory::Identifier),
+            ]
+        );
+    }
+
+    #[test]
+    fn unscannable() {
+        let source = "reminder \"rest\" ^ \"ok\"";
+        let categories: Vec<TokenCategory> = semantic_tokens(source)
+            .into_iter()
+            .map(|token| token.category)
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                TokenCategory::Keyword,
+                TokenCategory::String,
+                TokenCategory::String
+            ]
+        );
+    }
+}
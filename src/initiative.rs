@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+/// Everyone in a fight, in the order they act, and whose turn it is.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Initiative {
+    combatants: Vec<Combatant>,
+    // The index of whoever acts when the turn next advances
+    next: usize,
+    // Whose turn it is, or `None` before turns start, or once they've left the fight
+    current: Option<String>,
+    round: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Combatant {
+    pub name: String,
+    pub initiative: i32,
+}
+
+impl Initiative {
+    /// Add a combatant, acting last until the order is sorted. Returns whether they were added,
+    /// which they aren't if they're already in the fight.
+    pub fn add(&mut self, name: &str, initiative: i32) -> bool {
+        if self.position(name).is_some() {
+            return false;
+        }
+        self.combatants.push(Combatant {
+            name: name.into(),
+            initiative,
+        });
+        true
+    }
+
+    /// Take a combatant out of the fight, returning whether they were in it.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let Some(idx) = self.position(name) else {
+            return false;
+        };
+        self.combatants.remove(idx);
+        if idx < self.next {
+            self.next -= 1;
+        }
+        if self.current.as_deref() == Some(name) {
+            self.current = None;
+        }
+        true
+    }
+
+    /// Order combatants by initiative, highest first, and start turns over from the first round.
+    /// Ties keep the order they were added in.
+    pub fn sort(&mut self) {
+        self.combatants
+            .sort_by_key(|combatant| std::cmp::Reverse(combatant.initiative));
+        self.next = 0;
+        self.current = None;
+        self.round = 0;
+    }
+
+    /// Move on to the next combatant's turn, starting a new round after the last of them.
+    pub fn advance(&mut self) -> Option<&Combatant> {
+        if self.combatants.is_empty() {
+            return None;
+        }
+        if self.round == 0 || self.next >= self.combatants.len() {
+            self.next = 0;
+            self.round += 1;
+        }
+        let combatant = &self.combatants[self.next];
+        self.next += 1;
+        self.current = Some(combatant.name.clone());
+        Some(combatant)
+    }
+
+    /// Whoever's turn it is.
+    pub fn current(&self) -> Option<&Combatant> {
+        let current = self.current.as_deref()?;
+        self.combatants
+            .iter()
+            .find(|combatant| combatant.name == current)
+    }
+
+    pub fn combatants(&self) -> &[Combatant] {
+        &self.combatants
+    }
+
+    /// The round it is, or 0 before turns start.
+    pub fn round(&self) -> i32 {
+        self.round
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.combatants
+            .iter()
+            .position(|combatant| combatant.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(initiative: &Initiative) -> Vec<&str> {
+        initiative
+            .combatants()
+            .iter()
+            .map(|combatant| combatant.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn turns() {
+        let mut initiative = Initiative::default();
+        assert!(initiative.advance().is_none());
+        assert!(initiative.add("goblin", 12));
+        assert!(initiative.add("fighter", 17));
+        assert!(initiative.add("wizard", 12));
+        assert!(!initiative.add("goblin", 3));
+        initiative.sort();
+        assert_eq!(names(&initiative), ["fighter", "goblin", "wizard"]);
+        assert_eq!(initiative.round(), 0);
+
+        assert_eq!(initiative.advance().unwrap().name, "fighter");
+        assert_eq!(initiative.round(), 1);
+        assert_eq!(initiative.advance().unwrap().name, "goblin");
+        assert_eq!(initiative.advance().unwrap().name, "wizard");
+        assert_eq!(initiative.advance().unwrap().name, "fighter");
+        assert_eq!(initiative.round(), 2);
+        assert_eq!(initiative.current().unwrap().name, "fighter");
+    }
+
+    #[test]
+    fn remove() {
+        let mut initiative = Initiative::default();
+        for (name, roll) in [("fighter", 17), ("goblin", 12), ("wizard", 8)] {
+            initiative.add(name, roll);
+        }
+        initiative.advance();
+        initiative.advance();
+        // Taking out whoever's turn it is leaves it nobody's until the turn advances.
+        assert!(initiative.remove("goblin"));
+        assert!(initiative.current().is_none());
+        assert_eq!(initiative.advance().unwrap().name, "wizard");
+        assert!(initiative.remove("fighter"));
+        assert_eq!(initiative.current().unwrap().name, "wizard");
+        assert_eq!(initiative.advance().unwrap().name, "wizard");
+        assert_eq!(initiative.round(), 2);
+        assert!(!initiative.remove("fighter"));
+    }
+}
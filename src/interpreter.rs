@@ -1,60 +1,807 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, LazyLock};
 
-use crate::dice::{DiceRoll, DiceRollResult};
+use crate::bytecode::{self, CompiledRoll, Condition, Instruction};
+use crate::calendar::{Calendar, DateUnit};
+use crate::clocks::Clock;
+use crate::debugger::StepHook;
+use crate::dice::{
+    DiceLimits, DicePool, DiceRoll, DiceRollResult, Die, RollOutcome, is_usage_die,
+    step_down_usage_die,
+};
+use crate::downtime::{self, DowntimeReport};
+use crate::dry_run::{self, DryRun};
+use crate::encounters::{Distance, Encounters, NUMBER_APPEARING};
 use crate::error::CrawlError;
-use crate::facts::FactDatabase;
+use crate::facts::{Fact, FactDatabase};
+use crate::fronts::FrontAdvance;
+use crate::initiative::{Combatant, Initiative};
+use crate::lights::{self, BurningLight, Lights};
+use crate::locale::Locale;
+use crate::market::{self, Market, PriceListItem, Settlement};
+use crate::money::Denominations;
+use crate::names::NameTable;
+use crate::oracle::{
+    self, CHAOS_KEY, DEFAULT_CHAOS, Likelihood, MAX_CHAOS, MIN_CHAOS, ODDS_KEY, Odds, Oracle,
+};
+use crate::output::OutputSink;
 use crate::parser::{
-    Antecedent, CrawlStr, MatchingRollArm, ModifiedRollSpecifier, ProcedureDeclaration, Statement,
+    Amount, Antecedent, CheckArm, ChoiceArm, CrawlStr, DowntimeArm, MatchingRollArm,
+    ModifiedRollSpecifier, ModifierValue, Natural, Parser, ProcedureDeclaration, ReturnValue, Rule,
+    Statement, TableRollArm,
 };
-use crate::scanner::Token;
-use crate::tables::Table;
+use crate::prompt::{DicePrompt, Prompt};
+use crate::rolls::Degree;
+use crate::rooms;
+use crate::rumors::Rumor;
+use crate::scanner::{Scanner, Token};
+use crate::scenes::{self, RandomEvent, SceneOutcome};
+use crate::statblocks::{self, Statblock};
+use crate::storage::{Storage, campaign_key, check_campaign_name};
+use crate::tables::{Table, TableEntry};
+use crate::tally::{self, Tally};
+use crate::travel::{self, Travel};
+use crate::visitor::{Visitor, walk_statement};
+use crate::weather;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum StatementRecord {
+    AddCombatant {
+        combatant: String,
+        initiative: i32,
+    },
+    // How many of the item the owner has now
+    AddItem {
+        item: String,
+        owner: String,
+        count: i32,
+    },
+    // The coins added, and the purse's balance after, in the fewest coins
+    AddMoney {
+        purse: String,
+        added: String,
+        balance: String,
+    },
+    AdvanceClock {
+        clock: String,
+        filled: i32,
+        segments: i32,
+    },
+    // How many days the date moved on, and the date it came to
+    AdvanceDate {
+        days: i32,
+        date: String,
+    },
+    // XP awarded, and the session's and campaign's XP after it
+    AwardXp {
+        xp: i32,
+        session: i32,
+        campaign: i32,
+    },
+    // Treasure awarded, and the session's and campaign's treasure after it
+    AwardTreasure {
+        treasure: String,
+        session: String,
+        campaign: String,
+    },
+    // The XP and treasure awarded, and each character's share of the session's
+    XpReport {
+        party_size: i32,
+        session_xp: i32,
+        campaign_xp: i32,
+        xp_share: i32,
+        session_treasure: String,
+        treasure_share: String,
+    },
+    // How many turns passed, how many have all told, and the lights that went out
+    AdvanceTime {
+        by: i32,
+        turns: i32,
+        guttered: Vec<String>,
+    },
+    // Whose turn it became, in which round
+    AdvanceTurn {
+        combatant: String,
+        round: i32,
+    },
+    // What the weather was, and what it turned to
+    AdvanceWeather {
+        from: String,
+        to: String,
+    },
+    ApplyModifier(AppliedModifier),
+    // What was asserted, which held
+    Assert(String),
+    // The campaign switched to
+    Campaign(String),
     CheckFact(bool),
     CheckPersistentFact(bool),
+    Choice {
+        question: String,
+        chosen: String,
+        consequent: Box<StatementRecord>,
+    },
     ClearFact(String),
     ClearPersistentFact(String),
+    CreateClock {
+        clock: String,
+        segments: i32,
+    },
+    CreateFront {
+        faction: String,
+        segments: i32,
+    },
+    // Each front that advanced, in order by faction; the full ones were completed by it
+    AdvanceFronts(Vec<FrontAdvance>),
+    // Only the rumor, since its truth is kept from records until it's revealed
+    AddRumor(String),
+    RevealRumor {
+        rumor: String,
+        truth: bool,
+    },
+    // The rumors heard, which are all there were left if there weren't enough
+    HearRumors(Vec<String>),
+    // Every rumor heard, with its truth once it's revealed
+    RumorReport(Vec<(String, Option<bool>)>),
+    Damage {
+        entity: String,
+        damage: i32,
+        remaining: i32,
+    },
     IfThen {
         antecedent: bool,
         consequent: Option<Box<StatementRecord>>,
     },
+    Let {
+        name: String,
+        value: i32,
+    },
+    CurrentDate(String),
+    ChaosFactor(i32),
+    // The d10 rolled against the chaos factor, and the event that interrupted the scene, if one
+    // did
+    SceneCheck {
+        rolled: i32,
+        chaos: i32,
+        outcome: SceneOutcome,
+        event: Option<RandomEvent>,
+    },
+    // The entry a table's pinned to, for how many rolls
+    PinTable {
+        table: String,
+        entry: String,
+        rolls: i32,
+    },
+    // The chaos factor before and after, which stays put at its lowest or highest
+    AdjustChaos {
+        from: i32,
+        to: i32,
+    },
     LoadTable(String),
+    LoadNames(String),
+    // The names of the statblocks loaded from a file
+    LoadStatblocks {
+        file: String,
+        statblocks: Vec<String>,
+    },
+    // A name put together from a name table
+    NameRoll {
+        names: String,
+        name: String,
+    },
     MatchingRoll {
         matched_target: Option<Token>,
         consequent: Option<Box<StatementRecord>>,
     },
+    Check {
+        total: i32,
+        dc: i32,
+        degree: Degree,
+        consequent: Option<Box<StatementRecord>>,
+    },
+    Save {
+        statblock: String,
+        save: String,
+        total: i32,
+        target: i32,
+        degree: Degree,
+        consequent: Option<Box<StatementRecord>>,
+    },
+    Move {
+        name: String,
+        total: i32,
+        matched_target: Option<Token>,
+        consequent: Option<Box<StatementRecord>>,
+    },
+    MatchingFact {
+        fact: String,
+        values: Vec<String>,
+        // The pattern of the arm matched, if any did
+        matched: Option<String>,
+        consequent: Option<Box<StatementRecord>>,
+    },
+    MatchingTableRoll {
+        table: String,
+        entry: String,
+        // Whether the table was pinned to the entry
+        pinned: bool,
+        // The pattern of the arm matched, if any did
+        matched: Option<String>,
+        consequent: Option<Box<StatementRecord>>,
+    },
     NontargetedRoll(i32),
+    Oracle {
+        question: String,
+        likelihood: Likelihood,
+        rolled: i32,
+        answer: bool,
+    },
     ProcedureCall {
         identifier: String,
         records: Vec<Box<StatementRecord>>,
     },
     ProcedureDefinition(String),
+    // The fact the rule derives
+    RuleDefinition(String),
+    GeneratorDefinition(String),
+    // The name given to a roll with `define roll`
+    RollDefinition(String),
+    RemoveCombatant(String),
+    // How many of the item the owner has left
+    RemoveItem {
+        item: String,
+        owner: String,
+        count: i32,
+    },
+    RemoveModifier(AppliedModifier),
+    // The combatants, in the order they act
+    SortCombatants(Vec<Combatant>),
+    Generated {
+        generator: String,
+        value: String,
+    },
+    Include {
+        path: String,
+        records: Vec<Box<StatementRecord>>,
+    },
+    HostCall {
+        identifier: String,
+        value: String,
+    },
     Reminder(String),
+    // Every attribute and value known about the entity, local and persistent
+    FactReport {
+        entity: String,
+        facts: Vec<(String, String)>,
+    },
+    // Every item the owner has, and how many of each
+    InventoryReport {
+        owner: String,
+        items: Vec<(String, i32)>,
+    },
+    Return(String),
     SetFact(String),
     SetPersistentFact(String),
-    TableRoll(String),
+    // The coins spent, and the purse's balance after, in the fewest coins
+    SpendMoney {
+        purse: String,
+        spent: String,
+        balance: String,
+    },
+    SpendResource {
+        resource: String,
+        spent: i32,
+        remaining: i32,
+    },
+    TrackHitPoints {
+        entity: String,
+        hit_points: i32,
+    },
+    TrackResource {
+        resource: String,
+        remaining: i32,
+    },
+    // What a travel turn came to, with `None` for checks that didn't come up or weren't made
+    Travel {
+        terrain: String,
+        lost: bool,
+        encounter: Option<String>,
+        weather: Option<String>,
+        discovery: Option<String>,
+    },
+    // What was met, how many if the table says, how they reacted, and how far off they were
+    Encounter {
+        terrain: String,
+        creature: String,
+        number: Option<i32>,
+        reaction: String,
+        distance: String,
+    },
+    // The date downtime came to, the activities run, and what each character spent and got
+    Downtime {
+        weeks: i32,
+        date: String,
+        records: Vec<Box<StatementRecord>>,
+        reports: Vec<DowntimeReport>,
+    },
+    LoadPrices(String),
+    // The price in the fewest coins
+    Price {
+        item: String,
+        price: String,
+    },
+    // The price after haggling, and the percent the roll changed it by
+    Haggle {
+        item: String,
+        total: i32,
+        percent: i32,
+        price: String,
+    },
+    // A room rolled with `generate room`, numbered in the order they're generated
+    GenerateRoom {
+        number: i32,
+        shape: String,
+        exits: String,
+        contents: String,
+    },
+    // A light lit, and how many turns it burns for
+    Light {
+        light: String,
+        turns: i32,
+    },
+    // What a morale check rolled against the group's morale, and whether they held
+    MoraleCheck {
+        group: String,
+        total: i32,
+        morale: i32,
+        held: bool,
+    },
+    UsageDie {
+        resource: String,
+        sides: i32,
+    },
+    // What the statements in a `with-facts` block did
+    WithFacts(Vec<Box<StatementRecord>>),
+    UsageRoll {
+        resource: String,
+        rolled: i32,
+        // The die to roll next time, or `None` if the resource is used up
+        die: Option<i32>,
+    },
+    TableRoll {
+        table: String,
+        entry: String,
+        // Whether the table was pinned to the entry
+        pinned: bool,
+    },
+}
+
+impl StatementRecord {
+    /// What the statement comes to, where it's something that can be interpolated or bound: a
+    /// roll's total, an entry rolled, or what a procedure returned.
+    pub fn value(&self) -> Option<String> {
+        match self {
+            StatementRecord::NontargetedRoll(total)
+            | StatementRecord::Let { value: total, .. }
+            | StatementRecord::ChaosFactor(total) => Some(total.to_string()),
+            StatementRecord::HostCall { value, .. }
+            | StatementRecord::Generated { value, .. }
+            | StatementRecord::Return(value)
+            | StatementRecord::NameRoll { name: value, .. }
+            | StatementRecord::CurrentDate(value)
+            | StatementRecord::Price { price: value, .. }
+            | StatementRecord::Haggle { price: value, .. }
+            | StatementRecord::TableRoll { entry: value, .. } => Some(value.clone()),
+            // A procedure comes to its `return`, which ends it, or else its last statement.
+            StatementRecord::ProcedureCall { records, .. }
+            | StatementRecord::WithFacts(records) => {
+                records.last().and_then(|record| record.value())
+            }
+            StatementRecord::Choice { consequent, .. } => consequent.value(),
+            StatementRecord::IfThen {
+                consequent: Some(consequent),
+                ..
+            }
+            | StatementRecord::MatchingRoll {
+                consequent: Some(consequent),
+                ..
+            }
+            | StatementRecord::Check {
+                consequent: Some(consequent),
+                ..
+            }
+            | StatementRecord::Save {
+                consequent: Some(consequent),
+                ..
+            }
+            | StatementRecord::Move {
+                consequent: Some(consequent),
+                ..
+            }
+            | StatementRecord::MatchingTableRoll {
+                consequent: Some(consequent),
+                ..
+            }
+            | StatementRecord::MatchingFact {
+                consequent: Some(consequent),
+                ..
+            } => consequent.value(),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredProcedure", into = "StoredProcedure")]
 pub struct CrawlProcedure {
     identifier: String,
-    body: Vec<Statement>,
+    body: Arc<[Statement]>,
+    // The namespace it was defined in, which calls in its body are resolved against
+    namespace: Arc<str>,
+    // The body compiled, which is what's run when it's called
+    code: Arc<[Instruction]>,
+}
+
+// How a procedure is saved, without its compiled body, which is compiled again when it's loaded
+#[derive(Serialize, Deserialize)]
+struct StoredProcedure {
+    identifier: String,
+    body: Arc<[Statement]>,
+    #[serde(default)]
+    namespace: Arc<str>,
+}
+
+impl From<StoredProcedure> for CrawlProcedure {
+    fn from(stored: StoredProcedure) -> Self {
+        let mut procedure = CrawlProcedure::new(stored.identifier, stored.body);
+        procedure.namespace = stored.namespace;
+        procedure
+    }
+}
+
+impl From<CrawlProcedure> for StoredProcedure {
+    fn from(procedure: CrawlProcedure) -> Self {
+        StoredProcedure {
+            identifier: procedure.identifier,
+            body: procedure.body,
+            namespace: procedure.namespace,
+        }
+    }
 }
 
 impl CrawlProcedure {
-    pub fn new(identifier: String, body: Vec<Statement>) -> Self {
-        CrawlProcedure { identifier, body }
+    pub fn new(identifier: String, body: Arc<[Statement]>) -> Self {
+        let code = bytecode::compile(&body).into();
+        CrawlProcedure {
+            identifier,
+            body,
+            namespace: Arc::default(),
+            code,
+        }
+    }
+}
+
+/// Where a statement being evaluated lives: its index in the program, its index in the body
+/// of a procedure, or its index in an included file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatementLocation {
+    TopLevel(usize),
+    Procedure(String, usize),
+    Include(String, usize),
+}
+
+/// Everything a session has built up - facts, clocks, resources, usage dice, loaded tables, and
+/// defined procedures - so it can be saved and picked up again later.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub persistent_facts: FactDatabase,
+    pub local_facts: FactDatabase,
+    #[serde(default)]
+    pub clocks: HashMap<String, Clock>,
+    #[serde(default)]
+    pub resources: HashMap<String, i32>,
+    #[serde(default)]
+    pub usage_dice: HashMap<String, Option<i32>>,
+    #[serde(default)]
+    pub initiative: Initiative,
+    #[serde(default)]
+    pub hit_points: HashMap<String, i32>,
+    #[serde(default)]
+    pub inventories: HashMap<String, BTreeMap<String, i32>>,
+    #[serde(default)]
+    pub purses: HashMap<String, i32>,
+    #[serde(default)]
+    pub date: i32,
+    #[serde(default)]
+    pub turns: i32,
+    #[serde(default)]
+    pub rooms: i32,
+    #[serde(default)]
+    pub session_tally: Tally,
+    #[serde(default)]
+    pub campaign_tally: Tally,
+    #[serde(default)]
+    pub fronts: BTreeMap<String, Clock>,
+    #[serde(default)]
+    pub rumors: Vec<Rumor>,
+    #[serde(default = "default_chaos")]
+    pub chaos: i32,
+    #[serde(default)]
+    pub seen_entries: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub pins: HashMap<String, (String, i32)>,
+    #[serde(default)]
+    pub burning: Vec<BurningLight>,
+    #[serde(default)]
+    pub variables: HashMap<String, i32>,
+    pub tables: HashMap<String, Table>,
+    #[serde(default)]
+    pub names: HashMap<String, NameTable>,
+    #[serde(default)]
+    pub statblocks: HashMap<String, Statblock>,
+    #[serde(default)]
+    pub prices: HashMap<String, PriceListItem>,
+    pub procedures: HashMap<String, CrawlProcedure>,
+    #[serde(default)]
+    pub generators: HashMap<String, String>,
+    #[serde(default)]
+    pub named_rolls: HashMap<String, ModifiedRollSpecifier>,
+    #[serde(default)]
+    pub applied_modifiers: Vec<AppliedModifier>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// A modifier added to every roll, or every use of the roll named `rolls`, until it's removed -
+/// a blessing, or a curse.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AppliedModifier {
+    pub modifier: i32,
+    pub rolls: Option<String>,
+}
+
+impl StateSnapshot {
+    pub fn to_json(&self) -> Result<String, CrawlError> {
+        serde_json::to_string_pretty(self).map_err(|error| CrawlError::InterpreterError {
+            reason: format!("failed to serialize state ({error})"),
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, CrawlError> {
+        serde_json::from_str(json).map_err(|error| CrawlError::InterpreterError {
+            reason: format!("failed to deserialize state ({error})"),
+        })
+    }
+}
+
+/// A native function scripts can call like a procedure. It's handed the call's arguments, and
+/// what it returns is recorded, or substituted into an interpolated string.
+pub type HostFunction = Box<dyn FnMut(&[String]) -> Result<String, CrawlError> + Send>;
+
+type FactSetListener = Box<dyn FnMut(&Fact) + Send>;
+type TableRollListener = Box<dyn FnMut(&str, &str) + Send>;
+type ProcedureCallListener = Box<dyn FnMut(&str) + Send>;
+
+// Closures called as things happen, so embedders can react live.
+#[derive(Default)]
+struct Listeners {
+    fact_set: Vec<FactSetListener>,
+    table_roll: Vec<TableRollListener>,
+    procedure_call: Vec<ProcedureCallListener>,
+}
+
+// A roll embedded in a table entry, like `{1d4 + 1}`.
+static EMBEDDED_ROLL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\s*(\d+d\d+\s*(?:[+-]\s*\d+)?)\s*\}").unwrap());
+
+const DEFAULT_MAX_CALL_DEPTH: usize = 256;
+const DEFAULT_MAX_STEPS: usize = 1_000_000;
+/// How many of the latest rolls are kept in the roll history.
+pub const ROLL_HISTORY_LEN: usize = 100;
+/// The storage key persistent facts are saved under, as JSON.
+pub const PERSISTENT_FACTS_KEY: &str = "persistent-facts.json";
+/// The storage key inventories are saved under, next to the persistent facts.
+pub const INVENTORIES_KEY: &str = "inventories.json";
+/// The storage key the date is saved under, next to the persistent facts.
+pub const DATE_KEY: &str = "date.json";
+/// The storage key the campaign's XP and treasure are saved under, next to the persistent facts.
+pub const TALLY_KEY: &str = "tally.json";
+/// The storage key factions' fronts are saved under, next to the persistent facts.
+pub const FRONTS_KEY: &str = "fronts.json";
+/// The storage key rumors are saved under, next to the persistent facts.
+pub const RUMORS_KEY: &str = "rumors.json";
+/// The storage key the table entries seen are saved under, next to the persistent facts.
+pub const SEEN_KEY: &str = "seen.json";
+/// The storage key purses are saved under, next to the persistent facts.
+pub const PURSES_KEY: &str = "purses.json";
+// What's kept for the campaign, loaded and saved in this order.
+const CAMPAIGN_KEYS: [&str; 9] = [
+    PERSISTENT_FACTS_KEY,
+    INVENTORIES_KEY,
+    PURSES_KEY,
+    DATE_KEY,
+    TALLY_KEY,
+    FRONTS_KEY,
+    RUMORS_KEY,
+    CHAOS_KEY,
+    SEEN_KEY,
+];
+
+// Replace `state` with what's saved under `key` as JSON, if anything is. `what` names it if it
+// can't be read.
+fn load_json<T: DeserializeOwned>(
+    storage: &mut dyn Storage,
+    key: &str,
+    what: &str,
+    state: &mut T,
+) -> Result<(), CrawlError> {
+    if let Some(json) = storage.read(key)? {
+        *state = serde_json::from_str(&json).map_err(|error| CrawlError::InterpreterError {
+            reason: format!("failed to load {what} ({error})"),
+        })?;
+    }
+    Ok(())
+}
+
+// Save `state` under `key` as JSON. Sessions that never carry anything, see a day go by, or win
+// anything don't leave a file behind, so `unset` state is only saved over what was saved before.
+fn save_json<T: Serialize>(
+    storage: &mut dyn Storage,
+    key: &str,
+    what: &str,
+    state: &T,
+    unset: bool,
+) -> Result<(), CrawlError> {
+    if unset && storage.read(key)?.is_none() {
+        return Ok(());
+    }
+    let json = serde_json::to_string(state).map_err(|error| CrawlError::InterpreterError {
+        reason: format!("failed to save {what} ({error})"),
+    })?;
+    storage.write(key, &json)
+}
+
+fn default_chaos() -> i32 {
+    DEFAULT_CHAOS
+}
+
+// The tables a script loads.
+#[derive(Default)]
+struct TableLoads(Vec<String>);
+
+impl Visitor for TableLoads {
+    fn visit_statement(&mut self, statement: &Statement) {
+        if let Statement::LoadTable { table, .. }
+        | Statement::LoadNames(table)
+        | Statement::LoadStatblocks(table)
+        | Statement::LoadPrices(table) = statement
+        {
+            self.0.push(table.clone());
+        }
+        walk_statement(self, statement);
     }
 }
 
 pub struct Interpreter {
     procedures: HashMap<String, CrawlProcedure>,
+    // Defined inside the procedure running, and scoped to it like local facts
+    local_procedures: HashMap<String, CrawlProcedure>,
+    // Templates, by the name of the generator they were declared with
+    generators: HashMap<String, String>,
+    // Defined with `define roll`, with any named roll they were defined as filled in
+    named_rolls: HashMap<String, ModifiedRollSpecifier>,
+    // Applied with `apply modifier`, the last applied last
+    applied_modifiers: Vec<AppliedModifier>,
+    functions: HashMap<String, HostFunction>,
     tables: HashMap<String, Table>,
+    // Name tables, by the name they were loaded with
+    names: HashMap<String, NameTable>,
+    // Loaded with `load statblocks`, by name
+    statblocks: HashMap<String, Statblock>,
+    // Loaded with `load prices`, by item
+    prices: HashMap<String, PriceListItem>,
+    // The character whose downtime activity is running
+    downtime_character: Option<String>,
+    // Loaded with `no-clamp`, so reloading them keeps clamping off
+    unclamped_tables: HashSet<String>,
+    // Tables' CSV read ahead of time, or `None` if there was nothing to read, so loading them
+    // doesn't wait on IO
+    prefetched_tables: HashMap<String, Option<String>>,
     pub persistent_facts: FactDatabase,
     pub local_facts: FactDatabase,
+    rules: Vec<Rule>,
+    // What the rules derive from the facts set now, kept up to date as facts change
+    derived_facts: FactDatabase,
+    clocks: HashMap<String, Clock>,
+    // How much of each tracked resource is left
+    resources: HashMap<String, i32>,
+    // The die each resource's usage is rolled on, or `None` once it's used up
+    usage_dice: HashMap<String, Option<i32>>,
+    // The combatants in a fight, and whose turn it is
+    initiative: Initiative,
+    // How many hit points each entity tracked has left
+    hit_points: HashMap<String, i32>,
+    // How many of each item everyone has, by owner
+    inventories: HashMap<String, BTreeMap<String, i32>>,
+    // How much money is in each purse, in the smallest coin, kept for the campaign
+    purses: HashMap<String, i32>,
+    denominations: Denominations,
+    travel: Travel,
+    encounters: Encounters,
+    market: Market,
+    // Days since the calendar's first day
+    date: i32,
+    // Dungeon turns passed with `advance time`, and the lights burning down as they do
+    turns: i32,
+    // How many rooms `generate room` has rolled
+    rooms: i32,
+    burning: Vec<BurningLight>,
+    lights: Lights,
+    // XP and treasure awarded this session, and in the campaign, to share among the party
+    session_tally: Tally,
+    campaign_tally: Tally,
+    party_size: i32,
+    // Factions' fronts, kept for the campaign
+    fronts: BTreeMap<String, Clock>,
+    // Rumors, in the order they were added, kept for the campaign
+    rumors: Vec<Rumor>,
+    // The oracle's chaos factor, kept for the campaign
+    chaos: i32,
+    // The entries that have come up on tables rolled excluding seen ones, by table, kept for the
+    // campaign
+    seen_entries: BTreeMap<String, Vec<String>>,
+    // The entry each pinned table comes up with on rolls made on it directly, and for how many
+    // more rolls
+    pins: HashMap<String, (String, i32)>,
+    calendar: Calendar,
+    // Bound with `let`, and scoped like local facts
+    variables: HashMap<String, i32>,
+    // Set by the statement list running, to warn about setting one twice
+    facts_set: HashSet<Fact>,
+    oracle: Oracle,
+    // Read from the campaign's odds file, and used before the oracle's own
+    campaign_odds: HashMap<Likelihood, Odds>,
+    trace: bool,
+    call_depth: usize,
+    // Set by `return` until the procedure it's in has stopped
+    returning: bool,
+    max_call_depth: usize,
+    steps: usize,
+    max_steps: usize,
+    dice_limits: DiceLimits,
+    step_hook: Option<Box<dyn StepHook>>,
+    rng: StdRng,
+    record_rolls: bool,
+    rolls: Vec<RollOutcome>,
+    // The latest rolls, oldest first
+    roll_history: VecDeque<DiceRollResult>,
+    output: Option<Box<dyn OutputSink>>,
+    // Warnings are written in its strings
+    locale: Locale,
+    prompt: Option<Box<dyn Prompt>>,
+    // Asked for every roll's faces instead of rolling, if set
+    dice_prompt: Option<Box<dyn DicePrompt>>,
+    storage: Option<Box<dyn Storage>>,
+    // Persistent facts are kept in this campaign's directory in storage, if set
+    campaign: Option<String>,
+    source_path: Option<PathBuf>,
+    // The line each top-level statement is on, and what it reads, to point errors at
+    statement_lines: Vec<(usize, String)>,
+    // Files being included, innermost last
+    includes: Vec<PathBuf>,
+    // Prefixes procedures defined and called right now, like `hex.weather`
+    namespace: Arc<str>,
+    listeners: Listeners,
 }
 
 impl Default for Interpreter {
@@ -67,445 +814,6346 @@ impl Interpreter {
     pub fn new() -> Self {
         Interpreter {
             procedures: HashMap::new(),
+            local_procedures: HashMap::new(),
+            generators: HashMap::new(),
+            named_rolls: HashMap::new(),
+            applied_modifiers: Vec::new(),
+            functions: HashMap::new(),
             tables: HashMap::new(),
+            names: HashMap::new(),
+            statblocks: HashMap::new(),
+            prices: HashMap::new(),
+            downtime_character: None,
+            unclamped_tables: HashSet::new(),
+            prefetched_tables: HashMap::new(),
             persistent_facts: FactDatabase::default(),
             local_facts: FactDatabase::default(),
+            rules: Vec::new(),
+            derived_facts: FactDatabase::default(),
+            clocks: HashMap::new(),
+            resources: HashMap::new(),
+            usage_dice: HashMap::new(),
+            initiative: Initiative::default(),
+            hit_points: HashMap::new(),
+            inventories: HashMap::new(),
+            purses: HashMap::new(),
+            denominations: Denominations::default(),
+            travel: Travel::default(),
+            encounters: Encounters::default(),
+            market: Market::default(),
+            date: 0,
+            calendar: Calendar::default(),
+            turns: 0,
+            rooms: 0,
+            burning: Vec::new(),
+            lights: Lights::default(),
+            session_tally: Tally::default(),
+            campaign_tally: Tally::default(),
+            fronts: BTreeMap::new(),
+            rumors: Vec::new(),
+            chaos: DEFAULT_CHAOS,
+            seen_entries: BTreeMap::new(),
+            pins: HashMap::new(),
+            party_size: 4,
+            variables: HashMap::new(),
+            facts_set: HashSet::new(),
+            oracle: Oracle::default(),
+            campaign_odds: HashMap::new(),
+            trace: false,
+            call_depth: 0,
+            returning: false,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            steps: 0,
+            max_steps: DEFAULT_MAX_STEPS,
+            dice_limits: DiceLimits::default(),
+            step_hook: None,
+            rng: StdRng::from_entropy(),
+            record_rolls: false,
+            rolls: Vec::new(),
+            roll_history: VecDeque::new(),
+            output: None,
+            locale: Locale::default(),
+            prompt: None,
+            dice_prompt: None,
+            storage: None,
+            campaign: None,
+            source_path: None,
+            statement_lines: Vec::new(),
+            includes: Vec::new(),
+            namespace: Arc::default(),
+            listeners: Listeners::default(),
         }
     }
 
-    pub fn interpret(
-        &mut self,
-        statements: Vec<Statement>,
-    ) -> Vec<Result<StatementRecord, CrawlError>> {
-        let mut records = Vec::new();
-        for statement in statements {
-            records.push(self.evaluate_statement(&statement));
-        }
-        records
+    /// Seed the interpreter's dice so the same program rolls the same way every time.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
     }
 
-    fn evaluate_statement(&mut self, statement: &Statement) -> Result<StatementRecord, CrawlError> {
-        match statement {
-            Statement::ClearFact(fact) => self.evaluate_clear_fact(fact.clone()),
-            Statement::ClearPersistentFact(fact) => {
-                self.evaluate_clear_persistent_fact(fact.clone())
-            }
-            Statement::IfThen {
-                antecedent,
-                consequent,
-            } => self.evaluate_if_then(antecedent, consequent),
-            Statement::LoadTable(table_name) => self.evaluate_load_table(table_name.clone()),
-            Statement::MatchingRoll {
-                roll_specifier,
-                arms,
-            } => self.evaluate_matching_roll(roll_specifier, arms),
-            Statement::Procedure { declaration, body } => {
-                // How to avoid the vec copy?
-                self.evaluate_procedure_definition(
-                    declaration,
-                    body.iter().cloned().map(|s| *s).collect(),
-                )
-            }
-            Statement::ProcedureCall(identifier) => self.evaluate_procedure_call(identifier),
-            Statement::Reminder(reminder) => self.evaluate_reminder(reminder.clone()),
-            // Can you {operation}_fact as a top-level statement? What would that mean/do?
-            Statement::SetFact(fact) => self.evaluate_set_fact(fact.clone()),
-            Statement::SetPersistentFact(fact) => self.evaluate_set_persistent_fact(fact.clone()),
-            Statement::TableRoll(table_name) => self.evaluate_table_roll(table_name),
-            Statement::NontargetedRoll(specifier) => self.evaluate_nontargeted_roll(specifier),
-        }
+    /// Keep every roll made, in order, so they can be inspected with `rolls`.
+    pub fn with_roll_log(mut self, record_rolls: bool) -> Self {
+        self.record_rolls = record_rolls;
+        self
     }
 
-    fn evaluate_antecedent(&mut self, antecedent: &Antecedent) -> Result<bool, CrawlError> {
-        match antecedent {
-            Antecedent::CheckFact(fact) => self.evaluate_check_fact(fact.clone()),
-            Antecedent::CheckPersistentFact(fact) => {
-                self.evaluate_check_persistent_fact(fact.clone())
-            }
-            Antecedent::DiceRoll {
-                target,
-                roll_specifier,
-            } => self.evaluate_dice_roll(target, roll_specifier),
-        }
+    pub fn rolls(&self) -> &[RollOutcome] {
+        &self.rolls
     }
 
-    fn evaluate_consequent(
-        &mut self,
-        consequent: &Statement,
-    ) -> Result<StatementRecord, CrawlError> {
-        match consequent {
-            Statement::ClearFact(fact) => self.evaluate_clear_fact(fact.clone()),
-            Statement::ClearPersistentFact(fact) => {
-                self.evaluate_clear_persistent_fact(fact.clone())
-            }
-            Statement::ProcedureCall(procedure_identifier) => {
-                self.evaluate_procedure_call(procedure_identifier)
-            }
-            Statement::SetFact(fact) => self.evaluate_set_fact(fact.clone()),
-            Statement::SetPersistentFact(fact) => self.evaluate_set_persistent_fact(fact.clone()),
-            Statement::Reminder(reminder) => self.evaluate_reminder(reminder.clone()),
-            Statement::TableRoll(table_name) => self.evaluate_table_roll(table_name),
-            _ => Err(CrawlError::InterpreterError {
-                reason: "Invalid statement as consequent".into(),
-            }),
-        }
+    /// The latest rolls made, up to `ROLL_HISTORY_LEN` of them, oldest first.
+    pub fn roll_history(&self) -> impl Iterator<Item = &DiceRollResult> {
+        self.roll_history.iter()
     }
 
-    fn evaluate_if_then(
-        &mut self,
-        antecedent: &Antecedent,
-        consequent: &Statement,
-    ) -> Result<StatementRecord, CrawlError> {
-        let antecedent_value = self.evaluate_antecedent(antecedent)?;
-        if antecedent_value {
-            Ok(StatementRecord::IfThen {
-                antecedent: antecedent_value,
-                consequent: Some(Box::new(self.evaluate_consequent(consequent)?)),
-            })
-        } else {
-            Ok(StatementRecord::IfThen {
-                antecedent: antecedent_value,
-                consequent: None,
-            })
-        }
+    pub fn last_roll(&self) -> Option<&DiceRollResult> {
+        self.roll_history.back()
     }
 
-    fn evaluate_reminder(&self, reminder: String) -> Result<StatementRecord, CrawlError> {
-        Ok(StatementRecord::Reminder(reminder))
+    /// How deeply procedure calls may nest before evaluation fails, guarding against
+    /// procedures that (mutually) recurse forever.
+    pub fn with_max_call_depth(mut self, max_call_depth: usize) -> Self {
+        self.max_call_depth = max_call_depth;
+        self
     }
 
-    fn evaluate_load_table(&mut self, table_name: String) -> Result<StatementRecord, CrawlError> {
-        let table_load = Table::load(&table_name);
-        match table_load {
-            Ok(table) => {
-                self.tables.insert(table_name.clone(), table);
-                Ok(StatementRecord::LoadTable(table_name))
-            }
-            Err(error) => Err(CrawlError::InterpreterError {
-                reason: format!("Failed to load table {table_name} ({error})"),
-            }),
-        }
+    /// How many top-level and procedure body statements a single call to `interpret` may
+    /// execute before evaluation fails.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
     }
 
-    fn evaluate_table_roll(&mut self, table_name: &str) -> Result<StatementRecord, CrawlError> {
-        let table = self.tables.get(table_name).unwrap();
-        // TODO: support `roll 1d6 + 3 on table "crits"`
-        let table_roll_result = table.auto_roll()?;
-        Ok(StatementRecord::TableRoll(
-            table_roll_result.entry.value.clone(),
-        ))
+    /// The most dice a roll in a script can have, and the most sides each can have.
+    pub fn with_dice_limits(mut self, dice_limits: DiceLimits) -> Self {
+        self.dice_limits = dice_limits;
+        self
     }
 
-    fn evaluate_matching_roll(
-        &mut self,
-        modified_roll_specifier: &ModifiedRollSpecifier,
-        arms: &[MatchingRollArm],
-    ) -> Result<StatementRecord, CrawlError> {
-        let roll: DiceRoll = modified_roll_specifier.try_into()?;
-        let roll_result = roll.roll();
-        for arm in arms {
-            if self.roll_result_matches_target(&roll_result, &arm.target)? {
-                return Ok(StatementRecord::MatchingRoll {
-                    matched_target: Some(arm.target.clone()),
-                    consequent: Some(Box::new(self.evaluate_consequent(&arm.consequent)?)),
-                });
-            }
-        }
+    /// When enabled, every evaluation step (dice results, fact mutations, procedure entry
+    /// and exit) is logged to stderr, indented by procedure call depth.
+    pub fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
 
-        Ok(StatementRecord::MatchingRoll {
-            matched_target: None,
-            consequent: None,
-        })
+    /// Install a hook that's called before every top-level and procedure body statement.
+    pub fn with_step_hook(mut self, hook: Box<dyn StepHook>) -> Self {
+        self.step_hook = Some(hook);
+        self
     }
 
-    fn evaluate_procedure_definition(
-        &mut self,
-        declaration: &ProcedureDeclaration,
-        body: Vec<Statement>,
-    ) -> Result<StatementRecord, CrawlError> {
-        let ident = declaration.0.clone();
-        let def = CrawlProcedure::new(ident.clone(), body);
-        self.procedures.insert(def.identifier.clone(), def);
-        Ok(StatementRecord::ProcedureDefinition(ident.clone()))
+    pub fn set_step_hook(&mut self, hook: Option<Box<dyn StepHook>>) {
+        self.step_hook = hook;
     }
 
-    fn evaluate_procedure_call(
-        &mut self,
-        procedure_identifier: &str,
-    ) -> Result<StatementRecord, CrawlError> {
-        let outer_facts = self.local_facts.clone();
+    /// Send records, reminders, and errors to `output` as they're produced. Without one,
+    /// they're only returned from `interpret`.
+    pub fn with_output(mut self, output: Box<dyn OutputSink>) -> Self {
+        self.output = Some(output);
+        self
+    }
 
-        let proc = self.procedures.get(procedure_identifier).unwrap();
+    /// Write warnings in `locale`'s strings.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
 
-        let mut records = Vec::new();
-        // How to avoid this clone?
-        for statement in proc.body.clone() {
-            records.push(Box::new(self.evaluate_statement(&statement)?));
-        }
+    pub fn output(&mut self) -> Option<&mut (dyn OutputSink + 'static)> {
+        self.output.as_deref_mut()
+    }
 
-        self.local_facts = outer_facts;
-        Ok(StatementRecord::ProcedureCall {
-            identifier: procedure_identifier.into(),
-            records,
-        })
+    /// Ask `prompt` which option to take at `choose` statements. Without one, they fail.
+    pub fn with_prompt(mut self, prompt: Box<dyn Prompt>) -> Self {
+        self.prompt = Some(prompt);
+        self
     }
 
-    fn evaluate_check_persistent_fact(&mut self, fact: String) -> Result<bool, CrawlError> {
-        Ok(self.persistent_facts.check(&fact.try_into().unwrap()))
+    pub fn set_prompt(&mut self, prompt: Option<Box<dyn Prompt>>) {
+        self.prompt = prompt;
     }
 
-    fn evaluate_set_persistent_fact(
-        &mut self,
-        fact: String,
-    ) -> Result<StatementRecord, CrawlError> {
-        self.persistent_facts.set(fact.clone().try_into().unwrap());
-        Ok(StatementRecord::SetPersistentFact(fact))
+    /// Answer `oracle` questions from `oracle`'s odds rather than the default ones.
+    pub fn with_oracle(mut self, oracle: Oracle) -> Self {
+        self.oracle = oracle;
+        self
     }
 
-    fn evaluate_clear_persistent_fact(
-        &mut self,
-        fact: String,
-    ) -> Result<StatementRecord, CrawlError> {
-        self.persistent_facts
-            .clear(&fact.clone().try_into().unwrap());
-        Ok(StatementRecord::ClearPersistentFact(fact))
+    /// Count money in `denominations`' coins rather than gold, silver, and copper pieces.
+    pub fn with_denominations(mut self, denominations: Denominations) -> Self {
+        self.denominations = denominations;
+        self
     }
 
-    fn evaluate_check_fact(&mut self, fact: String) -> Result<bool, CrawlError> {
-        Ok(self.local_facts.check(&fact.try_into().unwrap()))
+    /// Check for getting lost, encounters, and discoveries on travel turns at `travel`'s chances.
+    pub fn with_travel(mut self, travel: Travel) -> Self {
+        self.travel = travel;
+        self
     }
 
-    fn evaluate_set_fact(&mut self, fact: CrawlStr) -> Result<StatementRecord, CrawlError> {
-        let evaluated_fact = self.evaluate_str(fact.clone())?;
-        self.local_facts
-            .set(evaluated_fact.clone().try_into().unwrap());
-        Ok(StatementRecord::SetFact(evaluated_fact))
+    /// Roll encounters' reactions and distances by `encounters` rather than B/X's.
+    pub fn with_encounters(mut self, encounters: Encounters) -> Self {
+        self.encounters = encounters;
+        self
     }
 
-    fn evaluate_clear_fact(&mut self, fact: String) -> Result<StatementRecord, CrawlError> {
-        self.local_facts.clear(&fact.clone().try_into().unwrap());
-        Ok(StatementRecord::ClearFact(fact))
+    /// Change prices by settlement size and haggling by `market`, rather than the defaults.
+    pub fn with_market(mut self, market: Market) -> Self {
+        self.market = market;
+        self
     }
 
-    fn evaluate_dice_roll(
-        &self,
-        target: &Token,
-        modified_roll_specifier: &ModifiedRollSpecifier,
-    ) -> Result<bool, CrawlError> {
-        let roll: DiceRoll = modified_roll_specifier.try_into()?;
-        let roll_result = roll.roll();
-        self.roll_result_matches_target(&roll_result, target)
+    /// Share XP and treasure among `party_size` characters, rather than 4.
+    pub fn with_party_size(mut self, party_size: i32) -> Self {
+        self.party_size = party_size.max(1);
+        self
     }
 
-    fn evaluate_nontargeted_roll(
-        &mut self,
-        modified_roll_specifier: &ModifiedRollSpecifier,
-    ) -> Result<StatementRecord, CrawlError> {
-        let roll: DiceRoll = modified_roll_specifier.try_into()?;
-        let roll_result = roll.roll();
-        Ok(StatementRecord::NontargetedRoll(roll_result.total))
+    /// Let the lights `lights` has be lit, for as many turns as it says, rather than torches
+    /// and lanterns.
+    pub fn with_lights(mut self, lights: Lights) -> Self {
+        self.lights = lights;
+        self
     }
 
-    fn evaluate_str(&mut self, s: CrawlStr) -> Result<String, CrawlError> {
-        match s {
-            CrawlStr::Str(raw_string) => Ok(raw_string.clone()),
-            CrawlStr::InterpolatedStr {
-                format_string,
-                expressions,
+    /// Read dates off `calendar` rather than the Gregorian months and weekdays.
+    pub fn with_calendar(mut self, calendar: Calendar) -> Self {
+        self.calendar = calendar;
+        self
+    }
+
+    /// Ask `prompt` what each roll came up as, for dice rolled by hand, instead of rolling.
+    pub fn with_manual_rolls(mut self, prompt: Box<dyn DicePrompt>) -> Self {
+        self.dice_prompt = Some(prompt);
+        self
+    }
+
+    /// Load tables from `storage`, keyed by table name, instead of the filesystem.
+    pub fn with_storage(mut self, storage: Box<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    pub fn set_storage(&mut self, storage: Option<Box<dyn Storage>>) {
+        self.storage = storage;
+    }
+
+    /// The file top-level statements come from, which includes are resolved relative to.
+    /// Without one, they're resolved relative to the working directory (or storage root).
+    pub fn set_source_path(&mut self, path: Option<PathBuf>) {
+        self.source_path = path;
+    }
+
+    /// The line number and text of each top-level statement about to be interpreted, so output
+    /// can show where errors happened.
+    pub fn set_statement_lines(&mut self, lines: Vec<(usize, String)>) {
+        self.statement_lines = lines;
+    }
+
+    pub fn campaign(&self) -> Option<&str> {
+        self.campaign.as_deref()
+    }
+
+    /// Save the persistent facts, inventories, purses, date, XP and treasure tally, fronts, and
+    /// rumors, then replace them with `campaign`'s. Campaigns are kept apart in storage, so this
+    /// needs some.
+    pub fn switch_campaign(&mut self, campaign: &str) -> Result<(), CrawlError> {
+        check_campaign_name(campaign)?;
+        if self.storage.is_none() {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("campaign {campaign:?} needs storage to keep its facts in"),
+            });
+        }
+        // Nothing's saved for a campaign that's never had anything happen, so picking one to start
+        // with doesn't leave empty files behind.
+        self.save_persistent_facts()?;
+        self.trace(format_args!("campaign {campaign:?}"));
+        self.campaign = Some(campaign.into());
+        self.persistent_facts = FactDatabase::default();
+        self.inventories = HashMap::new();
+        self.purses = HashMap::new();
+        self.date = 0;
+        self.session_tally = Tally::default();
+        self.campaign_tally = Tally::default();
+        self.fronts = BTreeMap::new();
+        self.rumors = Vec::new();
+        self.chaos = DEFAULT_CHAOS;
+        self.seen_entries = BTreeMap::new();
+        self.campaign_odds = HashMap::new();
+        self.load_persistent_facts()?;
+        self.derive_facts();
+        Ok(())
+    }
+
+    /// Write `value` under `key` in the campaign's directory in storage. Does nothing outside
+    /// a campaign.
+    pub fn write_to_campaign(&mut self, key: &str, value: &str) -> Result<(), CrawlError> {
+        let (Some(campaign), Some(storage)) = (&self.campaign, self.storage.as_mut()) else {
+            return Ok(());
+        };
+        storage.write(&campaign_key(campaign, key), value)
+    }
+
+    // Where `key` is kept in storage, in the campaign's directory if there is one.
+    fn storage_key(&self, key: &str) -> String {
+        match &self.campaign {
+            Some(campaign) => campaign_key(campaign, key),
+            None => key.into(),
+        }
+    }
+
+    /// Replace the persistent facts, inventories, purses, date, XP and treasure tally, fronts,
+    /// rumors, chaos factor, and table entries seen with those saved in storage, if any have been,
+    /// and read the oracle's odds for the campaign.
+    pub fn load_persistent_facts(&mut self) -> Result<(), CrawlError> {
+        let odds_key = self.storage_key(ODDS_KEY);
+        let keys = CAMPAIGN_KEYS.map(|key| self.storage_key(key));
+        let Some(storage) = self.storage.as_deref_mut() else {
+            return Ok(());
+        };
+        let [
+            facts,
+            inventories,
+            purses,
+            date,
+            tally,
+            fronts,
+            rumors,
+            chaos,
+            seen,
+        ] = &keys;
+        load_json(
+            storage,
+            facts,
+            "persistent facts",
+            &mut self.persistent_facts,
+        )?;
+        load_json(storage, inventories, "inventories", &mut self.inventories)?;
+        load_json(storage, purses, "purses", &mut self.purses)?;
+        load_json(storage, date, "the date", &mut self.date)?;
+        load_json(
+            storage,
+            tally,
+            "the xp and treasure tally",
+            &mut self.campaign_tally,
+        )?;
+        load_json(storage, fronts, "fronts", &mut self.fronts)?;
+        load_json(storage, rumors, "rumors", &mut self.rumors)?;
+        load_json(storage, chaos, "the chaos factor", &mut self.chaos)?;
+        load_json(
+            storage,
+            seen,
+            "the table entries seen",
+            &mut self.seen_entries,
+        )?;
+        // The odds are only read, since they're written by hand.
+        if let Some(csv) = storage.read(&odds_key)? {
+            self.campaign_odds =
+                oracle::parse_odds(&csv).map_err(|error| CrawlError::InterpreterError {
+                    reason: format!("failed to load the oracle's odds ({error})"),
+                })?;
+        }
+        self.derive_facts();
+        Ok(())
+    }
+
+    /// Save the persistent facts, inventories, purses, date, the campaign's XP and treasure tally,
+    /// fronts, rumors, chaos factor, and table entries seen to storage.
+    pub fn save_persistent_facts(&mut self) -> Result<(), CrawlError> {
+        let keys = CAMPAIGN_KEYS.map(|key| self.storage_key(key));
+        let Some(storage) = self.storage.as_deref_mut() else {
+            return Ok(());
+        };
+        let [
+            facts,
+            inventories,
+            purses,
+            date,
+            tally,
+            fronts,
+            rumors,
+            chaos,
+            seen,
+        ] = &keys;
+        save_json(
+            storage,
+            facts,
+            "persistent facts",
+            &self.persistent_facts,
+            self.persistent_facts.is_empty(),
+        )?;
+        save_json(
+            storage,
+            inventories,
+            "inventories",
+            &self.inventories,
+            self.inventories.is_empty(),
+        )?;
+        save_json(
+            storage,
+            purses,
+            "purses",
+            &self.purses,
+            self.purses.is_empty(),
+        )?;
+        save_json(storage, date, "the date", &self.date, self.date == 0)?;
+        save_json(
+            storage,
+            tally,
+            "the xp and treasure tally",
+            &self.campaign_tally,
+            self.campaign_tally.is_empty(),
+        )?;
+        save_json(
+            storage,
+            fronts,
+            "fronts",
+            &self.fronts,
+            self.fronts.is_empty(),
+        )?;
+        save_json(
+            storage,
+            rumors,
+            "rumors",
+            &self.rumors,
+            self.rumors.is_empty(),
+        )?;
+        save_json(
+            storage,
+            chaos,
+            "the chaos factor",
+            &self.chaos,
+            self.chaos == DEFAULT_CHAOS,
+        )?;
+        save_json(
+            storage,
+            seen,
+            "the table entries seen",
+            &self.seen_entries,
+            self.seen_entries.is_empty(),
+        )?;
+        Ok(())
+    }
+
+    /// Make `function` callable from scripts as `identifier`. Procedures defined by a script
+    /// take precedence over functions with the same name.
+    pub fn register_function(
+        &mut self,
+        identifier: impl Into<String>,
+        function: impl FnMut(&[String]) -> Result<String, CrawlError> + Send + 'static,
+    ) {
+        self.functions.insert(identifier.into(), Box::new(function));
+    }
+
+    /// Call `listener` whenever a local or persistent fact is set.
+    pub fn on_fact_set(&mut self, listener: impl FnMut(&Fact) + Send + 'static) {
+        self.listeners.fact_set.push(Box::new(listener));
+    }
+
+    /// Call `listener` with the table and the entry rolled whenever a table is rolled on.
+    pub fn on_table_roll(&mut self, listener: impl FnMut(&str, &str) + Send + 'static) {
+        self.listeners.table_roll.push(Box::new(listener));
+    }
+
+    /// Call `listener` with the procedure's identifier whenever a procedure is entered.
+    pub fn on_procedure_call(&mut self, listener: impl FnMut(&str) + Send + 'static) {
+        self.listeners.procedure_call.push(Box::new(listener));
+    }
+
+    pub fn function_names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+
+    pub fn add_table(&mut self, name: impl Into<String>, table: Table) {
+        self.tables.insert(name.into(), table);
+    }
+
+    /// Roll on a loaded table, as `roll on table` does, for the entries rolled and their text.
+    pub fn roll_table(
+        &mut self,
+        table: &str,
+    ) -> Result<(Vec<TableEntry>, Vec<String>), CrawlError> {
+        self.roll_on_table(table, None)
+    }
+
+    /// Roll dice outside a script, kept in the roll history like a script's rolls.
+    pub fn roll_dice(&mut self, roll: &DiceRoll) -> Result<i32, CrawlError> {
+        self.roll(roll)
+    }
+
+    /// The names of the tables loaded, as they were given to `load table`.
+    pub fn table_names(&self) -> impl Iterator<Item = &str> {
+        self.tables.keys().map(String::as_str)
+    }
+
+    /// Describe what the statements would do, without rolling or changing anything. Calls to
+    /// procedures already defined are followed into their bodies.
+    pub fn dry_run(&self, statements: &[Statement]) -> Vec<String> {
+        DryRun::new()
+            .with_procedures(
+                self.procedures
+                    .iter()
+                    .map(|(name, procedure)| (name.clone(), Arc::clone(&procedure.body))),
+            )
+            .narrate(statements)
+    }
+
+    /// Every table `statements` load, including in procedure bodies, and in the bodies of the
+    /// procedures already defined.
+    pub fn tables_loaded_by(&self, statements: &[Statement]) -> Vec<String> {
+        let mut loads = TableLoads::default();
+        for statement in statements {
+            loads.visit_statement(statement);
+        }
+        for procedure in self.procedures.values() {
+            for statement in procedure.body.iter() {
+                loads.visit_statement(statement);
+            }
+        }
+        loads.0.sort();
+        loads.0.dedup();
+        loads.0
+    }
+
+    /// Load `table` from `csv` rather than storage or the filesystem, until the prefetched tables
+    /// are cleared. With `None`, loading it fails as if it wasn't found.
+    pub fn prefetch_table(&mut self, table: impl Into<String>, csv: Option<String>) {
+        self.prefetched_tables.insert(table.into(), csv);
+    }
+
+    pub fn clear_prefetched_tables(&mut self) {
+        self.prefetched_tables.clear();
+    }
+
+    /// Load a table again, picking up any changes to it since it was loaded.
+    pub fn reload_table(&mut self, table_name: &str) -> Result<(), CrawlError> {
+        let no_clamp = self.unclamped_tables.contains(table_name);
+        self.evaluate_load_table(table_name.into(), no_clamp)?;
+        Ok(())
+    }
+
+    pub fn clock(&self, clock: &str) -> Option<&Clock> {
+        self.clocks.get(clock)
+    }
+
+    /// The value bound to a variable with `let`, in the current scope.
+    pub fn variable(&self, name: &str) -> Option<i32> {
+        self.variables.get(name).copied()
+    }
+
+    /// The combatants in a fight, in the order they act, and whose turn it is.
+    pub fn initiative(&self) -> &Initiative {
+        &self.initiative
+    }
+
+    /// How many hit points a tracked entity has left.
+    pub fn hit_points(&self, entity: &str) -> Option<i32> {
+        self.hit_points.get(entity).copied()
+    }
+
+    /// How many of an item the owner has.
+    pub fn item_count(&self, owner: &str, item: &str) -> i32 {
+        self.inventories
+            .get(owner)
+            .and_then(|items| items.get(item))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// How much money is in a purse, in the smallest coin.
+    pub fn purse(&self, purse: &str) -> Option<i32> {
+        self.purses.get(purse).copied()
+    }
+
+    /// How much of a tracked resource is left.
+    pub fn resource(&self, resource: &str) -> Option<i32> {
+        self.resources.get(resource).copied()
+    }
+
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            persistent_facts: self.persistent_facts.clone(),
+            local_facts: self.local_facts.clone(),
+            clocks: self.clocks.clone(),
+            resources: self.resources.clone(),
+            usage_dice: self.usage_dice.clone(),
+            initiative: self.initiative.clone(),
+            hit_points: self.hit_points.clone(),
+            inventories: self.inventories.clone(),
+            purses: self.purses.clone(),
+            date: self.date,
+            turns: self.turns,
+            rooms: self.rooms,
+            session_tally: self.session_tally,
+            campaign_tally: self.campaign_tally,
+            fronts: self.fronts.clone(),
+            rumors: self.rumors.clone(),
+            chaos: self.chaos,
+            seen_entries: self.seen_entries.clone(),
+            pins: self.pins.clone(),
+            burning: self.burning.clone(),
+            variables: self.variables.clone(),
+            tables: self.tables.clone(),
+            names: self.names.clone(),
+            statblocks: self.statblocks.clone(),
+            prices: self.prices.clone(),
+            procedures: self.procedures.clone(),
+            generators: self.generators.clone(),
+            named_rolls: self.named_rolls.clone(),
+            applied_modifiers: self.applied_modifiers.clone(),
+            rules: self.rules.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: StateSnapshot) {
+        self.persistent_facts = snapshot.persistent_facts;
+        self.local_facts = snapshot.local_facts;
+        self.clocks = snapshot.clocks;
+        self.resources = snapshot.resources;
+        self.usage_dice = snapshot.usage_dice;
+        self.initiative = snapshot.initiative;
+        self.hit_points = snapshot.hit_points;
+        self.inventories = snapshot.inventories;
+        self.purses = snapshot.purses;
+        self.date = snapshot.date;
+        self.turns = snapshot.turns;
+        self.rooms = snapshot.rooms;
+        self.session_tally = snapshot.session_tally;
+        self.campaign_tally = snapshot.campaign_tally;
+        self.fronts = snapshot.fronts;
+        self.rumors = snapshot.rumors;
+        self.chaos = snapshot.chaos;
+        self.seen_entries = snapshot.seen_entries;
+        self.pins = snapshot.pins;
+        self.burning = snapshot.burning;
+        self.variables = snapshot.variables;
+        self.tables = snapshot.tables;
+        self.names = snapshot.names;
+        self.statblocks = snapshot.statblocks;
+        self.prices = snapshot.prices;
+        self.procedures = snapshot.procedures;
+        self.generators = snapshot.generators;
+        self.named_rolls = snapshot.named_rolls;
+        self.applied_modifiers = snapshot.applied_modifiers;
+        self.rules = snapshot.rules;
+        self.derive_facts();
+    }
+
+    pub fn interpret(
+        &mut self,
+        statements: Vec<Statement>,
+    ) -> Vec<Result<StatementRecord, CrawlError>> {
+        self.steps = 0;
+        self.facts_set.clear();
+        // Facts can be changed directly between programs.
+        self.derive_facts();
+        let mut records = Vec::new();
+        for (idx, statement) in statements.into_iter().enumerate() {
+            if let Err(error) = self.step(|| StatementLocation::TopLevel(idx), &statement) {
+                // Every statement after this one would exceed the limit too, or fail to reach the
+                // debugger.
+                self.write_error(idx, &error);
+                records.push(Err(error));
+                break;
+            }
+            let record = self.evaluate_statement(&statement);
+            match &record {
+                Ok(record) => {
+                    if let Some(output) = self.output() {
+                        output.write_record(record);
+                    }
+                }
+                Err(error) => self.write_error(idx, error),
+            }
+            records.push(record);
+        }
+        records
+    }
+
+    // An error in the top-level statement at `idx`, pointed at its line if it's known.
+    fn write_error(&mut self, idx: usize, error: &CrawlError) {
+        let Some(output) = self.output.as_deref_mut() else {
+            return;
+        };
+        match self.statement_lines.get(idx) {
+            Some((line, source)) => output.write_error_at(error, *line, source),
+            None => output.write_error(error),
+        }
+    }
+
+    // `location` is only worked out if there's a hook to hand it to.
+    fn step(
+        &mut self,
+        location: impl FnOnce() -> StatementLocation,
+        statement: &Statement,
+    ) -> Result<(), CrawlError> {
+        self.steps += 1;
+        if self.steps > self.max_steps {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("maximum of {} executed statements exceeded", self.max_steps),
+            });
+        }
+
+        // Take the hook out so it can be handed the interpreter mutably.
+        if let Some(mut hook) = self.step_hook.take() {
+            let stepped = hook.before_statement(self, &location(), statement);
+            self.step_hook = Some(hook);
+            stepped?;
+        }
+        Ok(())
+    }
+
+    fn evaluate_statement(&mut self, statement: &Statement) -> Result<StatementRecord, CrawlError> {
+        match statement {
+            Statement::AddCombatant {
+                combatant,
+                roll_specifier,
+            } => self.evaluate_add_combatant(combatant, roll_specifier),
+            Statement::AdvanceClock { clock, amount } => self.evaluate_advance_clock(clock, amount),
+            Statement::AdvanceFronts(table) => self.evaluate_advance_fronts(table),
+            Statement::AddRumor { rumor, truth } => self.evaluate_add_rumor(rumor, *truth),
+            Statement::RevealRumor { rumor, truth } => self.evaluate_reveal_rumor(rumor, *truth),
+            Statement::HearRumors(amount) => self.evaluate_hear_rumors(amount),
+            Statement::ReportRumors => Ok(self.evaluate_report_rumors()),
+            Statement::AdvanceDate { amount, unit } => self.evaluate_advance_date(amount, *unit),
+            Statement::AdvanceTime(amount) => self.evaluate_advance_time(amount),
+            Statement::AwardXp { amount, multiplier } => {
+                self.evaluate_award_xp(amount, *multiplier)
+            }
+            Statement::AwardTreasure {
+                amount,
+                multiplier,
+                coin,
+            } => self.evaluate_award_treasure(amount, *multiplier, coin),
+            Statement::ReportXp => Ok(self.evaluate_report_xp()),
+            Statement::AdvanceTurn => self.evaluate_advance_turn(),
+            Statement::AdvanceWeather(table) => self.evaluate_advance_weather(table),
+            Statement::Assert(antecedent) => self.evaluate_assert(antecedent),
+            Statement::AssertEqual { actual, expected } => {
+                self.evaluate_assert_equal(actual, expected)
+            }
+            Statement::Choice { question, arms } => self.evaluate_choice(question, arms),
+            Statement::ClearFact(fact) => self.evaluate_clear_fact(fact.clone()),
+            Statement::ClearPersistentFact(fact) => {
+                self.evaluate_clear_persistent_fact(fact.clone())
+            }
+            Statement::CreateClock { clock, segments } => {
+                self.evaluate_create_clock(clock, *segments)
+            }
+            Statement::CreateFront { faction, segments } => {
+                self.evaluate_create_front(faction, *segments)
+            }
+            Statement::IfThen {
+                antecedent,
+                consequent,
+            } => self.evaluate_if_then(antecedent, consequent),
+            Statement::Include { path, namespace } => {
+                self.evaluate_include(path, namespace.as_deref())
+            }
+            Statement::Let { name, value } => self.evaluate_let(name, value),
+            Statement::LoadNames(names) => self.evaluate_load_names(names),
+            Statement::LoadStatblocks(file) => self.evaluate_load_statblocks(file),
+            Statement::LoadPrices(prices) => self.evaluate_load_prices(prices),
+            Statement::LoadTable { table, no_clamp } => {
+                // Checked here, as `reload_table` loading one again is intended.
+                if self.tables.contains_key(table) {
+                    let table = format!("{table:?}");
+                    let warning = self
+                        .locale
+                        .get("warning.table_loaded_again", &[("table", &table)]);
+                    self.warn(warning);
+                }
+                self.evaluate_load_table(table.clone(), *no_clamp)
+            }
+            Statement::MatchingRoll {
+                roll_specifier,
+                arms,
+                otherwise,
+            } => self.evaluate_matching_roll(roll_specifier, arms, otherwise.as_deref()),
+            Statement::Check {
+                roll_specifier,
+                dc,
+                arms,
+                otherwise,
+            } => self.evaluate_check(roll_specifier, dc, arms, otherwise.as_deref()),
+            Statement::Save {
+                statblock,
+                save,
+                arms,
+                otherwise,
+            } => self.evaluate_save(statblock, save, arms, otherwise.as_deref()),
+            Statement::Downtime { weeks, arms } => self.evaluate_downtime(weeks, arms),
+            Statement::Move {
+                name,
+                roll_specifier,
+                arms,
+                otherwise,
+            } => self.evaluate_move(name, roll_specifier, arms, otherwise.as_deref()),
+            Statement::MatchingFact {
+                fact,
+                arms,
+                otherwise,
+            } => self.evaluate_matching_fact(fact, arms, otherwise.as_deref()),
+            Statement::MatchingTableRoll {
+                table,
+                tag,
+                excluding_seen,
+                arms,
+            } => self.evaluate_matching_table_roll(table, tag.as_deref(), *excluding_seen, arms),
+            Statement::Oracle {
+                question,
+                likelihood,
+            } => self.evaluate_oracle(question, likelihood),
+            Statement::Procedure { declaration, body } => {
+                self.evaluate_procedure_definition(declaration, Arc::clone(body))
+            }
+            Statement::Generator { name, template } => {
+                self.evaluate_generator_definition(name, template)
+            }
+            Statement::DefineRoll { name, roll } => self.evaluate_roll_definition(name, roll),
+            Statement::ApplyModifier { modifier, rolls } => {
+                Ok(self.evaluate_apply_modifier(*modifier, rolls.clone()))
+            }
+            Statement::RemoveModifier(rolls) => self.evaluate_remove_modifier(rolls.as_deref()),
+            Statement::RemoveCombatant(combatant) => self.evaluate_remove_combatant(combatant),
+            Statement::SortCombatants => Ok(self.evaluate_sort_combatants()),
+            Statement::Rule(rule) => self.evaluate_rule_definition(rule),
+            Statement::WithFacts(body) => self.evaluate_with_facts(body),
+            Statement::Campaign(campaign) => {
+                self.switch_campaign(campaign)?;
+                Ok(StatementRecord::Campaign(campaign.clone()))
+            }
+            Statement::ProcedureCall {
+                identifier,
+                arguments,
+            } => self.evaluate_procedure_call(identifier, arguments),
+            Statement::Reminder(reminder) => self.evaluate_reminder(reminder.clone()),
+            Statement::ReportFacts(entity) => Ok(self.evaluate_report_facts(entity)),
+            Statement::ReportInventory(owner) => Ok(self.evaluate_report_inventory(owner)),
+            Statement::AddItem { item, owner } => Ok(self.evaluate_add_item(item, owner)),
+            Statement::RemoveItem { item, owner } => self.evaluate_remove_item(item, owner),
+            Statement::AddMoney {
+                amount,
+                coin,
+                purse,
+            } => self.evaluate_add_money(amount, coin, purse),
+            Statement::SpendMoney {
+                amount,
+                coin,
+                purse,
+            } => self.evaluate_spend_money(amount, coin, purse.as_deref()),
+            Statement::Return(value) => self.evaluate_return(value),
+            // Can you {operation}_fact as a top-level statement? What would that mean/do?
+            Statement::SetFact(fact) => self.evaluate_set_fact(fact.clone()),
+            Statement::SetPersistentFact(fact) => self.evaluate_set_persistent_fact(fact.clone()),
+            Statement::SpendResource { resource, amount } => {
+                self.evaluate_spend_resource(resource, amount)
+            }
+            Statement::Damage { entity, amount } => self.evaluate_damage(entity, amount),
+            Statement::TableRoll {
+                table,
+                field,
+                tag,
+                excluding_seen,
+            } => self.evaluate_table_roll(table, field.as_deref(), tag.as_deref(), *excluding_seen),
+            Statement::TrackHitPoints { entity, amount } => {
+                self.evaluate_track_hit_points(entity, amount)
+            }
+            Statement::TrackResource { resource, amount } => {
+                self.evaluate_track_resource(resource, *amount)
+            }
+            Statement::UsageDie { resource, sides } => self.evaluate_usage_die(resource, *sides),
+            Statement::UsageRoll(resource) => self.evaluate_usage_roll(resource),
+            Statement::NameRoll(names) => self.evaluate_name_roll(names),
+            Statement::Travel(terrain) => self.evaluate_travel(terrain),
+            Statement::Encounter(terrain) => self.evaluate_encounter(terrain),
+            Statement::GenerateRoom => self.evaluate_generate_room(),
+            Statement::SceneCheck => self.evaluate_scene_check(),
+            Statement::PinTable {
+                table,
+                entry,
+                rolls,
+            } => self.evaluate_pin_table(table, entry, *rolls),
+            Statement::AdjustChaos(by) => self.evaluate_adjust_chaos(*by),
+            Statement::Price { item, settlement } => {
+                self.evaluate_price(item, settlement.as_deref())
+            }
+            Statement::Haggle { item, settlement } => {
+                self.evaluate_haggle(item, settlement.as_deref())
+            }
+            Statement::MoraleCheck { group, morale } => self.evaluate_morale_check(group, morale),
+            Statement::Light(light) => self.evaluate_light(light),
+            Statement::NontargetedRoll(specifier) => self.evaluate_nontargeted_roll(specifier),
+            Statement::LastRoll => {
+                let last_roll = self
+                    .last_roll()
+                    .ok_or_else(|| CrawlError::InterpreterError {
+                        reason: "nothing has been rolled yet".into(),
+                    })?;
+                Ok(StatementRecord::NontargetedRoll(last_roll.checked_total()?))
+            }
+            Statement::CurrentDate => Ok(StatementRecord::CurrentDate(
+                self.calendar.format(self.date),
+            )),
+            Statement::ChaosFactor => Ok(StatementRecord::ChaosFactor(self.chaos)),
+        }
+    }
+
+    fn evaluate_antecedent(&mut self, antecedent: &Antecedent) -> Result<bool, CrawlError> {
+        match antecedent {
+            Antecedent::CheckClockFull(clock) => self.evaluate_check_clock_full(clock),
+            Antecedent::CheckResourceDepleted(resource) => {
+                self.evaluate_check_resource_depleted(resource)
+            }
+            Antecedent::CheckDead(entity) => self.evaluate_check_dead(entity),
+            Antecedent::DateIs(name) => {
+                let is = self.calendar.is(self.date, name)?;
+                self.trace(format_args!("check date is {name} => {is}"));
+                Ok(is)
+            }
+            Antecedent::InStock { item, settlement } => {
+                let chance = self.price(item, settlement.as_deref())?.1;
+                let d100 = DiceRoll::new(DicePool::new(vec![Die(100)]), 0);
+                let total = self.roll(&d100)?;
+                self.trace(format_args!(
+                    "check in stock {item:?}: {total} against {chance}%"
+                ));
+                Ok(total <= chance)
+            }
+            Antecedent::HasItem { item, owner } => {
+                let has = self.item_count(owner, item) > 0;
+                self.trace(format_args!("check {owner:?} has {item:?} => {has}"));
+                Ok(has)
+            }
+            Antecedent::CheckFact(fact) => self.evaluate_check_fact(fact),
+            Antecedent::CheckPersistentFact(fact) => self.evaluate_check_persistent_fact(fact),
+            Antecedent::DiceRoll {
+                target,
+                roll_specifier,
+            } => self.evaluate_dice_roll(target, roll_specifier),
+            Antecedent::NaturalRoll {
+                natural,
+                roll_specifier,
+            } => self.evaluate_natural_roll(*natural, roll_specifier),
+            Antecedent::DegreeOfSuccess {
+                degree,
+                roll_specifier,
+                dc,
             } => {
-                let re = Regex::new(r"\{.*\}").unwrap();
-                let mut replaced: Cow<'_, str> = format_string.clone().into();
-                for expr in expressions {
-                    replaced = re.replace(
-                        &format_string,
-                        format!("{:?}", self.evaluate_statement(&expr)?),
-                    );
+                let (_, _, rolled) = self.roll_against(roll_specifier, dc)?;
+                Ok(rolled.satisfies(*degree))
+            }
+        }
+    }
+
+    // What can be a consequent is up to the parser, so consequents are evaluated like any other
+    // statement.
+    fn evaluate_consequent(
+        &mut self,
+        consequent: &Statement,
+    ) -> Result<StatementRecord, CrawlError> {
+        self.evaluate_statement(consequent)
+    }
+
+    fn evaluate_if_then(
+        &mut self,
+        antecedent: &Antecedent,
+        consequent: &Statement,
+    ) -> Result<StatementRecord, CrawlError> {
+        let antecedent_value = self.evaluate_antecedent(antecedent)?;
+        self.trace(format_args!("if => {antecedent_value}"));
+        if antecedent_value {
+            Ok(StatementRecord::IfThen {
+                antecedent: antecedent_value,
+                consequent: Some(Box::new(self.evaluate_consequent(consequent)?)),
+            })
+        } else {
+            Ok(StatementRecord::IfThen {
+                antecedent: antecedent_value,
+                consequent: None,
+            })
+        }
+    }
+
+    fn evaluate_choice(
+        &mut self,
+        question: &str,
+        arms: &[ChoiceArm],
+    ) -> Result<StatementRecord, CrawlError> {
+        let options: Vec<String> = arms.iter().map(|arm| arm.option.clone()).collect();
+        let prompt = self
+            .prompt
+            .as_mut()
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("nothing to ask {question:?}"),
+            })?;
+        let arm = arms
+            .get(prompt.choose(question, &options)?)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("no such option for {question:?}"),
+            })?;
+        self.trace(format_args!("choose {question:?} => {:?}", arm.option));
+        Ok(StatementRecord::Choice {
+            question: question.into(),
+            chosen: arm.option.clone(),
+            consequent: Box::new(self.evaluate_consequent(&arm.consequent)?),
+        })
+    }
+
+    fn evaluate_reminder(&mut self, reminder: String) -> Result<StatementRecord, CrawlError> {
+        self.trace(format_args!("reminder {reminder:?}"));
+        if let Some(output) = self.output() {
+            output.write_reminder(&reminder);
+        }
+        Ok(StatementRecord::Reminder(reminder))
+    }
+
+    fn evaluate_include(
+        &mut self,
+        path: &str,
+        namespace: Option<&str>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let including = self.includes.last().or(self.source_path.as_ref());
+        let directory = including
+            .and_then(|path| path.parent())
+            .unwrap_or(Path::new(""));
+        let resolved = normalize_path(&directory.join(path));
+
+        let chain: Vec<PathBuf> = self
+            .source_path
+            .iter()
+            .map(|path| normalize_path(path))
+            .chain(self.includes.iter().cloned())
+            .collect();
+        if chain.contains(&resolved) {
+            let cycle: Vec<_> = chain
+                .iter()
+                .chain([&resolved])
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(CrawlError::InterpreterError {
+                reason: format!("include cycle {}", cycle.join(" -> ")),
+            });
+        }
+
+        let key = resolved.to_string_lossy();
+        let source = match self.storage.as_mut() {
+            Some(storage) => storage.read(&key)?,
+            None => fs::read_to_string(&resolved).ok(),
+        }
+        .ok_or_else(|| CrawlError::InterpreterError {
+            reason: format!("Failed to include {key}"),
+        })?;
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(&source))
+            .collect::<Result<_, _>>()
+            .map_err(|error| CrawlError::InterpreterError {
+                reason: format!("Failed to parse {key} ({error})"),
+            })?;
+
+        self.trace(format_args!("include {key:?}"));
+        let key = key.into_owned();
+        let outer_namespace = match namespace {
+            Some(namespace) => {
+                let inner_namespace = qualify(&self.namespace, namespace);
+                Some(std::mem::replace(
+                    &mut self.namespace,
+                    inner_namespace.into(),
+                ))
+            }
+            None => None,
+        };
+        self.includes.push(resolved);
+        let outer_facts_set = std::mem::take(&mut self.facts_set);
+        let records = self.evaluate_included(&key, &statements);
+        self.facts_set = outer_facts_set;
+        self.includes.pop();
+        if let Some(outer_namespace) = outer_namespace {
+            self.namespace = outer_namespace;
+        }
+
+        Ok(StatementRecord::Include {
+            path: key,
+            records: records?.into_iter().map(Box::new).collect(),
+        })
+    }
+
+    fn evaluate_included(
+        &mut self,
+        path: &str,
+        statements: &[Statement],
+    ) -> Result<Vec<StatementRecord>, CrawlError> {
+        let mut records = Vec::new();
+        for (idx, statement) in statements.iter().enumerate() {
+            self.step(|| StatementLocation::Include(path.into(), idx), statement)?;
+            records.push(self.evaluate_statement(statement)?);
+        }
+        Ok(records)
+    }
+
+    fn evaluate_load_table(
+        &mut self,
+        table_name: String,
+        no_clamp: bool,
+    ) -> Result<StatementRecord, CrawlError> {
+        let table_load = self
+            .read_table_csv(&table_name)?
+            .and_then(|csv| Table::from_reader(csv.as_bytes()));
+        match table_load {
+            Ok(table) => {
+                let table = if no_clamp {
+                    self.unclamped_tables.insert(table_name.clone());
+                    table.with_clamp(false, false)
+                } else {
+                    self.unclamped_tables.remove(&table_name);
+                    table
+                };
+                self.trace(format_args!("load table {table_name:?}"));
+                self.tables.insert(table_name.clone(), table);
+                Ok(StatementRecord::LoadTable(table_name))
+            }
+            Err(error) => Err(CrawlError::InterpreterError {
+                reason: format!("Failed to load table {table_name} ({error})"),
+            }),
+        }
+    }
+
+    // The CSV a table is loaded from: read ahead of time, from storage, or from the filesystem.
+    // Storage failing is an error in itself, and the table not being there is the inner error.
+    fn read_table_csv(
+        &mut self,
+        table_name: &str,
+    ) -> Result<Result<String, Box<dyn Error>>, CrawlError> {
+        Ok(
+            match (
+                self.prefetched_tables.get(table_name),
+                self.storage.as_mut(),
+            ) {
+                (Some(Some(csv)), _) => Ok(csv.clone()),
+                (Some(None), _) => Err("not found".into()),
+                (None, Some(storage)) => storage
+                    .read(table_name)?
+                    .ok_or_else(|| "not found in storage".into()),
+                (None, None) => fs::read_to_string(table_name).map_err(Into::into),
+            },
+        )
+    }
+
+    fn evaluate_load_names(&mut self, names: &str) -> Result<StatementRecord, CrawlError> {
+        let table = self
+            .read_table_csv(names)?
+            .and_then(|csv| NameTable::from_reader(csv.as_bytes()))
+            .map_err(|error| CrawlError::InterpreterError {
+                reason: format!("Failed to load names {names} ({error})"),
+            })?;
+        self.trace(format_args!("load names {names:?}"));
+        self.names.insert(names.into(), table);
+        Ok(StatementRecord::LoadNames(names.into()))
+    }
+
+    fn evaluate_load_statblocks(&mut self, file: &str) -> Result<StatementRecord, CrawlError> {
+        let loaded = self
+            .read_table_csv(file)?
+            .and_then(|text| statblocks::parse(file, &text))
+            .map_err(|error| CrawlError::InterpreterError {
+                reason: format!("Failed to load statblocks {file} ({error})"),
+            })?;
+        self.trace(format_args!("load statblocks {file:?}"));
+        let names = loaded
+            .iter()
+            .map(|statblock| statblock.name.clone())
+            .collect();
+        for statblock in loaded {
+            self.statblocks.insert(statblock.name.clone(), statblock);
+        }
+        Ok(StatementRecord::LoadStatblocks {
+            file: file.into(),
+            statblocks: names,
+        })
+    }
+
+    fn evaluate_load_prices(&mut self, prices: &str) -> Result<StatementRecord, CrawlError> {
+        let items = self
+            .read_table_csv(prices)?
+            .and_then(|csv| market::parse_prices(&csv))
+            .map_err(|error| CrawlError::InterpreterError {
+                reason: format!("Failed to load prices {prices} ({error})"),
+            })?;
+        self.trace(format_args!("load prices {prices:?}"));
+        self.prices.extend(items);
+        Ok(StatementRecord::LoadPrices(prices.into()))
+    }
+
+    // An item's price in the smallest coin, and the percent chance it's in stock, in the
+    // settlement if it's given.
+    fn price(&self, item: &str, settlement: Option<&str>) -> Result<(i32, i32), CrawlError> {
+        let listed = self
+            .prices
+            .get(item)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("{item:?} isn't on the price list"),
+            })?;
+        let too_dear = || out_of_range(format!("the price of {item:?}"));
+        let price = listed
+            .count
+            .checked_mul(self.coin_worth(&listed.coin)?)
+            .ok_or_else(too_dear)?;
+        let Settlement {
+            markup,
+            availability,
+        } = match settlement {
+            Some(size) => {
+                self.market
+                    .settlement(size)
+                    .ok_or_else(|| CrawlError::InterpreterError {
+                        reason: format!("there's no settlement size called {size:?}"),
+                    })?
+            }
+            None => Settlement {
+                markup: 0,
+                availability: 0,
+            },
+        };
+        Ok((
+            market::mark_up(price, markup).ok_or_else(too_dear)?,
+            listed.availability.saturating_add(availability),
+        ))
+    }
+
+    fn evaluate_price(
+        &self,
+        item: &str,
+        settlement: Option<&str>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let price = self.price(item, settlement)?.0;
+        Ok(StatementRecord::Price {
+            item: item.into(),
+            price: self.denominations.format(price),
+        })
+    }
+
+    fn evaluate_haggle(
+        &mut self,
+        item: &str,
+        settlement: Option<&str>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let price = self.price(item, settlement)?.0;
+        let haggle = self.market.haggle_roll().clone();
+        let total = self.roll(&haggle)?;
+        let percent = self.market.haggle(total);
+        let price = market::mark_up(price, percent)
+            .ok_or_else(|| out_of_range(format!("the price of {item:?}")))?;
+        let price = self.denominations.format(price);
+        self.trace(format_args!(
+            "haggle {item:?}: {total}, {percent:+}%, {price}"
+        ));
+        Ok(StatementRecord::Haggle {
+            item: item.into(),
+            total,
+            percent,
+            price,
+        })
+    }
+
+    fn statblock(&self, name: &str) -> Result<&Statblock, CrawlError> {
+        self.statblocks
+            .get(name)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("statblock {name:?} isn't loaded"),
+            })
+    }
+
+    fn evaluate_name_roll(&mut self, names: &str) -> Result<StatementRecord, CrawlError> {
+        // Taken out while rolling, like tables are.
+        let (key, table) =
+            self.names
+                .remove_entry(names)
+                .ok_or_else(|| CrawlError::InterpreterError {
+                    reason: format!("names {names:?} aren't loaded"),
+                })?;
+        let name = table.generate(|total| {
+            let roll = DiceRoll::new(DicePool::new(vec![Die(total)]), 0);
+            self.roll(&roll)
+        });
+        self.names.insert(key, table);
+        let name = name?;
+        self.trace(format_args!("roll name from {names:?}: {name:?}"));
+        Ok(StatementRecord::NameRoll {
+            names: names.into(),
+            name,
+        })
+    }
+
+    fn evaluate_table_roll(
+        &mut self,
+        table_name: &str,
+        field: Option<&str>,
+        tag: Option<&str>,
+        excluding_seen: bool,
+    ) -> Result<StatementRecord, CrawlError> {
+        let pinned = self.pinned_entry(table_name);
+        let (_, values) =
+            self.roll_on_table_where(table_name, field, tag, excluding_seen, pinned.as_deref())?;
+        self.use_pin(table_name);
+        Ok(StatementRecord::TableRoll {
+            table: table_name.into(),
+            entry: values.join("; "),
+            pinned: pinned.is_some(),
+        })
+    }
+
+    fn evaluate_pin_table(
+        &mut self,
+        table_name: &str,
+        entry: &str,
+        rolls: i32,
+    ) -> Result<StatementRecord, CrawlError> {
+        // Tables loaded later are checked when they're rolled on.
+        let missing = self
+            .tables
+            .get(table_name)
+            .is_some_and(|table| !table.entries().iter().any(|pinned| pinned.value == entry));
+        if missing {
+            return Err(not_on_table(table_name, entry));
+        }
+        self.trace(format_args!(
+            "pin table {table_name:?} to {entry:?} for {rolls} rolls"
+        ));
+        self.pins.insert(table_name.into(), (entry.into(), rolls));
+        Ok(StatementRecord::PinTable {
+            table: table_name.into(),
+            entry: entry.into(),
+            rolls,
+        })
+    }
+
+    // The entry a table's pinned to, if it is.
+    fn pinned_entry(&self, table_name: &str) -> Option<String> {
+        self.pins.get(table_name).map(|(entry, _)| entry.clone())
+    }
+
+    // Count off one of a pinned table's rolls, unpinning it after the last.
+    fn use_pin(&mut self, table_name: &str) {
+        if let Some((_, rolls)) = self.pins.get_mut(table_name) {
+            *rolls -= 1;
+            if *rolls <= 0 {
+                self.pins.remove(table_name);
+            }
+        }
+    }
+
+    fn evaluate_matching_table_roll(
+        &mut self,
+        table_name: &str,
+        tag: Option<&str>,
+        excluding_seen: bool,
+        arms: &[TableRollArm],
+    ) -> Result<StatementRecord, CrawlError> {
+        // Arms match the entries as written, not with their embedded rolls rolled.
+        let pinned = self.pinned_entry(table_name);
+        let (entries, values) =
+            self.roll_on_table_where(table_name, None, tag, excluding_seen, pinned.as_deref())?;
+        self.use_pin(table_name);
+        let (matched, consequent) = match arms
+            .iter()
+            .find(|arm| entries.iter().any(|entry| entry.matches(&arm.pattern)))
+        {
+            Some(arm) => {
+                self.trace(format_args!("matched {:?}", arm.pattern));
+                (
+                    Some(arm.pattern.clone()),
+                    Some(Box::new(self.evaluate_consequent(&arm.consequent)?)),
+                )
+            }
+            None => (None, None),
+        };
+        Ok(StatementRecord::MatchingTableRoll {
+            table: table_name.into(),
+            entry: values.join("; "),
+            pinned: pinned.is_some(),
+            matched,
+            consequent,
+        })
+    }
+
+    fn evaluate_matching_fact(
+        &mut self,
+        fact: &str,
+        arms: &[TableRollArm],
+        otherwise: Option<&Statement>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let values = self.fact_values(fact)?;
+        self.trace(format_args!("match fact {fact:?} => {values:?}"));
+        let (matched, consequent) = match arms.iter().find(|arm| values.contains(&arm.pattern)) {
+            Some(arm) => {
+                self.trace(format_args!("matched {:?}", arm.pattern));
+                (
+                    Some(arm.pattern.clone()),
+                    Some(Box::new(self.evaluate_consequent(&arm.consequent)?)),
+                )
+            }
+            None => match otherwise {
+                Some(otherwise) => {
+                    self.trace(format_args!("matched else"));
+                    (None, Some(Box::new(self.evaluate_consequent(otherwise)?)))
+                }
+                None => (None, None),
+            },
+        };
+        Ok(StatementRecord::MatchingFact {
+            fact: fact.into(),
+            values,
+            matched,
+            consequent,
+        })
+    }
+
+    fn evaluate_report_facts(&self, entity: &str) -> StatementRecord {
+        let mut facts: Vec<(String, String)> = self
+            .local_facts
+            .about(entity)
+            .chain(self.persistent_facts.about(entity))
+            .chain(self.derived_facts.about(entity))
+            .map(|fact| (fact.attribute().into(), fact.value().into()))
+            .collect();
+        facts.sort();
+        facts.dedup();
+        StatementRecord::FactReport {
+            entity: entity.into(),
+            facts,
+        }
+    }
+
+    fn evaluate_add_item(&mut self, item: &str, owner: &str) -> StatementRecord {
+        let count = self
+            .inventories
+            .entry(owner.into())
+            .or_default()
+            .entry(item.into())
+            .or_insert(0);
+        *count = count.saturating_add(1);
+        let count = *count;
+        self.trace(format_args!("add {item:?} to {owner:?}: {count}"));
+        StatementRecord::AddItem {
+            item: item.into(),
+            owner: owner.into(),
+            count,
+        }
+    }
+
+    fn evaluate_remove_item(
+        &mut self,
+        item: &str,
+        owner: &str,
+    ) -> Result<StatementRecord, CrawlError> {
+        let no_item = || CrawlError::InterpreterError {
+            reason: format!("{owner} has no {item}"),
+        };
+        let items = self.inventories.get_mut(owner).ok_or_else(no_item)?;
+        let count = items.get(item).copied().ok_or_else(no_item)? - 1;
+        // Once there are none left, the item's gone from the inventory.
+        if count == 0 {
+            items.remove(item);
+        } else {
+            items.insert(item.into(), count);
+        }
+        self.trace(format_args!("remove {item:?} from {owner:?}: {count}"));
+        Ok(StatementRecord::RemoveItem {
+            item: item.into(),
+            owner: owner.into(),
+            count,
+        })
+    }
+
+    fn evaluate_report_inventory(&self, owner: &str) -> StatementRecord {
+        let items = self
+            .inventories
+            .get(owner)
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|(item, count)| (item.clone(), *count))
+                    .collect()
+            })
+            .unwrap_or_default();
+        StatementRecord::InventoryReport {
+            owner: owner.into(),
+            items,
+        }
+    }
+
+    /// The entries rolled - two, if a roll-twice entry came up - and their text, or their
+    /// `field` column, with any rolls embedded in them rolled.
+    fn roll_on_table(
+        &mut self,
+        table_name: &str,
+        field: Option<&str>,
+    ) -> Result<(Vec<TableEntry>, Vec<String>), CrawlError> {
+        self.roll_on_table_where(table_name, field, None, false, None)
+    }
+
+    /// Roll on the table, as with `roll_on_table`, but only on the entries tagged `tag`, if
+    /// there is one, and rerolling entries seen before in the campaign if `excluding_seen`. If
+    /// it's `pinned` to an entry, that entry comes up instead.
+    fn roll_on_table_where(
+        &mut self,
+        table_name: &str,
+        field: Option<&str>,
+        tag: Option<&str>,
+        excluding_seen: bool,
+        pinned: Option<&str>,
+    ) -> Result<(Vec<TableEntry>, Vec<String>), CrawlError> {
+        let seen = match self.seen_entries.get(table_name) {
+            Some(seen) if excluding_seen => seen.clone(),
+            _ => Vec::new(),
+        };
+        // Take the table out so rolling can borrow the interpreter mutably, and put it back under
+        // the same key, so its name isn't copied on every roll.
+        let (key, table) =
+            self.tables
+                .remove_entry(table_name)
+                .ok_or_else(|| CrawlError::InterpreterError {
+                    reason: format!("table {table_name:?} isn't loaded"),
+                })?;
+        // TODO: support `roll 1d6 + 3 on table "crits"`
+        let entries = match pinned {
+            Some(pinned) => table
+                .entries()
+                .iter()
+                .find(|entry| entry.value == *pinned)
+                .map(|entry| vec![entry.clone()])
+                .ok_or_else(|| not_on_table(table_name, pinned)),
+            None => tag
+                .map(|tag| table.tagged(tag))
+                .transpose()
+                .and_then(|tagged| {
+                    let rolled = tagged.as_ref().unwrap_or(&table);
+                    let unseen =
+                        |entry: &TableEntry| !entry.is_roll_twice() && !seen.contains(&entry.value);
+                    if !seen.is_empty() && !rolled.entries().iter().any(unseen) {
+                        return Err(CrawlError::InterpreterError {
+                            reason: format!("every entry on table {table_name:?} has been seen"),
+                        });
+                    }
+                    let dice = rolled.auto_dice();
+                    rolled
+                        .roll_entries_excluding(|| self.roll(&dice), &seen)
+                        .map(|entries| entries.into_iter().cloned().collect::<Vec<_>>())
+                }),
+        };
+        self.tables.insert(key, table);
+        let entries = entries?;
+        if let Some(pinned) = pinned {
+            self.trace(format_args!("table {table_name:?} is pinned to {pinned:?}"));
+        }
+        if excluding_seen {
+            let seen = self.seen_entries.entry(table_name.into()).or_default();
+            for entry in &entries {
+                if !seen.contains(&entry.value) {
+                    seen.push(entry.value.clone());
+                }
+            }
+        }
+
+        let mut values = Vec::new();
+        for entry in &entries {
+            let text = match field {
+                Some(field) => entry
+                    .field(field)
+                    .ok_or_else(|| missing_field(table_name, field))?,
+                None => &entry.value,
+            };
+            let value = self.roll_embedded(text)?;
+            self.trace(format_args!("roll on table {table_name:?}: {value:?}"));
+            for listener in &mut self.listeners.table_roll {
+                listener(table_name, &value);
+            }
+            values.push(value);
+        }
+        Ok((entries, values))
+    }
+
+    /// Replace rolls embedded in a table entry, like `"{2d6} gold pieces and {1d4+1} gems"`,
+    /// with their totals.
+    fn roll_embedded(&mut self, value: &str) -> Result<String, CrawlError> {
+        let mut rolled = String::new();
+        let mut last = 0;
+        for captures in EMBEDDED_ROLL.captures_iter(value) {
+            let embedded = captures.get(0).unwrap();
+            let dice = DiceRoll::parse_within(&captures[1], self.dice_limits)?;
+            rolled.push_str(&value[last..embedded.start()]);
+            rolled.push_str(&self.roll(&dice)?.to_string());
+            last = embedded.end();
+        }
+        rolled.push_str(&value[last..]);
+        Ok(rolled)
+    }
+
+    fn evaluate_matching_roll(
+        &mut self,
+        modified_roll_specifier: &ModifiedRollSpecifier,
+        arms: &[MatchingRollArm],
+        otherwise: Option<&Statement>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let roll = self.dice_roll(modified_roll_specifier)?;
+        let total = self.roll(&roll)?;
+        self.match_roll(total, arms, otherwise)
+    }
+
+    fn evaluate_check(
+        &mut self,
+        modified_roll_specifier: &ModifiedRollSpecifier,
+        dc: &Amount,
+        arms: &[CheckArm],
+        otherwise: Option<&Statement>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let (total, dc, degree) = self.roll_against(modified_roll_specifier, dc)?;
+        self.trace(format_args!("{total} against {dc} is a {degree}"));
+        let consequent = self.check_consequent(degree, arms, otherwise)?;
+        Ok(StatementRecord::Check {
+            total,
+            dc,
+            degree,
+            consequent,
+        })
+    }
+
+    fn evaluate_save(
+        &mut self,
+        statblock: &str,
+        save: &str,
+        arms: &[CheckArm],
+        otherwise: Option<&Statement>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let target = self
+            .statblock(statblock)?
+            .saves
+            .get(save)
+            .copied()
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("statblock {statblock:?} has no save vs {save:?}"),
+            })?;
+        let total = self.roll(&DiceRoll::new(DicePool::new(vec![Die(20)]), 0))?;
+        let degree = Degree::of(total, target);
+        self.trace(format_args!(
+            "{statblock} rolled {total} against {target} to save vs {save}, a {degree}"
+        ));
+        let consequent = self.check_consequent(degree, arms, otherwise)?;
+        Ok(StatementRecord::Save {
+            statblock: statblock.into(),
+            save: save.into(),
+            total,
+            target,
+            degree,
+            consequent,
+        })
+    }
+
+    fn evaluate_downtime(
+        &mut self,
+        weeks: &Amount,
+        arms: &[DowntimeArm],
+    ) -> Result<StatementRecord, CrawlError> {
+        let weeks = self.evaluate_amount(weeks)?.max(0);
+        self.trace(format_args!("downtime for {weeks} weeks"));
+        // Downtime that would run past the last date there can be doesn't run at all.
+        self.date_after(weeks, DateUnit::Weeks)?;
+        let outer_character = self.downtime_character.take();
+        let mut records = Vec::new();
+        let mut spent = vec![0_i64; arms.len()];
+        let mut outcomes = vec![Vec::new(); arms.len()];
+        let mut run = || -> Result<(), CrawlError> {
+            if arms.is_empty() {
+                return Ok(());
+            }
+            for _ in 0..weeks {
+                for (idx, arm) in arms.iter().enumerate() {
+                    self.downtime_character = Some(arm.character.clone());
+                    for activity in &arm.activities {
+                        // Costs are what leaves the purses while the activity runs.
+                        let before = self.purses.values().copied().map(i64::from).sum::<i64>();
+                        let record = self.evaluate_procedure_call(activity, &[])?;
+                        let after = self.purses.values().copied().map(i64::from).sum::<i64>();
+                        spent[idx] = before
+                            .checked_sub(after)
+                            .and_then(|cost| spent[idx].checked_add(cost))
+                            .ok_or_else(|| out_of_range(format!("{}'s spending", arm.character)))?;
+                        outcomes[idx].extend(record.value());
+                        records.push(Box::new(record));
+                    }
+                }
+            }
+            Ok(())
+        };
+        let ran = run();
+        self.downtime_character = outer_character;
+        ran?;
+
+        self.move_date(weeks, DateUnit::Weeks)?;
+        let reports = arms
+            .iter()
+            .zip(spent)
+            .zip(outcomes)
+            .map(|((arm, spent), outcomes)| {
+                let spent = i32::try_from(spent.max(0))
+                    .map_err(|_| out_of_range(format!("{}'s spending", arm.character)))?;
+                Ok(DowntimeReport {
+                    character: arm.character.clone(),
+                    spent: self.denominations.format(spent),
+                    outcomes,
+                })
+            })
+            .collect::<Result<_, CrawlError>>()?;
+        Ok(StatementRecord::Downtime {
+            weeks,
+            date: self.calendar.format(self.date),
+            records,
+            reports,
+        })
+    }
+
+    // The consequent of the first arm `degree` counts as, or else of the `else` arm, evaluated.
+    fn check_consequent(
+        &mut self,
+        degree: Degree,
+        arms: &[CheckArm],
+        otherwise: Option<&Statement>,
+    ) -> Result<Option<Box<StatementRecord>>, CrawlError> {
+        let consequent = match arms.iter().find(|arm| degree.satisfies(arm.degree)) {
+            Some(arm) => Some(&arm.consequent),
+            None => otherwise,
+        };
+        Ok(match consequent {
+            Some(consequent) => Some(Box::new(self.evaluate_consequent(consequent)?)),
+            None => None,
+        })
+    }
+
+    fn evaluate_move(
+        &mut self,
+        name: &str,
+        modified_roll_specifier: &ModifiedRollSpecifier,
+        arms: &[MatchingRollArm],
+        otherwise: Option<&Statement>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let roll = self.dice_roll(modified_roll_specifier)?;
+        let total = self.roll(&roll)?;
+        let (matched_target, consequent) = self.match_arm(total, arms, otherwise)?;
+        Ok(StatementRecord::Move {
+            name: name.into(),
+            total,
+            matched_target,
+            consequent,
+        })
+    }
+
+    // The roll's total, the DC, and the degree of success the one comes to against the other.
+    fn roll_against(
+        &mut self,
+        modified_roll_specifier: &ModifiedRollSpecifier,
+        dc: &Amount,
+    ) -> Result<(i32, i32, Degree), CrawlError> {
+        let dc = self.evaluate_amount(dc)?;
+        let roll = self.dice_roll(modified_roll_specifier)?;
+        let total = self.roll(&roll)?;
+        Ok((total, dc, Degree::of(total, dc)))
+    }
+
+    fn match_roll(
+        &mut self,
+        total: i32,
+        arms: &[MatchingRollArm],
+        otherwise: Option<&Statement>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let (matched_target, consequent) = self.match_arm(total, arms, otherwise)?;
+        Ok(StatementRecord::MatchingRoll {
+            matched_target,
+            consequent,
+        })
+    }
+
+    // The target of the first arm the total matches, and what its consequent did - or else
+    // what the `else` arm did.
+    fn match_arm(
+        &mut self,
+        total: i32,
+        arms: &[MatchingRollArm],
+        otherwise: Option<&Statement>,
+    ) -> Result<(Option<Token>, Option<Box<StatementRecord>>), CrawlError> {
+        for arm in arms {
+            if !self.total_matches_target(total, &arm.target)? {
+                continue;
+            }
+            let guarded = match &arm.guard {
+                Some(guard) => self.evaluate_antecedent(guard)?,
+                None => true,
+            };
+            if guarded {
+                self.trace(format_args!("matched {:?}", arm.target));
+                let consequent = self.evaluate_consequent(&arm.consequent)?;
+                return Ok((Some(arm.target.clone()), Some(Box::new(consequent))));
+            }
+        }
+
+        let consequent = match otherwise {
+            Some(otherwise) => {
+                self.trace(format_args!("matched else"));
+                Some(Box::new(self.evaluate_consequent(otherwise)?))
+            }
+            None => None,
+        };
+        Ok((None, consequent))
+    }
+
+    fn evaluate_procedure_definition(
+        &mut self,
+        declaration: &ProcedureDeclaration,
+        body: Arc<[Statement]>,
+    ) -> Result<StatementRecord, CrawlError> {
+        // Procedures defined inside another are only visible to it, so aren't namespaced.
+        let ident = if self.call_depth > 0 {
+            declaration.0.clone()
+        } else {
+            qualify(&self.namespace, &declaration.0)
+        };
+        let mut def = CrawlProcedure::new(ident.clone(), body);
+        def.namespace = Arc::clone(&self.namespace);
+        self.trace(format_args!("define procedure {ident}"));
+        let shadowed = if self.call_depth > 0 {
+            self.local_procedures.insert(def.identifier.clone(), def)
+        } else {
+            self.procedures.insert(def.identifier.clone(), def)
+        };
+        if shadowed.is_some() {
+            let warning = self
+                .locale
+                .get("warning.procedure_redefined", &[("procedure", &ident)]);
+            self.warn(warning);
+        }
+        Ok(StatementRecord::ProcedureDefinition(ident.clone()))
+    }
+
+    fn evaluate_rule_definition(&mut self, rule: &Rule) -> Result<StatementRecord, CrawlError> {
+        Fact::try_from(rule.fact.as_str())?;
+        self.trace(format_args!("define rule deriving {:?}", rule.fact));
+        if !self.rules.contains(rule) {
+            self.rules.push(rule.clone());
+        }
+        self.derive_facts();
+        Ok(StatementRecord::RuleDefinition(rule.fact.clone()))
+    }
+
+    /// Derives everything the rules give from the facts set now. A rule can depend on facts
+    /// other rules derive, so this goes until nothing new is derived.
+    fn derive_facts(&mut self) {
+        let mut derived = FactDatabase::default();
+        loop {
+            let mut changed = false;
+            for rule in &self.rules {
+                let Ok(fact) = Fact::try_from(rule.fact.as_str()) else {
+                    continue;
+                };
+                if !derived.check(&fact)
+                    && rule
+                        .conditions
+                        .iter()
+                        .all(|condition| self.rule_condition_holds(condition, &derived))
+                {
+                    derived.set(fact);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        for fact in derived.iter() {
+            if !self.derived_facts.check(fact) {
+                self.trace(format_args!("derived fact {:?}", fact.to_string()));
+            }
+        }
+        self.derived_facts = derived;
+    }
+
+    fn rule_condition_holds(&self, condition: &Antecedent, derived: &FactDatabase) -> bool {
+        let holds = |facts: &FactDatabase, fact: &str| {
+            Fact::try_from(fact).is_ok_and(|fact| facts.check(&fact))
+        };
+        match condition {
+            Antecedent::CheckFact(fact) => holds(&self.local_facts, fact) || holds(derived, fact),
+            Antecedent::CheckPersistentFact(fact) => holds(&self.persistent_facts, fact),
+            // The parser only allows fact checks in rules.
+            _ => false,
+        }
+    }
+
+    fn evaluate_procedure_call(
+        &mut self,
+        procedure_identifier: &str,
+        arguments: &[String],
+    ) -> Result<StatementRecord, CrawlError> {
+        // Local procedures shadow everything, and procedures in the current namespace shadow
+        // those outside it.
+        let qualified = qualify(&self.namespace, procedure_identifier);
+        let procedure_identifier = if self.local_procedures.contains_key(procedure_identifier) {
+            procedure_identifier
+        } else if self.procedures.contains_key(&qualified) {
+            qualified.as_str()
+        } else if self.procedures.contains_key(procedure_identifier) {
+            procedure_identifier
+        } else if self.generators.contains_key(&qualified) {
+            return self.evaluate_generate(&qualified, arguments);
+        } else if self.generators.contains_key(procedure_identifier) {
+            return self.evaluate_generate(procedure_identifier, arguments);
+        } else {
+            return self.evaluate_host_call(procedure_identifier, arguments);
+        };
+        if !arguments.is_empty() {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("procedure {procedure_identifier} doesn't take arguments"),
+            });
+        }
+        if self.call_depth >= self.max_call_depth {
+            return Err(CrawlError::InterpreterError {
+                reason: format!(
+                    "maximum call depth of {} exceeded calling {procedure_identifier}",
+                    self.max_call_depth
+                ),
+            });
+        }
+
+        let outer_variables = self.variables.clone();
+        let outer_procedures = self.local_procedures.clone();
+
+        let proc = self
+            .local_procedures
+            .get(procedure_identifier)
+            .or_else(|| self.procedures.get(procedure_identifier))
+            .unwrap();
+        // Holding our own references lets the body run while `self` is borrowed mutably.
+        let body = Arc::clone(&proc.body);
+        let code = Arc::clone(&proc.code);
+        let outer_namespace = std::mem::replace(&mut self.namespace, Arc::clone(&proc.namespace));
+
+        self.trace(format_args!("enter procedure {procedure_identifier}"));
+        for listener in &mut self.listeners.procedure_call {
+            listener(procedure_identifier);
+        }
+        self.call_depth += 1;
+
+        let records =
+            self.with_local_facts(|interp| interp.run_compiled(procedure_identifier, &body, &code));
+
+        // Unwind even if the body failed, so later top-level statements start fresh.
+        self.call_depth -= 1;
+        self.returning = false;
+        self.trace(format_args!("exit procedure {procedure_identifier}"));
+
+        self.namespace = outer_namespace;
+        self.variables = outer_variables;
+        self.local_procedures = outer_procedures;
+        Ok(StatementRecord::ProcedureCall {
+            identifier: procedure_identifier.into(),
+            records: records?.into_iter().map(Box::new).collect(),
+        })
+    }
+
+    fn evaluate_with_facts(&mut self, body: &[Statement]) -> Result<StatementRecord, CrawlError> {
+        self.trace(format_args!("with facts"));
+        let records = self.with_local_facts(|interp| {
+            let mut records = Vec::new();
+            for statement in body {
+                records.push(interp.evaluate_statement(statement)?);
+                if interp.returning {
+                    break;
+                }
+            }
+            Ok(records)
+        });
+        Ok(StatementRecord::WithFacts(
+            records?.into_iter().map(Box::new).collect(),
+        ))
+    }
+
+    /// Runs `f` with local facts scoped to it, as procedures and `with-facts` blocks are:
+    /// whatever it sets or clears is undone after, even if it fails.
+    fn with_local_facts<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer_facts = self.local_facts.clone();
+        let outer_facts_set = std::mem::take(&mut self.facts_set);
+        let result = f(self);
+        self.local_facts = outer_facts;
+        self.facts_set = outer_facts_set;
+        self.derive_facts();
+        result
+    }
+
+    fn evaluate_generator_definition(
+        &mut self,
+        name: &str,
+        template: &str,
+    ) -> Result<StatementRecord, CrawlError> {
+        let name = qualify(&self.namespace, name);
+        self.trace(format_args!("define generator {name}"));
+        self.generators.insert(name.clone(), template.into());
+        Ok(StatementRecord::GeneratorDefinition(name))
+    }
+
+    fn evaluate_roll_definition(
+        &mut self,
+        name: &str,
+        roll: &ModifiedRollSpecifier,
+    ) -> Result<StatementRecord, CrawlError> {
+        self.trace(format_args!("define roll {name:?}"));
+        let roll = self.named_roll(roll)?.into_owned();
+        self.named_rolls.insert(name.into(), roll);
+        Ok(StatementRecord::RollDefinition(name.into()))
+    }
+
+    fn evaluate_apply_modifier(&mut self, modifier: i32, rolls: Option<String>) -> StatementRecord {
+        self.trace(format_args!("apply modifier {modifier:+} to {rolls:?}"));
+        let applied = AppliedModifier { modifier, rolls };
+        self.applied_modifiers.push(applied.clone());
+        StatementRecord::ApplyModifier(applied)
+    }
+
+    /// Remove the modifier last applied, or last applied to the roll named `rolls`.
+    fn evaluate_remove_modifier(
+        &mut self,
+        rolls: Option<&str>,
+    ) -> Result<StatementRecord, CrawlError> {
+        self.trace(format_args!("remove modifier from {rolls:?}"));
+        let idx = self
+            .applied_modifiers
+            .iter()
+            .rposition(|applied| rolls.is_none() || applied.rolls.as_deref() == rolls)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: match rolls {
+                    Some(rolls) => format!("no modifier is applied to rolls named {rolls:?}"),
+                    None => "no modifier is applied".into(),
+                },
+            })?;
+        Ok(StatementRecord::RemoveModifier(
+            self.applied_modifiers.remove(idx),
+        ))
+    }
+
+    /// The modifiers applied to every roll, and to the roll named `name`, added up.
+    fn applied_modifier(&self, name: Option<&str>) -> Result<i32, CrawlError> {
+        self.applied_modifiers
+            .iter()
+            .filter(|applied| applied.rolls.is_none() || applied.rolls.as_deref() == name)
+            .try_fold(0i32, |total, applied| total.checked_add(applied.modifier))
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: "applied modifiers are out of range".into(),
+            })
+    }
+
+    /// `roll_specifier` with the roll it names filled in, if it names one, and its modifiers
+    /// added to that roll's.
+    fn named_roll<'a>(
+        &self,
+        roll_specifier: &'a ModifiedRollSpecifier,
+    ) -> Result<Cow<'a, ModifiedRollSpecifier>, CrawlError> {
+        let Token::Str(name) = &roll_specifier.base_roll_specifier else {
+            return Ok(Cow::Borrowed(roll_specifier));
+        };
+        let named = self
+            .named_rolls
+            .get(name)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("no roll called {name:?} is defined"),
+            })?;
+        let modifier = named
+            .modifier
+            .checked_add(roll_specifier.modifier)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("modifier of {name:?} is out of range"),
+            })?;
+        Ok(Cow::Owned(ModifiedRollSpecifier {
+            base_roll_specifier: named.base_roll_specifier.clone(),
+            modifier,
+            terms: named
+                .terms
+                .iter()
+                .chain(&roll_specifier.terms)
+                .cloned()
+                .collect(),
+        }))
+    }
+
+    /// Fill in a generator's template, rolling on each table named in it. Anything else in
+    /// braces is rolled as an embedded roll, like `{1d6}`.
+    fn evaluate_generate(
+        &mut self,
+        generator: &str,
+        arguments: &[String],
+    ) -> Result<StatementRecord, CrawlError> {
+        if !arguments.is_empty() {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("generator {generator} doesn't take arguments"),
+            });
+        }
+        let template = self.generators[generator].clone();
+        let re = Regex::new(r"\{([^{}]+)\}").unwrap();
+        let mut value = String::new();
+        let mut last = 0;
+        for captures in re.captures_iter(&template) {
+            let placeholder = captures.get(0).unwrap();
+            let table_name = captures[1].trim();
+            let filled = if self.tables.contains_key(table_name) {
+                self.roll_on_table(table_name, None)?.1.join("; ")
+            } else {
+                let rolled = self.roll_embedded(placeholder.as_str())?;
+                if rolled == placeholder.as_str() {
+                    return Err(CrawlError::InterpreterError {
+                        reason: format!(
+                            "generator {generator} rolls on table {table_name}, which isn't loaded"
+                        ),
+                    });
+                }
+                rolled
+            };
+            value.push_str(&template[last..placeholder.start()]);
+            value.push_str(&filled);
+            last = placeholder.end();
+        }
+        value.push_str(&template[last..]);
+        self.trace(format_args!("generate {generator}: {value:?}"));
+        Ok(StatementRecord::Generated {
+            generator: generator.into(),
+            value,
+        })
+    }
+
+    fn evaluate_return(&mut self, value: &ReturnValue) -> Result<StatementRecord, CrawlError> {
+        if self.call_depth == 0 {
+            return Err(CrawlError::InterpreterError {
+                reason: "return outside a procedure".into(),
+            });
+        }
+        let value = self.evaluate_value(value)?;
+        self.trace(format_args!("return {value:?}"));
+        self.returning = true;
+        Ok(StatementRecord::Return(value))
+    }
+
+    fn evaluate_value(&mut self, value: &ReturnValue) -> Result<String, CrawlError> {
+        match value {
+            ReturnValue::Amount(amount) => Ok(self.evaluate_amount(amount)?.to_string()),
+            ReturnValue::Str(s) => self.evaluate_str(s.clone()),
+        }
+    }
+
+    fn evaluate_assert(&mut self, antecedent: &Antecedent) -> Result<StatementRecord, CrawlError> {
+        let assertion = dry_run::antecedent(antecedent);
+        if !self.evaluate_antecedent(antecedent)? {
+            return Err(assertion_failed(&assertion));
+        }
+        self.trace(format_args!("assert {assertion}"));
+        Ok(StatementRecord::Assert(assertion))
+    }
+
+    fn evaluate_assert_equal(
+        &mut self,
+        actual: &ReturnValue,
+        expected: &ReturnValue,
+    ) -> Result<StatementRecord, CrawlError> {
+        let actual = self.evaluate_value(actual)?;
+        let expected = self.evaluate_value(expected)?;
+        if actual != expected {
+            return Err(assertion_failed(&format!(
+                "expected {expected:?}, got {actual:?}"
+            )));
+        }
+        let assertion = format!("{actual:?} equals {expected:?}");
+        self.trace(format_args!("assert {assertion}"));
+        Ok(StatementRecord::Assert(assertion))
+    }
+
+    fn evaluate_host_call(
+        &mut self,
+        identifier: &str,
+        arguments: &[String],
+    ) -> Result<StatementRecord, CrawlError> {
+        let function =
+            self.functions
+                .get_mut(identifier)
+                .ok_or_else(|| CrawlError::InterpreterError {
+                    reason: format!("undefined procedure {identifier}"),
+                })?;
+        let value = function(arguments)?;
+        self.trace(format_args!("call {identifier} {arguments:?} => {value:?}"));
+        Ok(StatementRecord::HostCall {
+            identifier: identifier.into(),
+            value,
+        })
+    }
+
+    // Run a procedure's compiled body, recording each of its statements as `evaluate_statement`
+    // would.
+    fn run_compiled(
+        &mut self,
+        procedure_identifier: &str,
+        body: &[Statement],
+        code: &[Instruction],
+    ) -> Result<Vec<StatementRecord>, CrawlError> {
+        let mut records = Vec::new();
+        let mut pc = 0;
+        while let Some(instruction) = code.get(pc) {
+            pc += 1;
+            let record = match instruction {
+                Instruction::Step(idx) => {
+                    if self.returning {
+                        break;
+                    }
+                    self.step(
+                        || StatementLocation::Procedure(procedure_identifier.into(), *idx),
+                        &body[*idx],
+                    )?;
+                    continue;
+                }
+                Instruction::Evaluate(statement) => self.evaluate_statement(statement)?,
+                Instruction::EvaluateConsequent(statement) => {
+                    self.evaluate_consequent(statement)?
+                }
+                Instruction::Reminder(reminder) => self.evaluate_reminder(reminder.clone())?,
+                Instruction::SetFact { fact, parsed } => {
+                    self.set_parsed_fact(parsed.clone(), false);
+                    self.trace(format_args!("set fact {fact:?}"));
+                    StatementRecord::SetFact(fact.clone())
+                }
+                Instruction::ClearFact { fact, parsed } => {
+                    self.local_facts.clear(parsed);
+                    self.derive_facts();
+                    self.trace(format_args!("clear fact {fact:?}"));
+                    StatementRecord::ClearFact(fact.clone())
+                }
+                Instruction::Roll(roll) => {
+                    StatementRecord::NontargetedRoll(self.roll_compiled(roll)?)
+                }
+                Instruction::MatchingRoll {
+                    roll,
+                    arms,
+                    otherwise,
+                } => {
+                    let total = self.roll_compiled(roll)?;
+                    self.match_roll(total, arms, otherwise.as_deref())?
+                }
+                Instruction::If { condition, skip } => {
+                    let holds = match condition {
+                        Condition::Fact { fact, parsed } => {
+                            self.trace(format_args!("check fact {fact:?}"));
+                            self.local_facts.check(parsed) || self.derived_facts.check(parsed)
+                        }
+                        Condition::Roll { target, roll } => {
+                            let total = self.roll_compiled(roll)?;
+                            self.total_matches_target(total, target)?
+                        }
+                        Condition::Antecedent(antecedent) => {
+                            self.evaluate_antecedent(antecedent)?
+                        }
+                    };
+                    self.trace(format_args!("if => {holds}"));
+                    if holds {
+                        continue;
+                    }
+                    pc += skip;
+                    StatementRecord::IfThen {
+                        antecedent: false,
+                        consequent: None,
+                    }
+                }
+                Instruction::EndIf => {
+                    let consequent = records.pop().expect("`if` without a consequent");
+                    StatementRecord::IfThen {
+                        antecedent: true,
+                        consequent: Some(Box::new(consequent)),
+                    }
+                }
+            };
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    fn roll_compiled(&mut self, roll: &CompiledRoll) -> Result<i32, CrawlError> {
+        self.dice_limits.check(roll.spec)?;
+        match self.applied_modifier(None)? {
+            0 => self.roll(&roll.roll),
+            applied => {
+                let mut roll = roll.roll.clone();
+                roll.modifier = roll.modifier.checked_add(applied).ok_or_else(|| {
+                    CrawlError::InterpreterError {
+                        reason: format!("modifier of {roll} is out of range"),
+                    }
+                })?;
+                self.roll(&roll)
+            }
+        }
+    }
+
+    fn evaluate_check_persistent_fact(&mut self, fact: &str) -> Result<bool, CrawlError> {
+        self.trace(format_args!("check persistent fact {fact:?}"));
+        Ok(self.persistent_facts.check(&Fact::try_from(fact)?))
+    }
+
+    fn evaluate_set_persistent_fact(
+        &mut self,
+        fact: String,
+    ) -> Result<StatementRecord, CrawlError> {
+        self.set_fact(&fact, true)?;
+        self.trace(format_args!("set persistent fact {fact:?}"));
+        Ok(StatementRecord::SetPersistentFact(fact))
+    }
+
+    fn evaluate_clear_persistent_fact(
+        &mut self,
+        fact: String,
+    ) -> Result<StatementRecord, CrawlError> {
+        self.persistent_facts.clear(&Fact::try_from(fact.as_str())?);
+        self.derive_facts();
+        self.trace(format_args!("clear persistent fact {fact:?}"));
+        Ok(StatementRecord::ClearPersistentFact(fact))
+    }
+
+    fn evaluate_check_fact(&mut self, fact: &str) -> Result<bool, CrawlError> {
+        self.trace(format_args!("check fact {fact:?}"));
+        let fact = Fact::try_from(fact)?;
+        Ok(self.local_facts.check(&fact) || self.derived_facts.check(&fact))
+    }
+
+    fn evaluate_set_fact(&mut self, fact: CrawlStr) -> Result<StatementRecord, CrawlError> {
+        let evaluated_fact = self.evaluate_str(fact.clone())?;
+        self.set_fact(&evaluated_fact, false)?;
+        self.trace(format_args!("set fact {evaluated_fact:?}"));
+        Ok(StatementRecord::SetFact(evaluated_fact))
+    }
+
+    fn set_fact(&mut self, fact: &str, persistent: bool) -> Result<(), CrawlError> {
+        self.set_parsed_fact(Fact::try_from(fact)?, persistent);
+        Ok(())
+    }
+
+    fn set_parsed_fact(&mut self, fact: Fact, persistent: bool) {
+        if !self.facts_set.insert(fact.clone()) {
+            let fact = format!("{:?}", fact.to_string());
+            let warning = self
+                .locale
+                .get("warning.fact_set_twice", &[("fact", &fact)]);
+            self.warn(warning);
+        }
+        for listener in &mut self.listeners.fact_set {
+            listener(&fact);
+        }
+        if persistent {
+            self.persistent_facts.set(fact);
+        } else {
+            self.local_facts.set(fact);
+        }
+        self.derive_facts();
+    }
+
+    fn evaluate_clear_fact(&mut self, fact: String) -> Result<StatementRecord, CrawlError> {
+        self.local_facts.clear(&Fact::try_from(fact.as_str())?);
+        self.derive_facts();
+        self.trace(format_args!("clear fact {fact:?}"));
+        Ok(StatementRecord::ClearFact(fact))
+    }
+
+    fn evaluate_dice_roll(
+        &mut self,
+        target: &Token,
+        modified_roll_specifier: &ModifiedRollSpecifier,
+    ) -> Result<bool, CrawlError> {
+        let roll = self.dice_roll(modified_roll_specifier)?;
+        let total = self.roll(&roll)?;
+        self.total_matches_target(total, target)
+    }
+
+    fn evaluate_natural_roll(
+        &mut self,
+        natural: Natural,
+        modified_roll_specifier: &ModifiedRollSpecifier,
+    ) -> Result<bool, CrawlError> {
+        let roll = self.dice_roll(modified_roll_specifier)?;
+        let roll_result = self.roll_result(&roll)?;
+        Ok(match natural {
+            Natural::Critical => roll_result.natural_max,
+            Natural::Fumble => roll_result.natural_min,
+        })
+    }
+
+    fn evaluate_create_clock(
+        &mut self,
+        clock: &str,
+        segments: i32,
+    ) -> Result<StatementRecord, CrawlError> {
+        if segments <= 0 {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("clock {clock} must have at least one segment"),
+            });
+        }
+        self.trace(format_args!(
+            "create clock {clock:?} with {segments} segments"
+        ));
+        self.clocks.insert(clock.into(), Clock::new(segments));
+        Ok(StatementRecord::CreateClock {
+            clock: clock.into(),
+            segments,
+        })
+    }
+
+    fn evaluate_create_front(
+        &mut self,
+        faction: &str,
+        segments: i32,
+    ) -> Result<StatementRecord, CrawlError> {
+        if segments <= 0 {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("front {faction} must have at least one segment"),
+            });
+        }
+        self.trace(format_args!(
+            "create front {faction:?} with {segments} segments"
+        ));
+        self.fronts.insert(faction.into(), Clock::new(segments));
+        Ok(StatementRecord::CreateFront {
+            faction: faction.into(),
+            segments,
+        })
+    }
+
+    fn evaluate_advance_fronts(&mut self, table: &str) -> Result<StatementRecord, CrawlError> {
+        // Completed fronts don't advance any further.
+        let factions: Vec<String> = self
+            .fronts
+            .iter()
+            .filter(|(_, front)| !front.is_full())
+            .map(|(faction, _)| faction.clone())
+            .collect();
+        let mut advanced = Vec::new();
+        for faction in factions {
+            let (entries, values) = self.roll_on_table(table, None)?;
+            let by = match entries.iter().find_map(|entry| entry.field(&faction)) {
+                Some(by) => self.roll_number(by, "front advance")?,
+                None => self.roll_number(&values.join(""), "front advance")?,
+            };
+            let front = self
+                .fronts
+                .get_mut(&faction)
+                .expect("only fronts kept are advanced");
+            front.advance(by);
+            let (filled, segments) = (front.filled(), front.segments());
+            self.trace(format_args!(
+                "advance front {faction:?} by {by}: {filled}/{segments}"
+            ));
+            advanced.push(FrontAdvance {
+                faction,
+                by,
+                filled,
+                segments,
+            });
+        }
+        Ok(StatementRecord::AdvanceFronts(advanced))
+    }
+
+    fn evaluate_add_rumor(
+        &mut self,
+        rumor: &str,
+        truth: Option<bool>,
+    ) -> Result<StatementRecord, CrawlError> {
+        if self.rumors.iter().any(|added| added.text == rumor) {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("rumor {rumor:?} was already added"),
+            });
+        }
+        self.trace(format_args!("add rumor {rumor:?}, truth {truth:?}"));
+        self.rumors.push(Rumor::new(rumor, truth));
+        Ok(StatementRecord::AddRumor(rumor.into()))
+    }
+
+    fn evaluate_reveal_rumor(
+        &mut self,
+        rumor: &str,
+        truth: Option<bool>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let revealed = self
+            .rumors
+            .iter_mut()
+            .find(|added| added.text == rumor)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("there's no rumor {rumor:?}"),
+            })?;
+        let truth = truth
+            .or(revealed.truth)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!(
+                    "rumor {rumor:?} is hidden, so it's revealed `as true` or `as false`"
+                ),
+            })?;
+        revealed.truth = Some(truth);
+        revealed.revealed = true;
+        revealed.heard = true;
+        self.trace(format_args!("reveal rumor {rumor:?}: {truth}"));
+        Ok(StatementRecord::RevealRumor {
+            rumor: rumor.into(),
+            truth,
+        })
+    }
+
+    fn evaluate_hear_rumors(&mut self, amount: &Amount) -> Result<StatementRecord, CrawlError> {
+        let count = self.evaluate_amount(amount)?;
+        let mut heard = Vec::new();
+        for _ in 0..count {
+            let unheard: Vec<usize> = (0..self.rumors.len())
+                .filter(|idx| !self.rumors[*idx].heard)
+                .collect();
+            if unheard.is_empty() {
+                break;
+            }
+            let die = DiceRoll::new(DicePool::new(vec![Die(unheard.len() as i32)]), 0);
+            let idx = unheard[(self.roll(&die)? - 1) as usize];
+            self.rumors[idx].heard = true;
+            heard.push(self.rumors[idx].text.clone());
+        }
+        self.trace(format_args!("hear {count} rumors: {heard:?}"));
+        Ok(StatementRecord::HearRumors(heard))
+    }
+
+    fn evaluate_report_rumors(&self) -> StatementRecord {
+        StatementRecord::RumorReport(
+            self.rumors
+                .iter()
+                .filter(|rumor| rumor.heard)
+                .map(|rumor| (rumor.text.clone(), rumor.known_truth()))
+                .collect(),
+        )
+    }
+
+    fn evaluate_advance_clock(
+        &mut self,
+        clock: &str,
+        amount: &Amount,
+    ) -> Result<StatementRecord, CrawlError> {
+        let by = self.evaluate_amount(amount)?;
+        let advanced = self
+            .clocks
+            .get_mut(clock)
+            .ok_or_else(|| undefined_clock(clock))?;
+        advanced.advance(by);
+        let (filled, segments) = (advanced.filled(), advanced.segments());
+        self.trace(format_args!(
+            "advance clock {clock:?} by {by}: {filled}/{segments}"
+        ));
+        Ok(StatementRecord::AdvanceClock {
+            clock: clock.into(),
+            filled,
+            segments,
+        })
+    }
+
+    fn evaluate_award_xp(
+        &mut self,
+        amount: &Amount,
+        multiplier: i32,
+    ) -> Result<StatementRecord, CrawlError> {
+        let count = self.evaluate_amount(amount)?.max(0);
+        let xp = count
+            .checked_mul(multiplier)
+            .ok_or_else(|| out_of_range(format!("{count} x {multiplier} xp")))?;
+        let (session, campaign) = self
+            .session_tally
+            .xp
+            .checked_add(xp)
+            .zip(self.campaign_tally.xp.checked_add(xp))
+            .ok_or_else(|| out_of_range(format!("the xp tally with {xp} xp more")))?;
+        self.session_tally.xp = session;
+        self.campaign_tally.xp = campaign;
+        self.trace(format_args!("award {xp} xp: {session}, {campaign} in all"));
+        Ok(StatementRecord::AwardXp {
+            xp,
+            session,
+            campaign,
+        })
+    }
+
+    fn evaluate_award_treasure(
+        &mut self,
+        amount: &Amount,
+        multiplier: i32,
+        coin: &str,
+    ) -> Result<StatementRecord, CrawlError> {
+        let worth = self.coin_worth(coin)?;
+        let count = self.evaluate_amount(amount)?.max(0);
+        let (count, awarded) = count
+            .checked_mul(multiplier)
+            .and_then(|count| Some((count, count.checked_mul(worth)?)))
+            .ok_or_else(|| out_of_range(format!("{count} x {multiplier} {coin}")))?;
+        let (session, campaign) = self
+            .session_tally
+            .treasure
+            .checked_add(awarded)
+            .zip(self.campaign_tally.treasure.checked_add(awarded))
+            .ok_or_else(|| out_of_range(format!("the treasure tally with {count} {coin} more")))?;
+        self.session_tally.treasure = session;
+        self.campaign_tally.treasure = campaign;
+        let session = self.denominations.format(self.session_tally.treasure);
+        let campaign = self.denominations.format(self.campaign_tally.treasure);
+        self.trace(format_args!(
+            "award {count} {coin} in treasure: {session}, {campaign} in all"
+        ));
+        Ok(StatementRecord::AwardTreasure {
+            treasure: format!("{count} {coin}"),
+            session,
+            campaign,
+        })
+    }
+
+    fn evaluate_report_xp(&self) -> StatementRecord {
+        let Tally { xp, treasure } = self.session_tally;
+        StatementRecord::XpReport {
+            party_size: self.party_size,
+            session_xp: xp,
+            campaign_xp: self.campaign_tally.xp,
+            xp_share: tally::share(xp, self.party_size),
+            session_treasure: self.denominations.format(treasure),
+            treasure_share: self
+                .denominations
+                .format(tally::share(treasure, self.party_size)),
+        }
+    }
+
+    fn evaluate_advance_time(&mut self, amount: &Amount) -> Result<StatementRecord, CrawlError> {
+        let by = self.evaluate_amount(amount)?.max(0);
+        self.turns = self
+            .turns
+            .checked_add(by)
+            .ok_or_else(|| out_of_range(format!("{by} more turns")))?;
+        let (out, burning): (Vec<_>, Vec<_>) = std::mem::take(&mut self.burning)
+            .into_iter()
+            .map(|light| BurningLight {
+                turns_left: light.turns_left - by,
+                ..light
+            })
+            .partition(|light| light.turns_left <= 0);
+        self.burning = burning;
+        let guttered: Vec<String> = out.into_iter().map(|light| light.light).collect();
+        for light in &guttered {
+            if !self.burning.iter().any(|burning| &burning.light == light) {
+                self.swap_fact(&lights::lit_fact(light), &lights::out_fact(light))?;
+            }
+        }
+        self.trace(format_args!(
+            "advance time by {by} turns: {} turns, {guttered:?} guttered",
+            self.turns
+        ));
+        Ok(StatementRecord::AdvanceTime {
+            by,
+            turns: self.turns,
+            guttered,
+        })
+    }
+
+    fn evaluate_light(&mut self, light: &str) -> Result<StatementRecord, CrawlError> {
+        let turns = self
+            .lights
+            .duration(light)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("there's no light called {light:?} to light"),
+            })?;
+        self.burning.push(BurningLight {
+            light: light.into(),
+            turns_left: turns,
+        });
+        self.swap_fact(&lights::out_fact(light), &lights::lit_fact(light))?;
+        self.trace(format_args!("light {light:?} for {turns} turns"));
+        Ok(StatementRecord::Light {
+            light: light.into(),
+            turns,
+        })
+    }
+
+    // Clear the local fact `clear` and set `set`, if it isn't already. Facts that go back and
+    // forth like this aren't set twice.
+    fn swap_fact(&mut self, clear: &str, set: &str) -> Result<(), CrawlError> {
+        let cleared = Fact::try_from(clear)?;
+        self.local_facts.clear(&cleared);
+        self.facts_set.remove(&cleared);
+        let set = Fact::try_from(set)?;
+        if self.local_facts.check(&set) {
+            self.derive_facts();
+        } else {
+            self.set_parsed_fact(set, false);
+        }
+        Ok(())
+    }
+
+    fn evaluate_advance_weather(&mut self, table: &str) -> Result<StatementRecord, CrawlError> {
+        let (entity, attribute) = weather::WEATHER_FACT.split_once(' ').unwrap();
+        let current = self.persistent_facts.values(entity, attribute).next();
+        let from = match current {
+            Some(current) => current.to_string(),
+            None => {
+                let weather_table =
+                    self.tables
+                        .get(table)
+                        .ok_or_else(|| CrawlError::InterpreterError {
+                            reason: format!("table {table:?} isn't loaded"),
+                        })?;
+                let first = weather::states(weather_table)
+                    .first()
+                    .map(|state| state.to_string());
+                first.ok_or_else(|| CrawlError::InterpreterError {
+                    reason: format!(
+                        "table {table:?} needs a header row naming the weather each column follows"
+                    ),
+                })?
+            }
+        };
+        let to = self.roll_on_table(table, Some(&from))?.1.join("; ");
+        self.trace(format_args!("advance weather {from:?} => {to:?}"));
+
+        // The weather it was isn't set any more, so it can be set again without a warning.
+        let old = Fact::try_from(weather::fact(&from).as_str())?;
+        self.persistent_facts.clear(&old);
+        self.facts_set.remove(&old);
+        self.set_fact(&weather::fact(&to), true)?;
+        Ok(StatementRecord::AdvanceWeather { from, to })
+    }
+
+    fn evaluate_advance_date(
+        &mut self,
+        amount: &Amount,
+        unit: DateUnit,
+    ) -> Result<StatementRecord, CrawlError> {
+        let count = self.evaluate_amount(amount)?;
+        let days = self.move_date(count, unit)?;
+        let date = self.calendar.format(self.date);
+        self.trace(format_args!("advance date by {days} days: {date}"));
+        Ok(StatementRecord::AdvanceDate { days, date })
+    }
+
+    /// Move the date on by `count` `unit`s, and return how many days that was.
+    fn move_date(&mut self, count: i32, unit: DateUnit) -> Result<i32, CrawlError> {
+        let (date, days) = self.date_after(count, unit)?;
+        self.date = date;
+        Ok(days)
+    }
+
+    // The date `count` `unit`s on, and how many days that is.
+    fn date_after(&self, count: i32, unit: DateUnit) -> Result<(i32, i32), CrawlError> {
+        let days = count
+            .checked_mul(self.calendar.days_in(unit))
+            .ok_or_else(|| out_of_range(format!("{count} {unit}")))?;
+        let date = self
+            .date
+            .checked_add(days)
+            .ok_or_else(|| out_of_range(format!("the date {days} days on")))?;
+        Ok((date, days))
+    }
+
+    fn evaluate_add_combatant(
+        &mut self,
+        combatant: &str,
+        modified_roll_specifier: &ModifiedRollSpecifier,
+    ) -> Result<StatementRecord, CrawlError> {
+        let roll = self.dice_roll(modified_roll_specifier)?;
+        let initiative = self.roll(&roll)?;
+        self.trace(format_args!(
+            "add combatant {combatant:?} with initiative {initiative}"
+        ));
+        if !self.initiative.add(combatant, initiative) {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("combatant {combatant} is already in the fight"),
+            });
+        }
+        Ok(StatementRecord::AddCombatant {
+            combatant: combatant.into(),
+            initiative,
+        })
+    }
+
+    fn evaluate_remove_combatant(
+        &mut self,
+        combatant: &str,
+    ) -> Result<StatementRecord, CrawlError> {
+        self.trace(format_args!("remove combatant {combatant:?}"));
+        if !self.initiative.remove(combatant) {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("no combatant named {combatant}"),
+            });
+        }
+        Ok(StatementRecord::RemoveCombatant(combatant.into()))
+    }
+
+    fn evaluate_sort_combatants(&mut self) -> StatementRecord {
+        self.initiative.sort();
+        self.trace(format_args!("sort combatants"));
+        StatementRecord::SortCombatants(self.initiative.combatants().to_vec())
+    }
+
+    fn evaluate_advance_turn(&mut self) -> Result<StatementRecord, CrawlError> {
+        let combatant = match self.initiative.advance() {
+            Some(combatant) => combatant.name.clone(),
+            None => {
+                return Err(CrawlError::InterpreterError {
+                    reason: "there are no combatants".into(),
+                });
+            }
+        };
+        let round = self.initiative.round();
+        self.trace(format_args!("round {round}: {combatant:?}'s turn"));
+        Ok(StatementRecord::AdvanceTurn { combatant, round })
+    }
+
+    fn evaluate_check_clock_full(&mut self, clock: &str) -> Result<bool, CrawlError> {
+        let full = self
+            .clocks
+            .get(clock)
+            .ok_or_else(|| undefined_clock(clock))?
+            .is_full();
+        self.trace(format_args!("check clock {clock:?} full => {full}"));
+        Ok(full)
+    }
+
+    fn evaluate_track_resource(
+        &mut self,
+        resource: &str,
+        amount: i32,
+    ) -> Result<StatementRecord, CrawlError> {
+        let remaining = amount.max(0);
+        self.trace(format_args!("track {resource:?}: {remaining}"));
+        self.resources.insert(resource.into(), remaining);
+        Ok(StatementRecord::TrackResource {
+            resource: resource.into(),
+            remaining,
+        })
+    }
+
+    fn evaluate_spend_resource(
+        &mut self,
+        resource: &str,
+        amount: &Amount,
+    ) -> Result<StatementRecord, CrawlError> {
+        let amount = self.evaluate_amount(amount)?;
+        let remaining = self
+            .resources
+            .get_mut(resource)
+            .ok_or_else(|| untracked_resource(resource))?;
+        // There's only so much to spend.
+        let spent = amount.clamp(0, *remaining);
+        *remaining -= spent;
+        let remaining = *remaining;
+        self.trace(format_args!("spend {spent} {resource:?}: {remaining} left"));
+        Ok(StatementRecord::SpendResource {
+            resource: resource.into(),
+            spent,
+            remaining,
+        })
+    }
+
+    fn evaluate_add_money(
+        &mut self,
+        amount: &Amount,
+        coin: &str,
+        purse: &str,
+    ) -> Result<StatementRecord, CrawlError> {
+        let worth = self.coin_worth(coin)?;
+        let count = self.evaluate_amount(amount)?.max(0);
+        let balance = count
+            .checked_mul(worth)
+            .and_then(|added| self.purse(purse).unwrap_or(0).checked_add(added))
+            .ok_or_else(|| out_of_range(format!("{purse} with {count} {coin} more")))?;
+        self.purses.insert(purse.into(), balance);
+        self.trace(format_args!("add {count} {coin} to {purse:?}: {balance}"));
+        Ok(StatementRecord::AddMoney {
+            purse: purse.into(),
+            added: format!("{count} {coin}"),
+            balance: self.denominations.format(balance),
+        })
+    }
+
+    fn evaluate_spend_money(
+        &mut self,
+        amount: &Amount,
+        coin: &str,
+        purse: Option<&str>,
+    ) -> Result<StatementRecord, CrawlError> {
+        let worth = self.coin_worth(coin)?;
+        let count = self.evaluate_amount(amount)?.max(0);
+        let purse = match purse {
+            Some(purse) => purse.to_string(),
+            None => self.only_purse()?,
+        };
+        let balance = *self
+            .purses
+            .get(&purse)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("there's no purse named {purse}"),
+            })?;
+        let spent = count
+            .checked_mul(worth)
+            .ok_or_else(|| out_of_range(format!("{count} {coin}")))?;
+        // Coins are changed as needed, but only what's in the purse can be spent.
+        if spent > balance {
+            return Err(CrawlError::InterpreterError {
+                reason: format!(
+                    "{purse} has only {}, not {count} {coin}",
+                    self.denominations.format(balance)
+                ),
+            });
+        }
+        let balance = balance - spent;
+        self.purses.insert(purse.clone(), balance);
+        self.trace(format_args!(
+            "spend {count} {coin} from {purse:?}: {balance}"
+        ));
+        Ok(StatementRecord::SpendMoney {
+            purse,
+            spent: format!("{count} {coin}"),
+            balance: self.denominations.format(balance),
+        })
+    }
+
+    fn coin_worth(&self, coin: &str) -> Result<i32, CrawlError> {
+        self.denominations
+            .worth(coin)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("there's no coin called {coin}"),
+            })
+    }
+
+    // The purse to spend from when none is named, if there's just the one.
+    fn only_purse(&self) -> Result<String, CrawlError> {
+        let mut purses = self.purses.keys();
+        match (purses.next(), purses.next()) {
+            (Some(purse), None) => Ok(purse.clone()),
+            (None, _) => Err(CrawlError::InterpreterError {
+                reason: "there's no money to spend".into(),
+            }),
+            (Some(_), Some(_)) => Err(CrawlError::InterpreterError {
+                reason: "there's more than one purse, so say which to spend from".into(),
+            }),
+        }
+    }
+
+    fn evaluate_travel(&mut self, terrain: &str) -> Result<StatementRecord, CrawlError> {
+        let chances = self.travel.chances(terrain);
+        let d6 = DiceRoll::new(DicePool::new(vec![Die(6)]), 0);
+
+        let lost = self.roll(&d6)? <= chances.lost;
+        let lost_fact = Fact::try_from(travel::LOST_FACT)?;
+        if !lost {
+            self.local_facts.clear(&lost_fact);
+            self.derive_facts();
+        } else if !self.local_facts.check(&lost_fact) {
+            self.set_parsed_fact(lost_fact, false);
+        }
+
+        let encounter_table = travel::encounter_table(terrain);
+        let encounter =
+            if self.tables.contains_key(&encounter_table) && self.roll(&d6)? <= chances.encounter {
+                Some(self.roll_on_table(&encounter_table, None)?.1.join("; "))
+            } else {
+                None
+            };
+        let weather_table = travel::weather_table(terrain);
+        let weather = if self.tables.contains_key(&weather_table) {
+            Some(self.roll_on_table(&weather_table, None)?.1.join("; "))
+        } else {
+            None
+        };
+        let discovery_table = travel::discovery_table(terrain);
+        let discovery =
+            if self.tables.contains_key(&discovery_table) && self.roll(&d6)? <= chances.discovery {
+                Some(self.roll_on_table(&discovery_table, None)?.1.join("; "))
+            } else {
+                None
+            };
+
+        self.trace(format_args!(
+            "travel {terrain:?}: lost {lost}, encounter {encounter:?}, weather {weather:?}, discovery {discovery:?}"
+        ));
+        Ok(StatementRecord::Travel {
+            terrain: terrain.into(),
+            lost,
+            encounter,
+            weather,
+            discovery,
+        })
+    }
+
+    fn evaluate_morale_check(
+        &mut self,
+        group: &str,
+        morale: &ModifierValue,
+    ) -> Result<StatementRecord, CrawlError> {
+        let morale = self.modifier_value(morale)?;
+        let two_d6 = DiceRoll::new(DicePool::new(vec![Die(6); 2]), 0);
+        let total = self.roll(&two_d6)?;
+        let held = total <= morale;
+        self.trace(format_args!(
+            "morale check {group:?}: {total} against {morale} => held {held}"
+        ));
+        if !held {
+            self.set_fact(&format!("{group} are fleeing"), false)?;
+        }
+        Ok(StatementRecord::MoraleCheck {
+            group: group.into(),
+            total,
+            morale,
+            held,
+        })
+    }
+
+    fn evaluate_encounter(&mut self, terrain: &str) -> Result<StatementRecord, CrawlError> {
+        let (entries, values) = self.roll_on_table(&travel::encounter_table(terrain), None)?;
+        let number = match entries
+            .iter()
+            .find_map(|entry| entry.field(NUMBER_APPEARING))
+        {
+            Some(number) => Some(self.roll_number(number, "number appearing")?),
+            None => None,
+        };
+        let reaction_roll = self.encounters.reaction_roll().clone();
+        let total = self.roll(&reaction_roll)?;
+        let reaction = self.encounters.reaction(total).to_string();
+        let Distance { roll, scale, unit } = self.encounters.distance(terrain).clone();
+        let distance = format!("{} {unit}", self.roll(&roll)? * scale);
+
+        let creature = values.join("; ");
+        self.trace(format_args!(
+            "encounter {terrain:?}: {creature:?} x{number:?}, {reaction} ({total}), {distance}"
+        ));
+        Ok(StatementRecord::Encounter {
+            terrain: terrain.into(),
+            creature,
+            number,
+            reaction,
+            distance,
+        })
+    }
+
+    fn evaluate_generate_room(&mut self) -> Result<StatementRecord, CrawlError> {
+        let [shape, exits, contents] =
+            rooms::TABLES.map(|table| Ok(self.roll_on_table(table, None)?.1.join("; ")));
+        let (shape, exits, contents) = (shape?, exits?, contents?);
+        self.rooms += 1;
+        self.trace(format_args!(
+            "generate room {}: {shape:?}, {exits:?}, {contents:?}",
+            self.rooms
+        ));
+        Ok(StatementRecord::GenerateRoom {
+            number: self.rooms,
+            shape,
+            exits,
+            contents,
+        })
+    }
+
+    fn evaluate_adjust_chaos(&mut self, by: i32) -> Result<StatementRecord, CrawlError> {
+        let from = self.chaos;
+        self.chaos = from.saturating_add(by).clamp(MIN_CHAOS, MAX_CHAOS);
+        self.trace(format_args!("chaos factor {from} => {}", self.chaos));
+        Ok(StatementRecord::AdjustChaos {
+            from,
+            to: self.chaos,
+        })
+    }
+
+    fn evaluate_scene_check(&mut self) -> Result<StatementRecord, CrawlError> {
+        let rolled = self.roll(&DiceRoll::new(DicePool::new(vec![Die(10)]), 0))?;
+        let outcome = SceneOutcome::of(rolled, self.chaos);
+        self.trace(format_args!(
+            "scene check: {rolled} against chaos {}, {outcome}",
+            self.chaos
+        ));
+        let event = if outcome == SceneOutcome::Interrupted {
+            Some(RandomEvent {
+                focus: self.roll_on_table(scenes::EVENT_FOCUS, None)?.1.join("; "),
+                meaning: self
+                    .roll_on_table(scenes::EVENT_MEANING, None)?
+                    .1
+                    .join("; "),
+            })
+        } else {
+            None
+        };
+        Ok(StatementRecord::SceneCheck {
+            rolled,
+            chaos: self.chaos,
+            outcome,
+            event,
+        })
+    }
+
+    // A number from a table is a number, a roll like `2d4`, or has rolls embedded, like `{1d6}`.
+    // `what` it's a number of is for errors.
+    fn roll_number(&mut self, number: &str, what: &str) -> Result<i32, CrawlError> {
+        let rolled = self.roll_embedded(number)?;
+        let rolled = rolled.trim();
+        if let Ok(number) = rolled.parse() {
+            return Ok(number);
+        }
+        let roll: DiceRoll = rolled.parse().map_err(|_| CrawlError::InterpreterError {
+            reason: format!("{what} {number:?} isn't a number or a roll"),
+        })?;
+        self.roll(&roll)
+    }
+
+    fn evaluate_track_hit_points(
+        &mut self,
+        entity: &str,
+        amount: &Amount,
+    ) -> Result<StatementRecord, CrawlError> {
+        let hit_points = self.evaluate_amount(amount)?.max(0);
+        self.trace(format_args!("track hp {entity:?}: {hit_points}"));
+        self.hit_points.insert(entity.into(), hit_points);
+        Ok(StatementRecord::TrackHitPoints {
+            entity: entity.into(),
+            hit_points,
+        })
+    }
+
+    fn evaluate_damage(
+        &mut self,
+        entity: &str,
+        amount: &Amount,
+    ) -> Result<StatementRecord, CrawlError> {
+        let damage = self.evaluate_amount(amount)?.max(0);
+        let remaining = self
+            .hit_points
+            .get_mut(entity)
+            .ok_or_else(|| untracked_hit_points(entity))?;
+        *remaining = remaining.saturating_sub(damage).max(0);
+        let remaining = *remaining;
+        self.trace(format_args!(
+            "damage {entity:?} by {damage}: {remaining} left"
+        ));
+        Ok(StatementRecord::Damage {
+            entity: entity.into(),
+            damage,
+            remaining,
+        })
+    }
+
+    fn evaluate_check_dead(&mut self, entity: &str) -> Result<bool, CrawlError> {
+        let dead = self
+            .hit_points(entity)
+            .ok_or_else(|| untracked_hit_points(entity))?
+            == 0;
+        self.trace(format_args!("check {entity:?} dead => {dead}"));
+        Ok(dead)
+    }
+
+    fn evaluate_check_resource_depleted(&mut self, resource: &str) -> Result<bool, CrawlError> {
+        let depleted = self
+            .resource(resource)
+            .ok_or_else(|| untracked_resource(resource))?
+            == 0;
+        self.trace(format_args!("check {resource:?} depleted => {depleted}"));
+        Ok(depleted)
+    }
+
+    fn evaluate_usage_die(
+        &mut self,
+        resource: &str,
+        sides: i32,
+    ) -> Result<StatementRecord, CrawlError> {
+        if !is_usage_die(sides) {
+            return Err(CrawlError::InterpreterError {
+                reason: format!("d{sides} isn't a usage die"),
+            });
+        }
+        self.trace(format_args!("usage die {resource:?}: d{sides}"));
+        self.usage_dice.insert(resource.into(), Some(sides));
+        // Restocking something that ran out.
+        let depleted: Fact = depleted_fact(resource).try_into()?;
+        self.persistent_facts.clear(&depleted);
+        self.derive_facts();
+        Ok(StatementRecord::UsageDie {
+            resource: resource.into(),
+            sides,
+        })
+    }
+
+    fn evaluate_usage_roll(&mut self, resource: &str) -> Result<StatementRecord, CrawlError> {
+        let sides = self
+            .usage_dice
+            .get(resource)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("{resource} has no usage die"),
+            })?
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("{resource} is used up"),
+            })?;
+
+        let roll = DiceRoll::new(DicePool::new(vec![Die(sides)]), 0);
+        let rolled = self.roll(&roll)?;
+        let die = if rolled <= 2 {
+            step_down_usage_die(sides)
+        } else {
+            Some(sides)
+        };
+        self.usage_dice.insert(resource.into(), die);
+        match die {
+            Some(die) => self.trace(format_args!("usage {resource:?}: d{die}")),
+            None => {
+                self.trace(format_args!("usage {resource:?}: used up"));
+                self.set_fact(&depleted_fact(resource), true)?;
+            }
+        }
+
+        Ok(StatementRecord::UsageRoll {
+            resource: resource.into(),
+            rolled,
+            die,
+        })
+    }
+
+    fn evaluate_let(&mut self, name: &str, value: &Amount) -> Result<StatementRecord, CrawlError> {
+        let value = self.evaluate_amount(value)?;
+        self.trace(format_args!("let {name} = {value}"));
+        self.variables.insert(name.into(), value);
+        Ok(StatementRecord::Let {
+            name: name.into(),
+            value,
+        })
+    }
+
+    fn evaluate_oracle(
+        &mut self,
+        question: &str,
+        likelihood: &Likelihood,
+    ) -> Result<StatementRecord, CrawlError> {
+        let odds = self
+            .campaign_odds
+            .get(likelihood)
+            .or_else(|| self.oracle.odds(likelihood))
+            .cloned()
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("the oracle has no odds called {likelihood:?}"),
+            })?;
+        let rolled = self.roll(&odds.roll)?;
+        let answer = rolled <= self.oracle.target(&odds, self.chaos);
+        self.trace(format_args!(
+            "oracle {question:?} ({likelihood}) => {rolled}, {}",
+            if answer { "yes" } else { "no" }
+        ));
+        Ok(StatementRecord::Oracle {
+            question: question.into(),
+            likelihood: likelihood.clone(),
+            rolled,
+            answer,
+        })
+    }
+
+    fn evaluate_amount(&mut self, amount: &Amount) -> Result<i32, CrawlError> {
+        match amount {
+            Amount::Fixed(n) => Ok(*n),
+            Amount::Roll(modified_roll_specifier) => {
+                let roll = self.dice_roll(modified_roll_specifier)?;
+                Ok(self.roll(&roll)?)
+            }
+            Amount::Call(name) => match self.variable(name) {
+                Some(value) => Ok(value),
+                None => {
+                    let record = self.evaluate_procedure_call(name, &[])?;
+                    record
+                        .value()
+                        .and_then(|value| value.trim().parse().ok())
+                        .ok_or_else(|| CrawlError::InterpreterError {
+                            reason: format!("{name} didn't come to a number"),
+                        })
+                }
+            },
+        }
+    }
+
+    fn evaluate_nontargeted_roll(
+        &mut self,
+        modified_roll_specifier: &ModifiedRollSpecifier,
+    ) -> Result<StatementRecord, CrawlError> {
+        let roll = self.dice_roll(modified_roll_specifier)?;
+        Ok(StatementRecord::NontargetedRoll(self.roll(&roll)?))
+    }
+
+    fn evaluate_str(&mut self, s: CrawlStr) -> Result<String, CrawlError> {
+        match s {
+            CrawlStr::Str(raw_string) => Ok(raw_string.clone()),
+            CrawlStr::InterpolatedStr {
+                format_string,
+                expressions,
+            } => {
+                let mut values = Vec::with_capacity(expressions.len());
+                for expr in expressions {
+                    // A bare identifier parses as a call, but a variable bound with that name
+                    // wins.
+                    let value = match &expr {
+                        Statement::ProcedureCall {
+                            identifier,
+                            arguments,
+                        } if arguments.is_empty() && self.variables.contains_key(identifier) => {
+                            self.variables[identifier].to_string()
+                        }
+                        Statement::ProcedureCall {
+                            identifier,
+                            arguments,
+                        } if arguments.is_empty()
+                            && identifier == downtime::CHARACTER
+                            && self.downtime_character.is_some() =>
+                        {
+                            self.downtime_character.clone().unwrap()
+                        }
+                        _ => {
+                            let record = self.evaluate_statement(&expr)?;
+                            record.value().unwrap_or_else(|| format!("{record:?}"))
+                        }
+                    };
+                    values.push(value);
+                }
+
+                // Placeholders are filled in order, and any left over are kept as written.
+                let re = Regex::new(r"\{[^{}]*\}").unwrap();
+                let mut values = values.into_iter();
+                let replaced = re.replace_all(&format_string, |captures: &regex::Captures| {
+                    values.next().unwrap_or_else(|| captures[0].to_string())
+                });
+
+                Ok(replaced.to_string())
+            }
+        }
+    }
+
+    /// Report something likely to be a mistake, which doesn't stop the program.
+    fn warn(&mut self, warning: String) {
+        if let Some(output) = self.output() {
+            output.write_warning(&warning);
+        }
+    }
+
+    fn trace(&self, message: fmt::Arguments) {
+        if self.trace {
+            eprintln!("{}{message}", "  ".repeat(self.call_depth));
+        }
+    }
+
+    fn roll(&mut self, roll: &DiceRoll) -> Result<i32, CrawlError> {
+        self.roll_result(roll)?.checked_total()
+    }
+
+    // The roll's result, kept in the roll history.
+    fn roll_result(&mut self, roll: &DiceRoll) -> Result<&DiceRollResult, CrawlError> {
+        let roll_result = match self.dice_prompt.as_mut() {
+            Some(prompt) => roll.with_faces(&prompt.enter_faces(&roll.dice_pool)?)?,
+            None => roll.roll_with(&mut self.rng),
+        };
+        self.trace_roll(roll, &roll_result);
+        if self.record_rolls {
+            self.rolls.push(RollOutcome::new(roll, &roll_result));
+        }
+        if self.roll_history.len() == ROLL_HISTORY_LEN {
+            self.roll_history.pop_front();
+        }
+        self.roll_history.push_back(roll_result);
+        Ok(self.roll_history.back().unwrap())
+    }
+
+    fn trace_roll(&self, roll: &DiceRoll, roll_result: &DiceRollResult) {
+        let faces: Vec<String> = roll_result
+            .pool_result
+            .results
+            .iter()
+            .map(|die| die.to_string())
+            .collect();
+        self.trace(format_args!(
+            "roll {}{:+}: [{}] = {}",
+            roll.dice_pool,
+            roll.modifier,
+            faces.join(", "),
+            roll_result.total
+        ));
+    }
+
+    /// The roll `modified_roll_specifier` describes, with its modifier terms looked up, and
+    /// those whose conditions don't hold left out, and any modifiers applied to it added.
+    fn dice_roll(
+        &mut self,
+        modified_roll_specifier: &ModifiedRollSpecifier,
+    ) -> Result<DiceRoll, CrawlError> {
+        let applied = match &modified_roll_specifier.base_roll_specifier {
+            Token::Str(name) => self.applied_modifier(Some(name))?,
+            _ => self.applied_modifier(None)?,
+        };
+        let modified_roll_specifier = self.named_roll(modified_roll_specifier)?;
+        let modified_roll_specifier = modified_roll_specifier.as_ref();
+        if let Token::RollSpecifier(spec) = modified_roll_specifier.base_roll_specifier {
+            self.dice_limits.check(spec)?;
+        }
+        let mut roll: DiceRoll = modified_roll_specifier.try_into()?;
+        for term in &modified_roll_specifier.terms {
+            let applies = match &term.condition {
+                Some(condition) => self.evaluate_antecedent(condition)?,
+                None => true,
+            };
+            if !applies {
+                continue;
+            }
+            let value = self.modifier_value(&term.value)?;
+            let value = if term.negated {
+                value.checked_neg()
+            } else {
+                Some(value)
+            };
+            roll.modifier = value
+                .and_then(|value| roll.modifier.checked_add(value))
+                .ok_or_else(|| CrawlError::InterpreterError {
+                    reason: format!("modifier of {roll} is out of range"),
+                })?;
+        }
+        roll.modifier =
+            roll.modifier
+                .checked_add(applied)
+                .ok_or_else(|| CrawlError::InterpreterError {
+                    reason: format!("modifier of {roll} is out of range"),
+                })?;
+        Ok(roll)
+    }
+
+    fn modifier_value(&self, value: &ModifierValue) -> Result<i32, CrawlError> {
+        match value {
+            ModifierValue::Fixed(n) => Ok(*n),
+            ModifierValue::Variable(name) => self.lookup_variable(name),
+            ModifierValue::Fact(fact) => self.fact_value(fact),
+            ModifierValue::Stat { statblock, stat } => self
+                .statblock(statblock)?
+                .stats
+                .get(stat)
+                .copied()
+                .ok_or_else(|| CrawlError::InterpreterError {
+                    reason: format!("statblock {statblock:?} has no stat {stat:?}"),
+                }),
+        }
+    }
+
+    fn lookup_variable(&self, name: &str) -> Result<i32, CrawlError> {
+        self.variable(name).ok_or_else(|| undefined_variable(name))
+    }
+
+    /// The number a fact gives for an entity's attribute, like `"fighter str-mod"`, from local
+    /// facts or persistent ones.
+    fn fact_value(&self, fact: &str) -> Result<i32, CrawlError> {
+        match self.fact_values(fact)?.as_slice() {
+            [value] => value.parse().map_err(|_| CrawlError::InterpreterError {
+                reason: format!("{fact} is {value:?}, which isn't a number"),
+            }),
+            [] => Err(CrawlError::InterpreterError {
+                reason: format!("no fact gives {fact}"),
+            }),
+            _ => Err(CrawlError::InterpreterError {
+                reason: format!("{fact} has more than one value"),
+            }),
+        }
+    }
+
+    /// Every value facts give for an entity's attribute, local or persistent, in order.
+    fn fact_values(&self, fact: &str) -> Result<Vec<String>, CrawlError> {
+        let (entity, attribute) =
+            fact.split_once(' ')
+                .ok_or_else(|| CrawlError::InterpreterError {
+                    reason: format!("{fact:?} should be an entity and an attribute"),
+                })?;
+        let mut values: Vec<String> = self
+            .local_facts
+            .values(entity, attribute)
+            .chain(self.persistent_facts.values(entity, attribute))
+            .chain(self.derived_facts.values(entity, attribute))
+            .map(String::from)
+            .collect();
+        values.sort();
+        values.dedup();
+        Ok(values)
+    }
+
+    fn total_matches_target(&self, total: i32, target: &Token) -> Result<bool, CrawlError> {
+        match target {
+            Token::Num(n) => Ok(total == *n),
+            Token::NumRange(min, max) => Ok(*min <= total && total <= *max),
+            Token::OverOrEqual(n) => Ok(total >= *n),
+            Token::UnderOrEqual(n) => Ok(total <= *n),
+            Token::Identifier(name) => Ok(total == self.lookup_variable(name)?),
+            _ => Err(CrawlError::InterpreterError {
+                reason: "invalid roll target".into(),
+            }),
+        }
+    }
+}
+
+fn out_of_range(what: impl fmt::Display) -> CrawlError {
+    CrawlError::InterpreterError {
+        reason: format!("{what} is out of range"),
+    }
+}
+
+fn not_on_table(table_name: &str, entry: &str) -> CrawlError {
+    CrawlError::InterpreterError {
+        reason: format!("{entry:?} isn't an entry on table {table_name:?}"),
+    }
+}
+
+fn missing_field(table_name: &str, field: &str) -> CrawlError {
+    CrawlError::InterpreterError {
+        reason: format!("table {table_name} has no {field:?} column"),
+    }
+}
+
+fn assertion_failed(assertion: &str) -> CrawlError {
+    CrawlError::InterpreterError {
+        reason: format!("assertion failed: {assertion}"),
+    }
+}
+
+fn undefined_variable(name: &str) -> CrawlError {
+    CrawlError::InterpreterError {
+        reason: format!("variable {name} isn't bound"),
+    }
+}
+
+fn undefined_clock(clock: &str) -> CrawlError {
+    CrawlError::InterpreterError {
+        reason: format!("no clock named {clock}"),
+    }
+}
+
+/// The persistent fact set when a resource's usage die runs out.
+fn depleted_fact(resource: &str) -> String {
+    format!("{resource} is depleted")
+}
+
+fn untracked_resource(resource: &str) -> CrawlError {
+    CrawlError::InterpreterError {
+        reason: format!("resource {resource} isn't tracked"),
+    }
+}
+
+fn untracked_hit_points(entity: &str) -> CrawlError {
+    CrawlError::InterpreterError {
+        reason: format!("hit points for {entity} aren't tracked"),
+    }
+}
+
+fn qualify(namespace: &str, identifier: &str) -> String {
+    if namespace.is_empty() {
+        identifier.into()
+    } else {
+        format!("{namespace}.{identifier}")
+    }
+}
+
+// Resolve `.` and `..` without touching the filesystem, so the same file is recognized however
+// it's reached, even in storage.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dice::RollSpec;
+    use crate::facts::Fact;
+    use crate::fronts::FRONTS_TABLE;
+    use crate::output::{BufferSink, Output};
+    use crate::parser::ModifierTerm;
+    use crate::prompt::{ScriptedPrompt, TerminalPrompt};
+    use crate::storage::MemoryStorage;
+    use crate::travel::TerrainChances;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn interp_to_values(statements: Vec<Statement>) -> Vec<StatementRecord> {
+        Interpreter::new()
+            .interpret(statements)
+            .into_iter()
+            .map(|v| v.unwrap())
+            .collect()
+    }
+
+    fn make_proc_body() -> Arc<[Statement]> {
+        vec![
+            Statement::IfThen {
+                antecedent: Antecedent::DiceRoll {
+                    target: Token::Num(1),
+                    roll_specifier: ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+                        modifier: 0,
+                        terms: Vec::new(),
+                    },
+                },
+                consequent: Box::new(Statement::Reminder("you passed the check".into())),
+            },
+            Statement::Reminder("cool procedure".into()),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn interpret_reminder() {
+        let ast = Statement::Reminder("players must eat rations daily".into());
+        let value = interp_to_values(vec![ast]);
+        assert_eq!(
+            value,
+            vec![StatementRecord::Reminder(
+                "players must eat rations daily".into()
+            )],
+        );
+    }
+
+    #[test]
+    fn interpret_if_then_antecedent_true() {
+        let ast = Statement::IfThen {
+            antecedent: Antecedent::DiceRoll {
+                target: Token::Num(1),
+                roll_specifier: ModifiedRollSpecifier {
+                    base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+                    modifier: 0,
+                    terms: Vec::new(),
+                },
+            },
+            consequent: Box::new(Statement::Reminder("you passed the check".into())),
+        };
+        let value = interp_to_values(vec![ast]);
+        assert_eq!(
+            value,
+            vec![StatementRecord::IfThen {
+                antecedent: true,
+                consequent: Some(Box::new(StatementRecord::Reminder(
+                    "you passed the check".into()
+                )))
+            }]
+        );
+    }
+
+    #[test]
+    fn interpret_if_then_antecedent_false() {
+        let ast = Statement::IfThen {
+            antecedent: Antecedent::DiceRoll {
+                target: Token::Num(100),
+                roll_specifier: ModifiedRollSpecifier {
+                    base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+                    modifier: 0,
+                    terms: Vec::new(),
+                },
+            },
+            consequent: Box::new(Statement::Reminder("you passed the check".into())),
+        };
+        let value = interp_to_values(vec![ast]);
+        assert_eq!(
+            value,
+            vec![StatementRecord::IfThen {
+                antecedent: false,
+                consequent: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn interpret_proc_def() {
+        let body = make_proc_body();
+        let ast = Statement::Procedure {
+            declaration: ProcedureDeclaration("proc-name".into()),
+            body: body.clone(),
+        };
+        let mut interp = Interpreter::new();
+        let value: Vec<StatementRecord> = interp
+            .interpret(vec![ast])
+            .into_iter()
+            .map(|v| v.unwrap())
+            .collect();
+        assert_eq!(
+            value,
+            vec![StatementRecord::ProcedureDefinition("proc-name".into())]
+        );
+        // The definition shares the parsed body rather than copying it.
+        assert!(Arc::ptr_eq(
+            &interp.procedures.get("proc-name").unwrap().body,
+            &body
+        ));
+    }
+
+    #[test]
+    fn interpret_proc_call() {
+        let body = make_proc_body();
+        let proc = Statement::Procedure {
+            declaration: ProcedureDeclaration("proc-name".into()),
+            body: body.clone(),
+        };
+        let call = Statement::ProcedureCall {
+            identifier: "proc-name".into(),
+            arguments: Vec::new(),
+        };
+        let ast = vec![proc, call];
+        let values = interp_to_values(ast);
+        assert_eq!(
+            values,
+            vec![
+                StatementRecord::ProcedureDefinition("proc-name".into()),
+                StatementRecord::ProcedureCall {
+                    identifier: "proc-name".into(),
+                    records: vec![
+                        Box::new(StatementRecord::IfThen {
+                            antecedent: true,
+                            consequent: Some(Box::new(StatementRecord::Reminder(
+                                "you passed the check".into()
+                            ))),
+                        }),
+                        Box::new(StatementRecord::Reminder("cool procedure".into())),
+                    ],
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn max_call_depth() {
+        let ast = vec![
+            Statement::Procedure {
+                declaration: ProcedureDeclaration("ping".into()),
+                body: vec![Statement::ProcedureCall {
+                    identifier: "pong".into(),
+                    arguments: Vec::new(),
+                }]
+                .into(),
+            },
+            Statement::Procedure {
+                declaration: ProcedureDeclaration("pong".into()),
+                body: vec![Statement::ProcedureCall {
+                    identifier: "ping".into(),
+                    arguments: Vec::new(),
+                }]
+                .into(),
+            },
+            Statement::ProcedureCall {
+                identifier: "ping".into(),
+                arguments: Vec::new(),
+            },
+        ];
+        let mut interp = Interpreter::new().with_max_call_depth(10);
+        let results = interp.interpret(ast);
+        assert!(matches!(
+            results.last().unwrap(),
+            Err(CrawlError::InterpreterError { reason }) if reason.contains("maximum call depth")
+        ));
+        assert_eq!(interp.call_depth, 0);
+    }
+
+    #[test]
+    fn max_steps() {
+        let ast = vec![
+            Statement::Reminder("one".into()),
+            Statement::Reminder("two".into()),
+            Statement::Reminder("three".into()),
+        ];
+        let results = Interpreter::new().with_max_steps(2).interpret(ast);
+        assert_eq!(results.len(), 3);
+        assert!(results[1].is_ok());
+        assert!(matches!(
+            &results[2],
+            Err(CrawlError::InterpreterError { reason }) if reason.contains("maximum of 2")
+        ));
+    }
+
+    #[test]
+    fn dice_limits() {
+        let roll = |n_dice| {
+            Statement::NontargetedRoll(ModifiedRollSpecifier {
+                base_roll_specifier: Token::RollSpecifier(RollSpec::new(n_dice, 6)),
+                modifier: 0,
+                terms: Vec::new(),
+            })
+        };
+        let mut interp = Interpreter::new().with_dice_limits(DiceLimits {
+            max_dice: 3,
+            max_sides: 6,
+        });
+        let results = interp.interpret(vec![roll(3), roll(4)]);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            &results[1],
+            Err(CrawlError::InterpreterError { reason }) if reason == "4d6 rolls more than 3 dice"
+        ));
+    }
+
+    #[test]
+    fn seeded_rolls_repeat() {
+        let ast = vec![
+            Statement::LoadTable {
+                table: "examples/table.csv".into(),
+                no_clamp: false,
+            },
+            Statement::TableRoll {
+                table: "examples/table.csv".into(),
+                field: None,
+                tag: None,
+                excluding_seen: false,
+            },
+            Statement::NontargetedRoll(ModifiedRollSpecifier {
+                base_roll_specifier: Token::RollSpecifier(RollSpec::new(3, 20)),
+                modifier: 0,
+                terms: Vec::new(),
+            }),
+        ];
+        let mut first = Interpreter::new().with_seed(7).with_roll_log(true);
+        let mut second = Interpreter::new().with_seed(7).with_roll_log(true);
+        let _ = first.interpret(ast.clone());
+        let _ = second.interpret(ast);
+        assert_eq!(first.rolls().len(), 2);
+        assert_eq!(first.rolls(), second.rolls());
+    }
+
+    #[test]
+    fn clocks() {
+        let advance = Statement::AdvanceClock {
+            clock: "alarm".into(),
+            amount: Amount::Fixed(2),
+        };
+        let ast = vec![
+            Statement::CreateClock {
+                clock: "alarm".into(),
+                segments: 3,
+            },
+            advance.clone(),
+            advance,
+            Statement::IfThen {
+                antecedent: Antecedent::CheckClockFull("alarm".into()),
+                consequent: Box::new(Statement::Reminder("the guards arrive".into())),
+            },
+        ];
+        let mut interp = Interpreter::new();
+        let values: Vec<StatementRecord> = interp
+            .interpret(ast)
+            .into_iter()
+            .map(|v| v.unwrap())
+            .collect();
+        assert_eq!(
+            values[2],
+            StatementRecord::AdvanceClock {
+                clock: "alarm".into(),
+                filled: 3,
+                segments: 3,
+            }
+        );
+        assert_eq!(
+            values[3],
+            StatementRecord::IfThen {
+                antecedent: true,
+                consequent: Some(Box::new(StatementRecord::Reminder(
+                    "the guards arrive".into()
+                ))),
+            }
+        );
+        assert!(interp.clock("alarm").unwrap().is_full());
+
+        let undefined = interp.interpret(vec![Statement::AdvanceClock {
+            clock: "doom".into(),
+            amount: Amount::Fixed(1),
+        }]);
+        assert!(undefined[0].is_err());
+    }
+
+    #[test]
+    fn resources() {
+        let depleted = Statement::IfThen {
+            antecedent: Antecedent::CheckResourceDepleted("torches".into()),
+            consequent: Box::new(Statement::Reminder("darkness".into())),
+        };
+        let ast = vec![
+            Statement::TrackResource {
+                resource: "torches".into(),
+                amount: 2,
+            },
+            Statement::SpendResource {
+                resource: "torches".into(),
+                amount: Amount::Fixed(1),
+            },
+            depleted.clone(),
+            Statement::SpendResource {
+                resource: "torches".into(),
+                amount: Amount::Fixed(3),
+            },
+            depleted,
+        ];
+        let mut interp = Interpreter::new();
+        let values: Vec<StatementRecord> = interp
+            .interpret(ast)
+            .into_iter()
+            .map(|v| v.unwrap())
+            .collect();
+        assert_eq!(
+            values[1],
+            StatementRecord::SpendResource {
+                resource: "torches".into(),
+                spent: 1,
+                remaining: 1,
+            }
+        );
+        assert_eq!(
+            values[2],
+            StatementRecord::IfThen {
+                antecedent: false,
+                consequent: None,
+            }
+        );
+        assert_eq!(
+            values[3],
+            StatementRecord::SpendResource {
+                resource: "torches".into(),
+                spent: 1,
+                remaining: 0,
+            }
+        );
+        assert_eq!(
+            values[4],
+            StatementRecord::IfThen {
+                antecedent: true,
+                consequent: Some(Box::new(StatementRecord::Reminder("darkness".into()))),
+            }
+        );
+        assert_eq!(interp.resource("torches"), Some(0));
+    }
+
+    #[test]
+    fn usage_die() {
+        let mut interp = Interpreter::new().with_seed(0);
+        let mut ast = vec![Statement::UsageDie {
+            resource: "rations".into(),
+            sides: 4,
+        }];
+        // With this seed, the d4 is used up well within 20 rolls.
+        ast.extend(vec![Statement::UsageRoll("rations".into()); 20]);
+        let values = interp.interpret(ast);
+
+        let used_up = values
+            .iter()
+            .position(|value| {
+                matches!(
+                    value,
+                    Ok(StatementRecord::UsageRoll { die: None, rolled, .. }) if *rolled <= 2
+                )
+            })
+            .unwrap();
+        assert!(values[used_up + 1].is_err());
+        assert!(
+            interp
+                .persistent_facts
+                .check(&Fact::try_from(String::from("rations is depleted")).unwrap())
+        );
+
+        let _ = interp.interpret(vec![Statement::UsageDie {
+            resource: "rations".into(),
+            sides: 8,
+        }]);
+        assert!(interp.persistent_facts.is_empty());
+    }
+
+    #[test]
+    fn choice() {
+        let choice = Statement::Choice {
+            question: "Take the left or right passage?".into(),
+            arms: vec![
+                ChoiceArm {
+                    option: "left".into(),
+                    consequent: Statement::Reminder("it's a dead end".into()),
+                },
+                ChoiceArm {
+                    option: "right".into(),
+                    consequent: Statement::Reminder("you hear water".into()),
+                },
+            ],
+        };
+        let mut interp = Interpreter::new().with_prompt(Box::new(ScriptedPrompt::new(["right"])));
+        let values = interp.interpret(vec![choice.clone(), choice]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::Choice {
+                question: "Take the left or right passage?".into(),
+                chosen: "right".into(),
+                consequent: Box::new(StatementRecord::Reminder("you hear water".into())),
+            }
+        );
+        // Out of answers
+        assert!(values[1].is_err());
+    }
+
+    #[test]
+    fn inline_interpolation() {
+        let source = "let x = 2\nprocedure who\n\treturn \"party\"\nend\nset-fact \"{who} is {x}\"\nset-fact \"{who} is {}\" % x\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let mut interp = Interpreter::new();
+        assert!(interp.interpret(statements).iter().all(Result::is_ok));
+        for fact in ["party is 2", "2 is {}"] {
+            assert!(interp.local_facts.check(&Fact::try_from(fact).unwrap()));
+        }
+    }
+
+    #[test]
+    fn roll_history() {
+        let mut interp = Interpreter::new();
+        let statements: Vec<Statement> =
+            Parser::from_tokens(Scanner::new("set-fact \"attack rolled {last roll}\"\n"))
+                .map(|statement| statement.unwrap())
+                .collect();
+        assert!(interp.interpret(statements.clone())[0].is_err());
+
+        let source = "let x = roll 1d1 + 4\n".repeat(ROLL_HISTORY_LEN + 1);
+        let rolls: Vec<Statement> = Parser::from_tokens(Scanner::new(&source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        interp.interpret(rolls);
+        assert_eq!(interp.roll_history().count(), ROLL_HISTORY_LEN);
+        assert_eq!(interp.last_roll().unwrap().total, 5);
+        assert!(interp.interpret(statements)[0].is_ok());
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from("attack rolled 5").unwrap())
+        );
+    }
+
+    #[test]
+    fn named_rolls() {
+        let source = "define roll \"attack\" as 1d1 + 5\ndefine roll \"charge\" as \"attack\" + 2\nif roll 6+ on \"attack\" => set-fact \"orc is hit\"\nif roll 9+ on \"charge\" => set-fact \"orc is trampled\"\nlet damage = roll \"charge\" + 1\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(statements);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::RollDefinition("attack".into())
+        );
+        assert!(values.iter().all(Result::is_ok));
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from("orc is hit").unwrap())
+        );
+        assert!(
+            !interp
+                .local_facts
+                .check(&Fact::try_from("orc is trampled").unwrap())
+        );
+        assert_eq!(interp.variable("damage"), Some(9));
+
+        let statements: Vec<Statement> =
+            Parser::from_tokens(Scanner::new("let damage = roll \"parry\"\n"))
+                .map(|statement| statement.unwrap())
+                .collect();
+        assert!(interp.interpret(statements)[0].is_err());
+    }
+
+    #[test]
+    fn applied_modifiers() {
+        let source = "define roll \"attack\" as 1d1 + 5\napply modifier +1 to all rolls named \"attack\"\napply modifier -2 to all rolls\nlet a = roll \"attack\"\nlet b = roll 1d1\nremove modifier\nlet c = roll \"attack\"\nremove modifier from all rolls named \"attack\"\nlet d = roll \"attack\"\nprocedure hit\n\tif roll 4 on 1d1 => return \"hit\"\nend\napply modifier +3 to all rolls\nhit\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(statements);
+        assert!(values.iter().all(Result::is_ok));
+        assert_eq!(
+            values[5].as_ref().unwrap(),
+            &StatementRecord::RemoveModifier(AppliedModifier {
+                modifier: -2,
+                rolls: None,
+            })
+        );
+        assert_eq!(interp.variable("a"), Some(5));
+        assert_eq!(interp.variable("b"), Some(-1));
+        assert_eq!(interp.variable("c"), Some(7));
+        assert_eq!(interp.variable("d"), Some(6));
+        // Compiled rolls in a procedure get them too.
+        assert_eq!(values[11].as_ref().unwrap().value(), Some("hit".into()));
+
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "remove modifier\nremove modifier from all rolls named \"attack\"\n",
+        ))
+        .map(|statement| statement.unwrap())
+        .collect();
+        let values = interp.interpret(statements);
+        assert!(values[0].is_ok());
+        assert!(values[1].is_err());
+
+        // Or as consequents.
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "if roll 1 on 1d1 => apply modifier +2 to all rolls\nlet e = roll 1d1\n",
+        ))
+        .map(|statement| statement.unwrap())
+        .collect();
+        assert!(interp.interpret(statements).iter().all(Result::is_ok));
+        assert_eq!(interp.variable("e"), Some(3));
+    }
+
+    #[test]
+    fn let_bindings() {
+        let source = "let bonus = 2\nlet damage = roll 1d1 + bonus\nroll 1d1 + damage\n\tdamage => reminder \"missed\"\n\t4 => set-fact \"hit for {}\" % damage\nend\nprocedure inner\n\tlet bonus = 10\nend\ninner\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let mut interp = Interpreter::new();
+        assert!(interp.interpret(statements).iter().all(Result::is_ok));
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from(String::from("hit for 3")).unwrap())
+        );
+        // The procedure's binding ends with it.
+        assert_eq!(interp.variable("bonus"), Some(2));
+
+        let values = interp.interpret(vec![Statement::NontargetedRoll(ModifiedRollSpecifier {
+            base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+            modifier: 0,
+            terms: vec![ModifierTerm {
+                negated: false,
+                value: ModifierValue::Variable("missing".into()),
+                condition: None,
+            }],
+        })]);
+        assert!(values[0].is_err());
+    }
+
+    #[test]
+    fn fact_modifier() {
+        let roll = |fact: &str| {
+            Statement::NontargetedRoll(ModifiedRollSpecifier {
+                base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+                modifier: 0,
+                terms: vec![ModifierTerm {
+                    negated: true,
+                    value: ModifierValue::Fact(fact.into()),
+                    condition: None,
+                }],
+            })
+        };
+        let mut interp = Interpreter::new();
+        interp
+            .persistent_facts
+            .set(Fact::try_from(String::from("fighter str-mod 2")).unwrap());
+        interp
+            .local_facts
+            .set(Fact::try_from(String::from("fighter name Brega")).unwrap());
+        let values = interp.interpret(vec![
+            roll("fighter str-mod"),
+            roll("fighter name"),
+            roll("fighter dex-mod"),
+        ]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::NontargetedRoll(-1)
+        );
+        assert!(values[1].is_err());
+        assert!(values[2].is_err());
+    }
+
+    #[test]
+    fn conditional_modifier() {
+        let roll = Statement::NontargetedRoll(ModifiedRollSpecifier {
+            base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+            modifier: 0,
+            terms: vec![ModifierTerm {
+                negated: false,
+                value: ModifierValue::Fixed(2),
+                condition: Some(Box::new(Antecedent::CheckFact(
+                    "party has high ground".into(),
+                ))),
+            }],
+        });
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(vec![
+            roll.clone(),
+            Statement::SetFact(CrawlStr::Str("party has high ground".into())),
+            roll,
+        ]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::NontargetedRoll(1)
+        );
+        assert_eq!(
+            values[2].as_ref().unwrap(),
+            &StatementRecord::NontargetedRoll(3)
+        );
+    }
+
+    #[test]
+    fn checks() {
+        let source = "let dc = 15\ncheck 1d20 + 4 vs dc\n\tcritical success => set-fact \"orc is slain\"\n\tsuccess => set-fact \"orc is hit\"\nend\ncheck 1d20 vs dc\n\tsuccess => set-fact \"door is open\"\n\telse => set-fact \"door is stuck\"\nend\nif critical failure on 1d20 vs dc => set-fact \"sword is broken\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let prompt = TerminalPrompt::new("20\n14\n5\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        let values = interp.interpret(statements);
+        assert!(values.iter().all(Result::is_ok));
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::Check {
+                total: 24,
+                dc: 15,
+                degree: Degree::Success,
+                consequent: Some(Box::new(StatementRecord::SetFact("orc is hit".into()))),
+            }
+        );
+        for (fact, set) in [
+            ("orc is slain", false),
+            ("door is open", false),
+            ("door is stuck", true),
+            ("sword is broken", true),
+        ] {
+            assert_eq!(
+                interp.local_facts.check(&Fact::try_from(fact).unwrap()),
+                set
+            );
+        }
+    }
+
+    #[test]
+    fn money() {
+        let source =
+            "add 1d1 + 2 gp to \"party treasure\"\nspend 15 sp\nadd 4 cp to \"party treasure\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(statements);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::SpendMoney {
+                purse: "party treasure".into(),
+                spent: "15 sp".into(),
+                balance: "1 gp, 5 sp".into(),
+            }
+        );
+        assert_eq!(
+            values[2].as_ref().unwrap(),
+            &StatementRecord::AddMoney {
+                purse: "party treasure".into(),
+                added: "4 cp".into(),
+                balance: "1 gp, 5 sp, 4 cp".into(),
+            }
+        );
+        assert_eq!(interp.purse("party treasure"), Some(154));
+
+        let spend = |coin: &str, purse: Option<&str>| Statement::SpendMoney {
+            amount: Amount::Fixed(2),
+            coin: coin.into(),
+            purse: purse.map(Into::into),
+        };
+        // Too much, an unknown coin, and a purse that isn't there
+        assert!(interp.interpret(vec![spend("gp", None)])[0].is_err());
+        assert!(interp.interpret(vec![spend("ep", None)])[0].is_err());
+        assert!(interp.interpret(vec![spend("cp", Some("dragon hoard"))])[0].is_err());
+        assert_eq!(interp.purse("party treasure"), Some(154));
+
+        // More than a purse can hold is an error, and leaves it as it was.
+        let add = Statement::AddMoney {
+            amount: Amount::Fixed(i32::MAX),
+            coin: "gp".into(),
+            purse: "party treasure".into(),
+        };
+        assert!(interp.interpret(vec![add])[0].is_err());
+        assert_eq!(interp.purse("party treasure"), Some(154));
+
+        let mut interp =
+            Interpreter::new().with_denominations(Denominations::new().with_coin("ep", 50));
+        let values = interp.interpret(vec![
+            Statement::AddMoney {
+                amount: Amount::Fixed(3),
+                coin: "ep".into(),
+                purse: "party".into(),
+            },
+            spend("sp", Some("party")),
+        ]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::AddMoney {
+                purse: "party".into(),
+                added: "3 ep".into(),
+                balance: "1 gp, 1 ep".into(),
+            }
+        );
+        assert_eq!(interp.purse("party"), Some(130));
+    }
+
+    #[test]
+    fn name_roll() {
+        let source = "load names \"dwarf-names.csv\"\nroll name from \"dwarf-names.csv\"\nset-fact \"guide is {}\" % roll name from \"dwarf-names.csv\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let mut interp = Interpreter::new();
+        interp.prefetch_table(
+            "dwarf-names.csv",
+            Some("prefix,suffix,weight\nThor,in,1\n,dur,0\n".into()),
+        );
+        let values = interp.interpret(statements);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::NameRoll {
+                names: "dwarf-names.csv".into(),
+                name: "Thorin".into(),
+            }
+        );
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from("guide is Thorin").unwrap())
+        );
+        assert!(interp.interpret(vec![Statement::NameRoll("elf-names.csv".into())])[0].is_err());
+    }
+
+    #[test]
+    fn travel() {
+        let always = TerrainChances {
+            lost: 6,
+            encounter: 6,
+            discovery: 6,
+        };
+        let never = TerrainChances {
+            lost: 0,
+            encounter: 0,
+            discovery: 0,
+        };
+        let travel = Travel::new()
+            .with_terrain("forest", always)
+            .with_terrain("plains", never);
+        let mut interp = Interpreter::new().with_travel(travel);
+        for (table, entry) in [
+            ("forest encounters", "wolves"),
+            ("forest weather", "fog"),
+            ("forest discoveries", "a shrine"),
+            ("plains encounters", "bandits"),
+        ] {
+            let csv = format!("1,{entry}\n");
+            interp.add_table(table, Table::from_reader(csv.as_bytes()).unwrap());
+        }
+        let lost = Fact::try_from(crate::travel::LOST_FACT).unwrap();
+
+        let values = interp.interpret(vec![Statement::Travel("forest".into())]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::Travel {
+                terrain: "forest".into(),
+                lost: true,
+                encounter: Some("wolves".into()),
+                weather: Some("fog".into()),
+                discovery: Some("a shrine".into()),
+            }
+        );
+        assert!(interp.local_facts.check(&lost));
+
+        // No weather or discovery tables for the plains, and nothing comes up there.
+        let values = interp.interpret(vec![Statement::Travel("plains".into())]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::Travel {
+                terrain: "plains".into(),
+                lost: false,
+                encounter: None,
+                weather: None,
+                discovery: None,
+            }
+        );
+        assert!(!interp.local_facts.check(&lost));
+    }
+
+    #[test]
+    fn rumors() {
+        let source = "add rumor \"the well is cursed\" truth false\nadd rumor \"the baron is a vampire\" truth hidden\nadd rumor \"there's gold in the hills\"\nhear 2 rumors\nreveal rumor \"the well is cursed\"\nreport rumors\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let storage = MemoryStorage::new();
+        let prompt = TerminalPrompt::new("2\n1\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage.clone()))
+            .with_manual_rolls(Box::new(prompt));
+        let values = interp.interpret(statements.clone());
+        assert_eq!(
+            values[3].as_ref().unwrap(),
+            &StatementRecord::HearRumors(vec![
+                "the baron is a vampire".into(),
+                "the well is cursed".into(),
+            ])
+        );
+        assert_eq!(
+            values[4].as_ref().unwrap(),
+            &StatementRecord::RevealRumor {
+                rumor: "the well is cursed".into(),
+                truth: false,
+            }
+        );
+        // Truth is only reported once it's revealed.
+        assert_eq!(
+            values[5].as_ref().unwrap(),
+            &StatementRecord::RumorReport(vec![
+                ("the well is cursed".into(), Some(false)),
+                ("the baron is a vampire".into(), None),
+            ])
+        );
+        assert!(interp.interpret(statements[..1].to_vec())[0].is_err());
+
+        // Rumors carry over to the next session, and hidden ones are decided as they're revealed.
+        interp.save_persistent_facts().unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        interp.load_persistent_facts().unwrap();
+        let reveal = |truth| Statement::RevealRumor {
+            rumor: "the baron is a vampire".into(),
+            truth,
+        };
+        assert!(interp.interpret(vec![reveal(None)])[0].is_err());
+        let values = interp.interpret(vec![
+            reveal(Some(true)),
+            Statement::HearRumors(Amount::Fixed(3)),
+        ]);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::HearRumors(vec!["there's gold in the hills".into()])
+        );
+    }
+
+    #[test]
+    fn downtime() {
+        let source = "add 10 gp to \"party\"\nprocedure carousing\n\tspend 2 gp from \"party\"\n\treturn \"{character} woke up married\"\nend\nprocedure training\n\tset-fact \"{character} is trained\"\nend\ndowntime 2 weeks\n\t\"Brofist\" => carousing\n\t\"Ada\" => training\nend\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(statements);
+        let StatementRecord::Downtime {
+            weeks,
+            records,
+            reports,
+            ..
+        } = values[3].as_ref().unwrap()
+        else {
+            panic!("expected downtime, got {:?}", values[3]);
+        };
+        assert_eq!(*weeks, 2);
+        // Each character's activities run once a week, in turn.
+        let called: Vec<&str> = records
+            .iter()
+            .map(|record| match record.as_ref() {
+                StatementRecord::ProcedureCall { identifier, .. } => identifier.as_str(),
+                record => panic!("expected a procedure call, got {record:?}"),
+            })
+            .collect();
+        assert_eq!(called, ["carousing", "training", "carousing", "training"]);
+        assert_eq!(
+            reports,
+            &[
+                DowntimeReport {
+                    character: "Brofist".into(),
+                    spent: "4 gp".into(),
+                    outcomes: vec!["Brofist woke up married".into(); 2],
+                },
+                DowntimeReport {
+                    character: "Ada".into(),
+                    spent: "0 cp".into(),
+                    outcomes: Vec::new(),
+                },
+            ]
+        );
+        assert_eq!(
+            *records[1],
+            StatementRecord::ProcedureCall {
+                identifier: "training".into(),
+                records: vec![Box::new(StatementRecord::SetFact("Ada is trained".into()))],
+            }
+        );
+        assert_eq!(interp.purse("party"), Some(600));
+        assert_eq!(interp.date, 14);
+        assert!(interp.downtime_character.is_none());
+
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "downtime 1 week\n\t\"Brofist\" => gambling\nend\n",
+        ))
+        .map(|statement| statement.unwrap())
+        .collect();
+        assert!(interp.interpret(statements)[0].is_err());
+        assert!(interp.downtime_character.is_none());
+
+        // Running past the last date there can be is an error, before any activity runs.
+        interp.date = i32::MAX - 3;
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "procedure resting\n\tset-fact \"Ada is rested\"\nend\ndowntime 1 week\n\t\"Ada\" => resting\nend\n",
+        ))
+        .map(|statement| statement.unwrap())
+        .collect();
+        assert!(matches!(
+            &interp.interpret(statements)[1],
+            Err(CrawlError::InterpreterError { reason }) if reason.contains("out of range")
+        ));
+        assert_eq!(interp.date, i32::MAX - 3);
+        assert!(
+            !interp
+                .local_facts
+                .check(&Fact::try_from("Ada is rested").unwrap())
+        );
+        let downtime = Statement::Downtime {
+            weeks: Amount::Fixed(i32::MAX),
+            arms: Vec::new(),
+        };
+        assert!(interp.interpret(vec![downtime])[0].is_err());
+
+        // Purses too full to add up still count what was spent.
+        interp.date = 0;
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "procedure hoarding\n\tadd 2147483647 cp to \"hoard\"\nend\nprocedure saving\n\tadd 2147483647 cp to \"savings\"\nend\ndowntime 1 week\n\t\"Brofist\" => hoarding\n\t\"Ada\" => saving\nend\n",
+        ))
+        .map(|statement| statement.unwrap())
+        .collect();
+        let values = interp.interpret(statements);
+        let StatementRecord::Downtime { reports, .. } = values[2].as_ref().unwrap() else {
+            panic!("expected downtime");
+        };
+        assert!(reports.iter().all(|report| report.spent == "0 cp"));
+    }
+
+    #[test]
+    fn faction_fronts() {
+        let source = "create faction clock \"Cult of Orcus\" 4\ncreate faction clock \"Baron\" 6\nadvance faction fronts\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let storage = MemoryStorage::new();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage.clone()));
+        // The cult has a column of its own, and the baron advances by the entry.
+        let csv = "roll,advance,Cult of Orcus\n1,{1d1},4\n";
+        interp.add_table(FRONTS_TABLE, Table::from_reader(csv.as_bytes()).unwrap());
+        let values = interp.interpret(statements.clone());
+        assert_eq!(
+            values[2].as_ref().unwrap(),
+            &StatementRecord::AdvanceFronts(vec![
+                FrontAdvance {
+                    faction: "Baron".into(),
+                    by: 1,
+                    filled: 1,
+                    segments: 6,
+                },
+                FrontAdvance {
+                    faction: "Cult of Orcus".into(),
+                    by: 4,
+                    filled: 4,
+                    segments: 4,
+                },
+            ])
+        );
+
+        // Fronts carry over to the next session, and completed ones don't advance.
+        interp.save_persistent_facts().unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        interp.load_persistent_facts().unwrap();
+        interp.add_table(FRONTS_TABLE, Table::from_reader(csv.as_bytes()).unwrap());
+        let values = interp.interpret(statements[2..].to_vec());
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::AdvanceFronts(vec![FrontAdvance {
+                faction: "Baron".into(),
+                by: 1,
+                filled: 2,
+                segments: 6,
+            }])
+        );
+
+        let csv = "1,a lot\n";
+        interp.add_table(FRONTS_TABLE, Table::from_reader(csv.as_bytes()).unwrap());
+        assert!(interp.interpret(statements[2..].to_vec())[0].is_err());
+    }
+
+    #[test]
+    fn prices() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write(
+                "prices.csv",
+                "item,price,availability\nplate mail,60 gp,40\ncrown,99999999 gp,10\n",
+            )
+            .unwrap();
+        let source = "load prices \"prices.csv\"\nprice \"plate mail\" at \"village\"\nhaggle roll \"plate mail\"\nif in stock? \"plate mail\" at \"hamlet\" => set-fact \"hamlet sells plate\"\nif in stock? \"plate mail\" at \"city\" => set-fact \"city sells plate\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let prompt = TerminalPrompt::new("5 6\n30\n30\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage))
+            .with_manual_rolls(Box::new(prompt));
+        let values = interp.interpret(statements);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::Price {
+                item: "plate mail".into(),
+                price: "75 gp".into(),
+            }
+        );
+        assert_eq!(
+            values[2].as_ref().unwrap(),
+            &StatementRecord::Haggle {
+                item: "plate mail".into(),
+                total: 11,
+                percent: -20,
+                price: "48 gp".into(),
+            }
+        );
+        // A hamlet's 10% chance misses on 30, and a city's 55% doesn't.
+        let fact = |text: &str| Fact::try_from(text).unwrap();
+        assert!(!interp.local_facts.check(&fact("hamlet sells plate")));
+        assert!(interp.local_facts.check(&fact("city sells plate")));
+
+        let price = |item: &str, settlement: &str| Statement::Price {
+            item: item.into(),
+            settlement: Some(settlement.into()),
+        };
+        assert!(interp.interpret(vec![price("dragon", "town")])[0].is_err());
+        assert!(interp.interpret(vec![price("plate mail", "metropolis")])[0].is_err());
+        // Too dear to count
+        assert!(interp.interpret(vec![price("crown", "town")])[0].is_err());
+    }
+
+    #[test]
+    fn awards() {
+        let source = "award 250 xp\naward treasure 1d1 x 100 gp\naward 1d1 + 9 x 5 xp\nreport xp\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let storage = MemoryStorage::new();
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage.clone()))
+            .with_party_size(3);
+        let values = interp.interpret(statements.clone());
+        assert!(values.iter().all(Result::is_ok));
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::AwardTreasure {
+                treasure: "100 gp".into(),
+                session: "100 gp".into(),
+                campaign: "100 gp".into(),
+            }
+        );
+        assert_eq!(
+            values[3].as_ref().unwrap(),
+            &StatementRecord::XpReport {
+                party_size: 3,
+                session_xp: 300,
+                campaign_xp: 300,
+                xp_share: 100,
+                session_treasure: "100 gp".into(),
+                treasure_share: "33 gp, 3 sp, 3 cp".into(),
+            }
+        );
+
+        // The campaign's tally carries over to the next session, but the session's starts over.
+        interp.save_persistent_facts().unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        interp.load_persistent_facts().unwrap();
+        let values = interp.interpret(statements[2..].to_vec());
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::XpReport {
+                party_size: 4,
+                session_xp: 50,
+                campaign_xp: 350,
+                xp_share: 12,
+                session_treasure: "0 cp".into(),
+                treasure_share: "0 cp".into(),
+            }
+        );
+        let treasure = Statement::AwardTreasure {
+            amount: Amount::Fixed(1),
+            multiplier: 1,
+            coin: "pp".into(),
+        };
+        assert!(interp.interpret(vec![treasure])[0].is_err());
+
+        // Awards too big to tally are errors, not clamped.
+        let xp = |amount, multiplier| Statement::AwardXp {
+            amount: Amount::Fixed(amount),
+            multiplier,
+        };
+        let values = interp.interpret(vec![xp(i32::MAX, 1), xp(i32::MAX, 2)]);
+        assert!(values.iter().all(Result::is_err));
+        assert_eq!(interp.campaign_tally.xp, 350);
+    }
+
+    #[test]
+    fn lights() {
+        let source = "light torch\nadvance time by 2 turns\nlight lantern\nlight torch\nadvance time by 4 turns\nadvance time by 1d1 + 1 turns\nlight torch\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let buffer = BufferSink::new();
+        let mut interp = Interpreter::new()
+            .with_lights(Lights::new().with_light("lantern", 5))
+            .with_output(Box::new(buffer.clone()));
+        let fact = |fact: &str| Fact::try_from(fact).unwrap();
+        let values = interp.interpret(statements[..5].to_vec());
+        assert!(values.iter().all(Result::is_ok));
+        // The first torch is out, but the second still burns.
+        assert_eq!(
+            values[4].as_ref().unwrap(),
+            &StatementRecord::AdvanceTime {
+                by: 4,
+                turns: 6,
+                guttered: vec!["torch".into()],
+            }
+        );
+        assert!(interp.local_facts.check(&fact("torch is lit")));
+
+        let values = interp.interpret(statements[5..].to_vec());
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::AdvanceTime {
+                by: 2,
+                turns: 8,
+                guttered: vec!["lantern".into(), "torch".into()],
+            }
+        );
+        assert!(interp.local_facts.check(&fact("torch is lit")));
+        assert!(!interp.local_facts.check(&fact("torch is out")));
+        assert!(interp.local_facts.check(&fact("lantern is out")));
+        assert!(
+            buffer
+                .take()
+                .iter()
+                .all(|output| !matches!(output, Output::Warning(_)))
+        );
+        assert!(interp.interpret(vec![Statement::Light("sunrod".into())])[0].is_err());
+        let long = Statement::AdvanceTime(Amount::Fixed(i32::MAX));
+        assert!(interp.interpret(vec![long])[0].is_err());
+        assert_eq!(interp.turns, 8);
+    }
+
+    #[test]
+    fn statblock_modifiers() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write(
+                "party.toml",
+                "[[statblocks]]\nname = \"Brofist\"\nstats = { STR = 2 }\n",
+            )
+            .unwrap();
+        let source = "load statblocks \"party.toml\"\nif roll 15+ on 1d20 + stat \"Brofist\" \"STR\" => set-fact \"orc is hit\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let prompt = TerminalPrompt::new("13\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage))
+            .with_manual_rolls(Box::new(prompt));
+        let values = interp.interpret(statements);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::LoadStatblocks {
+                file: "party.toml".into(),
+                statblocks: vec!["Brofist".into()],
+            }
+        );
+        assert!(matches!(
+            values[1],
+            Ok(StatementRecord::IfThen {
+                antecedent: true,
+                ..
+            })
+        ));
+
+        for (statblock, stat) in [("Brofist", "DEX"), ("Goblin", "STR")] {
+            let statements = Parser::from_tokens(Scanner::new(&format!(
+                "if roll 15+ on 1d20 + stat {statblock:?} {stat:?} => set-fact \"orc is hit\"\n"
+            )))
+            .map(|statement| statement.unwrap())
+            .collect();
+            assert!(interp.interpret(statements)[0].is_err());
+        }
+    }
+
+    #[test]
+    fn save() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write(
+                "party.json",
+                r#"[{"name": "Brofist", "saves": {"poison": 12}}]"#,
+            )
+            .unwrap();
+        let source = "load statblocks \"party.json\"\nsave \"Brofist\" vs \"poison\"\n\tsuccess => reminder \"shrugs it off\"\n\tfailure => set-fact \"Brofist is poisoned\"\nend\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let prompt = TerminalPrompt::new("12\n5\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage))
+            .with_manual_rolls(Box::new(prompt));
+        let values = interp.interpret(statements.clone());
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::Save {
+                statblock: "Brofist".into(),
+                save: "poison".into(),
+                total: 12,
+                target: 12,
+                degree: Degree::Success,
+                consequent: Some(Box::new(StatementRecord::Reminder("shrugs it off".into()))),
+            }
+        );
+        let values = interp.interpret(statements[1..].to_vec());
+        assert!(matches!(
+            values[0],
+            Ok(StatementRecord::Save {
+                total: 5,
+                degree: Degree::Failure,
+                ..
+            })
+        ));
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from("Brofist is poisoned").unwrap())
+        );
+
+        let statements = vec![Statement::Save {
+            statblock: "Brofist".into(),
+            save: "dragon breath".into(),
+            arms: Vec::new(),
+            otherwise: None,
+        }];
+        assert!(interp.interpret(statements)[0].is_err());
+    }
+
+    #[test]
+    fn morale_check() {
+        let source = "morale check \"goblins\" 7\nmorale check \"orcs\" fact \"orcs morale\"\nmorale check \"goblins\" 7\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let prompt = TerminalPrompt::new("3 4\n4 5\n2 6\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        interp
+            .local_facts
+            .set(Fact::try_from("orcs morale 9").unwrap());
+        let values = interp.interpret(statements);
+        assert!(values.iter().all(Result::is_ok));
+        let fleeing = |group: &str| {
+            let fact = Fact::try_from(format!("{group} are fleeing").as_str()).unwrap();
+            interp.local_facts.check(&fact)
+        };
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::MoraleCheck {
+                group: "orcs".into(),
+                total: 9,
+                morale: 9,
+                held: true,
+            }
+        );
+        assert!(!fleeing("orcs"));
+        assert_eq!(
+            values[2].as_ref().unwrap(),
+            &StatementRecord::MoraleCheck {
+                group: "goblins".into(),
+                total: 8,
+                morale: 7,
+                held: false,
+            }
+        );
+        assert!(fleeing("goblins"));
+
+        let statements = vec![Statement::MoraleCheck {
+            group: "kobolds".into(),
+            morale: ModifierValue::Fact("kobolds morale".into()),
+        }];
+        assert!(interp.interpret(statements)[0].is_err());
+    }
+
+    #[test]
+    fn generate_room() {
+        let mut interp = Interpreter::new();
+        for (table, csv) in [
+            (rooms::SHAPES, "1,a round chamber\n"),
+            (rooms::EXITS, "1,two doors\n"),
+            (rooms::CONTENTS, "1,{1d1} goblins\n"),
+        ] {
+            interp.add_table(table, Table::from_reader(csv.as_bytes()).unwrap());
+        }
+        let values = interp.interpret(vec![Statement::GenerateRoom, Statement::GenerateRoom]);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::GenerateRoom {
+                number: 2,
+                shape: "a round chamber".into(),
+                exits: "two doors".into(),
+                contents: "1 goblins".into(),
+            }
+        );
+
+        // Nothing's numbered without all three tables.
+        let mut interp = Interpreter::new();
+        interp.add_table(
+            rooms::SHAPES,
+            Table::from_reader("1,a round chamber\n".as_bytes()).unwrap(),
+        );
+        assert!(interp.interpret(vec![Statement::GenerateRoom])[0].is_err());
+        assert_eq!(interp.rooms, 0);
+    }
+
+    #[test]
+    fn encounter() {
+        let encounters = Encounters::default()
+            .with_reactions("1d1".parse().unwrap(), &[(1, "friendly")])
+            .with_distance("forest", Distance::new("1d1", 30, "yards"));
+        let mut interp = Interpreter::new().with_encounters(encounters);
+        let csv = "roll,creature,number appearing\n1,goblins,{1d1}\n";
+        interp.add_table(
+            "forest encounters",
+            Table::from_reader(csv.as_bytes()).unwrap(),
+        );
+        let values = interp.interpret(vec![Statement::Encounter("forest".into())]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::Encounter {
+                terrain: "forest".into(),
+                creature: "goblins".into(),
+                number: Some(1),
+                reaction: "friendly".into(),
+                distance: "30 yards".into(),
+            }
+        );
+
+        // The number appearing can be a roll, and tables without one don't say.
+        for (csv, number) in [
+            ("roll,creature,number appearing\n1,goblins,2d1\n", Some(2)),
+            ("1,a dragon\n", None),
+        ] {
+            interp.add_table(
+                "hills encounters",
+                Table::from_reader(csv.as_bytes()).unwrap(),
+            );
+            let values = interp.interpret(vec![Statement::Encounter("hills".into())]);
+            match values[0].as_ref().unwrap() {
+                StatementRecord::Encounter {
+                    number: rolled,
+                    distance,
+                    ..
+                } => {
+                    assert_eq!(*rolled, number);
+                    assert!(distance.ends_with(" yards"));
                 }
+                record => panic!("unexpected record {record:?}"),
+            }
+        }
+
+        let csv = "roll,creature,number appearing\n1,goblins,lots\n";
+        interp.add_table(
+            "hills encounters",
+            Table::from_reader(csv.as_bytes()).unwrap(),
+        );
+        assert!(interp.interpret(vec![Statement::Encounter("hills".into())])[0].is_err());
+        assert!(interp.interpret(vec![Statement::Encounter("swamp".into())])[0].is_err());
+    }
+
+    #[test]
+    fn weather() {
+        let buffer = BufferSink::new();
+        let mut interp = Interpreter::new().with_output(Box::new(buffer.clone()));
+        let advance = Statement::AdvanceWeather(crate::weather::WEATHER_TABLE.into());
+        assert!(interp.interpret(vec![advance.clone()])[0].is_err());
+
+        let csv = "roll,clear,rain,fog\n1-6,rain,clear,fog\n";
+        interp.add_table("weather", Table::from_reader(csv.as_bytes()).unwrap());
+        // It starts out clear, the first column's weather, and turns back and forth.
+        let values = interp.interpret(vec![advance.clone(), advance.clone(), advance]);
+        let turned: Vec<_> = values
+            .into_iter()
+            .map(|value| match value.unwrap() {
+                StatementRecord::AdvanceWeather { from, to } => (from, to),
+                record => panic!("unexpected record {record:?}"),
+            })
+            .collect();
+        assert_eq!(
+            turned,
+            [
+                ("clear".into(), "rain".into()),
+                ("rain".into(), "clear".into()),
+                ("clear".into(), "rain".into()),
+            ]
+        );
+        let fact = |fact: &str| Fact::try_from(fact).unwrap();
+        assert!(interp.persistent_facts.check(&fact("weather is rain")));
+        assert!(!interp.persistent_facts.check(&fact("weather is clear")));
+        assert!(
+            buffer
+                .take()
+                .iter()
+                .all(|output| !matches!(output, Output::Warning(_)))
+        );
+
+        // Weather without a column of its own can't turn.
+        let values = interp.interpret(vec![
+            Statement::ClearPersistentFact("weather is rain".into()),
+            Statement::SetPersistentFact("weather is sleet".into()),
+            Statement::AdvanceWeather("weather".into()),
+        ]);
+        assert!(values[2].is_err());
+    }
+
+    #[test]
+    fn date() {
+        let source = "advance date by 2 weeks\nif date is full-moon => set-fact \"wolves howl on {current date}\"\nadvance date by 17 days\nif date is february => set-fact \"snow is falling\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let storage = MemoryStorage::new();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage.clone()));
+        let values = interp.interpret(statements);
+        assert!(values.iter().all(Result::is_ok));
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::AdvanceDate {
+                days: 14,
+                date: "Monday, 15 January, year 1".into(),
+            }
+        );
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from("wolves howl on Monday, 15 January, year 1").unwrap())
+        );
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from("snow is falling").unwrap())
+        );
+        let unknown = Statement::IfThen {
+            antecedent: Antecedent::DateIs("Moonday".into()),
+            consequent: Box::new(Statement::AdvanceTurn),
+        };
+        assert!(interp.interpret(vec![unknown])[0].is_err());
+        let far = Statement::AdvanceDate {
+            amount: Amount::Fixed(i32::MAX),
+            unit: DateUnit::Weeks,
+        };
+        assert!(interp.interpret(vec![far])[0].is_err());
 
-                Ok(replaced.to_string())
+        // Kept with the persistent facts, and read off the calendar given.
+        interp.save_persistent_facts().unwrap();
+        let calendar = Calendar::new()
+            .with_months(&[("Hammer", 30), ("Alturiak", 30)])
+            .unwrap()
+            .with_weekdays(&[]);
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage))
+            .with_calendar(calendar);
+        interp.load_persistent_facts().unwrap();
+        let values = interp.interpret(vec![Statement::CurrentDate]);
+        assert_eq!(
+            values[0].as_ref().unwrap().value().as_deref(),
+            Some("2 Alturiak, year 1")
+        );
+    }
+
+    #[test]
+    fn inventory() {
+        let source = "add item \"torch\" to \"party\"\nadd item \"torch\" to \"party\"\nadd item \"rope\" to \"party\"\nremove item \"rope\" from \"party\"\nif has item? \"rope\" in \"party\" => set-fact \"party has rope\"\nif has item? \"torch\" in \"party\" => set-fact \"party has light\"\nreport inventory for \"party\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(statements);
+        assert!(values.iter().all(Result::is_ok));
+        assert_eq!(
+            values[6].as_ref().unwrap(),
+            &StatementRecord::InventoryReport {
+                owner: "party".into(),
+                items: vec![("torch".into(), 2)],
+            }
+        );
+        assert!(
+            !interp
+                .local_facts
+                .check(&Fact::try_from("party has rope").unwrap())
+        );
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from("party has light").unwrap())
+        );
+
+        let statements = vec![Statement::RemoveItem {
+            item: "rope".into(),
+            owner: "party".into(),
+        }];
+        assert!(interp.interpret(statements)[0].is_err());
+
+        // Kept with the campaign's persistent facts.
+        let storage = MemoryStorage::new();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage.clone()));
+        let rope = Statement::AddItem {
+            item: "rope".into(),
+            owner: "party".into(),
+        };
+        let values = interp.interpret(vec![
+            Statement::Campaign("greyhawk".into()),
+            rope,
+            Statement::Campaign("west marches".into()),
+        ]);
+        assert!(values.iter().all(Result::is_ok));
+        assert_eq!(interp.item_count("party", "rope"), 0);
+        interp.interpret(vec![Statement::Campaign("greyhawk".into())]);
+        assert_eq!(interp.item_count("party", "rope"), 1);
+        assert!(
+            storage
+                .clone()
+                .read("campaigns/west marches/inventories.json")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn hit_points() {
+        let source = "track hp \"goblin\" 1d1 + 6\ndamage \"goblin\" 5\nif dead \"goblin\" => set-fact \"goblin is slain\"\ndamage \"goblin\" 1d1 + 9\nif dead \"goblin\" => set-fact \"goblin is dead\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(statements);
+        assert!(values.iter().all(Result::is_ok));
+        assert_eq!(
+            values[3].as_ref().unwrap(),
+            &StatementRecord::Damage {
+                entity: "goblin".into(),
+                damage: 10,
+                remaining: 0,
+            }
+        );
+        assert_eq!(interp.hit_points("goblin"), Some(0));
+        assert!(
+            !interp
+                .local_facts
+                .check(&Fact::try_from("goblin is slain").unwrap())
+        );
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from("goblin is dead").unwrap())
+        );
+
+        let statements = vec![Statement::Damage {
+            entity: "orc".into(),
+            amount: Amount::Fixed(1),
+        }];
+        assert!(interp.interpret(statements)[0].is_err());
+    }
+
+    #[test]
+    fn combatants() {
+        let source = "add combatant \"goblin\" rolling 1d20 + 1\nadd combatant \"fighter\" rolling 1d20\nsort combatants\nadvance turn\nadvance turn\nremove combatant \"fighter\"\nadvance turn\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let prompt = TerminalPrompt::new("5\n17\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        let values = interp.interpret(statements);
+        assert!(values.iter().all(Result::is_ok));
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::AddCombatant {
+                combatant: "goblin".into(),
+                initiative: 6,
+            }
+        );
+        assert_eq!(
+            values[3].as_ref().unwrap(),
+            &StatementRecord::AdvanceTurn {
+                combatant: "fighter".into(),
+                round: 1,
+            }
+        );
+        assert_eq!(
+            values[6].as_ref().unwrap(),
+            &StatementRecord::AdvanceTurn {
+                combatant: "goblin".into(),
+                round: 2,
             }
+        );
+        assert_eq!(interp.initiative().combatants().len(), 1);
+
+        for source in [
+            "add combatant \"goblin\" rolling 1d1\n",
+            "remove combatant \"fighter\"\n",
+        ] {
+            let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+                .map(|statement| statement.unwrap())
+                .collect();
+            assert!(interp.interpret(statements)[0].is_err());
         }
+        let mut interp = Interpreter::new();
+        let statements = vec![Statement::AdvanceTurn];
+        assert!(interp.interpret(statements)[0].is_err());
     }
 
-    fn roll_result_matches_target(
-        &self,
-        roll_result: &DiceRollResult,
-        target: &Token,
-    ) -> Result<bool, CrawlError> {
-        match target {
-            Token::Num(n) => Ok(roll_result.total == *n),
-            Token::NumRange(min, max) => Ok(*min <= roll_result.total && roll_result.total <= *max),
-            _ => Err(CrawlError::InterpreterError {
-                reason: "invalid roll target".into(),
-            }),
+    #[test]
+    fn moves() {
+        let source = "let sharp = 2\nmove \"Read a Sitch\" with +sharp\n\ton 10+ => set-fact \"sitch is read\"\n\ton 7-9 => reminder \"ask one\"\n\ton 6- => set-fact \"sitch is dire\"\nend\nmove \"Act Under Pressure\" with -1\n\ton 10+ => set-fact \"party is safe\"\n\ton 6- => set-fact \"party is caught\"\nend\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let prompt = TerminalPrompt::new("3 4\n2 5\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        let values = interp.interpret(statements);
+        assert!(values.iter().all(Result::is_ok));
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::Move {
+                name: "Read a Sitch".into(),
+                total: 9,
+                matched_target: Some(Token::NumRange(7, 9)),
+                consequent: Some(Box::new(StatementRecord::Reminder("ask one".into()))),
+            }
+        );
+        assert!(
+            interp
+                .local_facts
+                .check(&Fact::try_from("party is caught").unwrap())
+        );
+    }
+
+    #[test]
+    fn natural_rolls() {
+        let source = "if critical on 1d20 + 2 => set-fact \"orc is slain\"\nif critical on 2d6 => set-fact \"orc is maimed\"\nif fumble on 2d6 => set-fact \"sword is dropped\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let prompt = TerminalPrompt::new("20\n3 5\n1 4\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        assert!(interp.interpret(statements).iter().all(Result::is_ok));
+        for (fact, set) in [
+            ("orc is slain", true),
+            ("orc is maimed", false),
+            ("sword is dropped", true),
+        ] {
+            assert_eq!(
+                interp.local_facts.check(&Fact::try_from(fact).unwrap()),
+                set
+            );
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::facts::Fact;
+    #[test]
+    fn matching_table_roll() {
+        let arm = |pattern: &str| TableRollArm {
+            pattern: pattern.into(),
+            consequent: Statement::Reminder(pattern.into()),
+        };
+        let prompt = TerminalPrompt::new("3\n10\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        let roll = Statement::MatchingTableRoll {
+            table: "examples/reaction.csv".into(),
+            tag: None,
+            excluding_seen: false,
+            arms: vec![arm("attacks on sight"), arm("uncertain")],
+        };
+        let values = interp.interpret(vec![
+            Statement::LoadTable {
+                table: "examples/reaction.csv".into(),
+                no_clamp: false,
+            },
+            roll.clone(),
+            roll,
+        ]);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::MatchingTableRoll {
+                table: "examples/reaction.csv".into(),
+                entry: "attacks on sight".into(),
+                pinned: false,
+                matched: Some("attacks on sight".into()),
+                consequent: Some(Box::new(StatementRecord::Reminder(
+                    "attacks on sight".into()
+                ))),
+            }
+        );
+        assert!(matches!(
+            values[2],
+            Ok(StatementRecord::MatchingTableRoll { matched: None, .. })
+        ));
+    }
 
-    use super::*;
+    #[test]
+    fn campaigns() {
+        let storage = MemoryStorage::new();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage.clone()));
+        let values = interp.interpret(vec![
+            Statement::Campaign("greyhawk".into()),
+            Statement::SetPersistentFact("party is lost".into()),
+            Statement::Campaign("west marches".into()),
+            Statement::IfThen {
+                antecedent: Antecedent::CheckPersistentFact("party is lost".into()),
+                consequent: Box::new(Statement::Reminder("lost".into())),
+            },
+            Statement::Campaign("greyhawk".into()),
+            Statement::IfThen {
+                antecedent: Antecedent::CheckPersistentFact("party is lost".into()),
+                consequent: Box::new(Statement::Reminder("lost".into())),
+            },
+        ]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::Campaign("greyhawk".into())
+        );
+        assert!(matches!(
+            values[3].as_ref().unwrap(),
+            StatementRecord::IfThen {
+                antecedent: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            values[5].as_ref().unwrap(),
+            StatementRecord::IfThen {
+                antecedent: true,
+                ..
+            }
+        ));
+        assert!(
+            storage
+                .clone()
+                .read("campaigns/greyhawk/persistent-facts.json")
+                .unwrap()
+                .is_some()
+        );
 
-    fn interp_to_values(statements: Vec<Statement>) -> Vec<StatementRecord> {
-        Interpreter::new()
-            .interpret(statements)
-            .into_iter()
-            .map(|v| v.unwrap())
-            .collect()
+        // Each campaign's purses are its own.
+        let add = Statement::AddMoney {
+            amount: Amount::Fixed(5),
+            coin: "gp".into(),
+            purse: "party".into(),
+        };
+        let spend = Statement::SpendMoney {
+            amount: Amount::Fixed(5),
+            coin: "gp".into(),
+            purse: Some("party".into()),
+        };
+        let values = interp.interpret(vec![
+            add,
+            Statement::Campaign("west marches".into()),
+            spend.clone(),
+            Statement::Campaign("greyhawk".into()),
+            spend,
+        ]);
+        assert!(values[2].is_err());
+        assert!(values[4].is_ok());
+        assert!(
+            storage
+                .clone()
+                .read("campaigns/greyhawk/purses.json")
+                .unwrap()
+                .is_some()
+        );
+
+        let values = Interpreter::new().interpret(vec![Statement::Campaign("greyhawk".into())]);
+        assert!(values[0].is_err());
     }
 
-    fn make_proc_body() -> Vec<Box<Statement>> {
-        vec![
-            Box::new(Statement::IfThen {
-                antecedent: Antecedent::DiceRoll {
-                    target: Token::Num(1),
-                    roll_specifier: ModifiedRollSpecifier {
-                        base_roll_specifier: Token::RollSpecifier("1d1".into()),
-                        modifier: 0,
+    #[test]
+    fn embedded_rolls() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write("treasure.csv", "1,{2d1} gold pieces and { 1d1 + 2 } gems\n")
+            .unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        let values = interp.interpret(vec![
+            Statement::LoadTable {
+                table: "treasure.csv".into(),
+                no_clamp: false,
+            },
+            Statement::TableRoll {
+                table: "treasure.csv".into(),
+                field: None,
+                tag: None,
+                excluding_seen: false,
+            },
+        ]);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::TableRoll {
+                table: "treasure.csv".into(),
+                entry: "2 gold pieces and 3 gems".into(),
+                pinned: false,
+            }
+        );
+
+        // A modifier too big for a number is an error, not a panic.
+        interp.add_table(
+            "hoard",
+            Table::from_reader("1,{1d6+99999999999} gold\n".as_bytes()).unwrap(),
+        );
+        let values = interp.interpret(vec![Statement::TableRoll {
+            table: "hoard".into(),
+            field: None,
+            tag: None,
+            excluding_seen: false,
+        }]);
+        assert!(values[0].is_err());
+    }
+
+    #[test]
+    fn roll_twice() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write("treasure.csv", "1,{1d1} gold\n2,a gem\n3,roll twice\n")
+            .unwrap();
+        let prompt = TerminalPrompt::new("3\n3\n1\n2\n1\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage))
+            .with_manual_rolls(Box::new(prompt));
+        let values = interp.interpret(vec![
+            Statement::LoadTable {
+                table: "treasure.csv".into(),
+                no_clamp: false,
+            },
+            Statement::TableRoll {
+                table: "treasure.csv".into(),
+                field: None,
+                tag: None,
+                excluding_seen: false,
+            },
+        ]);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::TableRoll {
+                table: "treasure.csv".into(),
+                entry: "1 gold; a gem".into(),
+                pinned: false,
+            }
+        );
+    }
+
+    #[test]
+    fn no_clamp() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write(
+                "crits.csv",
+                "off-table,no-result\n2,a graze\n3,a deep cut\n",
+            )
+            .unwrap();
+        let prompt = TerminalPrompt::new("1\n1\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage))
+            .with_manual_rolls(Box::new(prompt));
+        let load = |no_clamp| Statement::LoadTable {
+            table: "crits.csv".into(),
+            no_clamp,
+        };
+        let roll = Statement::TableRoll {
+            table: "crits.csv".into(),
+            field: None,
+            tag: None,
+            excluding_seen: false,
+        };
+        let values = interp.interpret(vec![load(false), roll.clone(), load(true), roll]);
+        let rolled = |entry: &str| StatementRecord::TableRoll {
+            table: "crits.csv".into(),
+            entry: entry.into(),
+            pinned: false,
+        };
+        assert_eq!(values[1].as_ref().unwrap(), &rolled("a graze"));
+        assert_eq!(values[3].as_ref().unwrap(), &rolled(""));
+    }
+
+    #[test]
+    fn table_fields() {
+        let prompt = TerminalPrompt::new("6\n6\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        let roll = |field: &str| Statement::TableRoll {
+            table: "examples/encounters.csv".into(),
+            field: Some(field.into()),
+            tag: None,
+            excluding_seen: false,
+        };
+        let values = interp.interpret(vec![
+            Statement::LoadTable {
+                table: "examples/encounters.csv".into(),
+                no_clamp: false,
+            },
+            roll("treasure"),
+            roll("alignment"),
+        ]);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::TableRoll {
+                table: "examples/encounters.csv".into(),
+                entry: "a silver sword".into(),
+                pinned: false,
+            }
+        );
+        assert!(values[2].is_err());
+    }
+
+    #[test]
+    fn tagged_table_roll() {
+        let prompt = TerminalPrompt::new("4\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        interp.add_table(
+            "encounters",
+            Table::from_reader("1-2,wolf,night\n3,peasant,day\n4-6,ghoul,night\n".as_bytes())
+                .unwrap(),
+        );
+        let roll = |tag: &str| Statement::TableRoll {
+            table: "encounters".into(),
+            field: None,
+            tag: Some(tag.into()),
+            excluding_seen: false,
+        };
+        let values = interp.interpret(vec![roll("night"), roll("dusk")]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::TableRoll {
+                table: "encounters".into(),
+                entry: "ghoul".into(),
+                pinned: false,
+            }
+        );
+        assert!(values[1].is_err());
+        assert!(interp.tables.contains_key("encounters"));
+    }
+
+    #[test]
+    fn local_procedure() {
+        let call = |identifier: &str| Statement::ProcedureCall {
+            identifier: identifier.into(),
+            arguments: Vec::new(),
+        };
+        let forage = Statement::Procedure {
+            declaration: ProcedureDeclaration("forage".into()),
+            body: vec![Statement::Reminder("roll wisdom".into())].into(),
+        };
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(vec![
+            Statement::Procedure {
+                declaration: ProcedureDeclaration("travel-turn".into()),
+                body: vec![forage, call("forage")].into(),
+            },
+            call("travel-turn"),
+            call("forage"),
+        ]);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::ProcedureCall {
+                identifier: "travel-turn".into(),
+                records: vec![
+                    Box::new(StatementRecord::ProcedureDefinition("forage".into())),
+                    Box::new(StatementRecord::ProcedureCall {
+                        identifier: "forage".into(),
+                        records: vec![Box::new(StatementRecord::Reminder("roll wisdom".into()))],
+                    }),
+                ],
+            }
+        );
+        assert!(values[2].is_err());
+        assert!(!interp.snapshot().procedures.contains_key("forage"));
+    }
+
+    #[test]
+    fn return_value() {
+        let call = |identifier: &str| Statement::ProcedureCall {
+            identifier: identifier.into(),
+            arguments: Vec::new(),
+        };
+        let procedure = |name: &str, body: Vec<Statement>| Statement::Procedure {
+            declaration: ProcedureDeclaration(name.into()),
+            body: body.into(),
+        };
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(vec![
+            procedure(
+                "travel-dc",
+                vec![
+                    Statement::Let {
+                        name: "base".into(),
+                        value: Amount::Fixed(10),
                     },
+                    Statement::Return(ReturnValue::Amount(Amount::Call("base".into()))),
+                    Statement::Reminder("unreachable".into()),
+                ],
+            ),
+            procedure(
+                "weather",
+                vec![Statement::NontargetedRoll(ModifiedRollSpecifier {
+                    base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+                    modifier: 2,
+                    terms: Vec::new(),
+                })],
+            ),
+            Statement::Let {
+                name: "dc".into(),
+                value: Amount::Call("travel-dc".into()),
+            },
+            Statement::SetFact(CrawlStr::InterpolatedStr {
+                format_string: "weather is {}".into(),
+                expressions: vec![call("weather")],
+            }),
+            Statement::Return(ReturnValue::Amount(Amount::Fixed(1))),
+        ]);
+        assert_eq!(interp.variable("dc"), Some(10));
+        assert_eq!(
+            values[3].as_ref().unwrap(),
+            &StatementRecord::SetFact("weather is 3".into())
+        );
+        assert!(values[4].is_err());
+
+        let record = interp.interpret(vec![call("travel-dc")]).remove(0).unwrap();
+        assert_eq!(record.value(), Some("10".into()));
+        let StatementRecord::ProcedureCall { records, .. } = record else {
+            panic!("expected a procedure call");
+        };
+        assert_eq!(records.len(), 2);
+
+        let early_return = Statement::IfThen {
+            antecedent: Antecedent::DiceRoll {
+                target: Token::Num(1),
+                roll_specifier: ModifiedRollSpecifier {
+                    base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+                    modifier: 0,
+                    terms: Vec::new(),
                 },
-                consequent: Box::new(Statement::Reminder("you passed the check".into())),
+            },
+            consequent: Box::new(Statement::Return(ReturnValue::Str(CrawlStr::Str(
+                "stormy".into(),
+            )))),
+        };
+        let values = interp.interpret(vec![
+            procedure(
+                "forecast",
+                vec![early_return, Statement::Reminder("clear skies".into())],
+            ),
+            call("forecast"),
+        ]);
+        assert_eq!(values[1].as_ref().unwrap().value(), Some("stormy".into()));
+    }
+
+    #[test]
+    fn generator() {
+        let mut storage = MemoryStorage::new();
+        storage.write("names", "1,Aldo\n").unwrap();
+        storage.write("gear", "1,{1d1} torches\n").unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        let load = |table: &str| Statement::LoadTable {
+            table: table.into(),
+            no_clamp: false,
+        };
+        let call = |identifier: &str| Statement::ProcedureCall {
+            identifier: identifier.into(),
+            arguments: Vec::new(),
+        };
+        let generator = |name: &str, template: &str| Statement::Generator {
+            name: name.into(),
+            template: template.into(),
+        };
+        let values = interp.interpret(vec![
+            load("names"),
+            load("gear"),
+            generator("npc", "{names} carrying {gear} and { 1d1+1 } coins"),
+            call("npc"),
+            Statement::SetFact(CrawlStr::InterpolatedStr {
+                format_string: "met {}".into(),
+                expressions: vec![call("npc")],
             }),
-            Box::new(Statement::Reminder("cool procedure".into())),
-        ]
+            generator("hireling", "{names} the {adjectives}"),
+            call("hireling"),
+        ]);
+        assert_eq!(
+            values[3].as_ref().unwrap(),
+            &StatementRecord::Generated {
+                generator: "npc".into(),
+                value: "Aldo carrying 1 torches and 2 coins".into(),
+            }
+        );
+        assert_eq!(
+            values[4].as_ref().unwrap(),
+            &StatementRecord::SetFact("met Aldo carrying 1 torches and 2 coins".into())
+        );
+        assert!(values[6].is_err());
     }
 
     #[test]
-    fn interpret_reminder() {
-        let ast = Statement::Reminder("players must eat rations daily".into());
-        let value = interp_to_values(vec![ast]);
+    fn reload_table() {
+        let mut storage = MemoryStorage::new();
+        storage.write("crits.csv", "2,a graze\n").unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage.clone()));
+        let roll = Statement::TableRoll {
+            table: "crits.csv".into(),
+            field: None,
+            tag: None,
+            excluding_seen: false,
+        };
+        interp.interpret(vec![Statement::LoadTable {
+            table: "crits.csv".into(),
+            no_clamp: true,
+        }]);
+        assert_eq!(interp.table_names().collect::<Vec<_>>(), vec!["crits.csv"]);
+
+        storage.write("crits.csv", "1,a deep cut\n").unwrap();
+        interp.reload_table("crits.csv").unwrap();
+        let values = interp.interpret(vec![roll]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::TableRoll {
+                table: "crits.csv".into(),
+                entry: "a deep cut".into(),
+                pinned: false,
+            }
+        );
+        assert!(interp.reload_table("missing.csv").is_err());
+    }
+
+    #[test]
+    fn duplicate_warnings() {
+        let mut storage = MemoryStorage::new();
+        storage.write("crits.csv", "1,a graze\n").unwrap();
+        let buffer = BufferSink::new();
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage))
+            .with_output(Box::new(buffer.clone()));
+        let load = Statement::LoadTable {
+            table: "crits.csv".into(),
+            no_clamp: false,
+        };
+        let rest = Statement::Procedure {
+            declaration: ProcedureDeclaration("rest".into()),
+            body: vec![Statement::SetFact(CrawlStr::Str("party is rested".into()))].into(),
+        };
+        let call = Statement::ProcedureCall {
+            identifier: "rest".into(),
+            arguments: Vec::new(),
+        };
+        interp.interpret(vec![load.clone(), rest.clone(), call.clone(), call]);
+        let warnings = |buffer: &BufferSink| -> Vec<Output> {
+            buffer
+                .take()
+                .into_iter()
+                .filter(|output| matches!(output, Output::Warning(_)))
+                .collect()
+        };
+        // Each call's body is its own statement list.
+        assert!(warnings(&buffer).is_empty());
+
+        interp.interpret(vec![
+            load,
+            rest,
+            Statement::SetFact(CrawlStr::Str("party is lost".into())),
+            Statement::SetFact(CrawlStr::Str("party is lost".into())),
+        ]);
+        assert_eq!(
+            warnings(&buffer),
+            vec![
+                Output::Warning("table \"crits.csv\" loaded again".into()),
+                Output::Warning("procedure rest redefined".into()),
+                Output::Warning("fact \"party is lost\" set twice".into()),
+            ]
+        );
+        interp.reload_table("crits.csv").unwrap();
+        assert!(warnings(&buffer).is_empty());
+    }
+
+    #[test]
+    fn assertions() {
+        let mut interp = Interpreter::new();
+        let lost = Statement::Assert(Antecedent::CheckFact("party is lost".into()));
+        assert!(interp.interpret(vec![lost.clone()])[0].is_err());
+
+        let values = interp.interpret(vec![
+            Statement::SetFact(CrawlStr::Str("party is lost".into())),
+            lost,
+            Statement::AssertEqual {
+                actual: ReturnValue::Str(CrawlStr::Str("3".into())),
+                expected: ReturnValue::Amount(Amount::Fixed(3)),
+            },
+        ]);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::Assert("fact \"party is lost\" is set".into())
+        );
+        assert!(values[2].is_ok());
+
+        let values = interp.interpret(vec![Statement::AssertEqual {
+            actual: ReturnValue::Amount(Amount::Fixed(2)),
+            expected: ReturnValue::Amount(Amount::Fixed(3)),
+        }]);
+        assert_eq!(
+            values[0].as_ref().unwrap_err().to_string(),
+            assertion_failed("expected \"3\", got \"2\"").to_string()
+        );
+    }
+
+    #[test]
+    fn oracle() {
+        let oracle = Oracle::new().with_chance(Likelihood::Likely, 100);
+        let mut interp = Interpreter::new().with_oracle(oracle);
+        let values = interp.interpret(vec![Statement::Oracle {
+            question: "does the guard notice?".into(),
+            likelihood: Likelihood::Likely,
+        }]);
+        assert!(matches!(
+            values[0],
+            Ok(StatementRecord::Oracle { answer: true, .. })
+        ));
+
+        // It can be asked as a consequent.
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "if roll 1+ on 1d1 => oracle \"is it raining\" likely\n",
+        ))
+        .map(|statement| statement.unwrap())
+        .collect();
+        let values = interp.interpret(statements);
+        let Ok(StatementRecord::IfThen {
+            consequent: Some(consequent),
+            ..
+        }) = &values[0]
+        else {
+            panic!(
+                "expected the consequent to be evaluated, got {:?}",
+                values[0]
+            );
+        };
+        assert!(matches!(
+            **consequent,
+            StatementRecord::Oracle { answer: true, .. }
+        ));
+    }
+
+    #[test]
+    fn chaos_factor() {
+        let source = "oracle \"is the door locked?\" likely\nincrease chaos factor\noracle \"is the door locked?\" likely\nset-fact \"chaos is {chaos factor}\"\n";
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(|statement| statement.unwrap())
+            .collect();
+        let storage = MemoryStorage::new();
+        let oracle = Oracle::new()
+            .with_odds(Likelihood::Likely, "1d1".parse().unwrap(), 0)
+            .with_chaos_step(100);
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage.clone()))
+            .with_oracle(oracle);
+        let values = interp.interpret(statements);
+        let answer = |value: &Result<StatementRecord, CrawlError>| match value {
+            Ok(StatementRecord::Oracle { answer, .. }) => *answer,
+            value => panic!("expected an oracle answer, got {value:?}"),
+        };
+        // More chaos makes a yes likelier.
+        assert!(!answer(&values[0]));
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::AdjustChaos { from: 5, to: 6 }
+        );
+        assert!(answer(&values[2]));
+        assert_eq!(
+            values[3].as_ref().unwrap(),
+            &StatementRecord::SetFact("chaos is 6".into())
+        );
+
+        // It's kept for the campaign, and stays between 1 and 9.
+        interp.save_persistent_facts().unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        interp.load_persistent_facts().unwrap();
+        let values = interp.interpret(vec![Statement::AdjustChaos(1); 4]);
+        assert_eq!(
+            values[3].as_ref().unwrap(),
+            &StatementRecord::AdjustChaos { from: 9, to: 9 }
+        );
+        interp.switch_campaign("new").unwrap();
+        assert_eq!(interp.chaos, DEFAULT_CHAOS);
+    }
+
+    #[test]
+    fn pin_table() {
+        let prompt = TerminalPrompt::new("1\n1\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        interp.add_table(
+            "weather",
+            Table::from_reader("1,clear\n2,storm\n".as_bytes()).unwrap(),
+        );
+        let pin = |entry: &str| Statement::PinTable {
+            table: "weather".into(),
+            entry: entry.into(),
+            rolls: 2,
+        };
+        let roll = Statement::TableRoll {
+            table: "weather".into(),
+            field: None,
+            tag: None,
+            excluding_seen: false,
+        };
+        let values = interp.interpret(vec![pin("hail"), pin("storm")]);
+        assert!(values[0].is_err());
+        // Rolls made for other statements, like travel, aren't pinned.
+        assert_eq!(
+            interp.roll_on_table("weather", None).unwrap().1,
+            vec!["clear"]
+        );
+        let values = interp.interpret(vec![roll.clone(), roll.clone(), roll]);
+        let rolled = |entry: &str, pinned| StatementRecord::TableRoll {
+            table: "weather".into(),
+            entry: entry.into(),
+            pinned,
+        };
+        // The pin runs out after two rolls.
+        assert_eq!(values[0].as_ref().unwrap(), &rolled("storm", true));
+        assert_eq!(values[1].as_ref().unwrap(), &rolled("storm", true));
+        assert_eq!(values[2].as_ref().unwrap(), &rolled("clear", false));
+
+        // A roll that fails doesn't use up the pin.
+        let omens = Statement::TableRoll {
+            table: "omens".into(),
+            field: None,
+            tag: None,
+            excluding_seen: false,
+        };
+        let values = interp.interpret(vec![
+            Statement::PinTable {
+                table: "omens".into(),
+                entry: "a comet".into(),
+                rolls: 1,
+            },
+            omens,
+        ]);
+        assert!(values[1].is_err());
+        assert_eq!(interp.pinned_entry("omens").as_deref(), Some("a comet"));
+    }
+
+    #[test]
+    fn excluding_seen() {
+        let roll: Vec<Statement> =
+            Parser::from_tokens(Scanner::new("roll on table \"rumors\" excluding seen\n"))
+                .map(|statement| statement.unwrap())
+                .collect();
+        let rumors = || Table::from_reader("1,a\n2,b\n3,c\n".as_bytes()).unwrap();
+        let rolled = |values: &[Result<StatementRecord, CrawlError>]| match &values[0] {
+            Ok(StatementRecord::TableRoll { entry, .. }) => entry.clone(),
+            value => panic!("expected a table roll, got {value:?}"),
+        };
+        let storage = MemoryStorage::new();
+        let prompt = TerminalPrompt::new("1\n1\n2\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage.clone()))
+            .with_manual_rolls(Box::new(prompt));
+        interp.add_table("rumors", rumors());
+        assert_eq!(rolled(&interp.interpret(roll.clone())), "a");
+        // The second 1 is rerolled.
+        assert_eq!(rolled(&interp.interpret(roll.clone())), "b");
+        interp.save_persistent_facts().unwrap();
+
+        // They're kept for the campaign.
+        let prompt = TerminalPrompt::new("2\n1\n3\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new()
+            .with_storage(Box::new(storage))
+            .with_manual_rolls(Box::new(prompt));
+        interp.load_persistent_facts().unwrap();
+        interp.add_table("rumors", rumors());
+        assert_eq!(rolled(&interp.interpret(roll.clone())), "c");
+        assert!(interp.interpret(roll.clone())[0].is_err());
+        interp.switch_campaign("new").unwrap();
+        assert!(interp.seen_entries.is_empty());
+    }
+
+    #[test]
+    fn scene_check() {
+        let prompt = TerminalPrompt::new("8\n5\n4\n1\n2\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        let focus = "roll,focus\n1-2,NPC action\n";
+        interp.add_table(
+            scenes::EVENT_FOCUS,
+            Table::from_reader(focus.as_bytes()).unwrap(),
+        );
+        let meaning = "roll,meaning\n1-2,betray the baron\n";
+        interp.add_table(
+            scenes::EVENT_MEANING,
+            Table::from_reader(meaning.as_bytes()).unwrap(),
+        );
+        let values = interp.interpret(vec![Statement::SceneCheck; 3]);
+        let outcomes: Vec<SceneOutcome> = values
+            .iter()
+            .map(|value| match value {
+                Ok(StatementRecord::SceneCheck { outcome, .. }) => *outcome,
+                value => panic!("expected a scene check, got {value:?}"),
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                SceneOutcome::Expected,
+                SceneOutcome::Altered,
+                SceneOutcome::Interrupted
+            ]
+        );
+        let Ok(StatementRecord::SceneCheck { event, .. }) = &values[2] else {
+            unreachable!();
+        };
         assert_eq!(
-            value,
-            vec![StatementRecord::Reminder(
-                "players must eat rations daily".into()
-            )],
+            event.as_ref().unwrap(),
+            &RandomEvent {
+                focus: "NPC action".into(),
+                meaning: "betray the baron".into(),
+            }
         );
     }
 
     #[test]
-    fn interpret_if_then_antecedent_true() {
-        let ast = Statement::IfThen {
-            antecedent: Antecedent::DiceRoll {
-                target: Token::Num(1),
-                roll_specifier: ModifiedRollSpecifier {
-                    base_roll_specifier: Token::RollSpecifier("1d1".into()),
-                    modifier: 0,
-                },
-            },
-            consequent: Box::new(Statement::Reminder("you passed the check".into())),
+    fn campaign_odds() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write(
+                &campaign_key("mythic", ODDS_KEY),
+                "odds,roll,target\nlikely,1d1,0\nsure thing,1d1,1\n",
+            )
+            .unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        let oracle = |likelihood| Statement::Oracle {
+            question: "does the guard notice?".into(),
+            likelihood,
         };
-        let value = interp_to_values(vec![ast]);
-        assert_eq!(
-            value,
-            vec![StatementRecord::IfThen {
-                antecedent: true,
-                consequent: Some(Box::new(StatementRecord::Reminder(
-                    "you passed the check".into()
-                )))
-            }]
-        );
+        assert!(interp.interpret(vec![oracle(Likelihood::named("sure thing"))])[0].is_err());
+
+        // The campaign's odds replace the oracle's, and add to them.
+        interp.switch_campaign("mythic").unwrap();
+        let values = interp.interpret(vec![
+            oracle(Likelihood::Likely),
+            oracle(Likelihood::named("sure thing")),
+            oracle(Likelihood::VeryLikely),
+        ]);
+        let answers: Vec<bool> = values
+            .iter()
+            .map(|value| match value {
+                Ok(StatementRecord::Oracle { answer, .. }) => *answer,
+                value => panic!("expected an oracle answer, got {value:?}"),
+            })
+            .collect();
+        assert_eq!(answers[..2], [false, true]);
+
+        interp.switch_campaign("other").unwrap();
+        assert!(interp.interpret(vec![oracle(Likelihood::named("sure thing"))])[0].is_err());
     }
 
     #[test]
-    fn interpret_if_then_antecedent_false() {
-        let ast = Statement::IfThen {
-            antecedent: Antecedent::DiceRoll {
-                target: Token::Num(100),
-                roll_specifier: ModifiedRollSpecifier {
-                    base_roll_specifier: Token::RollSpecifier("1d1".into()),
-                    modifier: 0,
-                },
+    fn manual_rolls() {
+        let prompt = TerminalPrompt::new("2\n".as_bytes(), std::io::sink());
+        let mut interp = Interpreter::new().with_manual_rolls(Box::new(prompt));
+        let values = interp.interpret(vec![
+            Statement::UsageDie {
+                resource: "rations".into(),
+                sides: 8,
             },
-            consequent: Box::new(Statement::Reminder("you passed the check".into())),
-        };
-        let value = interp_to_values(vec![ast]);
+            Statement::UsageRoll("rations".into()),
+            Statement::UsageRoll("rations".into()),
+        ]);
         assert_eq!(
-            value,
-            vec![StatementRecord::IfThen {
-                antecedent: false,
-                consequent: None,
-            }]
+            values[1].as_ref().unwrap(),
+            &StatementRecord::UsageRoll {
+                resource: "rations".into(),
+                rolled: 2,
+                die: Some(6),
+            }
         );
+        // Nothing left to enter
+        assert!(values[2].is_err());
     }
 
     #[test]
-    fn interpret_proc_def() {
-        let body = make_proc_body();
-        let ast = Statement::Procedure {
-            declaration: ProcedureDeclaration("proc-name".into()),
-            body: body.clone(),
-        };
+    fn snapshot_and_restore() {
+        let ast = vec![
+            Statement::LoadTable {
+                table: "examples/table.csv".into(),
+                no_clamp: false,
+            },
+            Statement::Procedure {
+                declaration: ProcedureDeclaration("proc-name".into()),
+                body: make_proc_body(),
+            },
+            Statement::SetFact(CrawlStr::Str("party is lost".into())),
+            Statement::SetPersistentFact("weather is nice".into()),
+        ];
         let mut interp = Interpreter::new();
-        let value: Vec<StatementRecord> = interp
-            .interpret(vec![ast])
+        let _ = interp.interpret(ast);
+
+        let json = interp.snapshot().to_json().unwrap();
+        let mut restored = Interpreter::new();
+        restored.restore(StateSnapshot::from_json(&json).unwrap());
+
+        assert_eq!(restored.snapshot(), interp.snapshot());
+        let values: Vec<StatementRecord> = restored
+            .interpret(vec![Statement::ProcedureCall {
+                identifier: "proc-name".into(),
+                arguments: Vec::new(),
+            }])
             .into_iter()
             .map(|v| v.unwrap())
             .collect();
-        assert_eq!(
-            value,
-            vec![StatementRecord::ProcedureDefinition("proc-name".into())]
-        );
-        assert!(interp.procedures.contains_key("proc-name"));
-        assert_eq!(
-            *interp.procedures.get("proc-name").unwrap().body,
-            body.into_iter().map(|s| *s).collect::<Vec<Statement>>()
-        );
+        assert_eq!(values.len(), 1);
     }
 
     #[test]
-    fn interpret_proc_call() {
-        let body = make_proc_body();
-        let proc = Statement::Procedure {
-            declaration: ProcedureDeclaration("proc-name".into()),
-            body: body.clone(),
+    fn interpret_matching_roll() {
+        let ast = Statement::MatchingRoll {
+            roll_specifier: ModifiedRollSpecifier {
+                base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+                modifier: 0,
+                terms: Vec::new(),
+            },
+            arms: vec![MatchingRollArm {
+                target: Token::Num(1),
+                guard: None,
+                consequent: Statement::Reminder("matched 1".into()),
+            }],
+            otherwise: None,
         };
-        let call = Statement::ProcedureCall("proc-name".into());
-        let ast = vec![proc, call];
-        let values = interp_to_values(ast);
+        let values = interp_to_values(vec![ast]);
         assert_eq!(
             values,
-            vec![
-                StatementRecord::ProcedureDefinition("proc-name".into()),
-                StatementRecord::ProcedureCall {
-                    identifier: "proc-name".into(),
-                    records: vec![
-                        Box::new(StatementRecord::IfThen {
-                            antecedent: true,
-                            consequent: Some(Box::new(StatementRecord::Reminder(
-                                "you passed the check".into()
-                            ))),
-                        }),
-                        Box::new(StatementRecord::Reminder("cool procedure".into())),
-                    ],
-                },
+            vec![StatementRecord::MatchingRoll {
+                matched_target: Some(Token::Num(1)),
+                consequent: Some(Box::new(StatementRecord::Reminder("matched 1".into()))),
+            }]
+        )
+    }
+
+    #[test]
+    fn matching_roll_guard() {
+        let arm = |guard, reminder: &str| MatchingRollArm {
+            target: Token::Num(1),
+            guard,
+            consequent: Statement::Reminder(reminder.into()),
+        };
+        let ast = Statement::MatchingRoll {
+            roll_specifier: ModifiedRollSpecifier {
+                base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
+                modifier: 0,
+                terms: Vec::new(),
+            },
+            arms: vec![
+                arm(Some(Antecedent::CheckFact("hex is forest".into())), "bears"),
+                arm(None, "bandits"),
             ],
+            otherwise: None,
+        };
+        let values = interp_to_values(vec![ast]);
+        assert_eq!(
+            values,
+            vec![StatementRecord::MatchingRoll {
+                matched_target: Some(Token::Num(1)),
+                consequent: Some(Box::new(StatementRecord::Reminder("bandits".into()))),
+            }]
         )
     }
 
     #[test]
-    fn interpret_matching_roll() {
+    fn matching_roll_otherwise() {
         let ast = Statement::MatchingRoll {
             roll_specifier: ModifiedRollSpecifier {
-                base_roll_specifier: Token::RollSpecifier("1d1".into()),
+                base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
                 modifier: 0,
+                terms: Vec::new(),
             },
             arms: vec![MatchingRollArm {
-                target: Token::Num(1),
-                consequent: Statement::Reminder("matched 1".into()),
+                target: Token::Num(2),
+                guard: None,
+                consequent: Statement::Reminder("matched 2".into()),
             }],
+            otherwise: Some(Box::new(Statement::Reminder("matched nothing".into()))),
         };
         let values = interp_to_values(vec![ast]);
         assert_eq!(
             values,
             vec![StatementRecord::MatchingRoll {
-                matched_target: Some(Token::Num(1)),
-                consequent: Some(Box::new(StatementRecord::Reminder("matched 1".into()))),
+                matched_target: None,
+                consequent: Some(Box::new(StatementRecord::Reminder(
+                    "matched nothing".into()
+                ))),
             }]
         )
     }
 
+    #[test]
+    fn matching_fact() {
+        let matching = Statement::MatchingFact {
+            fact: "reaction is".into(),
+            arms: vec![TableRollArm {
+                pattern: "hostile".into(),
+                consequent: Statement::Reminder("ambush".into()),
+            }],
+            otherwise: Some(Box::new(Statement::Reminder("parley".into()))),
+        };
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(vec![
+            Statement::SetFact(CrawlStr::Str("reaction is hostile".into())),
+            matching.clone(),
+        ]);
+        assert_eq!(
+            values[1].as_ref().unwrap(),
+            &StatementRecord::MatchingFact {
+                fact: "reaction is".into(),
+                values: vec!["hostile".into()],
+                matched: Some("hostile".into()),
+                consequent: Some(Box::new(StatementRecord::Reminder("ambush".into()))),
+            }
+        );
+
+        let values = interp_to_values(vec![matching]);
+        assert_eq!(
+            values,
+            vec![StatementRecord::MatchingFact {
+                fact: "reaction is".into(),
+                values: Vec::new(),
+                matched: None,
+                consequent: Some(Box::new(StatementRecord::Reminder("parley".into()))),
+            }]
+        );
+    }
+
+    #[test]
+    fn with_facts() {
+        let check = |fact: &str| Statement::IfThen {
+            antecedent: Antecedent::CheckFact(fact.into()),
+            consequent: Box::new(Statement::Reminder(fact.into())),
+        };
+        let values = interp_to_values(vec![
+            Statement::SetFact(CrawlStr::Str("party is lost".into())),
+            Statement::WithFacts(vec![
+                Statement::SetFact(CrawlStr::Str("party is hidden".into())),
+                Statement::ClearFact("party is lost".into()),
+                check("party is hidden"),
+            ]),
+            check("party is hidden"),
+            check("party is lost"),
+            // Setting it again outside the block isn't setting it twice.
+            Statement::SetFact(CrawlStr::Str("party is hidden".into())),
+        ]);
+        let held = |fact: &str| StatementRecord::IfThen {
+            antecedent: true,
+            consequent: Some(Box::new(StatementRecord::Reminder(fact.into()))),
+        };
+        assert_eq!(
+            values[1],
+            StatementRecord::WithFacts(vec![
+                Box::new(StatementRecord::SetFact("party is hidden".into())),
+                Box::new(StatementRecord::ClearFact("party is lost".into())),
+                Box::new(held("party is hidden")),
+            ])
+        );
+        assert_eq!(
+            values[2],
+            StatementRecord::IfThen {
+                antecedent: false,
+                consequent: None,
+            }
+        );
+        assert_eq!(values[3], held("party is lost"));
+    }
+
+    #[test]
+    fn rules() {
+        let mut interp = Interpreter::new();
+        let values = interp.interpret(vec![
+            Statement::Rule(Rule {
+                conditions: vec![
+                    Antecedent::CheckFact("weather is storm".into()),
+                    Antecedent::CheckPersistentFact("party is travelling".into()),
+                ],
+                fact: "travel is slowed".into(),
+            }),
+            Statement::Rule(Rule {
+                conditions: vec![Antecedent::CheckFact("travel is slowed".into())],
+                fact: "rations are short".into(),
+            }),
+            Statement::SetFact(CrawlStr::Str("weather is storm".into())),
+            Statement::SetPersistentFact("party is travelling".into()),
+            Statement::IfThen {
+                antecedent: Antecedent::CheckFact("rations are short".into()),
+                consequent: Box::new(Statement::Reminder("forage".into())),
+            },
+            Statement::ClearFact("weather is storm".into()),
+            Statement::IfThen {
+                antecedent: Antecedent::CheckFact("travel is slowed".into()),
+                consequent: Box::new(Statement::Reminder("slowed".into())),
+            },
+        ]);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &StatementRecord::RuleDefinition("travel is slowed".into())
+        );
+        assert_eq!(
+            values[4].as_ref().unwrap(),
+            &StatementRecord::IfThen {
+                antecedent: true,
+                consequent: Some(Box::new(StatementRecord::Reminder("forage".into()))),
+            }
+        );
+        assert_eq!(
+            values[6].as_ref().unwrap(),
+            &StatementRecord::IfThen {
+                antecedent: false,
+                consequent: None,
+            }
+        );
+    }
+
+    #[test]
+    fn report_facts() {
+        let mut interp = Interpreter::new();
+        interp
+            .persistent_facts
+            .set(Fact::try_from(String::from("party is travelling")).unwrap());
+        let values = interp.interpret(vec![
+            Statement::SetFact(CrawlStr::Str("party is lost".into())),
+            Statement::SetFact(CrawlStr::Str("party rations 3".into())),
+            Statement::SetFact(CrawlStr::Str("weather is storm".into())),
+            Statement::ReportFacts("party".into()),
+            Statement::ReportFacts("wizard".into()),
+        ]);
+        assert_eq!(
+            values[3].as_ref().unwrap(),
+            &StatementRecord::FactReport {
+                entity: "party".into(),
+                facts: vec![
+                    ("is".into(), "lost".into()),
+                    ("is".into(), "travelling".into()),
+                    ("rations".into(), "3".into()),
+                ],
+            }
+        );
+        assert_eq!(
+            values[4].as_ref().unwrap(),
+            &StatementRecord::FactReport {
+                entity: "wizard".into(),
+                facts: Vec::new(),
+            }
+        );
+    }
+
     #[test]
     fn interpret_set_persistent_fact() {
         let ast = Statement::SetPersistentFact("weather is nice".into());
@@ -519,29 +7167,45 @@ mod tests {
             values,
             vec![StatementRecord::SetPersistentFact("weather is nice".into())]
         );
-        assert!(interp
-            .persistent_facts
-            .check(&Fact::try_from(String::from("weather is nice")).unwrap()));
+        assert!(
+            interp
+                .persistent_facts
+                .check(&Fact::try_from(String::from("weather is nice")).unwrap())
+        );
     }
 
     #[test]
     fn interpret_load_table() {
-        let ast = Statement::LoadTable("examples/table.csv".into());
+        let ast = Statement::LoadTable {
+            table: "examples/table.csv".into(),
+            no_clamp: false,
+        };
         let mut interp = Interpreter::new();
         let values: Vec<StatementRecord> = interp
             .interpret(vec![ast])
             .into_iter()
             .map(|v| v.unwrap())
             .collect();
-        assert_eq!(values, vec![StatementRecord::LoadTable("examples/table.csv".into())]);
+        assert_eq!(
+            values,
+            vec![StatementRecord::LoadTable("examples/table.csv".into())]
+        );
         assert!(interp.tables.contains_key("examples/table.csv"));
     }
 
     #[test]
     fn interpret_table_roll() {
         let ast = vec![
-            Statement::LoadTable("examples/table.csv".into()),
-            Statement::TableRoll("examples/table.csv".into()),
+            Statement::LoadTable {
+                table: "examples/table.csv".into(),
+                no_clamp: false,
+            },
+            Statement::TableRoll {
+                table: "examples/table.csv".into(),
+                field: None,
+                tag: None,
+                excluding_seen: false,
+            },
         ];
         // TODO: not really a test
         let _ = interp_to_values(ast);
@@ -552,8 +7216,9 @@ mod tests {
         let ast = vec![Statement::SetFact(CrawlStr::InterpolatedStr {
             format_string: "number is {}".into(),
             expressions: vec![Statement::NontargetedRoll(ModifiedRollSpecifier {
-                base_roll_specifier: Token::RollSpecifier("1d1".into()),
+                base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 1)),
                 modifier: 0,
+                terms: Vec::new(),
             })],
         })];
         let mut interp = Interpreter::new();
@@ -562,8 +7227,213 @@ mod tests {
             .into_iter()
             .map(|v| v.unwrap())
             .collect();
-        // TODO: just show the number
-        assert_eq!(values, vec![StatementRecord::SetFact("number is NontargetedRoll(1)".into())]);
+        assert_eq!(values, vec![StatementRecord::SetFact("number is 1".into())]);
+    }
+
+    #[test]
+    fn include() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write(
+                "campaign/main.crawl",
+                "include \"procedures/travel.crawl\"\n",
+            )
+            .unwrap();
+        storage
+            .write(
+                "campaign/procedures/travel.crawl",
+                "procedure travel\n\treminder \"mark a ration\"\nend\n",
+            )
+            .unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        let values: Vec<StatementRecord> = interp
+            .interpret(vec![
+                Statement::Include {
+                    path: "campaign/./main.crawl".into(),
+                    namespace: None,
+                },
+                Statement::ProcedureCall {
+                    identifier: "travel".into(),
+                    arguments: Vec::new(),
+                },
+            ])
+            .into_iter()
+            .map(|v| v.unwrap())
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                StatementRecord::Include {
+                    path: "campaign/main.crawl".into(),
+                    records: vec![Box::new(StatementRecord::Include {
+                        path: "campaign/procedures/travel.crawl".into(),
+                        records: vec![Box::new(StatementRecord::ProcedureDefinition(
+                            "travel".into()
+                        ))],
+                    })],
+                },
+                StatementRecord::ProcedureCall {
+                    identifier: "travel".into(),
+                    records: vec![Box::new(StatementRecord::Reminder("mark a ration".into()))],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn include_as_namespace() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write(
+                "hexcrawl.crawl",
+                "procedure travel-turn\n\tlost\nend\nprocedure lost\n\treminder \"roll for direction\"\nend\n",
+            )
+            .unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        let mut values = interp
+            .interpret(vec![
+                Statement::Include {
+                    path: "hexcrawl.crawl".into(),
+                    namespace: Some("hex".into()),
+                },
+                Statement::ProcedureCall {
+                    identifier: "hex.travel-turn".into(),
+                    arguments: Vec::new(),
+                },
+                Statement::ProcedureCall {
+                    identifier: "lost".into(),
+                    arguments: Vec::new(),
+                },
+            ])
+            .into_iter();
+        assert!(values.next().unwrap().is_ok());
+        assert_eq!(
+            values.next().unwrap().unwrap(),
+            StatementRecord::ProcedureCall {
+                identifier: "hex.travel-turn".into(),
+                records: vec![Box::new(StatementRecord::ProcedureCall {
+                    identifier: "hex.lost".into(),
+                    records: vec![Box::new(StatementRecord::Reminder(
+                        "roll for direction".into()
+                    ))],
+                })],
+            }
+        );
+        // Only reachable through the namespace
+        assert!(values.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn include_cycle() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .write("main.crawl", "include \"travel.crawl\"\n")
+            .unwrap();
+        storage
+            .write("travel.crawl", "include \"hexes/../main.crawl\"\n")
+            .unwrap();
+        let mut interp = Interpreter::new().with_storage(Box::new(storage));
+        interp.set_source_path(Some("main.crawl".into()));
+        let error = interp
+            .interpret(vec![Statement::Include {
+                path: "travel.crawl".into(),
+                namespace: None,
+            }])
+            .remove(0)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            CrawlError::InterpreterError { reason }
+                if reason == "include cycle main.crawl -> travel.crawl -> main.crawl"
+        ));
+    }
+
+    #[test]
+    fn host_function() {
+        let ast = vec![
+            Statement::ProcedureCall {
+                identifier: "weather-api".into(),
+                arguments: vec!["north".into()],
+            },
+            Statement::SetFact(CrawlStr::InterpolatedStr {
+                format_string: "weather is {}".into(),
+                expressions: vec![Statement::ProcedureCall {
+                    identifier: "weather-api".into(),
+                    arguments: vec!["south".into()],
+                }],
+            }),
+        ];
+        let mut interp = Interpreter::new();
+        interp.register_function("weather-api", |args| match args {
+            [region] if region == "north" => Ok("snowy".into()),
+            [_] => Ok("sunny".into()),
+            _ => Err(CrawlError::InterpreterError {
+                reason: "weather-api takes a region".into(),
+            }),
+        });
+        let values: Vec<StatementRecord> = interp
+            .interpret(ast)
+            .into_iter()
+            .map(|v| v.unwrap())
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                StatementRecord::HostCall {
+                    identifier: "weather-api".into(),
+                    value: "snowy".into(),
+                },
+                StatementRecord::SetFact("weather is sunny".into()),
+            ]
+        );
+        assert!(
+            interp.interpret(vec![Statement::ProcedureCall {
+                identifier: "weather-api".into(),
+                arguments: Vec::new(),
+            }])[0]
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn listeners() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut interp = Interpreter::new();
+        let log = Arc::clone(&events);
+        interp.on_fact_set(move |fact| log.lock().unwrap().push(format!("fact {fact}")));
+        let log = Arc::clone(&events);
+        interp.on_table_roll(move |table, entry| {
+            log.lock().unwrap().push(format!("table {table} {entry}"))
+        });
+        let log = Arc::clone(&events);
+        interp.on_procedure_call(move |identifier| {
+            log.lock().unwrap().push(format!("call {identifier}"))
+        });
+
+        let _ = interp.interpret(vec![
+            Statement::Procedure {
+                declaration: ProcedureDeclaration("lost".into()),
+                body: vec![Statement::SetPersistentFact("party is lost".into())].into(),
+            },
+            Statement::ProcedureCall {
+                identifier: "lost".into(),
+                arguments: Vec::new(),
+            },
+            Statement::LoadTable {
+                table: "examples/table.csv".into(),
+                no_clamp: false,
+            },
+            Statement::TableRoll {
+                table: "examples/table.csv".into(),
+                field: None,
+                tag: None,
+                excluding_seen: false,
+            },
+        ]);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[..2], ["call lost", "fact party is lost"]);
+        assert!(events[2].starts_with("table examples/table.csv "));
     }
 
     #[test]
@@ -575,4 +7445,30 @@ mod tests {
             StatementRecord::Reminder("players must eat rations daily".into())
         );
     }
+
+    #[test]
+    fn compiled_procedures() {
+        // The same statements, in a procedure and at the top level, come out the same.
+        let body = "set-fact \"party is lost\"\nif fact? \"party is lost\" => reminder \"forage\"\nif fact? \"party is rested\" => clear-fact \"party is lost\"\nif roll 4-6 on 1d6 => set-fact \"weather is fair\"\nroll 2d6\n\t2-6 => reminder \"miss\"\n\telse => reminder \"hit\"\nend\nlet bonus = 1\nroll 1d6 + bonus\n\t2-7 => reminder \"stars\"\nend\n";
+        let parse = |source: &str| -> Vec<Statement> {
+            Parser::from_tokens(Scanner::new(source))
+                .map(Result::unwrap)
+                .collect()
+        };
+        let top_level: Vec<StatementRecord> = Interpreter::new()
+            .with_seed(5)
+            .interpret(parse(body))
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+
+        let indented: String = body.lines().map(|line| format!("\t{line}\n")).collect();
+        let procedure = format!("procedure camp\n{indented}end\ncamp\n");
+        let mut records = Interpreter::new().with_seed(5).interpret(parse(&procedure));
+        let Ok(StatementRecord::ProcedureCall { records, .. }) = records.remove(1) else {
+            panic!("expected a procedure call");
+        };
+        let records: Vec<StatementRecord> = records.into_iter().map(|record| *record).collect();
+        assert_eq!(records, top_level);
+    }
 }
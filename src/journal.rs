@@ -0,0 +1,996 @@
+use std::fmt;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::initiative::Combatant;
+use crate::interpreter::StatementRecord;
+use crate::locale::Locale;
+use crate::recap::Recap;
+use crate::scenes::RandomEvent;
+
+#[derive(Debug, PartialEq)]
+pub struct JournalEntry {
+    pub timestamp: SystemTime,
+    pub record: StatementRecord,
+}
+
+/// Every record produced over a session, in order, for writing up campaign notes.
+#[derive(Debug)]
+pub struct Journal {
+    started: SystemTime,
+    entries: Vec<JournalEntry>,
+    locale: Locale,
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Journal {
+            started: now(),
+            entries: Vec::new(),
+            locale: Locale::default(),
+        }
+    }
+
+    /// Write the journal in `locale`'s strings.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// A file name for the journal, unique to the second the session started.
+    pub fn file_name(&self) -> String {
+        // Colons aren't allowed in file names everywhere.
+        let started = humantime::format_rfc3339_seconds(self.started).to_string();
+        format!("{}.md", started.replace(':', "-"))
+    }
+
+    pub fn record(&mut self, record: StatementRecord) {
+        self.entries.push(JournalEntry {
+            timestamp: now(),
+            record,
+        });
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n\n", self.locale.get("journal.title", &[]));
+        for entry in &self.entries {
+            let timestamp = humantime::format_rfc3339_seconds(entry.timestamp);
+            Self::write_record(
+                &mut markdown,
+                &entry.record,
+                0,
+                Some(&timestamp.to_string()),
+                Style::Markdown,
+                &self.locale,
+            );
+        }
+        markdown
+    }
+
+    /// The session as prose, a paragraph for each record.
+    pub fn to_recap(&self, recap: &Recap) -> String {
+        recap.render(self.entries.iter().map(|entry| &entry.record))
+    }
+
+    fn write_record(
+        markdown: &mut String,
+        record: &StatementRecord,
+        depth: usize,
+        timestamp: Option<&str>,
+        style: Style,
+        locale: &Locale,
+    ) {
+        let indent = "  ".repeat(depth);
+        let prefix = match (style, timestamp) {
+            (Style::Terminal { .. }, _) => indent,
+            (Style::Markdown, Some(timestamp)) => format!("{indent}- `{timestamp}` "),
+            (Style::Markdown, None) => format!("{indent}- "),
+        };
+        let mut line = |name: &str, fields: &[(&str, &str)]| {
+            markdown.push_str(&format!("{prefix}{}\n", locale.get(name, fields)));
+        };
+
+        match record {
+            StatementRecord::AddCombatant {
+                combatant,
+                initiative,
+            } => line(
+                "record.add_combatant",
+                &[
+                    ("combatant", combatant),
+                    ("initiative", &style.total(initiative)),
+                ],
+            ),
+            StatementRecord::AddItem { item, owner, count } => line(
+                "record.add_item",
+                &[
+                    ("item", item),
+                    ("owner", owner),
+                    ("count", &count.to_string()),
+                ],
+            ),
+            StatementRecord::AddMoney {
+                purse,
+                added,
+                balance,
+            } => line(
+                "record.add_money",
+                &[("purse", purse), ("added", added), ("balance", balance)],
+            ),
+            StatementRecord::SpendMoney {
+                purse,
+                spent,
+                balance,
+            } => line(
+                "record.spend_money",
+                &[("purse", purse), ("spent", spent), ("balance", balance)],
+            ),
+            StatementRecord::RemoveItem { item, owner, count } => line(
+                "record.remove_item",
+                &[
+                    ("item", item),
+                    ("owner", owner),
+                    ("count", &count.to_string()),
+                ],
+            ),
+            StatementRecord::InventoryReport { owner, items } if items.is_empty() => {
+                line("record.inventory_report_empty", &[("owner", owner)]);
+            }
+            StatementRecord::InventoryReport { owner, items } => line(
+                "record.inventory_report",
+                &[("owner", owner), ("items", &inventory(items))],
+            ),
+            StatementRecord::AdvanceTurn { combatant, round } => line(
+                "record.advance_turn",
+                &[("combatant", combatant), ("round", &round.to_string())],
+            ),
+            StatementRecord::AwardXp {
+                xp,
+                session,
+                campaign,
+            } => line(
+                "record.award_xp",
+                &[
+                    ("xp", &xp.to_string()),
+                    ("session", &session.to_string()),
+                    ("campaign", &campaign.to_string()),
+                ],
+            ),
+            StatementRecord::AwardTreasure {
+                treasure,
+                session,
+                campaign,
+            } => line(
+                "record.award_treasure",
+                &[
+                    ("treasure", treasure),
+                    ("session", session),
+                    ("campaign", campaign),
+                ],
+            ),
+            StatementRecord::XpReport {
+                party_size,
+                session_xp,
+                campaign_xp,
+                xp_share,
+                session_treasure,
+                treasure_share,
+            } => line(
+                "record.xp_report",
+                &[
+                    ("party_size", &party_size.to_string()),
+                    ("session_xp", &session_xp.to_string()),
+                    ("campaign_xp", &campaign_xp.to_string()),
+                    ("xp_share", &xp_share.to_string()),
+                    ("session_treasure", session_treasure),
+                    ("treasure_share", treasure_share),
+                ],
+            ),
+            StatementRecord::Light { light, turns } => line(
+                "record.light",
+                &[("light", light), ("turns", &turns.to_string())],
+            ),
+            StatementRecord::AdvanceTime {
+                by,
+                turns,
+                guttered,
+            } => {
+                line(
+                    "record.advance_time",
+                    &[("by", &by.to_string()), ("turns", &turns.to_string())],
+                );
+                for light in guttered {
+                    line("record.light_out", &[("light", light)]);
+                }
+            }
+            StatementRecord::MoraleCheck {
+                group,
+                total,
+                morale,
+                held,
+            } => line(
+                if *held {
+                    "record.morale_held"
+                } else {
+                    "record.morale_broke"
+                },
+                &[
+                    ("group", group),
+                    ("total", &style.total(total)),
+                    ("morale", &morale.to_string()),
+                ],
+            ),
+            StatementRecord::Encounter {
+                creature,
+                number,
+                reaction,
+                distance,
+                ..
+            } => {
+                let fields = [
+                    ("creature", creature.as_str()),
+                    ("reaction", reaction),
+                    ("distance", distance),
+                ];
+                match number {
+                    Some(number) => line(
+                        "record.encounter_numbered",
+                        &[&fields[..], &[("number", &number.to_string())]].concat(),
+                    ),
+                    None => line("record.encounter", &fields),
+                }
+            }
+            StatementRecord::GenerateRoom {
+                number,
+                shape,
+                exits,
+                contents,
+            } => line(
+                "record.generate_room",
+                &[
+                    ("number", &number.to_string()),
+                    ("shape", shape),
+                    ("exits", exits),
+                    ("contents", contents),
+                ],
+            ),
+            StatementRecord::AdvanceWeather { from, to } => {
+                line("record.advance_weather", &[("from", from), ("to", to)])
+            }
+            StatementRecord::AdvanceDate { days, date } => line(
+                "record.advance_date",
+                &[("days", &days.to_string()), ("date", date)],
+            ),
+            StatementRecord::CurrentDate(date) => line("record.current_date", &[("date", date)]),
+            StatementRecord::ChaosFactor(chaos) => {
+                line("record.chaos_factor", &[("chaos", &chaos.to_string())]);
+            }
+            StatementRecord::SceneCheck {
+                rolled,
+                chaos,
+                outcome,
+                event,
+            } => {
+                let outcome = locale.get(&format!("scene.{}", outcome.key()), &[]);
+                line(
+                    "record.scene_check",
+                    &[
+                        ("rolled", &style.total(rolled)),
+                        ("chaos", &chaos.to_string()),
+                        ("outcome", &outcome),
+                    ],
+                );
+                if let Some(RandomEvent { focus, meaning }) = event {
+                    line(
+                        "record.random_event",
+                        &[("focus", focus), ("meaning", meaning)],
+                    );
+                }
+            }
+            StatementRecord::AdjustChaos { from, to } => line(
+                "record.adjust_chaos",
+                &[("from", &from.to_string()), ("to", &to.to_string())],
+            ),
+            StatementRecord::PinTable {
+                table,
+                entry,
+                rolls,
+            } => line(
+                if *rolls == 1 {
+                    "record.pin_table_once"
+                } else {
+                    "record.pin_table"
+                },
+                &[
+                    ("table", table_title(table)),
+                    ("entry", entry),
+                    ("rolls", &rolls.to_string()),
+                ],
+            ),
+            StatementRecord::RemoveCombatant(combatant) => {
+                line("record.remove_combatant", &[("combatant", combatant)]);
+            }
+            StatementRecord::SortCombatants(combatants) => line(
+                "record.sort_combatants",
+                &[("combatants", &initiative_order(combatants))],
+            ),
+            StatementRecord::AdvanceClock {
+                clock,
+                filled,
+                segments,
+            } => line(
+                "record.advance_clock",
+                &[
+                    ("clock", clock),
+                    ("filled", &filled.to_string()),
+                    ("segments", &segments.to_string()),
+                ],
+            ),
+            StatementRecord::Assert(assertion) => {
+                line("record.assert", &[("assertion", assertion)]);
+            }
+            StatementRecord::Campaign(campaign) => {
+                line("record.campaign", &[("campaign", campaign)]);
+            }
+            StatementRecord::CheckFact(value) | StatementRecord::CheckPersistentFact(value) => {
+                line("record.check_fact", &[("result", &locale.yes_no(*value))]);
+            }
+            StatementRecord::Choice {
+                question,
+                chosen,
+                consequent,
+            } => {
+                line(
+                    "record.choice",
+                    &[("question", question), ("chosen", chosen)],
+                );
+                Self::write_record(markdown, consequent, depth + 1, None, style, locale);
+            }
+            StatementRecord::ClearFact(fact) => line("record.clear_fact", &[("fact", fact)]),
+            StatementRecord::ClearPersistentFact(fact) => {
+                line("record.clear_persistent_fact", &[("fact", fact)]);
+            }
+            StatementRecord::AddRumor(rumor) => line("record.add_rumor", &[("rumor", rumor)]),
+            StatementRecord::RevealRumor { rumor, truth } => line(
+                "record.reveal_rumor",
+                &[
+                    ("rumor", rumor),
+                    ("truth", &locale.get(&format!("rumor.{truth}"), &[])),
+                ],
+            ),
+            StatementRecord::HearRumors(heard) if heard.is_empty() => {
+                line("record.hear_rumors_none", &[]);
+            }
+            StatementRecord::HearRumors(heard) => {
+                line("record.hear_rumors", &[("rumors", &heard.join("; "))]);
+            }
+            StatementRecord::RumorReport(rumors) if rumors.is_empty() => {
+                line("record.rumor_report_empty", &[]);
+            }
+            StatementRecord::RumorReport(rumors) => {
+                let rumors: Vec<String> = rumors
+                    .iter()
+                    .map(|(rumor, truth)| match truth {
+                        Some(truth) => {
+                            format!("{rumor} ({})", locale.get(&format!("rumor.{truth}"), &[]))
+                        }
+                        None => rumor.clone(),
+                    })
+                    .collect();
+                line("record.rumor_report", &[("rumors", &rumors.join("; "))]);
+            }
+            StatementRecord::CreateFront { faction, segments } => line(
+                "record.create_front",
+                &[("faction", faction), ("segments", &segments.to_string())],
+            ),
+            StatementRecord::AdvanceFronts(advanced) => {
+                for front in advanced {
+                    line(
+                        "record.advance_front",
+                        &[
+                            ("faction", &front.faction),
+                            ("by", &front.by.to_string()),
+                            ("filled", &front.filled.to_string()),
+                            ("segments", &front.segments.to_string()),
+                        ],
+                    );
+                    if front.is_complete() {
+                        line("record.front_completed", &[("faction", &front.faction)]);
+                    }
+                }
+            }
+            StatementRecord::CreateClock { clock, segments } => line(
+                "record.create_clock",
+                &[("clock", clock), ("segments", &segments.to_string())],
+            ),
+            StatementRecord::IfThen {
+                consequent: Some(consequent),
+                ..
+            } => Self::write_record(markdown, consequent, depth, timestamp, style, locale),
+            StatementRecord::IfThen {
+                consequent: None, ..
+            } => line("record.condition_not_met", &[]),
+            StatementRecord::Let { name, value } => {
+                line(
+                    "record.let",
+                    &[("name", name), ("value", &value.to_string())],
+                );
+            }
+            StatementRecord::LoadTable(table) => {
+                line("record.load_table", &[("table", table_title(table))]);
+            }
+            StatementRecord::LoadNames(names) => {
+                line("record.load_names", &[("names", table_title(names))]);
+            }
+            StatementRecord::LoadPrices(prices) => {
+                line("record.load_prices", &[("prices", table_title(prices))]);
+            }
+            StatementRecord::Price { item, price } => {
+                line("record.price", &[("item", item), ("price", price)]);
+            }
+            StatementRecord::Haggle {
+                item, total, price, ..
+            } => line(
+                "record.haggle",
+                &[
+                    ("item", item),
+                    ("total", &total.to_string()),
+                    ("price", price),
+                ],
+            ),
+            StatementRecord::LoadStatblocks { file, statblocks } => line(
+                "record.load_statblocks",
+                &[("file", file), ("statblocks", &statblocks.join(", "))],
+            ),
+            StatementRecord::NameRoll { names, name } => line(
+                "record.name_roll",
+                &[("names", table_title(names)), ("name", name)],
+            ),
+            StatementRecord::MatchingRoll {
+                matched_target: Some(target),
+                consequent,
+            } => {
+                line("record.roll_matched", &[("target", &style.target(target))]);
+                if let Some(consequent) = consequent {
+                    Self::write_record(markdown, consequent, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::MatchingRoll {
+                matched_target: None,
+                consequent: Some(consequent),
+            } => {
+                line("record.roll_matched_no_target", &[]);
+                Self::write_record(markdown, consequent, depth + 1, None, style, locale);
+            }
+            StatementRecord::MatchingRoll {
+                matched_target: None,
+                consequent: None,
+            } => line("record.roll_matched_nothing", &[]),
+            StatementRecord::Save {
+                statblock,
+                save,
+                total,
+                target,
+                degree,
+                consequent,
+            } => {
+                let degree = locale.get(&format!("degree.{}", degree.key()), &[]);
+                line(
+                    "record.save",
+                    &[
+                        ("statblock", statblock),
+                        ("save", save),
+                        ("total", &total.to_string()),
+                        ("target", &target.to_string()),
+                        ("degree", &degree),
+                    ],
+                );
+                if let Some(consequent) = consequent {
+                    Self::write_record(markdown, consequent, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::Move {
+                name,
+                total,
+                consequent,
+                ..
+            } => {
+                line(
+                    "record.move",
+                    &[("move", name), ("total", &total.to_string())],
+                );
+                if let Some(consequent) = consequent {
+                    Self::write_record(markdown, consequent, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::Check {
+                total,
+                dc,
+                degree,
+                consequent,
+            } => {
+                let degree = locale.get(&format!("degree.{}", degree.key()), &[]);
+                line(
+                    "record.check",
+                    &[
+                        ("total", &total.to_string()),
+                        ("dc", &dc.to_string()),
+                        ("degree", &degree),
+                    ],
+                );
+                if let Some(consequent) = consequent {
+                    Self::write_record(markdown, consequent, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::MatchingFact {
+                fact,
+                values,
+                consequent,
+                ..
+            } => {
+                let values = match values.as_slice() {
+                    [] => locale.get("record.fact_not_set", &[]),
+                    values => values.join("; "),
+                };
+                line(
+                    "record.matching_fact",
+                    &[("fact", fact), ("values", &values)],
+                );
+                if let Some(consequent) = consequent {
+                    Self::write_record(markdown, consequent, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::MatchingTableRoll {
+                table,
+                entry,
+                pinned,
+                consequent,
+                ..
+            } => {
+                line(
+                    if *pinned {
+                        "record.table_roll_pinned"
+                    } else {
+                        "record.table_roll"
+                    },
+                    &[("table", table_title(table)), ("entry", entry)],
+                );
+                if let Some(consequent) = consequent {
+                    Self::write_record(markdown, consequent, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::NontargetedRoll(total) => {
+                line("record.roll", &[("total", &style.total(total))]);
+            }
+            StatementRecord::Oracle {
+                question,
+                likelihood,
+                rolled,
+                answer,
+            } => line(
+                "record.oracle",
+                &[
+                    ("question", question),
+                    ("likelihood", &likelihood.to_string()),
+                    ("rolled", &style.total(rolled)),
+                    ("answer", &locale.yes_no(*answer)),
+                ],
+            ),
+            StatementRecord::Travel {
+                terrain,
+                lost,
+                encounter,
+                weather,
+                discovery,
+            } => {
+                let mut events = Vec::new();
+                if *lost {
+                    events.push(locale.get("travel.lost", &[]));
+                }
+                if let Some(encounter) = encounter {
+                    events.push(locale.get("travel.encounter", &[("encounter", encounter)]));
+                }
+                if let Some(weather) = weather {
+                    events.push(locale.get("travel.weather", &[("weather", weather)]));
+                }
+                if let Some(discovery) = discovery {
+                    events.push(locale.get("travel.discovery", &[("discovery", discovery)]));
+                }
+                if events.is_empty() {
+                    events.push(locale.get("travel.uneventful", &[]));
+                }
+                line(
+                    "record.travel",
+                    &[("terrain", terrain), ("events", &events.join(", "))],
+                );
+            }
+            StatementRecord::ProcedureCall {
+                identifier,
+                records,
+            } => {
+                markdown.push_str(&format!("{prefix}{}\n", style.strong(identifier)));
+                for record in records {
+                    Self::write_record(markdown, record, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::Downtime {
+                weeks,
+                date,
+                records,
+                reports,
+            } => {
+                line(
+                    "record.downtime",
+                    &[("weeks", &weeks.to_string()), ("date", date)],
+                );
+                for report in reports {
+                    let outcomes = if report.outcomes.is_empty() {
+                        locale.get("record.downtime_no_outcomes", &[])
+                    } else {
+                        report.outcomes.join("; ")
+                    };
+                    line(
+                        "record.downtime_report",
+                        &[
+                            ("character", &report.character),
+                            ("spent", &report.spent),
+                            ("outcomes", &outcomes),
+                        ],
+                    );
+                }
+                for record in records {
+                    Self::write_record(markdown, record, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::WithFacts(records) => {
+                line("record.with_facts", &[]);
+                for record in records {
+                    Self::write_record(markdown, record, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::Include { path, records } => {
+                line("record.include", &[("path", path)]);
+                for record in records {
+                    Self::write_record(markdown, record, depth + 1, None, style, locale);
+                }
+            }
+            StatementRecord::HostCall { identifier, value } => line(
+                "record.host_call",
+                &[("function", identifier), ("value", value)],
+            ),
+            StatementRecord::ProcedureDefinition(identifier) => {
+                line("record.procedure_definition", &[("procedure", identifier)]);
+            }
+            StatementRecord::RuleDefinition(fact) => {
+                line("record.rule_definition", &[("fact", fact)]);
+            }
+            StatementRecord::GeneratorDefinition(generator) => {
+                line("record.generator_definition", &[("generator", generator)]);
+            }
+            StatementRecord::RollDefinition(roll) => {
+                line("record.roll_definition", &[("roll", roll)]);
+            }
+            StatementRecord::ApplyModifier(applied) | StatementRecord::RemoveModifier(applied) => {
+                let modifier = format!("{:+}", applied.modifier);
+                let name = match (record, &applied.rolls) {
+                    (StatementRecord::ApplyModifier(_), None) => "record.apply_modifier",
+                    (StatementRecord::ApplyModifier(_), Some(_)) => "record.apply_modifier_named",
+                    (_, None) => "record.remove_modifier",
+                    (_, Some(_)) => "record.remove_modifier_named",
+                };
+                let roll = applied.rolls.as_deref().unwrap_or_default();
+                line(name, &[("modifier", &modifier), ("roll", roll)]);
+            }
+            StatementRecord::Generated { generator, value } => line(
+                "record.generated",
+                &[("generator", generator), ("value", value)],
+            ),
+            StatementRecord::Reminder(_) if style != Style::Markdown => {}
+            StatementRecord::Reminder(reminder) => {
+                markdown.push_str(&format!("{prefix}{}\n", style.reminder(reminder, locale)));
+            }
+            StatementRecord::FactReport { entity, facts } if facts.is_empty() => {
+                line("record.fact_report_empty", &[("entity", entity)]);
+            }
+            StatementRecord::FactReport { entity, facts } => {
+                let facts: Vec<String> = facts
+                    .iter()
+                    .map(|(attribute, value)| format!("{attribute} {value}"))
+                    .collect();
+                line(
+                    "record.fact_report",
+                    &[("entity", entity), ("facts", &facts.join("; "))],
+                );
+            }
+            StatementRecord::Return(value) => line("record.return", &[("value", value)]),
+            StatementRecord::SetFact(fact) => line("record.set_fact", &[("fact", fact)]),
+            StatementRecord::SetPersistentFact(fact) => {
+                line("record.set_persistent_fact", &[("fact", fact)]);
+            }
+            StatementRecord::SpendResource {
+                resource,
+                spent,
+                remaining,
+            } => line(
+                "record.spend_resource",
+                &[
+                    ("spent", &spent.to_string()),
+                    ("resource", resource),
+                    ("remaining", &remaining.to_string()),
+                ],
+            ),
+            StatementRecord::Damage {
+                entity,
+                damage,
+                remaining,
+            } => line(
+                "record.damage",
+                &[
+                    ("entity", entity),
+                    ("damage", &style.total(damage)),
+                    ("remaining", &remaining.to_string()),
+                ],
+            ),
+            StatementRecord::TrackHitPoints { entity, hit_points } => line(
+                "record.track_hit_points",
+                &[("entity", entity), ("hit_points", &hit_points.to_string())],
+            ),
+            StatementRecord::TrackResource {
+                resource,
+                remaining,
+            } => line(
+                "record.track_resource",
+                &[
+                    ("resource", resource),
+                    ("remaining", &remaining.to_string()),
+                ],
+            ),
+            StatementRecord::UsageDie { resource, sides } => line(
+                "record.usage_die",
+                &[("resource", resource), ("sides", &sides.to_string())],
+            ),
+            StatementRecord::UsageRoll {
+                resource,
+                rolled,
+                die: Some(die),
+            } => line(
+                "record.usage_roll",
+                &[
+                    ("rolled", &style.total(rolled)),
+                    ("resource", resource),
+                    ("die", &die.to_string()),
+                ],
+            ),
+            StatementRecord::UsageRoll {
+                resource,
+                rolled,
+                die: None,
+            } => line(
+                "record.usage_roll_used_up",
+                &[("rolled", &style.total(rolled)), ("resource", resource)],
+            ),
+            StatementRecord::TableRoll { table, entry, .. } if entry.is_empty() => {
+                line("record.table_no_result", &[("table", table_title(table))]);
+            }
+            StatementRecord::TableRoll {
+                table,
+                entry,
+                pinned: true,
+            } => line(
+                "record.table_roll_pinned",
+                &[("table", table_title(table)), ("entry", entry)],
+            ),
+            StatementRecord::TableRoll { table, entry, .. } => line(
+                "record.table_roll",
+                &[("table", table_title(table)), ("entry", entry)],
+            ),
+        }
+    }
+}
+
+/// How records are written: as a Markdown list for journals, or as indented lines for a
+/// terminal, colored or not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    Markdown,
+    Terminal { color: bool },
+}
+
+impl Style {
+    // Procedure names
+    fn strong(self, text: &str) -> String {
+        match self {
+            Style::Markdown => format!("**{text}**"),
+            Style::Terminal { color } => ansi(color, BOLD, text),
+        }
+    }
+
+    // What a roll came up as
+    fn total(self, total: impl fmt::Display) -> String {
+        match self {
+            Style::Terminal { color } => ansi(color, BOLD_CYAN, &total.to_string()),
+            Style::Markdown => total.to_string(),
+        }
+    }
+
+    // The target a roll matched
+    fn target(self, target: impl fmt::Debug) -> String {
+        match self {
+            Style::Terminal { color } => ansi(color, BOLD, &format!("{target:?}")),
+            Style::Markdown => format!("{target:?}"),
+        }
+    }
+
+    /// A reminder, set apart from the records around it.
+    pub fn reminder(self, reminder: &str, locale: &Locale) -> String {
+        match self {
+            Style::Markdown => locale.get("reminder.markdown", &[("reminder", reminder)]),
+            Style::Terminal { color } => {
+                let mark = locale.get("reminder.mark", &[]);
+                format!("{} {reminder}", ansi(color, BOLD_YELLOW, &mark))
+            }
+        }
+    }
+}
+
+pub(crate) const BOLD: &str = "1";
+pub(crate) const BOLD_CYAN: &str = "1;36";
+pub(crate) const BOLD_YELLOW: &str = "1;33";
+pub(crate) const BOLD_RED: &str = "1;31";
+
+/// `text` in the ANSI color or weight given by `code`, if `color` is on.
+pub(crate) fn ansi(color: bool, code: &str, text: &str) -> String {
+    if color {
+        format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+        text.into()
+    }
+}
+
+/// A record as it's shown in a terminal, a line per record nested in it. Reminders are left
+/// out, as they're shown when they're reached.
+pub fn render_record(record: &StatementRecord, color: bool, locale: &Locale) -> String {
+    let mut text = String::new();
+    Journal::write_record(
+        &mut text,
+        record,
+        0,
+        None,
+        Style::Terminal { color },
+        locale,
+    );
+    text
+}
+
+/// Tables are named by their file path, which reads poorly in notes - "examples/weather.csv"
+/// becomes "weather".
+pub(crate) fn table_title(table: &str) -> &str {
+    Path::new(table)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(table)
+}
+
+/// Combatants in the order they act, with their initiatives, like "fighter (17), goblin (12)".
+pub(crate) fn initiative_order(combatants: &[Combatant]) -> String {
+    combatants
+        .iter()
+        .map(|combatant| format!("{} ({})", combatant.name, combatant.initiative))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Items and how many of each there are, like "rope, torch (3)".
+pub(crate) fn inventory(items: &[(String, i32)]) -> String {
+    items
+        .iter()
+        .map(|(item, count)| match count {
+            1 => item.clone(),
+            count => format!("{item} ({count})"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// `SystemTime::now` panics in the browser, so ask JavaScript for the time there.
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+fn now() -> SystemTime {
+    SystemTime::now()
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+fn now() -> SystemTime {
+    let millis = js_sys::Date::now();
+    SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(millis as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    #[test]
+    fn to_markdown() {
+        let journal = Journal {
+            started: UNIX_EPOCH,
+            entries: vec![
+                JournalEntry {
+                    timestamp: UNIX_EPOCH,
+                    record: StatementRecord::TableRoll {
+                        table: "examples/weather.csv".into(),
+                        entry: "fog seeps up through the dirt".into(),
+                        pinned: false,
+                    },
+                },
+                JournalEntry {
+                    timestamp: UNIX_EPOCH + Duration::from_secs(90),
+                    record: StatementRecord::ProcedureCall {
+                        identifier: "day".into(),
+                        records: vec![
+                            Box::new(StatementRecord::IfThen {
+                                antecedent: true,
+                                consequent: Some(Box::new(StatementRecord::SetFact(
+                                    "party is lost".into(),
+                                ))),
+                            }),
+                            Box::new(StatementRecord::Reminder("eat rations".into())),
+                        ],
+                    },
+                },
+            ],
+            locale: Locale::default(),
+        };
+        assert_eq!(
+            journal.to_markdown(),
+            "# Session journal\n\n\
+            - `1970-01-01T00:00:00Z` Rolled on weather: fog seeps up through the dirt\n\
+            - `1970-01-01T00:01:30Z` **day**\n  \
+              - party is lost\n  \
+              - Reminder: eat rations\n"
+        );
+        assert_eq!(journal.file_name(), "1970-01-01T00-00-00Z.md");
+    }
+
+    #[test]
+    fn terminal() {
+        let record = StatementRecord::ProcedureCall {
+            identifier: "day".into(),
+            records: vec![
+                Box::new(StatementRecord::NontargetedRoll(7)),
+                Box::new(StatementRecord::Reminder("eat rations".into())),
+            ],
+        };
+        assert_eq!(
+            render_record(&record, false, &Locale::default()),
+            "day\n  Rolled 7\n"
+        );
+        assert_eq!(
+            render_record(&record, true, &Locale::default()),
+            "\x1b[1mday\x1b[0m\n  Rolled \x1b[1;36m7\x1b[0m\n"
+        );
+        assert_eq!(
+            Style::Terminal { color: false }.reminder("eat rations", &Locale::default()),
+            "! eat rations"
+        );
+
+        let locale = Locale::new()
+            .with_strings("record.roll: \"Tirada: {total}\"\nreminder.mark: \"¡\"\n")
+            .unwrap();
+        assert_eq!(render_record(&record, false, &locale), "day\n  Tirada: 7\n");
+        assert_eq!(
+            Style::Terminal { color: false }.reminder("eat rations", &locale),
+            "¡ eat rations"
+        );
+    }
+}
@@ -1,8 +1,44 @@
-use crate::interpreter::{Interpreter, StatementRecord};
-use crate::parser::Parser;
-use crate::scanner::Scanner;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
 
-pub struct Crawl;
+use crate::calendar::Calendar;
+use crate::checker::Checker;
+use crate::debugger::Debugger;
+use crate::dice::{DiceLimits, DiceRollResult};
+use crate::encounters::Encounters;
+use crate::error::CrawlError;
+use crate::facts::Fact;
+use crate::initiative::Initiative;
+use crate::interpreter::{Interpreter, StateSnapshot, StatementRecord};
+use crate::journal::Journal;
+use crate::lights::Lights;
+use crate::locale::Locale;
+use crate::market::Market;
+use crate::money::Denominations;
+use crate::oracle::Oracle;
+use crate::output::{OutputSink, StdoutSink};
+use crate::parser::{Parser, SourceMap, Statement};
+use crate::prompt::{Prompt, StdinPrompt};
+use crate::scanner::{Scanner, Token};
+#[cfg(feature = "async")]
+use crate::storage::AsyncStorage;
+use crate::storage::Storage;
+use crate::tables::Table;
+use crate::transcript::Transcript;
+use crate::travel::Travel;
+
+pub struct Crawl {
+    interpreter: Interpreter,
+    trace: bool,
+    debug: bool,
+    journal: Journal,
+    journal_path: Option<PathBuf>,
+    transcript: Transcript,
+    transcript_path: Option<PathBuf>,
+    // The last source executed that rolled any dice, for `reroll`
+    last_roll_source: Option<String>,
+}
 
 impl Default for Crawl {
     fn default() -> Self {
@@ -12,35 +48,785 @@ impl Default for Crawl {
 
 impl Crawl {
     pub fn new() -> Self {
-        Crawl
+        let seed = rand::random();
+        Crawl {
+            interpreter: Interpreter::new()
+                .with_seed(seed)
+                .with_roll_log(true)
+                .with_output(Box::new(StdoutSink::new()))
+                .with_prompt(Box::new(StdinPrompt)),
+            trace: false,
+            debug: false,
+            journal: Journal::new(),
+            journal_path: None,
+            transcript: Transcript::new(seed),
+            transcript_path: None,
+            last_roll_source: None,
+        }
     }
 
-    pub fn execute(&self, source: &str) {
-        let toks = Scanner::new(source.chars().collect())
-            .tokens()
-            .into_iter()
-            .map(|tok| tok.unwrap())
-            .collect();
+    /// Seed the session's dice, so a session replays identically given the same seed and
+    /// sources.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.interpreter = self.interpreter.with_seed(seed);
+        self.transcript = Transcript::new(seed);
+        self
+    }
+
+    /// Write the journal and warnings in `locale`'s strings. Output sinks are given their own,
+    /// like `StdoutSink::with_locale`.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.interpreter = self.interpreter.with_locale(locale.clone());
+        self.journal = self.journal.with_locale(locale);
+        self
+    }
+
+    /// Besides tracing evaluation, dump each execution's tokens, syntax tree, and local facts
+    /// to stderr.
+    pub fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self.interpreter = self.interpreter.with_trace(trace);
+        self
+    }
+
+    /// Send records, reminders, warnings, and errors somewhere other than stdout.
+    pub fn with_output(mut self, output: Box<dyn OutputSink>) -> Self {
+        self.interpreter = self.interpreter.with_output(output);
+        self
+    }
+
+    /// Load tables from `storage` rather than the filesystem, and keep persistent facts there
+    /// between sessions: they're loaded now and saved after every execution.
+    pub fn with_storage(mut self, storage: Box<dyn Storage>) -> Self {
+        self.interpreter.set_storage(Some(storage));
+        if let Err(error) = self.interpreter.load_persistent_facts() {
+            self.write_error(&error);
+        }
+        self
+    }
+
+    /// Keep persistent facts and session journals in `campaign`'s own directory in storage,
+    /// apart from other campaigns'. Set storage first, with `with_storage`.
+    pub fn with_campaign(mut self, campaign: &str) -> Self {
+        if let Err(error) = self.interpreter.switch_campaign(campaign) {
+            self.write_error(&error);
+        }
+        self
+    }
+
+    pub fn campaign(&self) -> Option<&str> {
+        self.interpreter.campaign()
+    }
+
+    /// Answer `choose` statements some other way than asking on stdin.
+    pub fn with_prompt(mut self, prompt: Box<dyn Prompt>) -> Self {
+        self.interpreter = self.interpreter.with_prompt(prompt);
+        self
+    }
+
+    /// Answer `oracle` questions with `oracle`'s odds.
+    pub fn with_oracle(mut self, oracle: Oracle) -> Self {
+        self.interpreter = self.interpreter.with_oracle(oracle);
+        self
+    }
+
+    /// Count money in `denominations`' coins rather than gold, silver, and copper pieces.
+    pub fn with_denominations(mut self, denominations: Denominations) -> Self {
+        self.interpreter = self.interpreter.with_denominations(denominations);
+        self
+    }
+
+    /// Check for getting lost, encounters, and discoveries on travel turns at `travel`'s chances.
+    pub fn with_travel(mut self, travel: Travel) -> Self {
+        self.interpreter = self.interpreter.with_travel(travel);
+        self
+    }
+
+    /// Roll encounters' reactions and distances by `encounters` rather than B/X's.
+    pub fn with_encounters(mut self, encounters: Encounters) -> Self {
+        self.interpreter = self.interpreter.with_encounters(encounters);
+        self
+    }
+
+    /// Change prices by settlement size and haggling by `market`, rather than the defaults.
+    pub fn with_market(mut self, market: Market) -> Self {
+        self.interpreter = self.interpreter.with_market(market);
+        self
+    }
+
+    /// Share XP and treasure among `party_size` characters, rather than 4.
+    pub fn with_party_size(mut self, party_size: i32) -> Self {
+        self.interpreter = self.interpreter.with_party_size(party_size);
+        self
+    }
+
+    /// Let the lights `lights` has be lit, for as many turns as it says, rather than torches
+    /// and lanterns.
+    pub fn with_lights(mut self, lights: Lights) -> Self {
+        self.interpreter = self.interpreter.with_lights(lights);
+        self
+    }
+
+    /// Read dates off `calendar` rather than the Gregorian months and weekdays.
+    pub fn with_calendar(mut self, calendar: Calendar) -> Self {
+        self.interpreter = self.interpreter.with_calendar(calendar);
+        self
+    }
+
+    /// Refuse rolls with more dice, or dice with more sides, than `dice_limits` allows.
+    pub fn with_dice_limits(mut self, dice_limits: DiceLimits) -> Self {
+        self.interpreter = self.interpreter.with_dice_limits(dice_limits);
+        self
+    }
+
+    /// Ask on stdin what each roll came up as, for groups rolling physical dice.
+    pub fn with_manual_rolls(mut self, manual_rolls: bool) -> Self {
+        if manual_rolls {
+            self.interpreter = self.interpreter.with_manual_rolls(Box::new(StdinPrompt));
+        }
+        self
+    }
+
+    /// Run under the interactive debugger, driven from stdin.
+    pub fn with_debugger(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Export the session journal as Markdown to `path` after every execution.
+    pub fn with_journal_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.journal_path = Some(path.into());
+        self
+    }
+
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    /// Write the session transcript as JSON lines to `path` after every execution.
+    pub fn with_transcript_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.transcript_path = Some(path.into());
+        self
+    }
+
+    pub fn transcript(&self) -> &Transcript {
+        &self.transcript
+    }
+
+    /// Make a native function callable from scripts (see `Interpreter::register_function`).
+    pub fn register_function(
+        &mut self,
+        identifier: impl Into<String>,
+        function: impl FnMut(&[String]) -> Result<String, CrawlError> + Send + 'static,
+    ) {
+        self.interpreter.register_function(identifier, function);
+    }
+
+    pub fn on_fact_set(&mut self, listener: impl FnMut(&Fact) + Send + 'static) {
+        self.interpreter.on_fact_set(listener);
+    }
+
+    pub fn on_table_roll(&mut self, listener: impl FnMut(&str, &str) + Send + 'static) {
+        self.interpreter.on_table_roll(listener);
+    }
+
+    pub fn on_procedure_call(&mut self, listener: impl FnMut(&str) + Send + 'static) {
+        self.interpreter.on_procedure_call(listener);
+    }
 
-        println!("{toks:?}\n");
+    /// Add a table built in code, which scripts can roll on as `name` without loading it.
+    pub fn with_table(mut self, name: impl Into<String>, table: Table) -> Self {
+        self.interpreter.add_table(name, table);
+        self
+    }
+
+    /// The names of the tables the session has loaded.
+    pub fn table_names(&self) -> impl Iterator<Item = &str> {
+        self.interpreter.table_names()
+    }
+
+    /// Load a table again into the running session, keeping everything else as it is.
+    pub fn reload_table(&mut self, table_name: &str) -> Result<(), CrawlError> {
+        self.interpreter.reload_table(table_name)
+    }
 
-        let ast = Parser::new(toks)
+    pub fn snapshot(&self) -> StateSnapshot {
+        self.interpreter.snapshot()
+    }
+
+    /// Pick up a saved session's facts, tables, and procedures. Anything defined since is lost.
+    pub fn restore(&mut self, snapshot: StateSnapshot) {
+        self.interpreter.restore(snapshot);
+    }
+
+    /// The latest rolls made, oldest first.
+    pub fn roll_history(&self) -> impl Iterator<Item = &DiceRollResult> {
+        self.interpreter.roll_history()
+    }
+
+    /// The combatants in a fight, in the order they act, and whose turn it is.
+    pub fn initiative(&self) -> &Initiative {
+        self.interpreter.initiative()
+    }
+
+    /// Execute the last source that rolled any dice again. Returns whether there was one.
+    pub fn reroll(&mut self) -> bool {
+        match self.last_roll_source.clone() {
+            Some(source) => {
+                self.execute(&source);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-execute every source in `transcript` with its seed, checking each roll comes up the
+    /// same way it did originally.
+    pub fn replay(transcript: &Transcript) -> Result<Crawl, CrawlError> {
+        let seed = transcript
+            .seed()
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: "transcript has no session seed".into(),
+            })?;
+
+        let mut crawl = Crawl::new().with_seed(seed);
+        for source in transcript.sources() {
+            crawl.execute(source);
+        }
+
+        let original: Vec<_> = transcript.rolls().collect();
+        let replayed: Vec<_> = crawl.transcript.rolls().collect();
+        for (idx, (original, replayed)) in original.iter().zip(&replayed).enumerate() {
+            if original != replayed {
+                return Err(CrawlError::InterpreterError {
+                    reason: format!(
+                        "roll {} differs: transcript has {original:?}, replay has {replayed:?}",
+                        idx + 1
+                    ),
+                });
+            }
+        }
+        if original.len() != replayed.len() {
+            return Err(CrawlError::InterpreterError {
+                reason: format!(
+                    "transcript has {} rolls, replay has {}",
+                    original.len(),
+                    replayed.len()
+                ),
+            });
+        }
+
+        Ok(crawl)
+    }
+
+    /// Execute the script at `path`, resolving its includes relative to it.
+    pub fn execute_file(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        self.interpreter.set_source_path(Some(path.into()));
+        self.execute(&source);
+        self.interpreter.set_source_path(None);
+        Ok(())
+    }
+
+    /// What the source would do if it were executed, one line per step, without rolling or
+    /// changing the session.
+    pub fn dry_run(&self, source: &str) -> Result<Vec<String>, CrawlError> {
+        let ast: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
             .parse()
             .into_iter()
-            .map(|node| node.unwrap())
-            .collect();
+            .collect::<Result<_, _>>()?;
+        Ok(self.interpreter.dry_run(&ast))
+    }
+
+    pub fn execute(&mut self, source: &str) {
+        if let Some((ast, source_map)) = self.parse(source) {
+            self.run(source, ast, source_map);
+        }
+    }
+
+    /// Like `execute`, but the tables the source loads are read from `storage` up front,
+    /// awaiting it rather than blocking. Anything else stored, like persistent facts, is still
+    /// read and written through `with_storage`'s storage.
+    #[cfg(feature = "async")]
+    pub async fn execute_async(&mut self, source: &str, storage: &mut impl AsyncStorage) {
+        let Some((ast, source_map)) = self.parse(source) else {
+            return;
+        };
+        for table in self.interpreter.tables_loaded_by(&ast) {
+            match storage.read(&table).await {
+                Ok(csv) => self.interpreter.prefetch_table(table, csv),
+                Err(error) => {
+                    self.interpreter.clear_prefetched_tables();
+                    return self.write_error(&error);
+                }
+            }
+        }
+        self.run(source, ast, source_map);
+        self.interpreter.clear_prefetched_tables();
+    }
+
+    // Scan, parse, and check `source`, writing any errors or warnings to the output.
+    fn parse(&mut self, source: &str) -> Option<(Vec<Statement>, SourceMap)> {
+        let mut scanner = Scanner::new(source);
+        let toks: Vec<Token> = match scanner.tokens().into_iter().collect() {
+            Ok(toks) => toks,
+            Err(error) => {
+                let line = match &error {
+                    CrawlError::ScannerError { line, .. } => Some(*line),
+                    _ => None,
+                };
+                self.write_error_at(&error, source, line);
+                return None;
+            }
+        };
+        if self.trace {
+            eprintln!("{toks:?}\n");
+        }
 
-        println!("{ast:#?}\n");
+        let mut parser = Parser::new(toks)
+            .with_lines(scanner.lines().to_vec())
+            .with_spans(scanner.spans().to_vec());
+        let mut ast = Vec::new();
+        for statement in parser.by_ref() {
+            match statement {
+                Ok(statement) => ast.push(statement),
+                // What comes after an error can't be trusted to parse. Point at the statement
+                // it's in.
+                Err(error) => {
+                    let line = parser.statement_line();
+                    self.write_error_at(&error, source, line);
+                    return None;
+                }
+            }
+        }
+        if self.trace {
+            eprintln!("{ast:#?}\n");
+        }
 
-        let mut interpreter = Interpreter::new();
-        let records: Vec<StatementRecord> = interpreter
+        let diagnostics = Checker::new()
+            .with_host_functions(self.interpreter.function_names().map(String::from))
+            .with_loaded_tables(self.interpreter.table_names().map(String::from))
+            .check(&ast);
+        if let Some(output) = self.interpreter.output() {
+            for warning in parser.warnings() {
+                output.write_warning(warning);
+            }
+            for deprecation in parser.deprecations() {
+                output.write_deprecation(deprecation);
+            }
+            for diagnostic in diagnostics {
+                output.write_warning(&diagnostic.to_string());
+            }
+        }
+        Some((ast, parser.source_map().clone()))
+    }
+
+    fn run(&mut self, source: &str, ast: Vec<Statement>, source_map: SourceMap) {
+        let statement_lines = source_map
+            .top_level
+            .iter()
+            .map(|&line| (line, source_line(source, line).unwrap_or_default().into()))
+            .collect();
+        self.interpreter.set_statement_lines(statement_lines);
+        if self.debug {
+            // Not locked, as a locked stdin can't be handed between threads.
+            let debugger = Debugger::new(BufReader::new(io::stdin()), io::stdout(), source_map);
+            self.interpreter.set_step_hook(Some(Box::new(debugger)));
+        }
+        let previous_rolls = self.interpreter.rolls().len();
+        // Errors have already gone to the output as they happened.
+        let records: Vec<StatementRecord> = self
+            .interpreter
             .interpret(ast)
             .into_iter()
-            .map(|record| record.unwrap())
+            .filter_map(Result::ok)
             .collect();
+        self.interpreter.set_step_hook(None);
+        self.interpreter.set_statement_lines(Vec::new());
+
+        if self.trace {
+            eprintln!("{:#?}", self.interpreter.local_facts);
+        }
+
+        if let Err(error) = self.interpreter.save_persistent_facts() {
+            self.write_error(&error);
+        }
+
+        for record in records {
+            self.journal.record(record);
+        }
+        let journal_written = self.journal_path.as_ref().map(|path| {
+            fs::write(path, self.journal.to_markdown()).map_err(|error| {
+                CrawlError::InterpreterError {
+                    reason: format!("failed to write journal {} ({error})", path.display()),
+                }
+            })
+        });
+        if let Some(Err(error)) = journal_written {
+            self.write_error(&error);
+        }
+        if self.interpreter.campaign().is_some() {
+            let key = format!("journals/{}", self.journal.file_name());
+            if let Err(error) = self
+                .interpreter
+                .write_to_campaign(&key, &self.journal.to_markdown())
+            {
+                self.write_error(&error);
+            }
+        }
+
+        if self.interpreter.rolls().len() > previous_rolls {
+            self.last_roll_source = Some(source.into());
+        }
+        self.transcript
+            .record_execution(source, &self.interpreter.rolls()[previous_rolls..]);
+        let transcript_written = self.transcript_path.as_ref().map(|path| {
+            let file = File::create(path).map_err(|error| CrawlError::InterpreterError {
+                reason: format!("failed to create transcript {} ({error})", path.display()),
+            })?;
+            self.transcript.write_jsonl(file)
+        });
+        if let Some(Err(error)) = transcript_written {
+            self.write_error(&error);
+        }
+    }
+
+    fn write_error(&mut self, error: &CrawlError) {
+        if let Some(output) = self.interpreter.output() {
+            output.write_error(error);
+        }
+    }
+
+    fn write_error_at(&mut self, error: &CrawlError, source: &str, line: Option<usize>) {
+        let Some(output) = self.interpreter.output() else {
+            return;
+        };
+        match line.and_then(|line| Some((line, source_line(source, line)?))) {
+            Some((line, text)) => output.write_error_at(error, line, text),
+            None => output.write_error(error),
+        }
+    }
+}
+
+// Lines are counted from one.
+fn source_line(source: &str, line: usize) -> Option<&str> {
+    source.lines().nth(line.checked_sub(1)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use crate::facts::FactDatabase;
+    use crate::output::{BufferSink, Output};
+    use crate::storage::MemoryStorage;
+    use crate::tables::TableBuilder;
+
+    const SOURCE: &str =
+        "load table \"examples/table.csv\"\nroll on table \"examples/table.csv\"\n";
+
+    #[test]
+    fn replay() {
+        let mut crawl = Crawl::new().with_seed(3);
+        crawl.execute(SOURCE);
+        crawl.execute(SOURCE);
+
+        let replayed = Crawl::replay(crawl.transcript()).unwrap();
+        assert_eq!(replayed.transcript(), crawl.transcript());
+    }
+
+    #[test]
+    fn reroll() {
+        let mut crawl = Crawl::new().with_output(Box::new(BufferSink::new()));
+        assert!(!crawl.reroll());
+        crawl.execute("let x = roll 1d6\n");
+        crawl.execute("set-fact \"party is lost\"\n");
+        assert!(crawl.reroll());
+        assert_eq!(crawl.roll_history().count(), 2);
+        assert_eq!(crawl.transcript().rolls().count(), 2);
+    }
+
+    #[test]
+    fn state_persists_across_executions() {
+        let mut crawl = Crawl::new();
+        crawl.execute("procedure proc\n\treminder \"eat rations\"\nend\n");
+        crawl.execute("set-fact \"party is lost\"\nproc\n");
+        assert_eq!(crawl.snapshot().local_facts.len(), 1);
+        assert!(crawl.snapshot().procedures.contains_key("proc"));
+
+        let mut restored = Crawl::new();
+        restored.restore(crawl.snapshot());
+        assert_eq!(restored.snapshot(), crawl.snapshot());
+    }
+
+    // The lines errors are pointed at.
+    #[derive(Clone, Default)]
+    struct ErrorLines(Arc<Mutex<Vec<(usize, String)>>>);
+
+    impl OutputSink for ErrorLines {
+        fn write_record(&mut self, _: &StatementRecord) {}
+        fn write_reminder(&mut self, _: &str) {}
+        fn write_warning(&mut self, _: &str) {}
+        fn write_error(&mut self, _: &CrawlError) {}
+
+        fn write_error_at(&mut self, _: &CrawlError, line: usize, source: &str) {
+            self.0.lock().unwrap().push((line, source.into()));
+        }
+    }
+
+    #[test]
+    fn errors_point_at_lines() {
+        let lines = ErrorLines::default();
+        let mut crawl = Crawl::new().with_output(Box::new(lines.clone()));
+        crawl.execute("reminder \"rest\"\nmissing\n");
+        crawl.execute("reminder \"rest\"\nreminder ^\n");
+        assert_eq!(
+            *lines.0.lock().unwrap(),
+            vec![(2, "missing".into()), (2, "reminder ^".into())]
+        );
+    }
+
+    #[test]
+    fn malformed_scripts() {
+        // Each of these used to panic.
+        let scripts = [
+            "load",
+            "roll 1d6 ",
+            "procedure éd6",
+            "½",
+            "roll 1d6 + 2147483647 + 1\n\t1 => reminder \"hit\"\nend\n",
+            "roll on table \"unloaded\"\n",
+            "set-fact \"lost\"\n",
+            "if fact? \"lost\" => reminder \"forage\"\n",
+        ];
+        for script in scripts {
+            let buffer = BufferSink::new();
+            let mut crawl = Crawl::new().with_output(Box::new(buffer.clone()));
+            crawl.execute(script);
+            assert!(
+                matches!(buffer.take().last(), Some(Output::Error(_))),
+                "{script:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        fn assert_thread_safe<T: Send + Sync>() {}
+        assert_thread_safe::<Table>();
+        assert_thread_safe::<FactDatabase>();
+
+        let crawl = Arc::new(Mutex::new(
+            Crawl::new().with_output(Box::new(BufferSink::new())),
+        ));
+        let handles: Vec<_> = (0..4)
+            .map(|idx| {
+                let crawl = Arc::clone(&crawl);
+                thread::spawn(move || {
+                    let source = format!("set-fact \"torch {idx} is lit\"\n");
+                    crawl.lock().unwrap().execute(&source);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(crawl.lock().unwrap().snapshot().local_facts.len(), 4);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn execute_async() {
+        use std::collections::HashMap;
+        use std::pin::pin;
+        use std::task::{Context, Poll, Waker};
+
+        #[derive(Default)]
+        struct Remote(HashMap<String, String>, Vec<String>);
+
+        impl AsyncStorage for Remote {
+            async fn read(&mut self, key: &str) -> Result<Option<String>, CrawlError> {
+                self.1.push(key.into());
+                Ok(self.0.get(key).cloned())
+            }
+        }
+
+        fn block_on<F: Future>(future: F) -> F::Output {
+            let mut future = pin!(future);
+            let mut cx = Context::from_waker(Waker::noop());
+            loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+            }
+        }
+
+        let mut remote = Remote::default();
+        remote.0.insert(
+            "https://example.com/weather.csv".into(),
+            "1-6,rain\n".into(),
+        );
+        let buffer = BufferSink::new();
+        let mut crawl = Crawl::new().with_output(Box::new(buffer.clone()));
+        block_on(crawl.execute_async(
+            "procedure forecast\n\tload table \"https://example.com/weather.csv\"\nend\nforecast\nroll on table \"https://example.com/weather.csv\"\nload table \"missing.csv\"\n",
+            &mut remote,
+        ));
+        assert_eq!(remote.1, ["https://example.com/weather.csv", "missing.csv"]);
+        let output = buffer.take();
+        assert!(output.contains(&Output::Record(StatementRecord::TableRoll {
+            table: "https://example.com/weather.csv".into(),
+            entry: "rain".into(),
+            pinned: false,
+        })));
+        assert!(matches!(output.last(), Some(Output::Error(_))));
+
+        // Prefetched tables don't outlast the script.
+        crawl.execute("load table \"https://example.com/weather.csv\"\n");
+        assert!(matches!(buffer.take().last(), Some(Output::Error(_))));
+    }
+
+    #[test]
+    fn output_sink() {
+        let buffer = BufferSink::new();
+        let mut crawl = Crawl::new().with_output(Box::new(buffer.clone()));
+        crawl.execute("reminder \"eat rations\"\n= oops\n");
+        crawl.execute("procedure proc\n\treminder \"eat rations\"\nend\nreminder \"sleep\"\n");
+
+        let output = buffer.take();
+        assert!(matches!(output[0], Output::Error(_)));
+        assert_eq!(
+            output[1..],
+            [
+                Output::Warning("procedure \"proc\" is defined but never called".into()),
+                Output::Record(StatementRecord::ProcedureDefinition("proc".into())),
+                Output::Reminder("sleep".into()),
+                Output::Record(StatementRecord::Reminder("sleep".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn unwritable_journal() {
+        let buffer = BufferSink::new();
+        let path = std::env::temp_dir().join("crawl-missing-directory/journal.md");
+        let mut crawl = Crawl::new()
+            .with_output(Box::new(buffer.clone()))
+            .with_journal_file(path);
+        crawl.execute("reminder \"sleep\"\n");
+        assert!(matches!(buffer.take().last(), Some(Output::Error(_))));
+    }
+
+    #[test]
+    fn unwritable_transcript() {
+        let buffer = BufferSink::new();
+        let path = std::env::temp_dir().join("crawl-missing-directory/transcript.jsonl");
+        let mut crawl = Crawl::new()
+            .with_output(Box::new(buffer.clone()))
+            .with_transcript_file(path);
+        crawl.execute("reminder \"sleep\"\n");
+        assert!(matches!(buffer.take().last(), Some(Output::Error(_))));
+    }
+
+    #[test]
+    fn with_table() {
+        let table = TableBuilder::new().weighted("clear", 1).build().unwrap();
+        let buffer = BufferSink::new();
+        let mut crawl = Crawl::new()
+            .with_output(Box::new(buffer.clone()))
+            .with_table("weather", table);
+        crawl.execute("roll on table \"weather\"\n");
+        assert_eq!(
+            buffer.take(),
+            vec![Output::Record(StatementRecord::TableRoll {
+                table: "weather".into(),
+                entry: "clear".into(),
+                pinned: false,
+            })]
+        );
+    }
+
+    #[test]
+    fn dry_run() {
+        let buffer = BufferSink::new();
+        let mut crawl = Crawl::new().with_output(Box::new(buffer.clone()));
+        crawl.execute("procedure rest\n\tset-fact \"party is rested\"\nend\n");
+        buffer.take();
+        assert_eq!(
+            crawl.dry_run("rest\n").unwrap(),
+            vec!["call rest", "  set fact \"party is rested\""]
+        );
+        crawl.execute("if fact? \"party is rested\" => reminder \"already rested\"\n");
+        assert_eq!(
+            buffer.take(),
+            vec![Output::Record(StatementRecord::IfThen {
+                antecedent: false,
+                consequent: None,
+            })]
+        );
+    }
+
+    #[test]
+    fn storage() {
+        let mut storage = MemoryStorage::new();
+        storage.write("weather", "1,clear\n2,rain\n").unwrap();
+
+        let mut crawl = Crawl::new()
+            .with_output(Box::new(BufferSink::new()))
+            .with_storage(Box::new(storage.clone()));
+        crawl.execute("load table \"weather\"\nset-persistent-fact \"party is lost\"\n");
+        assert!(crawl.snapshot().tables.contains_key("weather"));
+
+        let crawl = Crawl::new().with_storage(Box::new(storage));
+        assert!(
+            crawl
+                .snapshot()
+                .persistent_facts
+                .check(&"party is lost".to_string().try_into().unwrap())
+        );
+    }
+
+    #[test]
+    fn campaign() {
+        let storage = MemoryStorage::new();
+        let mut crawl = Crawl::new()
+            .with_output(Box::new(BufferSink::new()))
+            .with_storage(Box::new(storage.clone()))
+            .with_campaign("greyhawk");
+        crawl.execute("set-persistent-fact \"party is lost\"\n");
+        assert_eq!(crawl.campaign(), Some("greyhawk"));
+
+        let key = format!(
+            "campaigns/greyhawk/journals/{}",
+            crawl.journal().file_name()
+        );
+        assert!(storage.clone().read(&key).unwrap().is_some());
+
+        let crawl = Crawl::new()
+            .with_storage(Box::new(storage.clone()))
+            .with_campaign("west marches");
+        assert!(crawl.snapshot().persistent_facts.is_empty());
+        let crawl = Crawl::new()
+            .with_storage(Box::new(storage))
+            .with_campaign("greyhawk");
+        assert_eq!(crawl.snapshot().persistent_facts.len(), 1);
+    }
+
+    #[test]
+    fn replay_detects_tampering() {
+        let mut crawl = Crawl::new().with_seed(3);
+        crawl.execute(SOURCE);
 
-        println!("{records:#?}\n");
+        let mut jsonl = Vec::new();
+        crawl.transcript().write_jsonl(&mut jsonl).unwrap();
+        let tampered = String::from_utf8(jsonl)
+            .unwrap()
+            .replace("\"total\":", "\"total\":100");
+        let transcript = Transcript::read_jsonl(tampered.as_bytes()).unwrap();
 
-        println!("{:#?}", interpreter.local_facts);
+        assert!(Crawl::replay(&transcript).is_err());
     }
 }
@@ -1,9 +1,47 @@
+pub mod bytecode;
+pub mod calendar;
+pub mod checker;
+pub mod clocks;
+pub mod debugger;
 pub mod dice;
+pub mod downtime;
+pub mod dry_run;
+pub mod encounters;
 pub mod error;
 pub mod facts;
+pub mod fronts;
+pub mod highlight;
+pub mod initiative;
 pub mod interpreter;
+pub mod journal;
 pub mod lang;
+pub mod lights;
+pub mod locale;
+pub mod lsp;
+pub mod market;
+pub mod money;
+pub mod names;
+pub mod oracle;
+pub mod output;
 pub mod parser;
+pub mod prompt;
+pub mod recap;
 pub mod rolls;
+pub mod rooms;
+pub mod rumors;
 pub mod scanner;
+pub mod scenes;
+pub mod simulation;
+pub mod statblocks;
+pub mod stocking;
+pub mod storage;
 pub mod tables;
+pub mod tally;
+pub mod transcript;
+pub mod travel;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod visitor;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod weather;
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+// Lights burn for some dungeon turns once they're lit, and burn down as `advance time` passes
+// turns. While one of a kind is burning, the fact `torch is lit` is set, and once the last of
+// them gutters out, `torch is out` is.
+
+/// How many turns each kind of light burns for. Torches burn for 6 turns, an hour, and a
+/// lantern for 24 on a flask of oil, by default.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lights {
+    durations: HashMap<String, i32>,
+}
+
+impl Default for Lights {
+    fn default() -> Self {
+        Lights {
+            durations: HashMap::from([("torch".into(), 6), ("lantern".into(), 24)]),
+        }
+    }
+}
+
+impl Lights {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let `light` be lit, burning for `turns` turns.
+    pub fn with_light(mut self, light: &str, turns: i32) -> Self {
+        self.durations.insert(light.into(), turns.max(1));
+        self
+    }
+
+    pub fn duration(&self, light: &str) -> Option<i32> {
+        self.durations.get(light).copied()
+    }
+}
+
+/// A light that's been lit, and how many more turns it burns for.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BurningLight {
+    pub light: String,
+    pub turns_left: i32,
+}
+
+/// The fact set while a kind of light is burning.
+pub fn lit_fact(light: &str) -> String {
+    format!("{light} is lit")
+}
+
+/// The fact set once the last of a kind of light has gone out.
+pub fn out_fact(light: &str) -> String {
+    format!("{light} is out")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        let lights = Lights::new().with_light("candle", 2).with_light("torch", 0);
+        assert_eq!(lights.duration("lantern"), Some(24));
+        assert_eq!(lights.duration("candle"), Some(2));
+        assert_eq!(lights.duration("torch"), Some(1));
+        assert_eq!(lights.duration("sunrod"), None);
+        assert_eq!(lit_fact("torch"), "torch is lit");
+    }
+}
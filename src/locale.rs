@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+
+use crate::error::CrawlError;
+
+// User-facing strings, looked up by name so a group can run sessions in their own language.
+// Each has an English default, and a locale file replaces any of them with lines like
+// `record.table_roll: "Tirada en {table}: {entry}"`. Fields in braces are filled in when the
+// string is used. What scripts say themselves - facts, reminders, table entries - is left as
+// written.
+
+// Every string there is, in English, with the fields each can use.
+const ENGLISH: &[(&str, &str)] = &[
+    ("journal.title", "Session journal"),
+    ("label.warning", "warning:"),
+    ("label.error", "error:"),
+    ("reminder.mark", "!"),
+    ("reminder.markdown", "Reminder: {reminder}"),
+    ("yes", "yes"),
+    ("no", "no"),
+    ("degree.critical_failure", "critical failure"),
+    ("degree.failure", "failure"),
+    ("degree.success", "success"),
+    ("degree.critical_success", "critical success"),
+    ("scene.expected", "as expected"),
+    ("scene.altered", "altered"),
+    ("scene.interrupted", "interrupted"),
+    (
+        "record.add_combatant",
+        "{combatant} joined the fight, with initiative {initiative}",
+    ),
+    ("record.add_item", "{owner} got {item}, {count} now"),
+    ("record.add_money", "{purse} got {added}, {balance} now"),
+    ("record.advance_clock", "Clock {clock}: {filled}/{segments}"),
+    ("record.advance_turn", "Round {round}: {combatant}'s turn"),
+    (
+        "record.advance_weather",
+        "The weather turned from {from} to {to}",
+    ),
+    (
+        "record.award_xp",
+        "Awarded {xp} XP, for {session} this session and {campaign} in all",
+    ),
+    (
+        "record.award_treasure",
+        "Awarded {treasure} in treasure, for {session} this session and {campaign} in all",
+    ),
+    (
+        "record.xp_report",
+        "{session_xp} XP and {session_treasure} this session, or {xp_share} XP and {treasure_share} each for {party_size}, and {campaign_xp} XP in all",
+    ),
+    ("record.light", "Lit a {light}, burning for {turns} turns"),
+    ("record.advance_time", "{by} turns passed, {turns} in all"),
+    ("record.light_out", "The {light} guttered out"),
+    (
+        "record.morale_held",
+        "{group} held, rolling {total} against morale {morale}",
+    ),
+    (
+        "record.morale_broke",
+        "{group} broke and fled, rolling {total} against morale {morale}",
+    ),
+    (
+        "record.encounter",
+        "Encountered {creature}, {reaction}, {distance} away",
+    ),
+    (
+        "record.generate_room",
+        "Room {number}: {shape}, with {exits}, and {contents}",
+    ),
+    (
+        "record.encounter_numbered",
+        "Encountered {number} {creature}, {reaction}, {distance} away",
+    ),
+    ("record.advance_date", "{days} days passed, to {date}"),
+    ("record.current_date", "It's {date}"),
+    ("record.chaos_factor", "The chaos factor is {chaos}"),
+    ("record.adjust_chaos", "Chaos factor {from} -> {to}"),
+    (
+        "record.scene_check",
+        "Rolled {rolled} against chaos {chaos}: scene {outcome}",
+    ),
+    ("record.random_event", "Random event: {focus}, {meaning}"),
+    ("record.apply_modifier", "Applied {modifier} to all rolls"),
+    (
+        "record.apply_modifier_named",
+        "Applied {modifier} to rolls named {roll}",
+    ),
+    ("record.assert", "Checked {assertion}"),
+    ("record.campaign", "Switched to campaign {campaign}"),
+    ("record.check_fact", "Checked a fact: {result}"),
+    ("record.choice", "{question} {chosen}"),
+    ("record.clear_fact", "No longer true: {fact}"),
+    (
+        "record.clear_persistent_fact",
+        "No longer true (persistent): {fact}",
+    ),
+    (
+        "record.create_clock",
+        "Started clock {clock} (0/{segments})",
+    ),
+    (
+        "record.create_front",
+        "Started the {faction} front (0/{segments})",
+    ),
+    (
+        "record.advance_front",
+        "The {faction} front advanced by {by} ({filled}/{segments})",
+    ),
+    ("record.front_completed", "The {faction} front completed"),
+    ("record.add_rumor", "Added the rumor: {rumor}"),
+    ("record.reveal_rumor", "The rumor that {rumor} is {truth}"),
+    ("record.hear_rumors", "Heard: {rumors}"),
+    (
+        "record.hear_rumors_none",
+        "There were no new rumors to hear",
+    ),
+    ("record.rumor_report", "Rumors: {rumors}"),
+    ("record.rumor_report_empty", "No rumors heard"),
+    ("rumor.true", "true"),
+    ("rumor.false", "false"),
+    ("record.condition_not_met", "Condition not met"),
+    (
+        "record.damage",
+        "{entity} took {damage} damage, {remaining} hp left",
+    ),
+    ("record.let", "{name} = {value}"),
+    ("record.load_table", "Loaded table {table}"),
+    ("record.load_names", "Loaded names {names}"),
+    (
+        "record.load_statblocks",
+        "Loaded statblocks {statblocks} from {file}",
+    ),
+    ("record.name_roll", "Rolled a name from {names}: {name}"),
+    ("record.load_prices", "Loaded prices {prices}"),
+    ("record.downtime", "{weeks} weeks of downtime, to {date}"),
+    (
+        "record.downtime_report",
+        "{character} spent {spent}: {outcomes}",
+    ),
+    ("record.downtime_no_outcomes", "nothing came of it"),
+    ("record.price", "{item} costs {price}"),
+    (
+        "record.haggle",
+        "Haggled over {item}, rolling {total}, for {price}",
+    ),
+    ("record.roll_matched", "Roll matched {target}"),
+    ("record.check", "Rolled {total} against {dc}: {degree}"),
+    (
+        "record.save",
+        "{statblock} rolled {total} against {target} to save vs {save}: {degree}",
+    ),
+    ("record.roll_matched_no_target", "Roll matched no target"),
+    ("record.roll_matched_nothing", "Roll matched nothing"),
+    ("record.move", "{move}: rolled {total}"),
+    ("record.matching_fact", "Fact {fact}: {values}"),
+    ("record.fact_not_set", "not set"),
+    ("record.roll", "Rolled {total}"),
+    (
+        "record.oracle",
+        "{question} ({likelihood}, rolled {rolled}): {answer}",
+    ),
+    ("record.with_facts", "With temporary facts"),
+    ("record.include", "Included {path}"),
+    ("record.host_call", "{function}: {value}"),
+    (
+        "record.procedure_definition",
+        "Defined procedure {procedure}",
+    ),
+    ("record.rule_definition", "Defined a rule deriving {fact}"),
+    (
+        "record.generator_definition",
+        "Defined generator {generator}",
+    ),
+    ("record.roll_definition", "Defined roll {roll}"),
+    ("record.remove_combatant", "{combatant} left the fight"),
+    ("record.remove_item", "{owner} lost {item}, {count} left"),
+    ("record.sort_combatants", "Initiative order: {combatants}"),
+    (
+        "record.remove_modifier",
+        "Removed {modifier} from all rolls",
+    ),
+    (
+        "record.remove_modifier_named",
+        "Removed {modifier} from rolls named {roll}",
+    ),
+    ("record.generated", "{generator}: {value}"),
+    ("record.fact_report", "{entity}: {facts}"),
+    ("record.fact_report_empty", "Nothing known about {entity}"),
+    ("record.inventory_report", "{owner} has {items}"),
+    ("record.inventory_report_empty", "{owner} has nothing"),
+    ("record.return", "Returned {value}"),
+    ("record.set_fact", "{fact}"),
+    ("record.set_persistent_fact", "{fact} (persistent)"),
+    (
+        "record.spend_money",
+        "{purse} spent {spent}, {balance} left",
+    ),
+    (
+        "record.spend_resource",
+        "Spent {spent} {resource}, {remaining} left",
+    ),
+    (
+        "record.track_hit_points",
+        "Tracking hp for {entity}: {hit_points}",
+    ),
+    ("record.track_resource", "Tracking {resource}: {remaining}"),
+    ("record.usage_die", "Usage die for {resource}: d{sides}"),
+    (
+        "record.usage_roll",
+        "Rolled {rolled} for {resource} usage, now d{die}",
+    ),
+    (
+        "record.usage_roll_used_up",
+        "Rolled {rolled} for {resource} usage, used up",
+    ),
+    ("record.travel", "Travelled through {terrain}: {events}"),
+    ("record.table_roll", "Rolled on {table}: {entry}"),
+    ("record.table_no_result", "Rolled on {table}: no result"),
+    (
+        "record.table_roll_pinned",
+        "Rolled on {table}: {entry} (pinned)",
+    ),
+    (
+        "record.pin_table",
+        "Pinned {table} to {entry} for {rolls} rolls",
+    ),
+    (
+        "record.pin_table_once",
+        "Pinned {table} to {entry} for the next roll",
+    ),
+    ("travel.lost", "got lost"),
+    ("travel.encounter", "met {encounter}"),
+    ("travel.weather", "weather {weather}"),
+    ("travel.discovery", "found {discovery}"),
+    ("travel.uneventful", "nothing happened"),
+    ("warning.table_loaded_again", "table {table} loaded again"),
+    (
+        "warning.procedure_redefined",
+        "procedure {procedure} redefined",
+    ),
+    ("warning.fact_set_twice", "fact {fact} set twice"),
+    // The errors default to their own messages.
+    (
+        "error.scanner",
+        "scanner error (line: {line}, position {position}, lexeme: {lexeme}, reason: {reason})",
+    ),
+    (
+        "error.version",
+        "version error (script targets crawl {script}, but this is crawl {supported})",
+    ),
+    ("error.parser", "parser error (token: {token})"),
+    ("error.interpreter", "interpreter error (reason: {reason})"),
+];
+
+/// The strings output is written with, English unless replaced.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Locale {
+    strings: HashMap<String, String>,
+}
+
+impl Locale {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Strings from a locale file, a line each like `label.error: "erreur :"`, in place of the
+    /// ones there are. Blank lines, and lines starting with `#`, are skipped.
+    pub fn with_strings(mut self, source: &str) -> Result<Self, CrawlError> {
+        let known = |name: &str| ENGLISH.iter().any(|(key, _)| *key == name);
+        self.strings.extend(read_strings(source, known)?);
+        Ok(self)
+    }
+
+    /// The names of every string there is.
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        ENGLISH.iter().map(|(name, _)| *name)
+    }
+
+    /// The string called `name`, with `fields` filled in.
+    pub fn get(&self, name: &str, fields: &[(&str, &str)]) -> String {
+        let string = match self.strings.get(name) {
+            Some(string) => string.as_str(),
+            None => ENGLISH
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map_or(name, |(_, string)| string),
+        };
+        fill(string, fields)
+    }
+
+    pub fn yes_no(&self, value: bool) -> String {
+        self.get(if value { "yes" } else { "no" }, &[])
+    }
+
+    /// An error's message.
+    pub fn error(&self, error: &CrawlError) -> String {
+        let (name, fields) = match error {
+            CrawlError::ScannerError {
+                position,
+                line,
+                lexeme,
+                reason,
+            } => (
+                "error.scanner",
+                vec![
+                    ("line", line.to_string()),
+                    ("position", position.to_string()),
+                    ("lexeme", format!("{lexeme:?}")),
+                    ("reason", format!("{reason:?}")),
+                ],
+            ),
+            CrawlError::VersionError { script, supported } => (
+                "error.version",
+                vec![("script", script.clone()), ("supported", supported.clone())],
+            ),
+            CrawlError::ParserError { token } => {
+                ("error.parser", vec![("token", format!("{token:?}"))])
+            }
+            CrawlError::InterpreterError { reason } => {
+                ("error.interpreter", vec![("reason", format!("{reason:?}"))])
+            }
+        };
+        if !self.strings.contains_key(name) {
+            return error.to_string();
+        }
+        let fields: Vec<(&str, &str)> = fields
+            .iter()
+            .map(|(field, value)| (*field, value.as_str()))
+            .collect();
+        self.get(name, &fields)
+    }
+}
+
+/// `string` with each `{field}` in it replaced by the field's value.
+pub(crate) fn fill(string: &str, fields: &[(&str, &str)]) -> String {
+    let mut filled = string.to_string();
+    for (field, value) in fields {
+        filled = filled.replace(&format!("{{{field}}}"), value);
+    }
+    filled
+}
+
+/// Named strings from a file, a line each like `name: "text"`, erroring on any name that
+/// isn't `known`.
+pub(crate) fn read_strings(
+    source: &str,
+    known: impl Fn(&str) -> bool,
+) -> Result<Vec<(String, String)>, CrawlError> {
+    let mut strings = Vec::new();
+    for (idx, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |reason: String| CrawlError::InterpreterError {
+            reason: format!("line {}: {reason}", idx + 1),
+        };
+        let Some((name, string)) = line.split_once(':') else {
+            return Err(error(format!("expected `name: \"text\"`, not {line:?}")));
+        };
+        let name = name.trim();
+        if !known(name) {
+            return Err(error(format!("there's nothing called {name:?} to replace")));
+        }
+        let string: String = serde_json::from_str(string.trim())
+            .map_err(|_| error(format!("{name} isn't a quoted string")))?;
+        strings.push((name.into(), string));
+    }
+    Ok(strings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings() {
+        let locale = Locale::new()
+            .with_strings(
+                "# español\nrecord.table_roll: \"Tirada en {table}: {entry}\"\n\
+                error.parser: \"error de análisis ({token})\"\n",
+            )
+            .unwrap();
+        assert_eq!(
+            locale.get(
+                "record.table_roll",
+                &[("table", "clima"), ("entry", "niebla")]
+            ),
+            "Tirada en clima: niebla"
+        );
+        assert_eq!(locale.get("label.error", &[]), "error:");
+
+        let error = CrawlError::ParserError {
+            token: "Eof".into(),
+        };
+        assert_eq!(locale.error(&error), "error de análisis (\"Eof\")");
+        let error = CrawlError::InterpreterError {
+            reason: "no".into(),
+        };
+        assert_eq!(Locale::new().error(&error), error.to_string());
+
+        assert!(Locale::new().with_strings("record.bogus: \"x\"").is_err());
+    }
+
+    #[test]
+    fn english_errors() {
+        let errors = [
+            CrawlError::ScannerError {
+                position: 3,
+                line: 1,
+                lexeme: "@".into(),
+                reason: "unexpected character".into(),
+            },
+            CrawlError::VersionError {
+                script: "1.0".into(),
+                supported: "0.1".into(),
+            },
+            CrawlError::ParserError {
+                token: "Eof".into(),
+            },
+            CrawlError::InterpreterError {
+                reason: "no".into(),
+            },
+        ];
+        // Replacing a string with itself leaves the message as it was.
+        let english: String = Locale::names()
+            .filter(|name| name.starts_with("error."))
+            .map(|name| format!("{name}: {:?}\n", Locale::new().get(name, &[])))
+            .collect();
+        let locale = Locale::new().with_strings(&english).unwrap();
+        for error in errors {
+            assert_eq!(locale.error(&error), error.to_string());
+        }
+    }
+}
@@ -0,0 +1,684 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::checker::{Checker, Diagnostic};
+use crate::error::CrawlError;
+use crate::highlight::{TokenCategory, semantic_tokens};
+use crate::parser::Parser;
+use crate::scanner::{KEYWORDS, Scanner, Token};
+use crate::tables::Table;
+
+// A language server for crawl scripts, spoken over stdin and stdout by `crawl lsp`. Documents
+// are synced whole, and checked as they change: scanner and parser errors, and the checker's
+// diagnostics, are published for the editor to show. Procedure calls go to their definitions,
+// table names to their files, and hovering over a table name shows its entries. Semantic tokens
+// give editors the same highlighting as the REPL.
+
+/// Something wrong with a script, on a line counted from zero.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    pub line: usize,
+    pub message: String,
+    // Warnings don't stop the script running
+    pub error: bool,
+    // Still works, but should be written differently
+    pub deprecated: bool,
+}
+
+/// Where something is defined: a line in the same script, or a file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Definition {
+    Line(usize),
+    File(PathBuf),
+}
+
+/// Scanner and parser errors in `source`, or the checker's diagnostics if there are none.
+pub fn problems(source: &str) -> Vec<Problem> {
+    let mut scanner = Scanner::new(source);
+    let scanned = scanner.tokens();
+    let lines = scanner.lines().to_vec();
+
+    let mut problems = Vec::new();
+    let mut tokens = Vec::new();
+    for token in scanned {
+        match token {
+            Ok(token) => tokens.push(token),
+            Err(error) => problems.push(error_problem(&error, 0)),
+        }
+    }
+    if !problems.is_empty() {
+        return problems;
+    }
+
+    let mut parser = Parser::new(tokens.clone())
+        .with_lines(lines.clone())
+        .with_spans(scanner.spans().to_vec());
+    let mut statements = Vec::new();
+    for statement in parser.by_ref() {
+        match statement {
+            Ok(statement) => statements.push(statement),
+            // What comes after an error can't be trusted to parse. Point at the statement it's in.
+            Err(error) => {
+                let line = parser.statement_line();
+                return vec![error_problem(&error, line.unwrap_or_default())];
+            }
+        }
+    }
+
+    // Warnings from the parser are about the `crawl-version` line at the top.
+    let warnings = parser.warnings().iter().map(|warning| Problem {
+        line: 0,
+        message: warning.clone(),
+        error: false,
+        deprecated: false,
+    });
+    let deprecations = parser.deprecations().iter().map(|deprecation| Problem {
+        line: deprecation.line.saturating_sub(1),
+        message: deprecation.to_string(),
+        error: false,
+        deprecated: true,
+    });
+    let diagnostics = Checker::new()
+        .check(&statements)
+        .into_iter()
+        .map(|diagnostic| {
+            let name = match &diagnostic {
+                Diagnostic::UndefinedProcedure(name)
+                | Diagnostic::UnloadedTable(name)
+                | Diagnostic::UnusedProcedure(name) => name,
+            };
+            Problem {
+                line: first_line_naming(&tokens, &lines, name),
+                message: diagnostic.to_string(),
+                error: false,
+                deprecated: false,
+            }
+        });
+    warnings.chain(deprecations).chain(diagnostics).collect()
+}
+
+fn error_problem(error: &CrawlError, parser_line: usize) -> Problem {
+    let line = match error {
+        CrawlError::ScannerError { line, .. } => *line,
+        _ => parser_line,
+    };
+    Problem {
+        line: line.saturating_sub(1),
+        message: error.to_string(),
+        error: true,
+        deprecated: false,
+    }
+}
+
+// The checker doesn't know where things are, so point at the first mention of the name.
+fn first_line_naming(tokens: &[Token], lines: &[usize], name: &str) -> usize {
+    tokens
+        .iter()
+        .position(|token| matches!(token, Token::Identifier(s) | Token::Str(s) if s == name))
+        .and_then(|idx| lines.get(idx))
+        .map_or(0, |line| line.saturating_sub(1))
+}
+
+/// Where the procedure, generator, or variable named at `line` and `character` is defined, or
+/// the file of the table named there. `dir` is what table paths are relative to.
+pub fn definition(source: &str, line: usize, character: usize, dir: &Path) -> Option<Definition> {
+    match word_at(source.lines().nth(line)?, character)? {
+        Word::Identifier(name) => {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.tokens();
+            let defined = tokens.windows(2).position(|pair| match pair {
+                [
+                    Ok(Token::Procedure | Token::Generator | Token::Let),
+                    Ok(Token::Identifier(defined)),
+                ] => *defined == name,
+                _ => false,
+            })?;
+            Some(Definition::Line(scanner.lines()[defined].saturating_sub(1)))
+        }
+        Word::Str(table) => {
+            let path = dir.join(&table);
+            (table_names(source).contains(&table) && path.is_file())
+                .then_some(Definition::File(path))
+        }
+    }
+}
+
+/// Every keyword, along with the procedures, generators, variables, and tables in `source`, and
+/// what kind of thing each is.
+pub fn completions(source: &str) -> Vec<(String, CompletionKind)> {
+    let mut completions: Vec<(String, CompletionKind)> = KEYWORDS
+        .iter()
+        .map(|keyword| (keyword.to_string(), CompletionKind::Keyword))
+        .collect();
+    let tokens: Vec<Token> = Scanner::new(source).filter_map(Result::ok).collect();
+    for pair in tokens.windows(2) {
+        if let [Token::Procedure | Token::Generator, Token::Identifier(name)] = pair {
+            completions.push((name.clone(), CompletionKind::Procedure));
+        }
+        if let [Token::Let, Token::Identifier(name)] = pair {
+            completions.push((name.clone(), CompletionKind::Variable));
+        }
+    }
+    for table in table_names(source) {
+        completions.push((table, CompletionKind::Table));
+    }
+    completions.dedup();
+    completions
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompletionKind {
+    Keyword,
+    Procedure,
+    Variable,
+    Table,
+}
+
+impl CompletionKind {
+    // The number the protocol uses for each kind
+    fn number(self) -> u32 {
+        match self {
+            CompletionKind::Keyword => 14,
+            CompletionKind::Procedure => 3,
+            CompletionKind::Variable => 6,
+            CompletionKind::Table => 17,
+        }
+    }
+}
+
+/// The entries of the table named at `line` and `character`, as Markdown.
+pub fn hover(source: &str, line: usize, character: usize, dir: &Path) -> Option<String> {
+    let Word::Str(name) = word_at(source.lines().nth(line)?, character)? else {
+        return None;
+    };
+    if !table_names(source).contains(&name) {
+        return None;
+    }
+    let table = Table::load(dir.join(&name).to_str()?).ok()?;
+    Some(table_markdown(&name, &table))
+}
+
+fn table_markdown(name: &str, table: &Table) -> String {
+    let mut markdown = format!("**{name}**\n\n| roll | entry |\n| --- | --- |\n");
+    for entry in table.entries() {
+        markdown.push_str(&format!("| {} | {} |\n", entry.roll_target(), entry.value));
+    }
+    markdown
+}
+
+// The tables `source` loads.
+fn table_names(source: &str) -> Vec<String> {
+    let tokens: Vec<Token> = Scanner::new(source).filter_map(Result::ok).collect();
+    let mut tables = Vec::new();
+    let loaded = tokens.windows(3).filter_map(|triple| match triple {
+        [Token::Load, Token::Table, Token::Str(table)] => Some(table),
+        _ => None,
+    });
+    for table in loaded {
+        if !tables.contains(table) {
+            tables.push(table.clone());
+        }
+    }
+    tables
+}
+
+#[derive(Debug, PartialEq)]
+enum Word {
+    Identifier(String),
+    // The text of the string the position is inside
+    Str(String),
+}
+
+// What's at `character`, counted in UTF-16 code units as the protocol does.
+fn word_at(line: &str, character: usize) -> Option<Word> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut units = 0;
+    let at = chars
+        .iter()
+        .position(|ch| {
+            units += ch.len_utf16();
+            units > character
+        })
+        .unwrap_or(chars.len());
+
+    // Inside a string if an odd number of quotes come before.
+    let quotes: Vec<usize> = (0..chars.len()).filter(|&i| chars[i] == '"').collect();
+    let before = quotes.iter().filter(|&&i| i < at).count();
+    if before % 2 == 1 {
+        let open = quotes[before - 1];
+        let close = quotes.get(before).copied().unwrap_or(chars.len());
+        return Some(Word::Str(chars[open + 1..close].iter().collect()));
+    }
+
+    let is_word = |ch: &char| ch.is_alphanumeric() || matches!(ch, '-' | '?' | '.');
+    let start = chars[..at.min(chars.len())]
+        .iter()
+        .rposition(|ch| !is_word(ch))
+        .map_or(0, |i| i + 1);
+    let end = chars[start..]
+        .iter()
+        .position(|ch| !is_word(ch))
+        .map_or(chars.len(), |i| start + i);
+    (start < end).then(|| Word::Identifier(chars[start..end].iter().collect()))
+}
+
+// The protocol's names for each category, in the order of `token_type`. Dice have no name of their
+// own, so they're `macro`s, which editors color differently from numbers.
+const TOKEN_TYPES: [&str; 6] = [
+    "keyword", "string", "macro", "variable", "number", "operator",
+];
+
+fn token_type(category: TokenCategory) -> u32 {
+    match category {
+        TokenCategory::Keyword => 0,
+        TokenCategory::String => 1,
+        TokenCategory::RollSpec => 2,
+        TokenCategory::Identifier => 3,
+        TokenCategory::Number => 4,
+        TokenCategory::Operator => 5,
+    }
+}
+
+/// The semantic tokens of `source`, encoded as the protocol wants them: five numbers per token,
+/// for its line and start relative to the last token's, its length, its type, and no modifiers.
+/// Positions are in UTF-16 code units, and tokens spanning lines are cut at the first line's end.
+pub fn encoded_semantic_tokens(source: &str) -> Vec<u32> {
+    let mut data = Vec::new();
+    let (mut last_line, mut last_start) = (0, 0);
+    for token in semantic_tokens(source) {
+        let before = &source[..token.span.start];
+        let line = before.matches('\n').count() as u32;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let start = utf16_len(&source[line_start..token.span.start]);
+        let text = source[token.span].lines().next().unwrap_or_default();
+        let start_delta = if line == last_line {
+            start - last_start
+        } else {
+            start
+        };
+        data.extend([
+            line - last_line,
+            start_delta,
+            utf16_len(text),
+            token_type(token.category),
+            0,
+        ]);
+        (last_line, last_start) = (line, start);
+    }
+    data
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
+
+/// Serves one editor over `input` and `output` until it exits.
+pub struct LanguageServer<R, W> {
+    input: R,
+    output: W,
+    // The text of each open document, by URI
+    documents: HashMap<String, String>,
+}
+
+impl<R: BufRead, W: Write> LanguageServer<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        LanguageServer {
+            input,
+            output,
+            documents: HashMap::new(),
+        }
+    }
+
+    pub fn run(mut self) -> io::Result<()> {
+        while let Some(message) = self.read_message()? {
+            let method = message["method"].as_str().unwrap_or_default();
+            let params = &message["params"];
+            let result = match method {
+                "initialize" => Some(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "definitionProvider": true,
+                        "hoverProvider": true,
+                        "completionProvider": {},
+                        "semanticTokensProvider": {
+                            "legend": { "tokenTypes": TOKEN_TYPES, "tokenModifiers": [] },
+                            "full": true,
+                        },
+                    },
+                    "serverInfo": { "name": "crawl" },
+                })),
+                "shutdown" => Some(Value::Null),
+                "exit" => return Ok(()),
+                "textDocument/didOpen" => {
+                    let document = &params["textDocument"];
+                    let text = document["text"].as_str().unwrap_or_default();
+                    self.update(uri(params), text.into())?;
+                    None
+                }
+                "textDocument/didChange" => {
+                    // Changes are whole documents, as asked for in `initialize`.
+                    let text = params["contentChanges"]
+                        .as_array()
+                        .and_then(|changes| changes.last())
+                        .and_then(|change| change["text"].as_str())
+                        .unwrap_or_default();
+                    self.update(uri(params), text.into())?;
+                    None
+                }
+                "textDocument/didClose" => {
+                    self.documents.remove(&uri(params));
+                    self.publish(&uri(params), &[])?;
+                    None
+                }
+                "textDocument/definition" => Some(self.definition(params)),
+                "textDocument/hover" => Some(self.hover(params)),
+                "textDocument/completion" => Some(self.completion(params)),
+                "textDocument/semanticTokens/full" => {
+                    let source = self.documents.get(&uri(params)).map_or("", String::as_str);
+                    Some(json!({ "data": encoded_semantic_tokens(source) }))
+                }
+                _ => None,
+            };
+
+            let Some(id) = message.get("id") else {
+                continue;
+            };
+            let response = match result {
+                Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                None => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("unsupported method {method}") },
+                }),
+            };
+            self.write_message(&response)?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, uri: String, text: String) -> io::Result<()> {
+        let problems = problems(&text);
+        self.documents.insert(uri.clone(), text);
+        self.publish(&uri, &problems)
+    }
+
+    fn publish(&mut self, uri: &str, problems: &[Problem]) -> io::Result<()> {
+        let diagnostics: Vec<Value> = problems
+            .iter()
+            .map(|problem| {
+                let mut diagnostic = json!({
+                    "range": {
+                        "start": { "line": problem.line, "character": 0 },
+                        "end": { "line": problem.line + 1, "character": 0 },
+                    },
+                    "severity": if problem.error { 1 } else { 2 },
+                    "source": "crawl",
+                    "message": problem.message,
+                });
+                // Editors strike deprecated code through.
+                if problem.deprecated {
+                    diagnostic["tags"] = json!([2]);
+                }
+                diagnostic
+            })
+            .collect();
+        self.write_message(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let uri = uri(params);
+        let Some((source, line, character)) = self.position(params) else {
+            return Value::Null;
+        };
+        let (uri, line) = match definition(source, line, character, &directory(&uri)) {
+            Some(Definition::Line(line)) => (uri, line),
+            Some(Definition::File(path)) => (format!("file://{}", path.display()), 0),
+            None => return Value::Null,
+        };
+        let position = json!({ "line": line, "character": 0 });
+        json!({ "uri": uri, "range": { "start": position, "end": position } })
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let Some((source, line, character)) = self.position(params) else {
+            return Value::Null;
+        };
+        match hover(source, line, character, &directory(&uri(params))) {
+            Some(markdown) => json!({ "contents": { "kind": "markdown", "value": markdown } }),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let source = self.documents.get(&uri(params)).map_or("", String::as_str);
+        let items: Vec<Value> = completions(source)
+            .into_iter()
+            .map(|(label, kind)| json!({ "label": label, "kind": kind.number() }))
+            .collect();
+        json!(items)
+    }
+
+    // The document and position a request is about.
+    fn position(&self, params: &Value) -> Option<(&str, usize, usize)> {
+        let source = self.documents.get(&uri(params))?;
+        let line = params["position"]["line"].as_u64()?;
+        let character = params["position"]["character"].as_u64()?;
+        Some((source, line as usize, character as usize))
+    }
+
+    // Messages are JSON, after a `Content-Length` header and a blank line.
+    fn read_message(&mut self) -> io::Result<Option<Value>> {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if self.input.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse().ok();
+            }
+        }
+        let length: usize = length.ok_or_else(|| invalid_data("missing Content-Length"))?;
+        let mut body = vec![0; length];
+        self.input.read_exact(&mut body)?;
+        serde_json::from_slice(&body)
+            .map(Some)
+            .map_err(|error| invalid_data(&error.to_string()))
+    }
+
+    fn write_message(&mut self, message: &Value) -> io::Result<()> {
+        let body = message.to_string();
+        write!(self.output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+        self.output.flush()
+    }
+}
+
+fn uri(params: &Value) -> String {
+    params["textDocument"]["uri"]
+        .as_str()
+        .unwrap_or_default()
+        .into()
+}
+
+// Tables are loaded relative to the script's directory.
+fn directory(uri: &str) -> PathBuf {
+    let path = uri
+        .strip_prefix("file://")
+        .unwrap_or(uri)
+        .replace("%20", " ");
+    Path::new(&path)
+        .parent()
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::TableBuilder;
+
+    const SOURCE: &str =
+        "load table \"weather.csv\"\nprocedure camp\n\troll on table \"weather.csv\"\nend\ncamp\n";
+
+    #[test]
+    fn script_problems() {
+        assert!(problems(SOURCE).is_empty());
+        assert_eq!(
+            problems("reminder \"rest\"\nreminder 5\n"),
+            vec![Problem {
+                line: 1,
+                message: "parser error (token: \"Num(5)\")".into(),
+                error: true,
+                deprecated: false,
+            }]
+        );
+        assert_eq!(
+            problems("reminder \"rest\"\nforage\n"),
+            vec![Problem {
+                line: 1,
+                message: "procedure \"forage\" is called but never defined".into(),
+                error: false,
+                deprecated: false,
+            }]
+        );
+        assert_eq!(
+            problems("let x = 2\nset-fact \"party is {}\" % x\n"),
+            vec![Problem {
+                line: 1,
+                message: "line 2: interpolating with `%` is deprecated, write \"party is {x}\" \
+                    instead"
+                    .into(),
+                error: false,
+                deprecated: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn go_to_definition() {
+        assert_eq!(
+            definition(SOURCE, 4, 2, Path::new(".")),
+            Some(Definition::Line(1))
+        );
+        assert_eq!(definition(SOURCE, 2, 3, Path::new(".")), None);
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(
+            word_at("\troll on table \"weather.csv\"", 18),
+            Some(Word::Str("weather.csv".into()))
+        );
+        assert_eq!(
+            word_at("\thex.camp", 3),
+            Some(Word::Identifier("hex.camp".into()))
+        );
+        assert_eq!(word_at("   ", 1), None);
+    }
+
+    #[test]
+    fn completion() {
+        let completions = completions(SOURCE);
+        assert!(completions.contains(&("camp".into(), CompletionKind::Procedure)));
+        assert!(completions.contains(&("weather.csv".into(), CompletionKind::Table)));
+        assert!(completions.contains(&("roll".into(), CompletionKind::Keyword)));
+    }
+
+    #[test]
+    fn table_entries() {
+        let table = TableBuilder::new()
+            .entry("1-3", "rain")
+            .entry("4-6", "fog")
+            .build()
+            .unwrap();
+        assert_eq!(
+            table_markdown("weather", &table),
+            "**weather**\n\n| roll | entry |\n| --- | --- |\n| 1-3 | rain |\n| 4-6 | fog |\n"
+        );
+    }
+
+    #[test]
+    fn hover_table() {
+        let source = "load table \"weather.csv\"\n";
+        let markdown = hover(source, 0, 14, Path::new("examples")).unwrap();
+        assert!(markdown.starts_with("**weather.csv**"));
+        assert!(markdown.contains("| 1 | sunny |"));
+        assert_eq!(
+            definition(source, 0, 14, Path::new("examples")),
+            Some(Definition::File(Path::new("examples").join("weather.csv")))
+        );
+    }
+
+    #[test]
+    fn semantic_token_encoding() {
+        let source = "roll 1d6\n\t\u{e9}t\u{e9} \"rain\"\n";
+        assert_eq!(
+            encoded_semantic_tokens(source),
+            vec![0, 0, 4, 0, 0, 0, 5, 3, 2, 0, 1, 1, 3, 3, 0, 0, 4, 6, 1, 0]
+        );
+    }
+
+    #[test]
+    fn session() {
+        let messages = [
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": { "textDocument": { "uri": "file:///camp.crawl", "text": SOURCE } },
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "textDocument/definition",
+                "params": {
+                    "textDocument": { "uri": "file:///camp.crawl" },
+                    "position": { "line": 4, "character": 1 },
+                },
+            }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ];
+        let input: String = messages
+            .iter()
+            .map(|message| {
+                let body = message.to_string();
+                format!("Content-Length: {}\r\n\r\n{body}", body.len())
+            })
+            .collect();
+        let mut output = Vec::new();
+        LanguageServer::new(input.as_bytes(), &mut output)
+            .run()
+            .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let responses: Vec<Value> = output
+            .split("Content-Length: ")
+            .filter_map(|message| message.split_once("\r\n\r\n"))
+            .map(|(_, body)| serde_json::from_str(body).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(
+            responses[0]["result"]["capabilities"]["hoverProvider"],
+            true
+        );
+        assert_eq!(
+            responses[1]["params"]["diagnostics"],
+            Value::Array(Vec::new())
+        );
+        assert_eq!(responses[2]["result"]["range"]["start"]["line"], 1);
+    }
+}
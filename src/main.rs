@@ -1,28 +1,204 @@
+use crawl::dice::{self, DiceRoll};
+use crawl::interpreter::StateSnapshot;
 use crawl::lang::Crawl;
+use crawl::locale::Locale;
+use crawl::lsp::LanguageServer;
+use crawl::output::{BufferSink, Output, StdoutSink};
+use crawl::recap::Recap;
+use crawl::simulation::Simulation;
+use crawl::stocking::{CONTENTS, MONSTERS, Stocking, System, TREASURE};
+use crawl::storage::{FileStorage, campaign_key};
+use crawl::tables::Table;
+use crawl::transcript::Transcript;
 use std::{
+    collections::HashMap,
     env,
     error::Error,
     ffi::OsString,
-    fs,
-    io::{self, Write},
+    fs::{self, File},
+    io::{self, BufReader, Write},
+    path::{Path, PathBuf},
     process::exit,
+    thread,
+    time::{Duration, SystemTime},
 };
 
 fn main() -> Result<(), Box<dyn Error>> {
-    match env::args_os().nth(1) {
-        Some(filepath) => execute_file(filepath),
-        None => repl(),
+    let args: Vec<OsString> = env::args_os().skip(1).collect();
+    match args.first().and_then(|arg| arg.to_str()) {
+        Some("odds") => odds(&args[1..]),
+        Some("table-odds") => table_odds(&args[1..]),
+        Some("verify-dice") => verify_dice(&args[1..]),
+        Some("replay") => replay(&args[1..]),
+        Some("simulate") => simulate(&args[1..]),
+        Some("stock") => stock(&args[1..]),
+        Some("test") => test(&args[1..]),
+        Some("lsp") => lsp(),
+        Some("tui") => tui(&args[1..]),
+        Some("run") => run(args[1..].to_vec()),
+        _ => run(args),
     }
 }
 
-fn execute_file(filepath: OsString) -> Result<(), Box<dyn Error>> {
-    let input = fs::read_to_string(filepath)?;
-    let crawl = Crawl::new();
-    crawl.execute(&input);
+fn run(args: Vec<OsString>) -> Result<(), Box<dyn Error>> {
+    let mut trace = false;
+    let mut debug = false;
+    let mut manual_rolls = false;
+    let mut watch = false;
+    let mut dry_run = false;
+    let mut no_color = false;
+    let mut journal = None;
+    let mut campaign = None;
+    let mut transcript = None;
+    let mut templates = None;
+    let mut locale = None;
+    let mut seed = None;
+    let mut filepath = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("--trace") => trace = true,
+            Some("--debug") => debug = true,
+            Some("--manual-rolls") => manual_rolls = true,
+            Some("--watch") => watch = true,
+            Some("--dry-run") => dry_run = true,
+            Some("--no-color") => no_color = true,
+            Some("--journal") => journal = Some(args.next().ok_or("--journal needs a path")?),
+            Some("--campaign") => {
+                let name = args.next().ok_or("--campaign needs a name")?;
+                campaign = Some(name.into_string().map_err(|_| "--campaign needs a name")?);
+            }
+            Some("--transcript") => {
+                transcript = Some(args.next().ok_or("--transcript needs a path")?)
+            }
+            Some("--templates") => {
+                let path = args.next().ok_or("--templates needs a path")?;
+                templates = Some(Recap::new().with_templates(&fs::read_to_string(path)?)?);
+            }
+            Some("--locale") => {
+                let path = args.next().ok_or("--locale needs a path")?;
+                locale = Some(Locale::new().with_strings(&fs::read_to_string(path)?)?);
+            }
+            Some("--seed") => {
+                let value = args.next().ok_or("--seed needs a number")?;
+                seed = Some(value.to_str().ok_or("--seed needs a number")?.parse()?);
+            }
+            _ => filepath = Some(arg),
+        }
+    }
+
+    let new_crawl = || {
+        let mut crawl = Crawl::new()
+            .with_trace(trace)
+            .with_debugger(debug)
+            .with_manual_rolls(manual_rolls);
+        if no_color || templates.is_some() || locale.is_some() {
+            let mut output = StdoutSink::new();
+            if no_color {
+                output = output.with_color(false);
+            }
+            if let Some(templates) = &templates {
+                output = output.with_recap(templates.clone());
+            }
+            if let Some(locale) = &locale {
+                output = output.with_locale(locale.clone());
+                crawl = crawl.with_locale(locale.clone());
+            }
+            crawl = crawl.with_output(Box::new(output));
+        }
+        if let Some(seed) = seed {
+            crawl = crawl.with_seed(seed);
+        }
+        if let Some(journal) = &journal {
+            crawl = crawl.with_journal_file(journal);
+        }
+        if let Some(campaign) = &campaign {
+            // Campaigns are kept under `campaigns/` in the working directory.
+            crawl = crawl
+                .with_storage(Box::new(FileStorage::new(".")))
+                .with_campaign(campaign);
+        }
+        if let Some(transcript) = &transcript {
+            crawl = crawl.with_transcript_file(transcript);
+        }
+        crawl
+    };
+    match filepath {
+        Some(filepath) if dry_run => dry_run_file(&new_crawl(), filepath),
+        None if dry_run => Err("--dry-run needs a script to describe".into()),
+        Some(filepath) if watch => watch_file(new_crawl, filepath),
+        Some(filepath) => execute_file(&mut new_crawl(), filepath),
+        None if watch => Err("--watch needs a script to run".into()),
+        None => repl(&mut new_crawl()),
+    }
+}
+
+/// Run the script, then keep watching it and the tables it loaded. The script is run again in
+/// a fresh session when it changes; a table that changes is reloaded into the running session.
+fn watch_file(new_crawl: impl Fn() -> Crawl, filepath: OsString) -> Result<(), Box<dyn Error>> {
+    let mut crawl = new_crawl();
+    execute_file(&mut crawl, filepath.clone())?;
+    let mut script_modified = modified(&filepath);
+    let mut tables_modified = table_modified_times(&crawl);
+
+    loop {
+        thread::sleep(WATCH_INTERVAL);
+
+        if modified(&filepath) != script_modified {
+            println!("{} changed, running it again", filepath.to_string_lossy());
+            crawl = new_crawl();
+            // Keep watching if the script is mid-save, or was moved away.
+            if let Err(error) = execute_file(&mut crawl, filepath.clone()) {
+                eprintln!("{error}");
+            }
+            script_modified = modified(&filepath);
+            tables_modified = table_modified_times(&crawl);
+            continue;
+        }
+
+        for (table, last_modified) in tables_modified.iter_mut() {
+            let table_modified = modified(table);
+            if table_modified == *last_modified {
+                continue;
+            }
+            *last_modified = table_modified;
+            match crawl.reload_table(table) {
+                Ok(()) => println!("reloaded table {table}"),
+                Err(error) => eprintln!("{error}"),
+            }
+        }
+    }
+}
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+fn modified(path: impl AsRef<Path>) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn table_modified_times(crawl: &Crawl) -> HashMap<String, Option<SystemTime>> {
+    crawl
+        .table_names()
+        .map(|table| (table.to_string(), modified(table)))
+        .collect()
+}
+
+fn execute_file(crawl: &mut Crawl, filepath: OsString) -> Result<(), Box<dyn Error>> {
+    crawl.execute_file(filepath)?;
+    Ok(())
+}
+
+/// Print what the script would do, without running it.
+fn dry_run_file(crawl: &Crawl, filepath: OsString) -> Result<(), Box<dyn Error>> {
+    for line in crawl.dry_run(&fs::read_to_string(filepath)?)? {
+        println!("{line}");
+    }
     Ok(())
 }
 
-fn repl() -> Result<(), Box<dyn Error>> {
+fn repl(crawl: &mut Crawl) -> Result<(), Box<dyn Error>> {
     ctrlc::set_handler(move || exit(1)).expect("failed to set ctrlc handler");
 
     loop {
@@ -35,7 +211,415 @@ fn repl() -> Result<(), Box<dyn Error>> {
             .read_line(&mut input)
             .expect("failed to read line");
 
-        let crawl = Crawl::new();
-        crawl.execute(&input);
+        if input.trim() == ":reroll" {
+            if !crawl.reroll() {
+                eprintln!("nothing has been rolled yet");
+            }
+            continue;
+        }
+        if input.trim() == ":initiative" {
+            print_initiative(crawl);
+            continue;
+        }
+        let command = input.trim().split_once(' ');
+        match command {
+            Some((":save", path)) => {
+                let path = session_path(crawl, path.trim());
+                match save(crawl, &path) {
+                    Ok(()) => println!("saved session to {}", path.display()),
+                    Err(error) => eprintln!("failed to save session: {error}"),
+                }
+            }
+            Some((":restore", path)) => {
+                let path = session_path(crawl, path.trim());
+                match restore(crawl, &path) {
+                    Ok(()) => println!("restored session from {}", path.display()),
+                    Err(error) => eprintln!("failed to restore session: {error}"),
+                }
+            }
+            _ => crawl.execute(&input),
+        }
+    }
+}
+
+/// The combatants in the order they act, marking whose turn it is.
+fn print_initiative(crawl: &Crawl) {
+    let initiative = crawl.initiative();
+    if initiative.combatants().is_empty() {
+        println!("nobody is in the fight");
+        return;
+    }
+    if initiative.round() > 0 {
+        println!("round {}", initiative.round());
+    }
+    let current = initiative
+        .current()
+        .map(|combatant| combatant.name.as_str());
+    for combatant in initiative.combatants() {
+        let mark = if current == Some(combatant.name.as_str()) {
+            ">"
+        } else {
+            " "
+        };
+        println!("{mark} {} ({})", combatant.name, combatant.initiative);
+    }
+}
+
+/// Sessions saved in a campaign are kept in its directory.
+fn session_path(crawl: &Crawl, path: &str) -> PathBuf {
+    match crawl.campaign() {
+        Some(campaign) => PathBuf::from(campaign_key(campaign, path)),
+        None => PathBuf::from(path),
+    }
+}
+
+fn save(crawl: &Crawl, path: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, crawl.snapshot().to_json()?)?;
+    Ok(())
+}
+
+fn restore(crawl: &mut Crawl, path: &Path) -> Result<(), Box<dyn Error>> {
+    let snapshot = StateSnapshot::from_json(&fs::read_to_string(path)?)?;
+    crawl.restore(snapshot);
+    Ok(())
+}
+
+/// `crawl lsp` - serve editors over stdin and stdout, with the language server protocol.
+fn lsp() -> Result<(), Box<dyn Error>> {
+    LanguageServer::new(io::stdin().lock(), io::stdout()).run()?;
+    Ok(())
+}
+
+/// `crawl tui file` - run a script in a terminal UI, with panes for procedures, the log, facts,
+/// and tables, then run whatever's typed in.
+#[cfg(feature = "tui")]
+fn tui(args: &[OsString]) -> Result<(), Box<dyn Error>> {
+    let filepath = args.first().ok_or("usage: crawl tui <file>")?;
+    crawl::tui::Tui::new(Crawl::new()).run(filepath)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "tui"))]
+fn tui(_args: &[OsString]) -> Result<(), Box<dyn Error>> {
+    Err("crawl was built without the terminal UI; build it with `--features tui`".into())
+}
+
+/// The seed test scripts are run with, so their rolls come up the same every time.
+const TEST_SEED: u64 = 0;
+
+/// `crawl test [path...]` - run every `*.test.crawl` script under the paths given, or the current
+/// directory, reporting which pass. A script fails if any of it errors, like a failed `assert`.
+fn test(args: &[OsString]) -> Result<(), Box<dyn Error>> {
+    let mut scripts = Vec::new();
+    if args.is_empty() {
+        find_test_scripts(Path::new("."), &mut scripts)?;
+    }
+    for arg in args {
+        let path = Path::new(arg);
+        if path.is_dir() {
+            find_test_scripts(path, &mut scripts)?;
+        } else {
+            scripts.push(path.into());
+        }
     }
+    scripts.sort();
+
+    let mut failed = 0;
+    for script in &scripts {
+        let buffer = BufferSink::new();
+        let mut crawl = Crawl::new()
+            .with_seed(TEST_SEED)
+            .with_output(Box::new(buffer.clone()));
+        crawl.execute_file(script)?;
+        let errors: Vec<String> = buffer
+            .take()
+            .into_iter()
+            .filter_map(|output| match output {
+                Output::Error(error) => Some(error),
+                _ => None,
+            })
+            .collect();
+        if errors.is_empty() {
+            println!("ok     {}", script.display());
+        } else {
+            failed += 1;
+            println!("FAILED {}", script.display());
+            for error in errors {
+                println!("       {error}");
+            }
+        }
+    }
+
+    println!("\n{} passed, {failed} failed", scripts.len() - failed);
+    if failed > 0 {
+        exit(1);
+    }
+    Ok(())
+}
+
+fn find_test_scripts(directory: &Path, scripts: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_test_scripts(&path, scripts)?;
+        } else if path.to_string_lossy().ends_with(".test.crawl") {
+            scripts.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// `crawl replay transcript.jsonl` - re-execute a recorded session with its seed, checking
+/// every roll comes up as it did originally.
+fn replay(args: &[OsString]) -> Result<(), Box<dyn Error>> {
+    let filepath = args.first().ok_or("usage: crawl replay <transcript>")?;
+    let transcript = Transcript::read_jsonl(BufReader::new(File::open(filepath)?))?;
+    let crawl = Crawl::replay(&transcript)?;
+    println!(
+        "replayed {} rolls, all matching the transcript",
+        crawl.transcript().rolls().count()
+    );
+    Ok(())
+}
+
+/// `crawl simulate script.crawl --runs 100000` - run a script many times across threads, and
+/// print how often each fact ended up true and each table entry came up.
+fn simulate(args: &[OsString]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: crawl simulate <file> [--runs <n>] [--threads <n>] [--seed <n>]";
+    let mut filepath = None;
+    let mut runs = None;
+    let mut threads = None;
+    let mut seed = None;
+    let mut args = args.iter().map(|arg| arg.to_str().ok_or(usage));
+    while let Some(arg) = args.next() {
+        match arg? {
+            // Parsed as a float so it can be given like `1e5`.
+            "--runs" => runs = Some(args.next().ok_or(usage)??.parse::<f64>()? as u64),
+            "--threads" => threads = Some(args.next().ok_or(usage)??.parse()?),
+            "--seed" => seed = Some(args.next().ok_or(usage)??.parse()?),
+            arg => filepath = Some(arg),
+        }
+    }
+    let mut simulation = Simulation::from_file(filepath.ok_or(usage)?);
+    if let Some(runs) = runs {
+        simulation = simulation.with_runs(runs);
+    }
+    if let Some(threads) = threads {
+        simulation = simulation.with_threads(threads);
+    }
+    if let Some(seed) = seed {
+        simulation = simulation.with_seed(seed);
+    }
+
+    let outcomes = simulation.run();
+    println!("ran {} times, {} failed", outcomes.runs, outcomes.failed);
+    let percent = |count: u64, total: u64| count as f64 / total.max(1) as f64 * 100.0;
+
+    let mut facts: Vec<(&String, &u64)> = outcomes.facts.iter().collect();
+    facts.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    if !facts.is_empty() {
+        println!("\nfacts true at the end");
+    }
+    for (fact, count) in facts {
+        println!("{:>7.2}%  {fact}", percent(*count, outcomes.runs));
+    }
+
+    let mut tables: Vec<_> = outcomes.table_rolls.iter().collect();
+    tables.sort_by_key(|(table, _)| *table);
+    for (table, entries) in tables {
+        let rolls = entries.values().sum();
+        println!("\n{table}, rolled {rolls} times");
+        let mut entries: Vec<(&String, &u64)> = entries.iter().collect();
+        entries.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (entry, count) in entries {
+            println!("{:>7.2}%  {entry}", percent(*count, rolls));
+        }
+    }
+    Ok(())
+}
+
+/// `crawl stock --rooms 12 --system bx` - stock a dungeon's rooms by the system's tables, or
+/// tables given in their place, and print its key as JSON.
+fn stock(args: &[OsString]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: crawl stock --rooms <n> [--system bx] [--contents <table>] \
+                 [--monsters <table>] [--treasure <table>] [--seed <n>]";
+    let mut rooms = None;
+    let mut system = System::Bx;
+    let mut tables = Vec::new();
+    let mut seed = None;
+    let mut args = args.iter().map(|arg| arg.to_str().ok_or(usage));
+    while let Some(arg) = args.next() {
+        match arg? {
+            "--rooms" => rooms = Some(args.next().ok_or(usage)??.parse()?),
+            "--system" => system = args.next().ok_or(usage)??.parse()?,
+            "--contents" => tables.push((CONTENTS, args.next().ok_or(usage)??)),
+            "--monsters" => tables.push((MONSTERS, args.next().ok_or(usage)??)),
+            "--treasure" => tables.push((TREASURE, args.next().ok_or(usage)??)),
+            "--seed" => seed = Some(args.next().ok_or(usage)??.parse()?),
+            _ => return Err(usage.into()),
+        }
+    }
+    let mut stocking = Stocking::new(system);
+    for (name, filepath) in tables {
+        stocking = stocking.with_table(name, Table::load(filepath)?);
+    }
+    if let Some(seed) = seed {
+        stocking = stocking.with_seed(seed);
+    }
+    let rooms = stocking.stock(rooms.ok_or(usage)?)?;
+    println!("{}", serde_json::to_string_pretty(&rooms)?);
+    Ok(())
+}
+
+/// `crawl odds "2d6+3 >= 9"` - print the exact distribution of a roll, and the chance a
+/// comparison against it succeeds.
+fn odds(args: &[OsString]) -> Result<(), Box<dyn Error>> {
+    let expression = args
+        .first()
+        .and_then(|arg| arg.to_str())
+        .ok_or("usage: crawl odds \"<roll> [<comparison> <number>]\"")?;
+
+    // Two-character operators first so ">=" isn't read as ">".
+    let comparison = [">=", "<=", "==", "!=", ">", "<", "="]
+        .into_iter()
+        .find_map(|op| expression.split_once(op).map(|(lhs, rhs)| (lhs, op, rhs)));
+    let (spec, comparison) = match comparison {
+        Some((spec, op, target)) => (spec, Some((op, target.trim().parse::<i64>()?))),
+        None => (expression, None),
+    };
+
+    let spec = spec.trim();
+    let roll: DiceRoll = spec.parse()?;
+    println!(
+        "{spec}: min {}, max {}, mean {:.2}",
+        roll.min(),
+        roll.max(),
+        roll.mean()
+    );
+
+    let distribution = roll.distribution()?;
+    let mut totals: Vec<(i64, f64)> = distribution.iter().map(|(&total, &p)| (total, p)).collect();
+    totals.sort_by_key(|(total, _)| *total);
+    for (total, probability) in totals {
+        println!("{total:>4} {:>7.2}%", probability * 100.0);
+    }
+
+    if let Some((op, target)) = comparison {
+        let probability = dice::probability(&distribution, |total| match op {
+            ">=" => total >= target,
+            "<=" => total <= target,
+            "!=" => total != target,
+            ">" => total > target,
+            "<" => total < target,
+            _ => total == target,
+        });
+        println!("P({spec} {op} {target}) = {:.2}%", probability * 100.0);
+    }
+
+    Ok(())
+}
+
+/// `crawl verify-dice 2d6 --runs 1e6` - roll many times, and check the totals against the exact
+/// distribution with a chi-squared test, to confirm the dice are fair.
+fn verify_dice(args: &[OsString]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: crawl verify-dice <roll> [--runs <n>]";
+    let mut spec = None;
+    let mut runs = DEFAULT_VERIFY_RUNS;
+    let mut args = args.iter().map(|arg| arg.to_str().ok_or(usage));
+    while let Some(arg) = args.next() {
+        match arg? {
+            "--runs" => {
+                // Parsed as a float so it can be given like `1e6`.
+                let value: f64 = args.next().ok_or(usage)??.parse()?;
+                if value < 1.0 {
+                    return Err("--runs needs to be at least 1".into());
+                }
+                runs = value as u64;
+            }
+            arg => spec = Some(arg),
+        }
+    }
+    let spec = spec.ok_or(usage)?;
+    let roll: DiceRoll = spec.parse()?;
+
+    let mut counts: HashMap<i64, u64> = HashMap::new();
+    for _ in 0..runs {
+        *counts.entry(roll.roll().total).or_default() += 1;
+    }
+
+    let mut distribution: Vec<(i64, f64)> = roll.distribution()?.into_iter().collect();
+    distribution.sort_by_key(|(total, _)| *total);
+    println!("rolled {spec} {runs} times\n\ntotal expected observed");
+    for (total, probability) in distribution {
+        let observed = counts.get(&total).copied().unwrap_or(0) as f64 / runs as f64;
+        println!(
+            "{total:>5} {:>7.2}% {:>7.2}%",
+            probability * 100.0,
+            observed * 100.0
+        );
+    }
+
+    let (statistic, degrees_of_freedom) = roll.chi_squared(&counts)?;
+    if degrees_of_freedom == 0 {
+        println!("\nonly one total is possible, so there's nothing to test");
+        return Ok(());
+    }
+    let critical = chi_squared_critical_value(degrees_of_freedom);
+    println!(
+        "\nchi-squared {statistic:.2} with {degrees_of_freedom} degrees of freedom \
+         (critical value {critical:.2} at p = 0.01)"
+    );
+    if statistic > critical {
+        println!("the totals are NOT consistent with fair dice");
+        exit(1);
+    }
+    println!("the totals are consistent with fair dice");
+    Ok(())
+}
+
+const DEFAULT_VERIFY_RUNS: u64 = 100_000;
+
+/// The chi-squared value exceeded with probability 0.01, by the Wilson-Hilferty approximation.
+fn chi_squared_critical_value(degrees_of_freedom: usize) -> f64 {
+    // The standard normal's 99th percentile
+    const Z: f64 = 2.326;
+    let k = degrees_of_freedom as f64;
+    let spread = 2.0 / (9.0 * k);
+    k * (1.0 - spread + Z * spread.sqrt()).powi(3)
+}
+
+/// `crawl table-odds weather.csv [2d6]` - print the chance of landing on each entry of a
+/// table, rolling the given dice or the table's default die.
+fn table_odds(args: &[OsString]) -> Result<(), Box<dyn Error>> {
+    let filepath = args
+        .first()
+        .and_then(|arg| arg.to_str())
+        .ok_or("usage: crawl table-odds <table> [<roll>]")?;
+    let table = Table::load(filepath)?;
+    let dice = match args.get(1).and_then(|arg| arg.to_str()) {
+        Some(spec) => spec.parse()?,
+        None => table.auto_dice(),
+    };
+
+    let distribution = table.distribution(&dice)?;
+    let on_table: f64 = distribution
+        .iter()
+        .map(|(_, probability)| probability)
+        .sum();
+    for (entry, probability) in distribution {
+        println!(
+            "{:>6} {:>7.2}%  {}",
+            entry.roll_target().to_string(),
+            probability * 100.0,
+            entry.value
+        );
+    }
+    if on_table < 1.0 - 1e-9 {
+        println!("{:>6} {:>7.2}%", "none", (1.0 - on_table) * 100.0);
+    }
+
+    Ok(())
 }
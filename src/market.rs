@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dice::DiceRoll;
+
+// A price list is loaded from CSV with `load prices "prices.csv"`, giving each item's price, in
+// some number of a coin, and the percent chance it's in stock:
+//
+//     item,price,availability
+//     plate mail,60 gp,40
+//     torch,1 cp,100
+//
+// Prices are marked up or down by the size of the settlement they're asked in, which also makes
+// items likelier or less likely to be in stock, and haggling moves a price by a roll.
+
+/// An item on a price list: its price, as some number of a coin, and the percent chance it's in
+/// stock.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PriceListItem {
+    pub count: i32,
+    pub coin: String,
+    pub availability: i32,
+}
+
+/// The items on the price list in `csv`, by name.
+pub fn parse_prices(csv: &str) -> Result<HashMap<String, PriceListItem>, Box<dyn Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+    let mut items = HashMap::new();
+    for record in reader.records() {
+        let record = record?;
+        let (Some(item), Some(price), Some(availability)) =
+            (record.get(0), record.get(1), record.get(2))
+        else {
+            return Err(format!("{record:?} needs an item, a price, and an availability").into());
+        };
+        let (count, coin) = price
+            .split_once(' ')
+            .ok_or_else(|| format!("price {price:?} needs a number and a coin, like \"60 gp\""))?;
+        items.insert(
+            item.into(),
+            PriceListItem {
+                count: count.trim().parse()?,
+                coin: coin.trim().into(),
+                availability: availability.parse()?,
+            },
+        );
+    }
+    Ok(items)
+}
+
+/// How a settlement of some size changes prices, and the chance of items being in stock, by some
+/// percent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settlement {
+    pub markup: i32,
+    pub availability: i32,
+}
+
+/// How prices change by settlement and by haggling. Hamlets charge half again and villages a
+/// quarter more, towns the list price, and cities a tenth less, with items as much less or more
+/// likely to be in stock, by default. Haggling rolls 2d6, from a fifth more on a 2 to a fifth off
+/// on a 12.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Market {
+    settlements: HashMap<String, Settlement>,
+    haggle: DiceRoll,
+    // The highest total for each percent a price changes by, lowest first
+    haggles: Vec<(i32, i32)>,
+}
+
+impl Default for Market {
+    fn default() -> Self {
+        Market {
+            settlements: [
+                ("hamlet", 50, -30),
+                ("village", 25, -15),
+                ("town", 0, 0),
+                ("city", -10, 15),
+            ]
+            .into_iter()
+            .map(|(size, markup, availability)| {
+                (
+                    size.into(),
+                    Settlement {
+                        markup,
+                        availability,
+                    },
+                )
+            })
+            .collect(),
+            haggle: "2d6".parse().unwrap(),
+            haggles: vec![(3, 20), (5, 10), (8, 0), (10, -10), (12, -20)],
+        }
+    }
+}
+
+impl Market {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_settlement(mut self, size: &str, settlement: Settlement) -> Self {
+        self.settlements.insert(size.into(), settlement);
+        self
+    }
+
+    /// Haggle on `roll`, with each percent a price changes by given the highest total it's had
+    /// on, lowest first. Totals past the last change it by the last.
+    pub fn with_haggles(mut self, roll: DiceRoll, haggles: &[(i32, i32)]) -> Self {
+        self.haggle = roll;
+        self.haggles = haggles.to_vec();
+        self
+    }
+
+    pub fn settlement(&self, size: &str) -> Option<Settlement> {
+        self.settlements.get(size).copied()
+    }
+
+    pub fn haggle_roll(&self) -> &DiceRoll {
+        &self.haggle
+    }
+
+    /// The percent a haggling roll of `total` changes a price by.
+    pub fn haggle(&self, total: i32) -> i32 {
+        self.haggles
+            .iter()
+            .find(|(highest, _)| total <= *highest)
+            .or(self.haggles.last())
+            .map_or(0, |(_, percent)| *percent)
+    }
+}
+
+/// `price` changed by `percent` percent, rounded down, if it's not too big to count.
+pub fn mark_up(price: i32, percent: i32) -> Option<i32> {
+    Some(price.checked_mul(percent.checked_add(100)?)? / 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices() {
+        let items =
+            parse_prices("item,price,availability\nplate mail, 60 gp, 40\ntorch,1 cp,100\n")
+                .unwrap();
+        assert_eq!(
+            items["plate mail"],
+            PriceListItem {
+                count: 60,
+                coin: "gp".into(),
+                availability: 40,
+            }
+        );
+        assert!(parse_prices("item,price,availability\nrope,a lot,50\n").is_err());
+
+        let market = Market::default();
+        assert_eq!(market.settlement("village").unwrap().markup, 25);
+        assert_eq!(market.settlement("metropolis"), None);
+        assert_eq!(market.haggle(2), 20);
+        assert_eq!(market.haggle(7), 0);
+        assert_eq!(market.haggle(15), -20);
+        assert_eq!(mark_up(6000, 25), Some(7500));
+        assert_eq!(mark_up(6000, -10), Some(5400));
+        assert_eq!(mark_up(i32::MAX, 25), None);
+    }
+}
@@ -0,0 +1,74 @@
+/// The coins money is counted in, and what each is worth in the smallest of them. Gold, silver,
+/// and copper pieces by default, at ten to one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Denominations {
+    // Each coin and its worth, most valuable first
+    coins: Vec<(String, i32)>,
+}
+
+impl Default for Denominations {
+    fn default() -> Self {
+        Denominations {
+            coins: vec![("gp".into(), 100), ("sp".into(), 10), ("cp".into(), 1)],
+        }
+    }
+}
+
+impl Denominations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count `coin` as worth `worth` of the smallest coin, which is worth 1, adding it if it
+    /// isn't one already.
+    pub fn with_coin(mut self, coin: &str, worth: i32) -> Self {
+        self.coins.retain(|(name, _)| name != coin);
+        self.coins.push((coin.into(), worth.max(1)));
+        self.coins
+            .sort_by_key(|(_, worth)| std::cmp::Reverse(*worth));
+        self
+    }
+
+    /// What one `coin` is worth in the smallest coin.
+    pub fn worth(&self, coin: &str) -> Option<i32> {
+        self.coins
+            .iter()
+            .find(|(name, _)| name == coin)
+            .map(|(_, worth)| *worth)
+    }
+
+    /// An amount in the smallest coin, in the fewest coins it makes, like "2 gp, 5 sp".
+    pub fn format(&self, amount: i32) -> String {
+        let mut left = amount;
+        let mut coins = Vec::new();
+        for (coin, worth) in &self.coins {
+            let count = left / worth;
+            if count > 0 {
+                coins.push(format!("{count} {coin}"));
+                left -= count * worth;
+            }
+        }
+        match self.coins.last() {
+            Some((coin, _)) if coins.is_empty() => format!("0 {coin}"),
+            _ => coins.join(", "),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format() {
+        let denominations = Denominations::new();
+        assert_eq!(denominations.format(253), "2 gp, 5 sp, 3 cp");
+        assert_eq!(denominations.format(1000), "10 gp");
+        assert_eq!(denominations.format(0), "0 cp");
+
+        let denominations = denominations.with_coin("pp", 1000).with_coin("sp", 20);
+        assert_eq!(denominations.worth("sp"), Some(20));
+        assert_eq!(denominations.format(1150), "1 pp, 1 gp, 2 sp, 10 cp");
+        assert_eq!(denominations.worth("ep"), None);
+    }
+}
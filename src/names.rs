@@ -0,0 +1,136 @@
+use std::{error::Error, fs::File, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CrawlError;
+
+// Name tables put names together from parts, like a prefix, a middle, and a suffix, each picked
+// from its own column. A header row names the parts, and a `weight` column weighs the part in
+// the column before it; parts without weights are equally likely. A blank part with a weight is
+// a chance of leaving that part out, and a blank part without one is skipped, so the columns
+// can be different lengths:
+//
+//     prefix,weight,suffix
+//     Thor,3,in
+//     Bal,1,dur
+//     Gim,2,
+
+/// A table of name parts, put together a name at a time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NameTable {
+    // Each part's options, with their weights, in the order the parts go together
+    parts: Vec<Vec<(String, i32)>>,
+}
+
+impl NameTable {
+    pub fn load(filepath: &str) -> Result<Self, Box<dyn Error>> {
+        Self::from_reader(File::open(filepath)?)
+    }
+
+    pub fn from_reader(reader: impl io::Read) -> Result<Self, Box<dyn Error>> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(reader);
+        let mut records = rdr.records();
+        let header = records.next().ok_or("a name table needs a header row")??;
+
+        // Each part's column, and its weight column if it has one
+        let mut columns: Vec<(usize, Option<usize>)> = Vec::new();
+        for (idx, name) in header.iter().enumerate() {
+            if name.trim() != "weight" {
+                columns.push((idx, None));
+                continue;
+            }
+            match columns.last_mut() {
+                Some((_, weight @ None)) => *weight = Some(idx),
+                _ => return Err("a weight column needs a part before it".into()),
+            }
+        }
+
+        let mut parts = vec![Vec::new(); columns.len()];
+        for record in records {
+            let record = record?;
+            for ((part, weight), options) in columns.iter().zip(&mut parts) {
+                let part = record.get(*part).unwrap_or_default().trim();
+                let weight = weight
+                    .and_then(|weight| record.get(weight))
+                    .map(str::trim)
+                    .filter(|weight| !weight.is_empty());
+                let weight =
+                    match (part, weight) {
+                        ("", None) => continue,
+                        (_, None) => 1,
+                        (_, Some(weight)) => weight
+                            .parse::<i32>()
+                            .ok()
+                            .filter(|weight| *weight >= 0)
+                            .ok_or_else(|| format!("weight {weight:?} isn't a whole number"))?,
+                    };
+                options.push((part.to_string(), weight));
+            }
+        }
+        for (options, name) in parts.iter().zip(header.iter()) {
+            if options.iter().map(|(_, weight)| weight).sum::<i32>() == 0 {
+                return Err(format!("part {name:?} has nothing to pick").into());
+            }
+        }
+        Ok(NameTable { parts })
+    }
+
+    /// Put a name together, picking each part by `roll`, which is given the part's total weight
+    /// and rolls from 1 up to it.
+    pub fn generate(
+        &self,
+        mut roll: impl FnMut(i32) -> Result<i32, CrawlError>,
+    ) -> Result<String, CrawlError> {
+        let mut name = String::new();
+        for options in &self.parts {
+            let mut rolled = roll(options.iter().map(|(_, weight)| weight).sum())?;
+            for (part, weight) in options {
+                if rolled <= *weight {
+                    name.push_str(part);
+                    break;
+                }
+                rolled -= weight;
+            }
+        }
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate() {
+        let table = NameTable::from_reader(
+            "prefix,weight,middle,suffix,weight\nThor,3,,in,1\nBal,1,,dur,\nGim,2,,,0\n".as_bytes(),
+        );
+        // The middle column has nothing in it.
+        assert!(table.is_err());
+
+        let table = NameTable::from_reader(
+            "prefix,weight,suffix,weight\nThor,3,in,1\nBal,1,dur,\nGim,2,,2\n".as_bytes(),
+        )
+        .unwrap();
+        let name = |rolls: [i32; 2]| {
+            let mut rolls = rolls.into_iter();
+            table.generate(|_| Ok(rolls.next().unwrap())).unwrap()
+        };
+        assert_eq!(name([1, 1]), "Thorin");
+        assert_eq!(name([4, 2]), "Baldur");
+        // A blank part with a weight leaves it out.
+        assert_eq!(name([6, 4]), "Gim");
+
+        let mut totals = Vec::new();
+        table
+            .generate(|total| {
+                totals.push(total);
+                Ok(1)
+            })
+            .unwrap();
+        assert_eq!(totals, [6, 4]);
+    }
+}
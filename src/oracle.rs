@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dice::DiceRoll;
+
+// Other oracles' odds, like Mythic's, can be loaded from a CSV of the words for each, the roll,
+// and the target a yes is on or under:
+//
+//     odds,roll,target
+//     almost certain,1d100,85
+//     50/50,1d100,50
+//
+// The file is `odds.csv`, next to the persistent facts, so each campaign can use its own.
+//
+// Like Mythic's, the oracle has a chaos factor, from 1 to 9, that's raised as things get out of
+// hand and lowered as the party takes control. At 5 the odds are as given, and each point above or
+// below moves every target up or down by a tenth of what the roll can come to, by default.
+
+/// The storage key a campaign's oracle odds are read from.
+pub const ODDS_KEY: &str = "odds.csv";
+/// The storage key the chaos factor is saved under, next to the persistent facts.
+pub const CHAOS_KEY: &str = "chaos.json";
+/// The chaos factor a campaign starts at, where the odds are as given.
+pub const DEFAULT_CHAOS: i32 = 5;
+pub const MIN_CHAOS: i32 = 1;
+pub const MAX_CHAOS: i32 = 9;
+
+/// How likely an oracle question is to be answered yes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Likelihood {
+    VeryLikely,
+    Likely,
+    Even,
+    Unlikely,
+    VeryUnlikely,
+    // Odds with words of their own, like "almost certain"
+    Named(String),
+}
+
+impl Likelihood {
+    /// The likelihood `words` name, built in or not.
+    pub fn named(words: &str) -> Self {
+        match words {
+            "very likely" => Likelihood::VeryLikely,
+            "likely" => Likelihood::Likely,
+            "even" => Likelihood::Even,
+            "unlikely" => Likelihood::Unlikely,
+            "very unlikely" => Likelihood::VeryUnlikely,
+            _ => Likelihood::Named(words.into()),
+        }
+    }
+}
+
+impl fmt::Display for Likelihood {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Likelihood::VeryLikely => "very likely",
+            Likelihood::Likely => "likely",
+            Likelihood::Even => "even",
+            Likelihood::Unlikely => "unlikely",
+            Likelihood::VeryUnlikely => "very unlikely",
+            Likelihood::Named(words) => words,
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The roll for a likelihood, and the target a yes is on or under.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Odds {
+    pub roll: DiceRoll,
+    pub target: i32,
+}
+
+/// The yes/no table oracle questions are answered from: the roll and target for each
+/// likelihood, by default a d100 against the chance out of 100 of a yes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Oracle {
+    odds: HashMap<Likelihood, Odds>,
+    // The percent of a roll's range each point of chaos moves targets by
+    chaos_step: i32,
+}
+
+impl Default for Oracle {
+    fn default() -> Self {
+        [
+            (Likelihood::VeryLikely, 90),
+            (Likelihood::Likely, 75),
+            (Likelihood::Even, 50),
+            (Likelihood::Unlikely, 25),
+            (Likelihood::VeryUnlikely, 10),
+        ]
+        .into_iter()
+        .fold(
+            Oracle {
+                odds: HashMap::new(),
+                chaos_step: 10,
+            },
+            |oracle, (likelihood, chance)| oracle.with_chance(likelihood, chance),
+        )
+    }
+}
+
+impl Oracle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer yes to `likelihood` questions on `chance` or under, out of 100.
+    pub fn with_chance(self, likelihood: Likelihood, chance: i32) -> Self {
+        self.with_odds(likelihood, "1d100".parse().unwrap(), chance)
+    }
+
+    /// Answer yes to `likelihood` questions when `roll` comes to `target` or under.
+    pub fn with_odds(mut self, likelihood: Likelihood, roll: DiceRoll, target: i32) -> Self {
+        self.odds.insert(likelihood, Odds { roll, target });
+        self
+    }
+
+    /// Move targets by `percent` of what a roll can come to for each point of chaos above or
+    /// below 5, rather than 10. 0 leaves the odds alone.
+    pub fn with_chaos_step(mut self, percent: i32) -> Self {
+        self.chaos_step = percent;
+        self
+    }
+
+    pub fn odds(&self, likelihood: &Likelihood) -> Option<&Odds> {
+        self.odds.get(likelihood)
+    }
+
+    /// The target for `odds` at the chaos factor `chaos`.
+    pub fn target(&self, odds: &Odds, chaos: i32) -> i32 {
+        let range = odds.roll.max() - odds.roll.min() + 1;
+        let shift = i64::from(chaos - DEFAULT_CHAOS) * i64::from(self.chaos_step) * range / 100;
+        odds.target + shift as i32
+    }
+}
+
+/// The odds in `csv`, by likelihood.
+pub fn parse_odds(csv: &str) -> Result<HashMap<Likelihood, Odds>, Box<dyn Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+    let mut odds = HashMap::new();
+    for record in reader.records() {
+        let record = record?;
+        let (Some(words), Some(roll), Some(target)) = (record.get(0), record.get(1), record.get(2))
+        else {
+            return Err(format!("{record:?} needs odds, a roll, and a target").into());
+        };
+        odds.insert(
+            Likelihood::named(words),
+            Odds {
+                roll: roll.parse()?,
+                target: target.parse()?,
+            },
+        );
+    }
+    Ok(odds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer() {
+        let oracle = Oracle::new().with_chance(Likelihood::Even, 60);
+        let target = |likelihood, chaos| oracle.target(oracle.odds(&likelihood).unwrap(), chaos);
+        assert_eq!(target(Likelihood::Even, DEFAULT_CHAOS), 60);
+        assert_eq!(target(Likelihood::VeryLikely, DEFAULT_CHAOS), 90);
+        assert_eq!(target(Likelihood::VeryUnlikely, MIN_CHAOS), -30);
+        assert_eq!(target(Likelihood::Likely, 7), 95);
+        assert_eq!(oracle.odds(&Likelihood::named("almost certain")), None);
+
+        // Chaos moves targets by the roll's range.
+        let oracle = Oracle::new()
+            .with_odds(Likelihood::Even, "2d6".parse().unwrap(), 7)
+            .with_chaos_step(20);
+        assert_eq!(
+            oracle.target(oracle.odds(&Likelihood::Even).unwrap(), 8),
+            13
+        );
+        let oracle = oracle.with_chaos_step(0);
+        assert_eq!(oracle.target(oracle.odds(&Likelihood::Even).unwrap(), 8), 7);
+    }
+
+    #[test]
+    fn odds() {
+        let odds = parse_odds("odds,roll,target\nalmost certain,1d100,85\nlikely,2d6,8\n").unwrap();
+        assert_eq!(odds[&Likelihood::named("almost certain")].target, 85);
+        assert_eq!(odds[&Likelihood::Likely].roll, "2d6".parse().unwrap());
+        assert!(parse_odds("odds,roll,target\nlikely,a d6,4\n").is_err());
+    }
+}
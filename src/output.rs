@@ -0,0 +1,243 @@
+use std::env;
+use std::io::{self, IsTerminal};
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::error::CrawlError;
+use crate::interpreter::StatementRecord;
+use crate::journal::{BOLD_RED, BOLD_YELLOW, Style, ansi, render_record};
+use crate::locale::Locale;
+use crate::parser::Deprecation;
+use crate::recap::Recap;
+
+/// Where a session's output goes. Reminders are written as soon as they're reached, even
+/// mid-procedure, and records once their top-level statement finishes. Sinks are `Send`, so a
+/// session can be handed between threads.
+pub trait OutputSink: Send {
+    fn write_record(&mut self, record: &StatementRecord);
+    fn write_reminder(&mut self, reminder: &str);
+    fn write_warning(&mut self, warning: &str);
+    fn write_error(&mut self, error: &CrawlError);
+
+    /// An error in the statement on `line` of the script, which reads `source`. Sinks that don't
+    /// show where errors happened can leave this to `write_error`.
+    fn write_error_at(&mut self, error: &CrawlError, line: usize, source: &str) {
+        let _ = (line, source);
+        self.write_error(error);
+    }
+
+    /// Syntax the script still runs with, but should move off of. Sinks that don't need where it
+    /// is can leave this to `write_warning`.
+    fn write_deprecation(&mut self, deprecation: &Deprecation) {
+        self.write_warning(&deprecation.to_string());
+    }
+}
+
+/// One piece of output, owned so it can be kept or handed off.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "kebab-case")]
+pub enum Output {
+    Record(StatementRecord),
+    Reminder(String),
+    Warning(String),
+    Error(String),
+}
+
+/// Prints to stdout, and errors to stderr, in color if stdout is a terminal and `NO_COLOR`
+/// isn't set.
+#[derive(Debug)]
+pub struct StdoutSink {
+    color: bool,
+    // Writes records a sentence a line instead, if set
+    recap: Option<Recap>,
+    locale: Locale,
+}
+
+impl Default for StdoutSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StdoutSink {
+    pub fn new() -> Self {
+        let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+        StdoutSink {
+            color,
+            recap: None,
+            locale: Locale::default(),
+        }
+    }
+
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Print records, reminders, warnings, and errors in `locale`'s strings.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Print records from `recap`'s templates, like a session recap, a sentence a line.
+    pub fn with_recap(mut self, recap: Recap) -> Self {
+        self.recap = Some(recap);
+        self
+    }
+}
+
+impl OutputSink for StdoutSink {
+    fn write_record(&mut self, record: &StatementRecord) {
+        match &self.recap {
+            Some(recap) => {
+                for sentence in recap.sentences(record) {
+                    println!("{sentence}");
+                }
+            }
+            None => print!("{}", render_record(record, self.color, &self.locale)),
+        }
+    }
+
+    fn write_reminder(&mut self, reminder: &str) {
+        println!(
+            "{}",
+            Style::Terminal { color: self.color }.reminder(reminder, &self.locale)
+        );
+    }
+
+    fn write_warning(&mut self, warning: &str) {
+        let label = self.locale.get("label.warning", &[]);
+        println!("{} {warning}", ansi(self.color, BOLD_YELLOW, &label));
+    }
+
+    fn write_error(&mut self, error: &CrawlError) {
+        let label = self.locale.get("label.error", &[]);
+        let error = self.locale.error(error);
+        eprintln!("{} {error}", ansi(self.color, BOLD_RED, &label));
+    }
+
+    fn write_error_at(&mut self, error: &CrawlError, line: usize, source: &str) {
+        self.write_error(error);
+        eprintln!("{line:>5} | {}", source.trim_end());
+    }
+}
+
+/// Collects output in memory. Clones share the same buffer, so keep one to read from after
+/// handing another to a `Crawl` or `Interpreter`.
+#[derive(Clone, Debug, Default)]
+pub struct BufferSink(Arc<Mutex<Vec<Output>>>);
+
+impl BufferSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Everything written since the last `take`.
+    pub fn take(&self) -> Vec<Output> {
+        mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+impl OutputSink for BufferSink {
+    fn write_record(&mut self, record: &StatementRecord) {
+        self.0.lock().unwrap().push(Output::Record(record.clone()));
+    }
+
+    fn write_reminder(&mut self, reminder: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(Output::Reminder(reminder.into()));
+    }
+
+    fn write_warning(&mut self, warning: &str) {
+        self.0.lock().unwrap().push(Output::Warning(warning.into()));
+    }
+
+    fn write_error(&mut self, error: &CrawlError) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(Output::Error(error.to_string()));
+    }
+}
+
+/// Hands each piece of output to a closure as it's written.
+pub struct CallbackSink<F>(F);
+
+impl<F: FnMut(Output) + Send> CallbackSink<F> {
+    pub fn new(callback: F) -> Self {
+        CallbackSink(callback)
+    }
+}
+
+impl<F: FnMut(Output) + Send> OutputSink for CallbackSink<F> {
+    fn write_record(&mut self, record: &StatementRecord) {
+        (self.0)(Output::Record(record.clone()));
+    }
+
+    fn write_reminder(&mut self, reminder: &str) {
+        (self.0)(Output::Reminder(reminder.into()));
+    }
+
+    fn write_warning(&mut self, warning: &str) {
+        (self.0)(Output::Warning(warning.into()));
+    }
+
+    fn write_error(&mut self, error: &CrawlError) {
+        (self.0)(Output::Error(error.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::parser::Statement;
+
+    #[test]
+    fn buffer_sink() {
+        let buffer = BufferSink::new();
+        let mut interpreter = Interpreter::new()
+            .with_max_steps(1)
+            .with_output(Box::new(buffer.clone()));
+        let _ = interpreter.interpret(vec![
+            Statement::Reminder("eat rations".into()),
+            Statement::Reminder("sleep".into()),
+        ]);
+
+        let output = buffer.take();
+        assert_eq!(
+            output[..2],
+            [
+                Output::Reminder("eat rations".into()),
+                Output::Record(StatementRecord::Reminder("eat rations".into())),
+            ]
+        );
+        assert!(matches!(output[2], Output::Error(_)));
+        assert!(buffer.take().is_empty());
+    }
+
+    #[test]
+    fn callback_sink() {
+        let reminders = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let reminders = Arc::clone(&reminders);
+            CallbackSink::new(move |output| {
+                if let Output::Reminder(reminder) = output {
+                    reminders.lock().unwrap().push(reminder);
+                }
+            })
+        };
+        let mut interpreter = Interpreter::new().with_output(Box::new(sink));
+        let _ = interpreter.interpret(vec![Statement::Reminder("eat rations".into())]);
+
+        assert_eq!(
+            *reminders.lock().unwrap(),
+            vec![String::from("eat rations")]
+        );
+    }
+}
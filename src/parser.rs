@@ -1,40 +1,328 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::vec;
+
+use crate::calendar::DateUnit;
+use crate::dice::RollSpec;
 use crate::error::CrawlError;
+use crate::fronts::FRONTS_TABLE;
+use crate::oracle::Likelihood;
+use crate::rolls::Degree;
 use crate::scanner::Token;
+use crate::weather::WEATHER_TABLE;
 
 // TODO: replace expects with automatically filled out expected tokens in consume
-// TODO: lots of cloning - Rc?
 // TODO: crazy error handling
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Statement {
+    AdvanceClock {
+        clock: String,
+        amount: Amount,
+    },
+    // Puts a combatant in the fight, with an initiative rolled for them
+    AddCombatant {
+        combatant: String,
+        roll_specifier: ModifiedRollSpecifier,
+    },
+    // Puts an item in someone's inventory, like the party's
+    AddItem {
+        item: String,
+        owner: String,
+    },
+    // Puts coins in a purse, like `add 2d6 gp to "party treasure"`
+    AddMoney {
+        amount: Amount,
+        coin: String,
+        purse: String,
+    },
+    // Adds XP to the session's and campaign's tallies, like `award 250 xp`
+    AwardXp {
+        amount: Amount,
+        multiplier: i32,
+    },
+    // Adds treasure to the tallies, like `award treasure 1d6 x 100 gp`
+    AwardTreasure {
+        amount: Amount,
+        multiplier: i32,
+        coin: String,
+    },
+    // Reports the XP and treasure awarded, and each character's share
+    ReportXp,
+    // Moves the calendar on, like `advance date by 3 days`
+    AdvanceDate {
+        amount: Amount,
+        unit: DateUnit,
+    },
+    // Passes dungeon turns, like `advance time by 3 turns`, burning lights down
+    AdvanceTime(Amount),
+    // Moves on to the next combatant's turn
+    AdvanceTurn,
+    // Rolls what the weather turns to on a weather table's column for today's weather
+    AdvanceWeather(String),
+    // Adds a modifier to every roll, or every use of a named one, until it's removed
+    ApplyModifier {
+        modifier: i32,
+        rolls: Option<String>,
+    },
+    // Stops the program with an error unless the antecedent holds
+    Assert(Antecedent),
+    AssertEqual {
+        actual: ReturnValue,
+        expected: ReturnValue,
+    },
+    // Switches to another campaign's persistent facts
+    Campaign(String),
+    // Rolls against a DC, and evaluates the consequent of the first arm the degree of success
+    // counts as, or else the `else` arm
+    Check {
+        roll_specifier: ModifiedRollSpecifier,
+        dc: Amount,
+        arms: Vec<CheckArm>,
+        otherwise: Option<Box<Statement>>,
+    },
+    // Rolls a d20 for a statblock's saving throw, succeeding on its save's target or more, and
+    // evaluates the consequent of the first arm the degree of success counts as, like `Check`
+    Save {
+        statblock: String,
+        save: String,
+        arms: Vec<CheckArm>,
+        otherwise: Option<Box<Statement>>,
+    },
+    // Asks the player to pick an option, and evaluates its consequent
+    Choice {
+        question: String,
+        arms: Vec<ChoiceArm>,
+    },
     ClearFact(String),
     ClearPersistentFact(String),
+    CreateClock {
+        clock: String,
+        segments: i32,
+    },
+    // A clock for a faction's front, kept for the campaign
+    CreateFront {
+        faction: String,
+        segments: i32,
+    },
+    // Advances every faction's front by a roll on the table
+    AdvanceFronts(String),
+    // A rumor the party might hear, true or false, or `None` until it's decided
+    AddRumor {
+        rumor: String,
+        truth: Option<bool>,
+    },
+    // Reveals a rumor's truth, deciding it first if it's given
+    RevealRumor {
+        rumor: String,
+        truth: Option<bool>,
+    },
+    // Picks rumors the party hasn't heard yet at random, for them to hear
+    HearRumors(Amount),
+    ReportRumors,
+    // Takes hit points off an entity, down to 0
+    Damage {
+        entity: String,
+        amount: Amount,
+    },
+    // Names a roll, like `"attack"`, to use anywhere a roll specifier goes
+    DefineRoll {
+        name: String,
+        roll: ModifiedRollSpecifier,
+    },
     // NonTargetedRoll is only used for string interpolation values
     NontargetedRoll(ModifiedRollSpecifier),
+    // The last roll's total, also only used for string interpolation values
+    LastRoll,
+    // Today's date on the calendar, also only used for string interpolation values
+    CurrentDate,
+    // The oracle's chaos factor, also only used for string interpolation values
+    ChaosFactor,
+    // Raises or lowers the chaos factor by one
+    AdjustChaos(i32),
+    // Rolls whether a scene goes as expected, is altered, or is interrupted by a random event
+    SceneCheck,
     IfThen {
         antecedent: Antecedent,
         consequent: Box<Statement>,
     },
-    LoadTable(String),
+    // Binds a variable for the rest of the enclosing scope
+    Let {
+        name: String,
+        value: Amount,
+    },
+    // Names a template of table rolls, like `"{names} the {adjectives}"`, filled in when it's
+    // called like a procedure
+    Generator {
+        name: String,
+        template: String,
+    },
+    // Runs another script, resolved relative to the including one. Procedures it defines are
+    // prefixed with `namespace.`, if given.
+    Include {
+        path: String,
+        namespace: Option<String>,
+    },
+    // `no-clamp` stops rolls off the end of the table landing on its first or last entry.
+    LoadTable {
+        table: String,
+        no_clamp: bool,
+    },
+    // Loads a name table, of name parts to put together
+    LoadNames(String),
+    // Puts a name together from a name table, like `roll name from "dwarf-names.csv"`
+    NameRoll(String),
+    // Loads statblocks from a TOML or JSON file
+    LoadStatblocks(String),
     MatchingRoll {
         roll_specifier: ModifiedRollSpecifier,
         arms: Vec<MatchingRollArm>,
+        // The `else` arm, evaluated if no other arm matches
+        #[serde(default)]
+        otherwise: Option<Box<Statement>>,
+    },
+    // A 2d6 move, like `move "Act Under Pressure" with +1`, rolled with its modifiers and
+    // matched against `on 10+`, `on 7-9`, and `on 6-` arms like a matching roll
+    Move {
+        name: String,
+        roll_specifier: ModifiedRollSpecifier,
+        arms: Vec<MatchingRollArm>,
+        otherwise: Option<Box<Statement>>,
+    },
+    // Looks up the values of a fact, like `"reaction is"`, and evaluates the consequent of the
+    // first arm one of them matches, or else the `else` arm
+    MatchingFact {
+        fact: String,
+        arms: Vec<TableRollArm>,
+        otherwise: Option<Box<Statement>>,
+    },
+    // Rolls on a table, and evaluates the consequent of the first arm the entry matches
+    MatchingTableRoll {
+        table: String,
+        tag: Option<String>,
+        excluding_seen: bool,
+        arms: Vec<TableRollArm>,
+    },
+    // Answers a yes/no question, weighted by how likely a yes is
+    Oracle {
+        question: String,
+        likelihood: Likelihood,
     },
     Procedure {
         declaration: ProcedureDeclaration,
-        body: Vec<Box<Statement>>,
+        // Shared with the interpreter's procedure table, so calls don't copy the body.
+        body: Arc<[Statement]>,
+    },
+    // Calls a procedure, or a function registered by the host application
+    ProcedureCall {
+        identifier: String,
+        arguments: Vec<String>,
     },
-    ProcedureCall(String),
     Reminder(String),
+    // Lists every fact known about an entity
+    ReportFacts(String),
+    // Lists everything in someone's inventory
+    ReportInventory(String),
+    // Takes a combatant out of the fight
+    RemoveCombatant(String),
+    // Takes one of an item out of someone's inventory
+    RemoveItem {
+        item: String,
+        owner: String,
+    },
+    // Removes the modifier last applied, or last applied to the named roll
+    RemoveModifier(Option<String>),
+    Rule(Rule),
+    // Ends the procedure it's in, giving its caller a value
+    Return(ReturnValue),
     SetFact(CrawlStr),
     SetPersistentFact(String),
-    TableRoll(String),
+    // Orders the combatants by initiative, and starts turns over
+    SortCombatants,
+    // Takes coins out of a purse, making change, from the only purse if none is named
+    SpendMoney {
+        amount: Amount,
+        coin: String,
+        purse: Option<String>,
+    },
+    SpendResource {
+        resource: String,
+        amount: Amount,
+    },
+    // Lights a light, like `light torch`, which burns for its turns
+    Light(String),
+    // Rolls 2d6 against a group's morale, like `morale check "goblins" 7`, and sets
+    // `"goblins are fleeing"` if it's over
+    MoraleCheck {
+        group: String,
+        morale: ModifierValue,
+    },
+    // Runs a travel turn through a terrain, rolling on its tables
+    Travel(String),
+    // Meets something on a terrain's encounter table, rolling how many, their reaction, and
+    // how far off they are
+    Encounter(String),
+    // Rolls the next room the party comes to, on the room shapes, exits, and contents tables
+    GenerateRoom,
+    // Runs each character's activities once a week for some weeks, then moves the date on
+    Downtime {
+        weeks: Amount,
+        arms: Vec<DowntimeArm>,
+    },
+    // Loads a price list, of items' prices and availability
+    LoadPrices(String),
+    // An item's price, marked up or down in the settlement if it's given
+    Price {
+        item: String,
+        settlement: Option<String>,
+    },
+    // An item's price, moved by a haggling roll
+    Haggle {
+        item: String,
+        settlement: Option<String>,
+    },
+    // Rolls the entry's `field` column, on tables with named columns, and only the entries
+    // tagged `tag`, if there is one. Excluding seen entries rerolls any that have come up before
+    // in the campaign.
+    TableRoll {
+        table: String,
+        field: Option<String>,
+        tag: Option<String>,
+        excluding_seen: bool,
+    },
+    // Makes the next `rolls` rolls on a table come up with `entry`, like `pin table "weather" to
+    // "storm" for 3 rolls`
+    PinTable {
+        table: String,
+        entry: String,
+        rolls: i32,
+    },
+    // Starts tracking an entity's hit points, like `track hp "goblin" 2d8`
+    TrackHitPoints {
+        entity: String,
+        amount: Amount,
+    },
+    TrackResource {
+        resource: String,
+        amount: i32,
+    },
+    UsageDie {
+        resource: String,
+        sides: i32,
+    },
+    UsageRoll(String),
+    // Runs the statements, then puts local facts back how they were before
+    WithFacts(Vec<Statement>),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ProcedureDeclaration(pub String);
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CrawlStr {
     Str(String),
     InterpolatedStr {
@@ -43,72 +331,463 @@ pub enum CrawlStr {
     },
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ModifiedRollSpecifier {
     // These fields are public so DiceRoll can implement TryFrom<ModifiedRollSpecifier>.
     // Don't really like it, but idk what the best thing to do is.
+    // A roll specifier, or the name of a roll defined with `define roll`
     pub base_roll_specifier: Token,
     pub modifier: i32,
+    // Modifiers only known when the roll is evaluated, added to `modifier`
+    #[serde(default)]
+    pub terms: Vec<ModifierTerm>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// A modifier added to or subtracted from a roll, looked up when it's rolled.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModifierTerm {
+    pub negated: bool,
+    pub value: ModifierValue,
+    // Only applies if this holds, like a bonus for having the high ground
+    #[serde(default)]
+    pub condition: Option<Box<Antecedent>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ModifierValue {
+    Fixed(i32),
+    // A variable bound with `let`
+    Variable(String),
+    // The numeric value of a fact, named by its entity and attribute, like `"fighter str-mod"`
+    Fact(String),
+    // A stat from a loaded statblock, like `stat "Brofist" "STR"`
+    Stat { statblock: String, stat: String },
+}
+
+/// How much to advance or spend by: a fixed number, or a roll.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Amount {
+    Fixed(i32),
+    Roll(ModifiedRollSpecifier),
+    // A variable, or else a procedure returning a number
+    Call(String),
+}
+
+/// What a procedure's `return` gives back to its caller.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReturnValue {
+    Amount(Amount),
+    Str(CrawlStr),
+}
+
+// A character and the activities they spend each week of downtime on
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DowntimeArm {
+    pub character: String,
+    pub activities: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CheckArm {
+    pub degree: Degree,
+    pub consequent: Statement,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MatchingRollArm {
     pub target: Token,
+    // Must also hold for the arm to match, or the roll falls through to later arms
+    #[serde(default)]
+    pub guard: Option<Antecedent>,
     pub consequent: Statement,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TableRollArm {
+    // The entry's text, or one of its tags - or a fact's value, when matching a fact
+    pub pattern: String,
+    pub consequent: Statement,
+}
+
+/// Derives a fact while all its conditions hold. Conditions can only check facts, so the rule
+/// can be re-checked whenever facts change.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub conditions: Vec<Antecedent>,
+    pub fact: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChoiceArm {
+    pub option: String,
+    pub consequent: Statement,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Antecedent {
+    CheckClockFull(String),
     CheckFact(String),
     CheckPersistentFact(String),
+    CheckResourceDepleted(String),
+    // Holds once an entity's hit points are down to 0
+    CheckDead(String),
+    // Holds while today is in the month, or on the weekday, named, or is a `full-moon` or
+    // `new-moon`
+    DateIs(String),
+    // Holds while the owner has at least one of the item
+    HasItem {
+        item: String,
+        owner: String,
+    },
+    // Holds if a d100 roll comes under the item's availability, in the settlement if it's given
+    InStock {
+        item: String,
+        settlement: Option<String>,
+    },
     DiceRoll {
         target: Token,
         roll_specifier: ModifiedRollSpecifier,
     },
+    // Holds if a roll against a DC comes to a degree of success that counts as `degree`
+    DegreeOfSuccess {
+        degree: Degree,
+        roll_specifier: ModifiedRollSpecifier,
+        dc: Amount,
+    },
+    // Holds if any die lands on its highest face, for a critical, or on a 1, for a fumble
+    NaturalRoll {
+        natural: Natural,
+        roll_specifier: ModifiedRollSpecifier,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Natural {
+    Critical,
+    Fumble,
+}
+
+/// The source line each parsed statement starts on. Top-level statements are indexed by their
+/// position in the program, and procedure body statements by their position in the body.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceMap {
+    pub top_level: Vec<usize>,
+    pub procedures: HashMap<String, Vec<usize>>,
 }
 
+/// Syntax that still parses, but is on its way out, with what to write instead if there's a
+/// straight swap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deprecation {
+    // Bytes of the source it covers, empty if the parser wasn't given spans
+    pub span: Range<usize>,
+    pub line: usize,
+    pub message: String,
+    pub replacement: Option<String>,
+}
+
+impl fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {} is deprecated", self.line, self.message)?;
+        if let Some(replacement) = &self.replacement {
+            write!(f, ", write {replacement} instead")?;
+        }
+        Ok(())
+    }
+}
+
+// Enough to see the token to be recognized and the one after it.
+const LOOKAHEAD: usize = 2;
+
+/// The version of the language this parser reads, as major and minor numbers. Scripts can say
+/// which version they're written for with a `crawl-version` line at the top.
+pub const GRAMMAR_VERSION: (u32, u32) = (0, 1);
+
+/// Parses statements one at a time, pulling tokens from `tokens` only as they're needed, so a
+/// program doesn't have to be scanned up front.
 #[derive(Debug)]
-pub struct Parser {
-    tokens: Vec<Token>,
-    position: usize, // Index of the token to be recognized
+pub struct Parser<I = vec::IntoIter<Result<Token, CrawlError>>> {
+    tokens: I,
+    // Pulled but not yet recognized tokens, with their index in `tokens`
+    lookahead: VecDeque<(Token, usize)>,
+    pulled: usize,
+    // Scanner errors with their index in `tokens`, reported in place of the statement they're in
+    scan_errors: VecDeque<(usize, CrawlError)>,
+    lines: Vec<usize>,
+    source_map: SourceMap,
+    // The line the statement, or `crawl-version` line, parsed last starts on
+    statement_line: Option<usize>,
+    // How many procedure bodies the statement being parsed is nested in, so lines indented
+    // under it can be told apart from the next statement in the body
+    depth: usize,
+    spans: Vec<Range<usize>>,
+    warnings: Vec<String>,
+    deprecations: Vec<Deprecation>,
 }
 
 // TODO: `reason` in parser error
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser {
+        let tokens: Vec<_> = tokens.into_iter().map(Ok).collect();
+        Parser::from_tokens(tokens.into_iter())
+    }
+}
+
+impl<I: Iterator<Item = Result<Token, CrawlError>>> Parser<I> {
+    /// Parse from a stream of tokens, like a `Scanner`.
+    pub fn from_tokens(tokens: I) -> Self {
+        let mut parser = Parser {
             tokens,
-            position: 0,
-        }
+            lookahead: VecDeque::with_capacity(LOOKAHEAD),
+            pulled: 0,
+            scan_errors: VecDeque::new(),
+            lines: Vec::new(),
+            source_map: SourceMap::default(),
+            statement_line: None,
+            depth: 0,
+            spans: Vec::new(),
+            warnings: Vec::new(),
+            deprecations: Vec::new(),
+        };
+        parser.fill_lookahead();
+        parser
+    }
+
+    /// Attach the line of each token (see `Scanner::lines`) so a `SourceMap` can be built.
+    pub fn with_lines(mut self, lines: Vec<usize>) -> Self {
+        self.lines = lines;
+        self
     }
 
+    /// Attach the span of each token (see `Scanner::spans`) so deprecations can point at it.
+    pub fn with_spans(mut self, spans: Vec<Range<usize>>) -> Self {
+        self.spans = spans;
+        self
+    }
+
+    /// Parse everything at once. Iterate over the parser instead to parse a statement at a time.
     pub fn parse(&mut self) -> Vec<Result<Statement, CrawlError>> {
-        let mut statements = Vec::new();
-        while !self.is_at_end() {
-            statements.push(Ok(self.statement().unwrap()));
+        self.collect()
+    }
+
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// The line the statement parsed last starts on, or the `crawl-version` line's, to point an
+    /// error at. `None` before anything's been parsed, or without lines.
+    pub fn statement_line(&self) -> Option<usize> {
+        self.statement_line
+    }
+
+    /// Things that parsed, but might not mean what they used to.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Syntax that parsed, but that scripts should move off of.
+    pub fn deprecations(&self) -> &[Deprecation] {
+        &self.deprecations
+    }
+
+    // A script written for a newer version of the language may not parse as intended, so it
+    // isn't parsed at all. One written for an older version is, with a warning.
+    fn crawl_version(&mut self) -> Result<(), CrawlError> {
+        self.consume(Token::CrawlVersion)?;
+        let Token::Version(major, minor) = *self.peek() else {
+            return Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            });
+        };
+        self.advance();
+        if !self.is_at_end() {
+            self.consume(Token::Newline)?;
+        }
+        while *self.peek() == Token::Newline {
+            self.advance();
+        }
+
+        let (supported_major, supported_minor) = GRAMMAR_VERSION;
+        if (major, minor) > GRAMMAR_VERSION {
+            return Err(CrawlError::VersionError {
+                script: format!("{major}.{minor}"),
+                supported: format!("{supported_major}.{supported_minor}"),
+            });
         }
-        statements
+        if (major, minor) < GRAMMAR_VERSION {
+            self.warnings.push(format!(
+                "script targets crawl {major}.{minor}, but this is crawl \
+                {supported_major}.{supported_minor}, so it may not run as it used to"
+            ));
+        }
+        Ok(())
     }
 
     fn statement(&mut self) -> Result<Statement, CrawlError> {
         let result = match self.peek() {
+            Token::Add if self.next_is("item") => self.add_item(),
+            Token::Add if self.next_is("combatant") => self.add_combatant(),
+            Token::Add if self.next_is("rumor") => self.add_rumor(),
+            Token::Add => self.add_money(),
+            Token::Advance if self.next_is("turn") => self.advance_turn(),
+            Token::Advance if self.next_is("date") => self.advance_date(),
+            Token::Advance if self.next_is("weather") => self.advance_weather(),
+            Token::Advance if self.next_is("time") => self.advance_time(),
+            Token::Advance if self.next_is("faction") => self.advance_fronts(),
+            Token::Advance => self.advance_clock(),
+            Token::Apply => self.apply_modifier(),
+            Token::Assert => self.assert(),
+            Token::AssertEqual => self.assert_equal(),
+            Token::Campaign => self.campaign(),
+            Token::Check => self.check(),
+            Token::Move => self.move_roll(),
+            Token::Choose => self.choice(),
             Token::ClearFact => self.clear_fact(),
             Token::ClearPersistentFact => self.clear_persistent_fact(),
+            Token::Create if self.next_is("faction") => self.create_front(),
+            Token::Create => self.create_clock(),
+            Token::Define => self.define_roll(),
+            Token::Generator => self.generator(),
+            Token::Identifier(word) if word == "sort" && self.next_is("combatants") => {
+                self.sort_combatants()
+            }
+            // Not a keyword, so `damage` can still name a variable
+            Token::Identifier(word)
+                if word == "damage" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.damage()
+            }
+            Token::Identifier(word)
+                if word == "travel" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.travel()
+            }
+            Token::Identifier(word)
+                if word == "encounter" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.encounter()
+            }
+            Token::Identifier(word)
+                if word == "generate"
+                    && matches!(self.peek_next(), Token::Identifier(next) if next == "room") =>
+            {
+                self.words(&["generate", "room"])?;
+                Ok(Statement::GenerateRoom)
+            }
+            Token::Identifier(word) if word == "morale" && *self.peek_next() == Token::Check => {
+                self.morale_check()
+            }
+            Token::Identifier(word) if word == "pin" && *self.peek_next() == Token::Table => {
+                self.pin_table()
+            }
+            Token::Identifier(word) if word == "scene" && *self.peek_next() == Token::Check => {
+                self.advance();
+                self.consume(Token::Check)?;
+                Ok(Statement::SceneCheck)
+            }
+            Token::Identifier(word)
+                if word == "save" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.save()
+            }
+            Token::Identifier(word)
+                if word == "downtime"
+                    && matches!(
+                        self.peek_next(),
+                        Token::Num(_) | Token::RollSpecifier(_) | Token::Identifier(_)
+                    ) =>
+            {
+                self.downtime()
+            }
+            Token::Identifier(word)
+                if word == "award"
+                    && matches!(
+                        self.peek_next(),
+                        Token::Num(_) | Token::RollSpecifier(_) | Token::Identifier(_)
+                    ) =>
+            {
+                self.award()
+            }
+            Token::Identifier(word)
+                if word == "price" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.advance();
+                let item = self.string()?;
+                let settlement = self.settlement()?;
+                Ok(Statement::Price { item, settlement })
+            }
+            Token::Identifier(word) if word == "haggle" && *self.peek_next() == Token::Roll => {
+                self.advance();
+                self.consume(Token::Roll)?;
+                let item = self.string()?;
+                let settlement = self.settlement()?;
+                Ok(Statement::Haggle { item, settlement })
+            }
+            Token::Identifier(word)
+                if (word == "increase" || word == "decrease") && self.next_is("chaos") =>
+            {
+                self.adjust_chaos()
+            }
+            Token::Identifier(word) if word == "reveal" && self.next_is("rumor") => {
+                self.reveal_rumor()
+            }
+            Token::Identifier(word)
+                if word == "hear"
+                    && matches!(self.peek_next(), Token::Num(_) | Token::RollSpecifier(_)) =>
+            {
+                self.hear_rumors()
+            }
+            Token::Identifier(word)
+                if word == "light" && matches!(self.peek_next(), Token::Identifier(_)) =>
+            {
+                self.light()
+            }
             Token::Identifier(_) => self.procedure_call(),
             Token::If => self.if_then(),
+            Token::Include => self.include(),
+            Token::Let => self.let_binding(),
+            Token::Load if self.next_is("names") => self.load_names(),
+            Token::Load if self.next_is("statblocks") => self.load_statblocks(),
+            Token::Load if self.next_is("prices") => {
+                self.consume(Token::Load)?;
+                self.words(&["prices"])?;
+                Ok(Statement::LoadPrices(self.string()?))
+            }
             Token::Load => self.load_table(),
+            Token::Match => self.matching_fact(),
+            Token::Oracle => self.oracle(),
             Token::Procedure => self.procedure(),
             Token::Reminder => self.reminder(),
+            Token::Remove if self.next_is("combatant") => self.remove_combatant(),
+            Token::Remove if self.next_is("item") => self.remove_item(),
+            Token::Remove => self.remove_modifier(),
+            Token::Report if self.next_is("inventory") => self.report_inventory(),
+            Token::Report if self.next_is("xp") => self.report_xp(),
+            Token::Report if self.next_is("rumors") => {
+                self.consume(Token::Report)?;
+                self.words(&["rumors"])?;
+                Ok(Statement::ReportRumors)
+            }
+            Token::Report => self.report_facts(),
+            Token::Return => self.return_value(),
+            Token::Rule => self.rule(),
+            Token::Roll if self.next_is("name") => self.name_roll(),
             Token::Roll => match self.peek_next() {
-                Token::On => self.table_roll(),
-                Token::RollSpecifier(_) => self.matching_roll(),
+                Token::On => self.matching_table_roll(),
+                Token::RollSpecifier(_) | Token::Str(_) => self.matching_roll(),
+                Token::Usage => self.usage_roll(),
                 _ => Err(CrawlError::ParserError {
                     token: format!("{:?}", self.peek()),
                 }),
             },
             Token::SetFact => self.set_fact(),
             Token::SetPersistentFact => self.set_persistent_fact(),
+            Token::Spend => self.spend(),
+            Token::Track if self.next_is("hp") => self.track_hit_points(),
+            Token::Track => self.track_resource(),
+            Token::UsageDie => self.usage_die(),
+            Token::WithFacts => self.with_facts(),
             _ => Err(CrawlError::ParserError {
                 token: format!("{:?}", self.peek()),
             }),
@@ -131,7 +810,7 @@ impl Parser {
     }
 
     fn procedure(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::Procedure).expect("expected procedure");
+        self.consume(Token::Procedure)?;
 
         let declaration = if let Token::Identifier(name) = self.peek() {
             Ok(ProcedureDeclaration(name.clone()))
@@ -142,22 +821,65 @@ impl Parser {
         }?;
 
         self.advance();
-        self.consume(Token::Newline).expect("expected newline");
+        self.consume(Token::Newline)?;
+
+        self.depth += 1;
+        let body = self.procedure_body();
+        self.depth -= 1;
+        let (body, body_lines) = body?;
+
+        self.source_map
+            .procedures
+            .insert(declaration.0.clone(), body_lines);
+
+        Ok(Statement::Procedure {
+            declaration,
+            body: body.into(),
+        })
+    }
 
+    // The statements in a procedure, each indented one deeper than it, up to its `end`.
+    fn procedure_body(&mut self) -> Result<(Vec<Statement>, Vec<usize>), CrawlError> {
         let mut body = Vec::new();
-        while *self.peek() != Token::End {
-            self.consume(Token::Indent).expect("expected indent");
-            body.push(Box::new(self.statement()?));
+        let mut body_lines = Vec::new();
+        loop {
+            // A procedure defined in another is indented along with the rest of its body.
+            for _ in 1..self.depth {
+                self.consume(Token::Indent)?;
+            }
+            if *self.peek() == Token::End {
+                break;
+            }
+            self.consume(Token::Indent)?;
+            body_lines.push(self.line());
+            body.push(self.statement()?);
         }
-        self.consume(Token::End).expect("expected end");
+        self.consume(Token::End)?;
+        Ok((body, body_lines))
+    }
 
-        Ok(Statement::Procedure { declaration, body })
+    fn with_facts(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::WithFacts)?;
+        self.consume(Token::Newline)?;
+        // The block's statements are indented like a procedure's.
+        self.depth += 1;
+        let body = self.procedure_body();
+        self.depth -= 1;
+        Ok(Statement::WithFacts(body?.0))
     }
 
     fn procedure_call(&mut self) -> Result<Statement, CrawlError> {
-        if let Token::Identifier(name) = self.peek().clone() {
+        if let Token::Identifier(identifier) = self.peek().clone() {
             self.advance();
-            Ok(Statement::ProcedureCall(name))
+            let mut arguments = Vec::new();
+            while let Token::Str(argument) = self.peek() {
+                arguments.push(argument.clone());
+                self.advance();
+            }
+            Ok(Statement::ProcedureCall {
+                identifier,
+                arguments,
+            })
         } else {
             Err(CrawlError::ParserError {
                 token: format!("{:?}", self.peek()),
@@ -166,10 +888,10 @@ impl Parser {
     }
 
     fn if_then(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::If).expect("expected if");
+        self.consume(Token::If)?;
         let antecedent = self.antecedent()?;
 
-        self.consume(Token::Arrow).expect("expected arrow");
+        self.consume(Token::Arrow)?;
         let consequent = self.consequent()?;
 
         Ok(Statement::IfThen {
@@ -179,14 +901,58 @@ impl Parser {
     }
 
     fn matching_roll(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::Roll).expect("expected roll");
+        self.consume(Token::Roll)?;
         let roll_specifier = self.modified_specifier()?;
 
-        self.consume(Token::Newline).expect("expected newline");
+        self.consume(Token::Newline)?;
+        let (arms, otherwise) = self.roll_arms(false)?;
+
+        Ok(Statement::MatchingRoll {
+            roll_specifier,
+            arms,
+            otherwise,
+        })
+    }
+
+    fn move_roll(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Move)?;
+        let name = self.string()?;
+        let (modifier, terms) = match self.peek() {
+            Token::Identifier(word) if word == "with" => {
+                self.advance();
+                if !matches!(self.peek(), Token::Plus | Token::Minus) {
+                    return Err(CrawlError::ParserError {
+                        token: format!("{:?}", self.peek()),
+                    });
+                }
+                self.modifier_terms()?
+            }
+            _ => (0, Vec::new()),
+        };
+        self.consume(Token::Newline)?;
+        let (arms, otherwise) = self.roll_arms(true)?;
+
+        Ok(Statement::Move {
+            name,
+            roll_specifier: ModifiedRollSpecifier {
+                base_roll_specifier: Token::RollSpecifier(RollSpec::new(2, 6)),
+                modifier,
+                terms,
+            },
+            arms,
+            otherwise,
+        })
+    }
 
+    // The arms of a matching roll, up to its `end`, each starting with `on` in a move.
+    fn roll_arms(
+        &mut self,
+        on: bool,
+    ) -> Result<(Vec<MatchingRollArm>, Option<Box<Statement>>), CrawlError> {
         let mut arms: Vec<MatchingRollArm> = Vec::new();
+        let mut otherwise = None;
         while *self.peek() != Token::End {
-            self.consume(Token::Indent).expect("expected indent");
+            self.consume(Token::Indent)?;
             while *self.peek() == Token::Indent {
                 self.advance();
             }
@@ -196,558 +962,3508 @@ impl Parser {
                 break;
             }
 
+            if *self.peek() == Token::Else && otherwise.is_none() {
+                self.advance();
+                self.consume(Token::Arrow)?;
+                otherwise = Some(Box::new(self.consequent()?));
+                self.consume(Token::Newline)?;
+                continue;
+            }
+
+            if on {
+                self.consume(Token::On)?;
+            }
             let target = match self.peek() {
-                Token::Num(_) | Token::NumRange(_, _) => Ok(self.peek().clone()),
+                Token::Num(_)
+                | Token::NumRange(_, _)
+                | Token::OverOrEqual(_)
+                | Token::UnderOrEqual(_)
+                | Token::Identifier(_) => Ok(self.peek().clone()),
                 _ => Err(CrawlError::ParserError {
                     token: format!("{:?}", self.peek()),
                 }),
             }?;
             self.advance();
+            let guard = if *self.peek() == Token::If {
+                self.advance();
+                Some(self.antecedent()?)
+            } else {
+                None
+            };
 
-            self.consume(Token::Arrow).expect("expected arrow");
+            self.consume(Token::Arrow)?;
             let consequent = self.consequent()?;
-            let arm = MatchingRollArm { target, consequent };
+            let arm = MatchingRollArm {
+                target,
+                guard,
+                consequent,
+            };
             arms.push(arm);
 
-            self.consume(Token::Newline).expect("expected newline");
+            self.consume(Token::Newline)?;
         }
 
-        self.consume(Token::End).expect("expected end");
+        self.consume(Token::End)?;
+        Ok((arms, otherwise))
+    }
 
-        Ok(Statement::MatchingRoll {
+    fn check(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Check)?;
+        let roll_specifier = self.modified_specifier()?;
+        self.words(&["vs"])?;
+        let dc = self.amount()?;
+        self.consume(Token::Newline)?;
+        let (arms, otherwise) = self.check_arms()?;
+
+        Ok(Statement::Check {
             roll_specifier,
+            dc,
             arms,
+            otherwise,
         })
     }
 
-    fn modified_specifier(&mut self) -> Result<ModifiedRollSpecifier, CrawlError> {
-        let base_roll_specifier = if let Token::RollSpecifier(_) = self.peek() {
-            Ok(self.peek().clone())
-        } else {
-            Err(CrawlError::ParserError {
-                token: format!("{:?}", self.peek()),
-            })
-        }?;
+    fn save(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["save"])?;
+        let statblock = self.string()?;
+        self.words(&["vs"])?;
+        let save = self.string()?;
+        self.consume(Token::Newline)?;
+        let (arms, otherwise) = self.check_arms()?;
+
+        Ok(Statement::Save {
+            statblock,
+            save,
+            arms,
+            otherwise,
+        })
+    }
+
+    fn pin_table(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["pin"])?;
+        self.consume(Token::Table)?;
+        let table = self.string()?;
+        self.words(&["to"])?;
+        let entry = self.string()?;
+        self.words(&["for"])?;
+        let rolls = match self.peek() {
+            Token::Num(rolls) if *rolls > 0 => *rolls,
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        };
         self.advance();
+        match self.peek() {
+            Token::Roll => self.advance(),
+            Token::Identifier(word) if word == "rolls" => self.advance(),
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        }
+        Ok(Statement::PinTable {
+            table,
+            entry,
+            rolls,
+        })
+    }
 
-        let mut modifier: i32 = 0;
+    fn downtime(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["downtime"])?;
+        let weeks = self.amount()?;
         match self.peek() {
-            Token::Plus => {
+            Token::Identifier(word) if word == "week" || word == "weeks" => self.advance(),
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        }
+        self.consume(Token::Newline)?;
+
+        let mut arms = Vec::new();
+        while *self.peek() != Token::End {
+            self.consume(Token::Indent)?;
+            while *self.peek() == Token::Indent {
                 self.advance();
-                if let Token::Num(n) = self.peek() {
-                    modifier = *n;
-                }
+            }
+            if *self.peek() == Token::End {
+                break;
+            }
+            let character = self.string()?;
+            self.consume(Token::Arrow)?;
+            let mut activities = Vec::new();
+            while let Token::Identifier(activity) = self.peek().clone() {
                 self.advance();
+                activities.push(activity);
+            }
+            if activities.is_empty() {
+                return Err(CrawlError::ParserError {
+                    token: format!("{:?}", self.peek()),
+                });
             }
-            Token::Minus => {
+            arms.push(DowntimeArm {
+                character,
+                activities,
+            });
+            self.consume(Token::Newline)?;
+        }
+        self.consume(Token::End)?;
+        Ok(Statement::Downtime { weeks, arms })
+    }
+
+    // Arms of degrees of success, and an `else` arm, up to `end`
+    fn check_arms(&mut self) -> Result<(Vec<CheckArm>, Option<Box<Statement>>), CrawlError> {
+        let mut arms = Vec::new();
+        let mut otherwise = None;
+        while *self.peek() != Token::End {
+            self.consume(Token::Indent)?;
+            while *self.peek() == Token::Indent {
                 self.advance();
-                if let Token::Num(n) = self.peek() {
-                    modifier = -*n;
-                }
+            }
+            if *self.peek() == Token::End {
+                break;
+            }
+
+            if *self.peek() == Token::Else && otherwise.is_none() {
                 self.advance();
+                self.consume(Token::Arrow)?;
+                otherwise = Some(Box::new(self.consequent()?));
+                self.consume(Token::Newline)?;
+                continue;
             }
-            _ => modifier = 0,
-        }
 
-        Ok(ModifiedRollSpecifier {
-            base_roll_specifier,
-            modifier,
-        })
+            let degree = self.degree()?;
+            self.consume(Token::Arrow)?;
+            let consequent = self.consequent()?;
+            arms.push(CheckArm { degree, consequent });
+            self.consume(Token::Newline)?;
+        }
+        self.consume(Token::End)?;
+        Ok((arms, otherwise))
     }
 
-    fn reminder(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::Reminder).expect("expected reminder");
+    fn choice(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Choose)?;
+        let question = self.string()?;
+        self.consume(Token::Newline)?;
 
-        let reminder = if let Token::Str(reminder) = self.peek() {
-            Ok(reminder.clone())
+        let mut arms = Vec::new();
+        while *self.peek() != Token::End {
+            self.consume(Token::Indent)?;
+            while *self.peek() == Token::Indent {
+                self.advance();
+            }
+            if *self.peek() == Token::End {
+                break;
+            }
+
+            let option = self.string()?;
+            self.consume(Token::Arrow)?;
+            let consequent = self.consequent()?;
+            arms.push(ChoiceArm { option, consequent });
+
+            self.consume(Token::Newline)?;
+        }
+
+        self.consume(Token::End)?;
+
+        Ok(Statement::Choice { question, arms })
+    }
+
+    fn modified_specifier(&mut self) -> Result<ModifiedRollSpecifier, CrawlError> {
+        let base_roll_specifier = if let Token::RollSpecifier(_) | Token::Str(_) = self.peek() {
+            Ok(self.peek().clone())
         } else {
             Err(CrawlError::ParserError {
                 token: format!("{:?}", self.peek()),
             })
         }?;
-
         self.advance();
+        let (modifier, terms) = self.modifier_terms()?;
 
-        Ok(Statement::Reminder(reminder))
+        Ok(ModifiedRollSpecifier {
+            base_roll_specifier,
+            modifier,
+            terms,
+        })
     }
 
-    fn load_table(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::Load).expect("expected load");
-        self.consume(Token::Table).expect("expected table");
+    // Any `+ 2`, `- x`, or `+ 1 if ...` following a roll, with the fixed numbers summed.
+    fn modifier_terms(&mut self) -> Result<(i32, Vec<ModifierTerm>), CrawlError> {
+        let mut modifier: i32 = 0;
+        let mut terms = Vec::new();
+        loop {
+            let negated = match self.peek() {
+                Token::Plus => false,
+                Token::Minus => true,
+                _ => break,
+            };
+            self.advance();
+            let value = self.modifier_value()?;
+            let condition = if *self.peek() == Token::If {
+                self.advance();
+                Some(Box::new(self.antecedent()?))
+            } else {
+                None
+            };
+
+            // Unconditional numbers are known now, so they're folded in.
+            match (value, condition) {
+                (ModifierValue::Fixed(n), None) => {
+                    let n = if negated { -n } else { n };
+                    modifier = modifier
+                        .checked_add(n)
+                        .ok_or_else(|| CrawlError::ParserError {
+                            token: n.to_string(),
+                        })?;
+                }
+                (value, condition) => terms.push(ModifierTerm {
+                    negated,
+                    value,
+                    condition,
+                }),
+            }
+        }
+        Ok((modifier, terms))
+    }
 
-        let load_table = if let Token::Str(table_name) = self.peek() {
-            Ok(Statement::LoadTable(table_name.clone()))
+    fn modifier_value(&mut self) -> Result<ModifierValue, CrawlError> {
+        let value = match self.peek() {
+            Token::Num(n) => ModifierValue::Fixed(*n),
+            Token::Identifier(name)
+                if name == "fact" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.advance();
+                return Ok(ModifierValue::Fact(self.string()?));
+            }
+            Token::Identifier(name)
+                if name == "stat" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.advance();
+                let statblock = self.string()?;
+                let stat = self.string()?;
+                return Ok(ModifierValue::Stat { statblock, stat });
+            }
+            Token::Identifier(name) => ModifierValue::Variable(name.clone()),
+            _ => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{:?}", self.peek()),
+                });
+            }
+        };
+        self.advance();
+        Ok(value)
+    }
+
+    fn reminder(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Reminder)?;
+
+        let reminder = if let Token::Str(reminder) = self.peek() {
+            Ok(reminder.clone())
         } else {
             Err(CrawlError::ParserError {
                 token: format!("{:?}", self.peek()),
             })
-        };
+        }?;
 
         self.advance();
 
-        load_table
+        Ok(Statement::Reminder(reminder))
     }
 
-    fn antecedent(&mut self) -> Result<Antecedent, CrawlError> {
-        match self.peek() {
-            Token::Roll => self.dice_roll(),
-            Token::FactTest => self.fact_check(),
-            Token::PersistentFactTest => self.persistent_fact_check(),
-            _ => Err(CrawlError::ParserError {
-                token: format!("{:?}", self.peek()),
-            }),
-        }
-    }
+    fn load_table(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Load)?;
+        self.consume(Token::Table)?;
 
-    fn consequent(&mut self) -> Result<Statement, CrawlError> {
-        match self.peek() {
-            Token::ClearFact => self.clear_fact(),
-            Token::ClearPersistentFact => self.clear_persistent_fact(),
-            Token::Identifier(_) => self.procedure_call(),
-            Token::Reminder => self.reminder(),
-            Token::Roll => self.table_roll(),
-            Token::SetFact => self.set_fact(),
-            Token::SetPersistentFact => self.set_persistent_fact(),
-            _ => Err(CrawlError::ParserError {
-                token: format!("{:?}", self.peek()),
-            }),
+        let table = self.string()?;
+        let no_clamp = matches!(self.peek(), Token::Identifier(option) if option == "no-clamp");
+        if no_clamp {
+            self.advance();
         }
-    }
-
-    fn dice_roll(&mut self) -> Result<Antecedent, CrawlError> {
-        self.consume(Token::Roll).expect("expected roll");
-        let target = match self.peek() {
-            Token::Num(_) | Token::NumRange(_, _) => Ok(self.peek().clone()),
-            _ => Err(CrawlError::ParserError {
-                token: format!("{:?}", self.peek()),
-            }),
-        }?;
 
-        self.advance();
+        Ok(Statement::LoadTable { table, no_clamp })
+    }
 
-        self.consume(Token::On).expect("expected on");
+    fn load_names(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Load)?;
+        self.words(&["names"])?;
+        Ok(Statement::LoadNames(self.string()?))
+    }
 
-        let roll_specifier = self.modified_specifier()?;
+    fn load_statblocks(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Load)?;
+        self.words(&["statblocks"])?;
+        Ok(Statement::LoadStatblocks(self.string()?))
+    }
 
-        Ok(Antecedent::DiceRoll {
-            target,
-            roll_specifier,
-        })
+    fn name_roll(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Roll)?;
+        self.words(&["name", "from"])?;
+        Ok(Statement::NameRoll(self.string()?))
     }
 
-    fn fact_check(&mut self) -> Result<Antecedent, CrawlError> {
-        self.consume(Token::FactTest).expect("expected fact?");
-        let fact = if let Token::Str(fact) = self.peek() {
-            Ok(fact.clone())
+    fn include(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Include)?;
+
+        let path = if let Token::Str(path) = self.peek() {
+            Ok(path.clone())
         } else {
             Err(CrawlError::ParserError {
                 token: format!("{:?}", self.peek()),
             })
-        };
+        }?;
 
         self.advance();
 
-        Ok(Antecedent::CheckFact(fact?))
+        let mut namespace = None;
+        if *self.peek() == Token::As {
+            self.advance();
+            namespace = if let Token::Identifier(namespace) = self.peek() {
+                Ok(Some(namespace.clone()))
+            } else {
+                Err(CrawlError::ParserError {
+                    token: format!("{:?}", self.peek()),
+                })
+            }?;
+            self.advance();
+        }
+
+        Ok(Statement::Include { path, namespace })
     }
 
-    fn persistent_fact_check(&mut self) -> Result<Antecedent, CrawlError> {
-        self.consume(Token::PersistentFactTest)
-            .expect("expected persistent-fact?");
-        let fact = if let Token::Str(fact) = self.peek() {
-            Ok(fact.clone())
+    fn create_clock(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Create)?;
+        self.consume(Token::Clock)?;
+        let clock = self.string()?;
+
+        let segments = if let Token::Num(segments) = self.peek() {
+            Ok(*segments)
         } else {
             Err(CrawlError::ParserError {
                 token: format!("{:?}", self.peek()),
             })
-        };
-
+        }?;
         self.advance();
 
-        Ok(Antecedent::CheckPersistentFact(fact?))
+        Ok(Statement::CreateClock { clock, segments })
     }
 
-    fn set_fact(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::SetFact).expect("expected set-fact");
-        let fact = self.str()?;
+    fn create_front(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Create)?;
+        self.words(&["faction"])?;
+        self.consume(Token::Clock)?;
+        let faction = self.string()?;
+        let segments = match self.peek() {
+            Token::Num(segments) => *segments,
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        };
+        self.advance();
+        Ok(Statement::CreateFront { faction, segments })
+    }
 
-        Ok(Statement::SetFact(fact))
+    fn advance_fronts(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Advance)?;
+        self.words(&["faction", "fronts"])?;
+        let table = match self.peek() {
+            Token::On => {
+                self.advance();
+                self.consume(Token::Table)?;
+                self.string()?
+            }
+            _ => FRONTS_TABLE.into(),
+        };
+        Ok(Statement::AdvanceFronts(table))
     }
 
-    fn set_persistent_fact(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::SetPersistentFact)
-            .expect("expected set-persistent-fact");
-        let fact = if let Token::Str(fact) = self.peek() {
-            Ok(fact.clone())
+    fn advance_clock(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Advance)?;
+        self.consume(Token::Clock)?;
+        let clock = self.string()?;
+        self.consume(Token::By)?;
+        let amount = self.amount()?;
+        Ok(Statement::AdvanceClock { clock, amount })
+    }
+
+    fn track_resource(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Track)?;
+        let resource = self.string()?;
+
+        let amount = if let Token::Num(amount) = self.peek() {
+            Ok(*amount)
         } else {
             Err(CrawlError::ParserError {
                 token: format!("{:?}", self.peek()),
             })
         }?;
-
         self.advance();
 
-        Ok(Statement::SetPersistentFact(fact))
+        Ok(Statement::TrackResource { resource, amount })
     }
 
-    fn clear_fact(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::ClearFact).expect("expected clear-fact");
-        let fact = if let Token::Str(fact) = self.peek() {
-            Ok(fact.clone())
+    // Spends a resource, or coins when the amount's followed by a coin rather than a string.
+    fn spend(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Spend)?;
+        let amount = self.amount()?;
+        if let Token::Str(_) = self.peek() {
+            let resource = self.string()?;
+            return Ok(Statement::SpendResource { resource, amount });
+        }
+        let coin = self.coin()?;
+        let purse = if matches!(self.peek(), Token::Identifier(word) if word == "from") {
+            self.advance();
+            Some(self.string()?)
         } else {
-            Err(CrawlError::ParserError {
-                token: format!("{:?}", self.peek()),
-            })
-        }?;
+            None
+        };
+        Ok(Statement::SpendMoney {
+            amount,
+            coin,
+            purse,
+        })
+    }
+
+    fn add_money(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Add)?;
+        let amount = self.amount()?;
+        let coin = self.coin()?;
+        self.words(&["to"])?;
+        let purse = self.string()?;
+        Ok(Statement::AddMoney {
+            amount,
+            coin,
+            purse,
+        })
+    }
 
+    // A coin's name, like `gp`, which the interpreter checks it knows.
+    fn coin(&mut self) -> Result<String, CrawlError> {
+        let Token::Identifier(coin) = self.peek() else {
+            return Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            });
+        };
+        let coin = coin.clone();
         self.advance();
+        Ok(coin)
+    }
 
-        Ok(Statement::ClearFact(fact))
+    fn track_hit_points(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Track)?;
+        self.words(&["hp"])?;
+        let entity = self.string()?;
+        let amount = self.amount()?;
+        Ok(Statement::TrackHitPoints { entity, amount })
     }
 
-    fn clear_persistent_fact(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::ClearPersistentFact)
-            .expect("expected clear-persistent-fact");
-        let fact = if let Token::Str(fact) = self.peek() {
-            Ok(fact.clone())
-        } else {
-            Err(CrawlError::ParserError {
-                token: format!("{:?}", self.peek()),
-            })
-        }?;
+    fn damage(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["damage"])?;
+        let entity = self.string()?;
+        let amount = self.amount()?;
+        Ok(Statement::Damage { entity, amount })
+    }
 
-        self.advance();
+    fn travel(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["travel"])?;
+        Ok(Statement::Travel(self.string()?))
+    }
 
-        Ok(Statement::ClearPersistentFact(fact))
+    fn encounter(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["encounter"])?;
+        Ok(Statement::Encounter(self.string()?))
     }
 
-    fn table_roll(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::Roll).expect("expected roll");
-        self.consume(Token::On).expect("expected on");
-        self.consume(Token::Table).expect("expected table");
-        let table_identifier = if let Token::Str(table_id) = self.peek() {
-            Ok(table_id.to_string())
-        } else {
-            Err(CrawlError::ParserError {
+    fn light(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["light"])?;
+        match self.peek() {
+            Token::Identifier(light) => {
+                let light = light.clone();
+                self.advance();
+                Ok(Statement::Light(light))
+            }
+            token => Err(CrawlError::ParserError {
+                token: format!("{token:?}"),
+            }),
+        }
+    }
+
+    fn morale_check(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["morale"])?;
+        self.consume(Token::Check)?;
+        let group = self.string()?;
+        let morale = self.modifier_value()?;
+        Ok(Statement::MoraleCheck { group, morale })
+    }
+
+    fn usage_die(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::UsageDie)?;
+        let resource = self.string()?;
+
+        let sides = match self.peek() {
+            Token::Die(sides) => Ok(*sides),
+            Token::RollSpecifier(spec) if spec.n_dice == 1 => Ok(spec.n_sides),
+            _ => Err(CrawlError::ParserError {
                 token: format!("{:?}", self.peek()),
-            })
+            }),
         }?;
-
         self.advance();
 
-        Ok(Statement::TableRoll(table_identifier))
+        Ok(Statement::UsageDie { resource, sides })
     }
 
-    fn nontargeted_roll(&mut self) -> Result<Statement, CrawlError> {
-        self.consume(Token::Roll).expect("expected roll");
-        let spec = self.modified_specifier()?;
-        Ok(Statement::NontargetedRoll(spec))
+    fn usage_roll(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Roll)?;
+        self.consume(Token::Usage)?;
+        let resource = self.string()?;
+        Ok(Statement::UsageRoll(resource))
     }
 
-    fn str(&mut self) -> Result<CrawlStr, CrawlError> {
-        let s = if let Token::Str(st) = self.peek() {
-            Ok(st.clone())
-        } else {
-            Err(CrawlError::ParserError {
+    fn let_binding(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Let)?;
+        let name = match self.peek() {
+            Token::Identifier(name) => Ok(name.clone()),
+            _ => Err(CrawlError::ParserError {
                 token: format!("{:?}", self.peek()),
-            })
+            }),
         }?;
-
         self.advance();
+        self.consume(Token::Equals)?;
 
-        // TODO: only allows one placeholder
-        if let Token::Percent = *self.peek() {
+        let value = match self.peek() {
+            Token::Roll => {
+                self.advance();
+                Amount::Roll(self.modified_specifier()?)
+            }
+            _ => self.amount()?,
+        };
+        Ok(Statement::Let { name, value })
+    }
+
+    fn return_value(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Return)?;
+        Ok(Statement::Return(self.value()?))
+    }
+
+    fn campaign(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Campaign)?;
+        Ok(Statement::Campaign(self.string()?))
+    }
+
+    fn report_facts(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Report)?;
+        self.words(&["facts", "for"])?;
+        Ok(Statement::ReportFacts(self.string()?))
+    }
+
+    fn report_inventory(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Report)?;
+        self.words(&["inventory", "for"])?;
+        Ok(Statement::ReportInventory(self.string()?))
+    }
+
+    fn report_xp(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Report)?;
+        self.words(&["xp"])?;
+        Ok(Statement::ReportXp)
+    }
+
+    fn add_rumor(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Add)?;
+        self.words(&["rumor"])?;
+        let rumor = self.string()?;
+        let truth = if matches!(self.peek(), Token::Identifier(word) if word == "truth") {
             self.advance();
-            let expr = match self.peek_next() {
-                Token::On => self.table_roll(),
-                Token::RollSpecifier(_) => self.nontargeted_roll(),
-                _ => Err(CrawlError::ParserError {
-                    token: format!("{:?}", self.peek()),
-                }),
-            }?;
-            Ok(CrawlStr::InterpolatedStr {
-                format_string: s,
-                expressions: vec![expr],
-            })
+            self.truth(true)?
         } else {
-            Ok(CrawlStr::Str(s))
-        }
+            None
+        };
+        Ok(Statement::AddRumor { rumor, truth })
     }
 
-    fn advance(&mut self) {
-        self.position += 1;
+    fn reveal_rumor(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["reveal", "rumor"])?;
+        let rumor = self.string()?;
+        let truth = if *self.peek() == Token::As {
+            self.advance();
+            self.truth(false)?
+        } else {
+            None
+        };
+        Ok(Statement::RevealRumor { rumor, truth })
     }
 
-    fn consume(&mut self, token: Token) -> Result<Token, CrawlError> {
-        if *self.peek() == token {
+    // `true` or `false`, or `hidden` for a truth not decided yet, if that's allowed
+    fn truth(&mut self, hidden: bool) -> Result<Option<bool>, CrawlError> {
+        let truth = match self.peek() {
+            Token::Identifier(word) if word == "true" => Some(true),
+            Token::Identifier(word) if word == "false" => Some(false),
+            Token::Identifier(word) if word == "hidden" && hidden => None,
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        };
+        self.advance();
+        Ok(truth)
+    }
+
+    fn adjust_chaos(&mut self) -> Result<Statement, CrawlError> {
+        let by = match self.peek() {
+            Token::Identifier(word) if word == "increase" => 1,
+            _ => -1,
+        };
+        self.advance();
+        self.words(&["chaos", "factor"])?;
+        Ok(Statement::AdjustChaos(by))
+    }
+
+    // The settlement a price is asked in, like `at "village"`, if one's given
+    fn settlement(&mut self) -> Result<Option<String>, CrawlError> {
+        if matches!(self.peek(), Token::Identifier(word) if word == "at") {
             self.advance();
-            Ok(token)
+            Ok(Some(self.string()?))
         } else {
-            Err(CrawlError::ParserError {
-                token: format!("{:?}", self.peek()),
-            })
+            Ok(None)
         }
     }
 
-    fn peek(&self) -> &Token {
-        if self.tokens.len() > self.position {
-            return &self.tokens[self.position];
+    fn hear_rumors(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["hear"])?;
+        let amount = self.amount()?;
+        match self.peek() {
+            Token::Identifier(word) if word == "rumor" || word == "rumors" => self.advance(),
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
         }
-        &Token::Eof
+        Ok(Statement::HearRumors(amount))
     }
 
-    fn peek_next(&self) -> &Token {
-        if self.tokens.len() > self.position + 1 {
-            return &self.tokens[self.position + 1];
+    fn award(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["award"])?;
+        if matches!(self.peek(), Token::Identifier(word) if word == "treasure") {
+            self.advance();
+            let amount = self.amount()?;
+            let multiplier = self.multiplier()?;
+            let coin = self.coin()?;
+            return Ok(Statement::AwardTreasure {
+                amount,
+                multiplier,
+                coin,
+            });
         }
-        &Token::Eof
+        let amount = self.amount()?;
+        let multiplier = self.multiplier()?;
+        self.words(&["xp"])?;
+        Ok(Statement::AwardXp { amount, multiplier })
     }
 
-    fn is_at_end(&self) -> bool {
-        *self.peek() == Token::Eof
+    // An optional `x 100` after an amount, multiplying it.
+    fn multiplier(&mut self) -> Result<i32, CrawlError> {
+        if !matches!(self.peek(), Token::Identifier(word) if word == "x") {
+            return Ok(1);
+        }
+        self.advance();
+        match self.peek() {
+            Token::Num(n) => {
+                let n = *n;
+                self.advance();
+                Ok(n)
+            }
+            token => Err(CrawlError::ParserError {
+                token: format!("{token:?}"),
+            }),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn add_item(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Add)?;
+        self.words(&["item"])?;
+        let item = self.string()?;
+        self.words(&["to"])?;
+        let owner = self.string()?;
+        Ok(Statement::AddItem { item, owner })
+    }
 
-    #[test]
-    fn parse_procedure_call() {
-        let toks = vec![
-            Token::Identifier("proc-name".into()),
-            Token::Newline,
-            Token::Eof,
-        ];
-        let parsed: Vec<Statement> = Parser::new(toks)
-            .parse()
-            .into_iter()
-            .map(|t| t.unwrap())
-            .collect();
-        assert_eq!(parsed, vec![Statement::ProcedureCall("proc-name".into())]);
+    fn remove_item(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Remove)?;
+        self.words(&["item"])?;
+        let item = self.string()?;
+        self.words(&["from"])?;
+        let owner = self.string()?;
+        Ok(Statement::RemoveItem { item, owner })
     }
 
-    #[test]
-    fn parse_procedure_def() {
-        let toks = vec![
-            Token::Procedure,
-            Token::Identifier("proc".into()),
-            Token::Newline,
+    fn apply_modifier(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Apply)?;
+        self.words(&["modifier"])?;
+        let negated = match self.peek() {
+            Token::Plus => false,
+            Token::Minus => true,
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        };
+        self.advance();
+        let modifier = match self.peek() {
+            Token::Num(n) if negated => -n,
+            Token::Num(n) => *n,
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        };
+        self.advance();
+        self.words(&["to", "all", "rolls"])?;
+        let rolls = if matches!(self.peek(), Token::Identifier(word) if word == "named") {
+            self.advance();
+            Some(self.string()?)
+        } else {
+            None
+        };
+        Ok(Statement::ApplyModifier { modifier, rolls })
+    }
+
+    fn remove_modifier(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Remove)?;
+        self.words(&["modifier"])?;
+        let rolls = if matches!(self.peek(), Token::Identifier(word) if word == "from") {
+            self.words(&["from", "all", "rolls", "named"])?;
+            Some(self.string()?)
+        } else {
+            None
+        };
+        Ok(Statement::RemoveModifier(rolls))
+    }
+
+    fn add_combatant(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Add)?;
+        self.words(&["combatant"])?;
+        let combatant = self.string()?;
+        self.words(&["rolling"])?;
+        let roll_specifier = self.modified_specifier()?;
+        Ok(Statement::AddCombatant {
+            combatant,
+            roll_specifier,
+        })
+    }
+
+    fn remove_combatant(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Remove)?;
+        self.words(&["combatant"])?;
+        Ok(Statement::RemoveCombatant(self.string()?))
+    }
+
+    fn sort_combatants(&mut self) -> Result<Statement, CrawlError> {
+        self.words(&["sort", "combatants"])?;
+        Ok(Statement::SortCombatants)
+    }
+
+    fn advance_turn(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Advance)?;
+        self.words(&["turn"])?;
+        Ok(Statement::AdvanceTurn)
+    }
+
+    fn advance_date(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Advance)?;
+        self.words(&["date"])?;
+        self.consume(Token::By)?;
+        let amount = self.amount()?;
+        let unit = match self.peek() {
+            Token::Identifier(word) if word == "day" || word == "days" => DateUnit::Days,
+            Token::Identifier(word) if word == "week" || word == "weeks" => DateUnit::Weeks,
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        };
+        self.advance();
+        Ok(Statement::AdvanceDate { amount, unit })
+    }
+
+    fn advance_time(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Advance)?;
+        self.words(&["time"])?;
+        self.consume(Token::By)?;
+        let amount = self.amount()?;
+        match self.peek() {
+            Token::Identifier(word) if word == "turn" || word == "turns" => self.advance(),
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        };
+        Ok(Statement::AdvanceTime(amount))
+    }
+
+    fn advance_weather(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Advance)?;
+        self.words(&["weather"])?;
+        let table = match self.peek() {
+            Token::On => {
+                self.advance();
+                self.consume(Token::Table)?;
+                self.string()?
+            }
+            _ => WEATHER_TABLE.into(),
+        };
+        Ok(Statement::AdvanceWeather(table))
+    }
+
+    // Whether the token after this one is the word given, like the `turn` of `advance turn`
+    fn next_is(&self, word: &str) -> bool {
+        matches!(self.peek_next(), Token::Identifier(next) if next == word)
+    }
+
+    // Words that read as part of a statement without being keywords, like `facts for`
+    fn words(&mut self, words: &[&str]) -> Result<(), CrawlError> {
+        for expected in words {
+            match self.peek() {
+                Token::Identifier(word) if word == expected => self.advance(),
+                token => {
+                    return Err(CrawlError::ParserError {
+                        token: format!("{token:?}"),
+                    });
+                }
+            };
+        }
+        Ok(())
+    }
+
+    fn rule(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Rule)?;
+        self.consume(Token::Colon)?;
+        self.consume(Token::If)?;
+        let mut conditions = vec![self.rule_condition()?];
+        while matches!(self.peek(), Token::Identifier(word) if word == "and") {
+            self.advance();
+            conditions.push(self.rule_condition()?);
+        }
+        self.consume(Token::Arrow)?;
+        self.consume(Token::DeriveFact)?;
+        let fact = self.string()?;
+        Ok(Statement::Rule(Rule { conditions, fact }))
+    }
+
+    fn rule_condition(&mut self) -> Result<Antecedent, CrawlError> {
+        match self.peek() {
+            Token::FactTest | Token::PersistentFactTest => self.antecedent(),
+            token => Err(CrawlError::ParserError {
+                token: format!("{token:?}"),
+            }),
+        }
+    }
+
+    fn assert(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Assert)?;
+        Ok(Statement::Assert(self.antecedent()?))
+    }
+
+    fn assert_equal(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::AssertEqual)?;
+        let actual = self.value()?;
+        let expected = self.value()?;
+        Ok(Statement::AssertEqual { actual, expected })
+    }
+
+    // A string, or a number, like `return` gives back
+    fn value(&mut self) -> Result<ReturnValue, CrawlError> {
+        Ok(match self.peek() {
+            Token::Str(_) => ReturnValue::Str(self.str()?),
+            Token::Roll => {
+                self.advance();
+                ReturnValue::Amount(Amount::Roll(self.modified_specifier()?))
+            }
+            _ => ReturnValue::Amount(self.amount()?),
+        })
+    }
+
+    fn define_roll(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Define)?;
+        self.consume(Token::Roll)?;
+        let name = self.string()?;
+        self.consume(Token::As)?;
+        let roll = self.modified_specifier()?;
+        Ok(Statement::DefineRoll { name, roll })
+    }
+
+    fn generator(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Generator)?;
+        let name = match self.peek() {
+            Token::Identifier(name) => Ok(name.clone()),
+            token => Err(CrawlError::ParserError {
+                token: format!("{token:?}"),
+            }),
+        }?;
+        self.advance();
+        let template = self.string()?;
+        Ok(Statement::Generator { name, template })
+    }
+
+    fn oracle(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Oracle)?;
+        let question = self.string()?;
+        let likelihood = self.likelihood()?;
+        Ok(Statement::Oracle {
+            question,
+            likelihood,
+        })
+    }
+
+    // `very likely`, `likely`, `even`, `unlikely`, or `very unlikely`; even if left out. Other
+    // words, or a string for odds that aren't words, like "50/50", are looked up when asked.
+    fn likelihood(&mut self) -> Result<Likelihood, CrawlError> {
+        let mut words = Vec::new();
+        while let Token::Identifier(word) = self.peek() {
+            words.push(word.clone());
+            self.advance();
+        }
+        match self.peek() {
+            Token::Str(odds) if words.is_empty() => {
+                let likelihood = Likelihood::named(odds);
+                self.advance();
+                Ok(likelihood)
+            }
+            Token::Newline | Token::Eof if words.is_empty() => Ok(Likelihood::Even),
+            _ if !words.is_empty() => Ok(Likelihood::named(&words.join(" "))),
+            token => Err(CrawlError::ParserError {
+                token: format!("{token:?}"),
+            }),
+        }
+    }
+
+    fn amount(&mut self) -> Result<Amount, CrawlError> {
+        match self.peek() {
+            Token::Num(n) => {
+                let n = *n;
+                self.advance();
+                Ok(Amount::Fixed(n))
+            }
+            Token::RollSpecifier(_) | Token::Str(_) => Ok(Amount::Roll(self.modified_specifier()?)),
+            Token::Identifier(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(Amount::Call(name))
+            }
+            _ => Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            }),
+        }
+    }
+
+    fn antecedent(&mut self) -> Result<Antecedent, CrawlError> {
+        match self.peek() {
+            Token::Roll => self.dice_roll(),
+            Token::Identifier(word) if word == "dead" => self.dead_check(),
+            Token::Identifier(word) if word == "has" => self.item_check(),
+            Token::Identifier(word) if word == "date" && self.next_is("is") => self.date_check(),
+            Token::Identifier(word) if word == "in" && self.next_is("stock?") => {
+                self.words(&["in", "stock?"])?;
+                let item = self.string()?;
+                let settlement = self.settlement()?;
+                Ok(Antecedent::InStock { item, settlement })
+            }
+            Token::Identifier(_) => self.roll_outcome(),
+            Token::Clock => self.clock_check(),
+            Token::Depleted => self.depleted_check(),
+            Token::FactTest => self.fact_check(),
+            Token::PersistentFactTest => self.persistent_fact_check(),
+            _ => Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            }),
+        }
+    }
+
+    fn consequent(&mut self) -> Result<Statement, CrawlError> {
+        match self.peek() {
+            Token::Add if self.next_is("item") => self.add_item(),
+            Token::Add if self.next_is("combatant") => self.add_combatant(),
+            Token::Add if self.next_is("rumor") => self.add_rumor(),
+            Token::Add => self.add_money(),
+            Token::Advance if self.next_is("turn") => self.advance_turn(),
+            Token::Advance if self.next_is("date") => self.advance_date(),
+            Token::Advance if self.next_is("weather") => self.advance_weather(),
+            Token::Advance if self.next_is("time") => self.advance_time(),
+            Token::Advance if self.next_is("faction") => self.advance_fronts(),
+            Token::Advance => self.advance_clock(),
+            Token::Apply => self.apply_modifier(),
+            Token::ClearFact => self.clear_fact(),
+            Token::ClearPersistentFact => self.clear_persistent_fact(),
+            Token::Identifier(word)
+                if word == "damage" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.damage()
+            }
+            Token::Identifier(word)
+                if word == "travel" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.travel()
+            }
+            Token::Identifier(word)
+                if word == "encounter" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.encounter()
+            }
+            Token::Identifier(word)
+                if word == "generate"
+                    && matches!(self.peek_next(), Token::Identifier(next) if next == "room") =>
+            {
+                self.words(&["generate", "room"])?;
+                Ok(Statement::GenerateRoom)
+            }
+            Token::Identifier(word) if word == "morale" && *self.peek_next() == Token::Check => {
+                self.morale_check()
+            }
+            Token::Identifier(word) if word == "pin" && *self.peek_next() == Token::Table => {
+                self.pin_table()
+            }
+            Token::Identifier(word) if word == "scene" && *self.peek_next() == Token::Check => {
+                self.advance();
+                self.consume(Token::Check)?;
+                Ok(Statement::SceneCheck)
+            }
+            Token::Identifier(word)
+                if word == "award"
+                    && matches!(
+                        self.peek_next(),
+                        Token::Num(_) | Token::RollSpecifier(_) | Token::Identifier(_)
+                    ) =>
+            {
+                self.award()
+            }
+            Token::Identifier(word)
+                if word == "price" && matches!(self.peek_next(), Token::Str(_)) =>
+            {
+                self.advance();
+                let item = self.string()?;
+                let settlement = self.settlement()?;
+                Ok(Statement::Price { item, settlement })
+            }
+            Token::Identifier(word) if word == "haggle" && *self.peek_next() == Token::Roll => {
+                self.advance();
+                self.consume(Token::Roll)?;
+                let item = self.string()?;
+                let settlement = self.settlement()?;
+                Ok(Statement::Haggle { item, settlement })
+            }
+            Token::Identifier(word)
+                if (word == "increase" || word == "decrease") && self.next_is("chaos") =>
+            {
+                self.adjust_chaos()
+            }
+            Token::Identifier(word) if word == "reveal" && self.next_is("rumor") => {
+                self.reveal_rumor()
+            }
+            Token::Identifier(word)
+                if word == "hear"
+                    && matches!(self.peek_next(), Token::Num(_) | Token::RollSpecifier(_)) =>
+            {
+                self.hear_rumors()
+            }
+            Token::Identifier(word)
+                if word == "light" && matches!(self.peek_next(), Token::Identifier(_)) =>
+            {
+                self.light()
+            }
+            Token::Identifier(_) => self.procedure_call(),
+            Token::Oracle => self.oracle(),
+            Token::Reminder => self.reminder(),
+            Token::Remove if self.next_is("combatant") => self.remove_combatant(),
+            Token::Remove if self.next_is("item") => self.remove_item(),
+            Token::Remove => self.remove_modifier(),
+            Token::Report if self.next_is("inventory") => self.report_inventory(),
+            Token::Report if self.next_is("xp") => self.report_xp(),
+            Token::Report if self.next_is("rumors") => {
+                self.consume(Token::Report)?;
+                self.words(&["rumors"])?;
+                Ok(Statement::ReportRumors)
+            }
+            Token::Report => self.report_facts(),
+            Token::Return => self.return_value(),
+            Token::Roll if *self.peek_next() == Token::Usage => self.usage_roll(),
+            Token::Roll if self.next_is("name") => self.name_roll(),
+            Token::Roll => self.table_roll(),
+            Token::SetFact => self.set_fact(),
+            Token::SetPersistentFact => self.set_persistent_fact(),
+            Token::Spend => self.spend(),
+            _ => Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            }),
+        }
+    }
+
+    fn dice_roll(&mut self) -> Result<Antecedent, CrawlError> {
+        self.consume(Token::Roll)?;
+        let target = match self.peek() {
+            Token::Num(_)
+            | Token::NumRange(_, _)
+            | Token::OverOrEqual(_)
+            | Token::UnderOrEqual(_)
+            | Token::Identifier(_) => Ok(self.peek().clone()),
+            _ => Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            }),
+        }?;
+
+        self.advance();
+
+        self.consume(Token::On)?;
+
+        let roll_specifier = self.modified_specifier()?;
+
+        Ok(Antecedent::DiceRoll {
+            target,
+            roll_specifier,
+        })
+    }
+
+    // `critical on 1d20`, or a degree of success like `success on 1d20 + 4 vs 15`
+    fn roll_outcome(&mut self) -> Result<Antecedent, CrawlError> {
+        let natural = match (self.peek(), self.peek_next()) {
+            (Token::Identifier(word), Token::On) if word == "critical" => Some(Natural::Critical),
+            (Token::Identifier(word), Token::On) if word == "fumble" => Some(Natural::Fumble),
+            _ => None,
+        };
+        if let Some(natural) = natural {
+            self.advance();
+            self.consume(Token::On)?;
+            let roll_specifier = self.modified_specifier()?;
+            return Ok(Antecedent::NaturalRoll {
+                natural,
+                roll_specifier,
+            });
+        }
+        let degree = self.degree()?;
+        self.consume(Token::On)?;
+        let roll_specifier = self.modified_specifier()?;
+        self.words(&["vs"])?;
+        let dc = self.amount()?;
+        Ok(Antecedent::DegreeOfSuccess {
+            degree,
+            roll_specifier,
+            dc,
+        })
+    }
+
+    // `success`, `failure`, or either with `critical` before it
+    fn degree(&mut self) -> Result<Degree, CrawlError> {
+        let critical = matches!(self.peek(), Token::Identifier(word) if word == "critical");
+        if critical {
+            self.advance();
+        }
+        let degree = match (critical, self.peek()) {
+            (false, Token::Identifier(word)) if word == "success" => Degree::Success,
+            (false, Token::Identifier(word)) if word == "failure" => Degree::Failure,
+            (true, Token::Identifier(word)) if word == "success" => Degree::CriticalSuccess,
+            (true, Token::Identifier(word)) if word == "failure" => Degree::CriticalFailure,
+            (_, token) => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        };
+        self.advance();
+        Ok(degree)
+    }
+
+    fn clock_check(&mut self) -> Result<Antecedent, CrawlError> {
+        self.consume(Token::Clock)?;
+        let clock = self.string()?;
+        self.consume(Token::Full)?;
+        Ok(Antecedent::CheckClockFull(clock))
+    }
+
+    fn depleted_check(&mut self) -> Result<Antecedent, CrawlError> {
+        self.consume(Token::Depleted)?;
+        let resource = self.string()?;
+        Ok(Antecedent::CheckResourceDepleted(resource))
+    }
+
+    fn dead_check(&mut self) -> Result<Antecedent, CrawlError> {
+        self.words(&["dead"])?;
+        let entity = self.string()?;
+        Ok(Antecedent::CheckDead(entity))
+    }
+
+    fn date_check(&mut self) -> Result<Antecedent, CrawlError> {
+        self.words(&["date", "is"])?;
+        let date = match self.peek() {
+            Token::Identifier(date) => {
+                let date = date.clone();
+                self.advance();
+                date
+            }
+            _ => self.string()?,
+        };
+        Ok(Antecedent::DateIs(date))
+    }
+
+    fn item_check(&mut self) -> Result<Antecedent, CrawlError> {
+        self.words(&["has", "item?"])?;
+        let item = self.string()?;
+        self.words(&["in"])?;
+        let owner = self.string()?;
+        Ok(Antecedent::HasItem { item, owner })
+    }
+
+    fn fact_check(&mut self) -> Result<Antecedent, CrawlError> {
+        self.consume(Token::FactTest)?;
+        let fact = if let Token::Str(fact) = self.peek() {
+            Ok(fact.clone())
+        } else {
+            Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            })
+        };
+
+        self.advance();
+
+        Ok(Antecedent::CheckFact(fact?))
+    }
+
+    fn persistent_fact_check(&mut self) -> Result<Antecedent, CrawlError> {
+        self.consume(Token::PersistentFactTest)?;
+        let fact = if let Token::Str(fact) = self.peek() {
+            Ok(fact.clone())
+        } else {
+            Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            })
+        };
+
+        self.advance();
+
+        Ok(Antecedent::CheckPersistentFact(fact?))
+    }
+
+    fn set_fact(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::SetFact)?;
+        let fact = self.str()?;
+
+        Ok(Statement::SetFact(fact))
+    }
+
+    fn set_persistent_fact(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::SetPersistentFact)?;
+        let fact = if let Token::Str(fact) = self.peek() {
+            Ok(fact.clone())
+        } else {
+            Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            })
+        }?;
+
+        self.advance();
+
+        Ok(Statement::SetPersistentFact(fact))
+    }
+
+    fn clear_fact(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::ClearFact)?;
+        let fact = if let Token::Str(fact) = self.peek() {
+            Ok(fact.clone())
+        } else {
+            Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            })
+        }?;
+
+        self.advance();
+
+        Ok(Statement::ClearFact(fact))
+    }
+
+    fn clear_persistent_fact(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::ClearPersistentFact)?;
+        let fact = if let Token::Str(fact) = self.peek() {
+            Ok(fact.clone())
+        } else {
+            Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            })
+        }?;
+
+        self.advance();
+
+        Ok(Statement::ClearPersistentFact(fact))
+    }
+
+    fn table_roll(&mut self) -> Result<Statement, CrawlError> {
+        let table = self.rolled_table()?;
+        let field = self.table_field()?;
+        let tag = self.table_tag()?;
+        let excluding_seen = self.excluding_seen()?;
+        Ok(Statement::TableRoll {
+            table,
+            field,
+            tag,
+            excluding_seen,
+        })
+    }
+
+    fn rolled_table(&mut self) -> Result<String, CrawlError> {
+        self.consume(Token::Roll)?;
+        self.consume(Token::On)?;
+        self.consume(Token::Table)?;
+        self.string()
+    }
+
+    fn table_field(&mut self) -> Result<Option<String>, CrawlError> {
+        match self.peek() {
+            Token::Identifier(word) if word == "field" => {
+                self.advance();
+                Ok(Some(self.string()?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // `where tag "night"`, after a table roll
+    fn table_tag(&mut self) -> Result<Option<String>, CrawlError> {
+        match self.peek() {
+            Token::Identifier(word) if word == "where" => {
+                self.advance();
+                match self.peek() {
+                    Token::Identifier(word) if word == "tag" => self.advance(),
+                    token => {
+                        return Err(CrawlError::ParserError {
+                            token: format!("{token:?}"),
+                        });
+                    }
+                }
+                Ok(Some(self.string()?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // `excluding seen`, after a table roll
+    fn excluding_seen(&mut self) -> Result<bool, CrawlError> {
+        match self.peek() {
+            Token::Identifier(word) if word == "excluding" => {
+                self.advance();
+                match self.peek() {
+                    Token::Identifier(word) if word == "seen" => self.advance(),
+                    token => {
+                        return Err(CrawlError::ParserError {
+                            token: format!("{token:?}"),
+                        });
+                    }
+                }
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    // A table roll, with arms if the lines after it are indented under it.
+    fn matching_table_roll(&mut self) -> Result<Statement, CrawlError> {
+        let table = self.rolled_table()?;
+        let field = self.table_field()?;
+        let tag = self.table_tag()?;
+        let excluding_seen = self.excluding_seen()?;
+        if field.is_some() || !self.starts_block() {
+            return Ok(Statement::TableRoll {
+                table,
+                field,
+                tag,
+                excluding_seen,
+            });
+        }
+        self.consume(Token::Newline)?;
+
+        let mut arms = Vec::new();
+        while *self.peek() != Token::End {
+            self.consume(Token::Indent)?;
+            while *self.peek() == Token::Indent {
+                self.advance();
+            }
+            if *self.peek() == Token::End {
+                break;
+            }
+
+            let pattern = self.string()?;
+            self.consume(Token::Arrow)?;
+            let consequent = self.consequent()?;
+            arms.push(TableRollArm {
+                pattern,
+                consequent,
+            });
+
+            self.consume(Token::Newline)?;
+        }
+
+        self.consume(Token::End)?;
+
+        Ok(Statement::MatchingTableRoll {
+            table,
+            tag,
+            excluding_seen,
+            arms,
+        })
+    }
+
+    fn matching_fact(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Match)?;
+        match self.peek() {
+            Token::Identifier(word) if word == "fact" => self.advance(),
+            token => {
+                return Err(CrawlError::ParserError {
+                    token: format!("{token:?}"),
+                });
+            }
+        };
+        let fact = self.string()?;
+        self.consume(Token::Newline)?;
+
+        let mut arms = Vec::new();
+        let mut otherwise = None;
+        while *self.peek() != Token::End {
+            self.consume(Token::Indent)?;
+            while *self.peek() == Token::Indent {
+                self.advance();
+            }
+            if *self.peek() == Token::End {
+                break;
+            }
+
+            if *self.peek() == Token::Else && otherwise.is_none() {
+                self.advance();
+                self.consume(Token::Arrow)?;
+                otherwise = Some(Box::new(self.consequent()?));
+            } else {
+                let pattern = self.string()?;
+                self.consume(Token::Arrow)?;
+                let consequent = self.consequent()?;
+                arms.push(TableRollArm {
+                    pattern,
+                    consequent,
+                });
+            }
+
+            self.consume(Token::Newline)?;
+        }
+
+        self.consume(Token::End)?;
+
+        Ok(Statement::MatchingFact {
+            fact,
+            arms,
+            otherwise,
+        })
+    }
+
+    fn nontargeted_roll(&mut self) -> Result<Statement, CrawlError> {
+        self.consume(Token::Roll)?;
+        let spec = self.modified_specifier()?;
+        Ok(Statement::NontargetedRoll(spec))
+    }
+
+    fn str(&mut self) -> Result<CrawlStr, CrawlError> {
+        let s = if let Token::Str(st) = self.peek() {
+            Ok(st.clone())
+        } else {
+            Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            })
+        }?;
+
+        let start = self.index();
+        self.advance();
+
+        // TODO: only allows one placeholder
+        if let Token::Percent = *self.peek() {
+            self.advance();
+            let expr = match (self.peek(), self.peek_next()) {
+                (Token::Identifier(word), Token::Roll) if word == "last" => {
+                    self.advance();
+                    self.advance();
+                    Ok(Statement::LastRoll)
+                }
+                (Token::Identifier(_), _) => self.procedure_call(),
+                (Token::Roll, Token::Identifier(word)) if word == "name" => self.name_roll(),
+                (_, Token::On) => self.table_roll(),
+                (_, Token::RollSpecifier(_) | Token::Str(_)) => self.nontargeted_roll(),
+                _ => Err(CrawlError::ParserError {
+                    token: format!("{:?}", self.peek()),
+                }),
+            }?;
+            // A table roll's quotes can't go inside the string, so only names can move into it.
+            let inlinable = match &expr {
+                Statement::ProcedureCall {
+                    identifier,
+                    arguments,
+                } if arguments.is_empty() => Some(identifier.as_str()),
+                Statement::LastRoll => Some("last roll"),
+                _ => None,
+            };
+            if let Some(identifier) = inlinable {
+                let placeholder = Regex::new(r"\{[^{}]*\}").unwrap();
+                let inline = placeholder.replace(&s, format!("{{{identifier}}}"));
+                self.deprecate(
+                    start,
+                    "interpolating with `%`".into(),
+                    Some(format!("{inline:?}")),
+                );
+            }
+            Ok(CrawlStr::InterpolatedStr {
+                format_string: s,
+                expressions: vec![expr],
+            })
+        } else {
+            // Names in braces are interpolated in place.
+            if !s.contains('{') {
+                return Ok(CrawlStr::Str(s));
+            }
+            let placeholder = Regex::new(
+                r"\{(last roll|current date|chaos factor|\p{Alphabetic}[\p{Alphabetic}\-?.]*)\}",
+            )
+            .unwrap();
+            let expressions: Vec<Statement> = placeholder
+                .captures_iter(&s)
+                .map(|captures| match &captures[1] {
+                    "last roll" => Statement::LastRoll,
+                    "current date" => Statement::CurrentDate,
+                    "chaos factor" => Statement::ChaosFactor,
+                    identifier => Statement::ProcedureCall {
+                        identifier: identifier.to_string(),
+                        arguments: Vec::new(),
+                    },
+                })
+                .collect();
+            if expressions.is_empty() {
+                Ok(CrawlStr::Str(s))
+            } else {
+                Ok(CrawlStr::InterpolatedStr {
+                    format_string: s,
+                    expressions,
+                })
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, CrawlError> {
+        let string = if let Token::Str(string) = self.peek() {
+            Ok(string.clone())
+        } else {
+            Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            })
+        }?;
+        self.advance();
+        Ok(string)
+    }
+
+    fn advance(&mut self) {
+        self.lookahead.pop_front();
+        self.fill_lookahead();
+    }
+
+    fn fill_lookahead(&mut self) {
+        self.fill_lookahead_to(LOOKAHEAD);
+    }
+
+    fn fill_lookahead_to(&mut self, len: usize) {
+        while self.lookahead.len() < len {
+            match self.tokens.next() {
+                Some(Ok(token)) => self.lookahead.push_back((token, self.pulled)),
+                Some(Err(error)) => self.scan_errors.push_back((self.pulled, error)),
+                None => break,
+            }
+            self.pulled += 1;
+        }
+    }
+
+    fn consume(&mut self, token: Token) -> Result<Token, CrawlError> {
+        if *self.peek() == token {
+            self.advance();
+            Ok(token)
+        } else {
+            Err(CrawlError::ParserError {
+                token: format!("{:?}", self.peek()),
+            })
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        match self.lookahead.front() {
+            Some((token, _)) => token,
+            None => &Token::Eof,
+        }
+    }
+
+    fn peek_next(&self) -> &Token {
+        match self.lookahead.get(1) {
+            Some((token, _)) => token,
+            None => &Token::Eof,
+        }
+    }
+
+    /// Whether the next line is indented deeper than the statement being parsed.
+    fn starts_block(&mut self) -> bool {
+        let indents = self.depth + 1;
+        self.fill_lookahead_to(indents + 1);
+        *self.peek() == Token::Newline
+            && self.lookahead.len() > indents
+            && self
+                .lookahead
+                .range(1..=indents)
+                .all(|(token, _)| *token == Token::Indent)
+    }
+
+    /// The first scanner error among the tokens recognized so far, dropping any others.
+    fn take_scan_error(&mut self) -> Option<CrawlError> {
+        let recognized = self.lookahead.front().map_or(usize::MAX, |(_, idx)| *idx);
+        let mut first = None;
+        while let Some((idx, _)) = self.scan_errors.front() {
+            if *idx >= recognized {
+                break;
+            }
+            let (_, error) = self.scan_errors.pop_front().unwrap();
+            first.get_or_insert(error);
+        }
+        first
+    }
+
+    // The index in `tokens` of the token to be recognized.
+    fn index(&self) -> usize {
+        self.lookahead.front().map_or(self.pulled, |(_, idx)| *idx)
+    }
+
+    // Record that the tokens from `start` up to the one to be recognized are deprecated.
+    fn deprecate(&mut self, start: usize, message: String, replacement: Option<String>) {
+        let end = self.index().saturating_sub(1);
+        let span = match (self.spans.get(start), self.spans.get(end)) {
+            (Some(first), Some(last)) => first.start..last.end,
+            _ => 0..0,
+        };
+        self.deprecations.push(Deprecation {
+            span,
+            line: self.lines.get(start).copied().unwrap_or_default(),
+            message,
+            replacement,
+        });
+    }
+
+    fn line(&self) -> usize {
+        self.lookahead
+            .front()
+            .and_then(|(_, idx)| self.lines.get(*idx))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn is_at_end(&self) -> bool {
+        *self.peek() == Token::Eof
+    }
+}
+
+impl<I: Iterator<Item = Result<Token, CrawlError>>> Iterator for Parser<I> {
+    type Item = Result<Statement, CrawlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_at_end() {
+            // A bad token at the very end still has to be reported.
+            return self.take_scan_error().map(Err);
+        }
+
+        // Only a script's first line can say what version it's for.
+        if *self.peek() == Token::CrawlVersion && self.source_map.top_level.is_empty() {
+            self.statement_line = Some(self.line());
+            if let Err(error) = self.crawl_version() {
+                return Some(Err(error));
+            }
+            if self.is_at_end() {
+                return self.take_scan_error().map(Err);
+            }
+        }
+
+        let line = self.line();
+        self.statement_line = Some(line);
+        self.source_map.top_level.push(line);
+        let statement = self.statement();
+        match self.take_scan_error() {
+            Some(error) => Some(Err(error)),
+            None => Some(statement),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::RollSpec;
+    use crate::scanner::Scanner;
+
+    #[test]
+    fn parse_procedure_call() {
+        let toks = vec![
+            Token::Identifier("proc-name".into()),
+            Token::Newline,
+            Token::Eof,
+        ];
+        let parsed: Vec<Statement> = Parser::new(toks)
+            .parse()
+            .into_iter()
+            .map(|t| t.unwrap())
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![Statement::ProcedureCall {
+                identifier: "proc-name".into(),
+                arguments: Vec::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_procedure_def() {
+        let toks = vec![
+            Token::Procedure,
+            Token::Identifier("proc".into()),
+            Token::Newline,
+            Token::Indent,
+            Token::Identifier("other-proc".into()),
+            Token::Newline,
+            Token::End,
+            Token::Newline,
+            Token::Eof,
+        ];
+        let parsed: Vec<Statement> = Parser::new(toks)
+            .parse()
+            .into_iter()
+            .map(|t| t.unwrap())
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![Statement::Procedure {
+                declaration: ProcedureDeclaration("proc".into()),
+                body: vec![Statement::ProcedureCall {
+                    identifier: "other-proc".into(),
+                    arguments: Vec::new(),
+                }]
+                .into()
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_source_map() {
+        let toks = vec![
+            Token::Procedure,
+            Token::Identifier("proc".into()),
+            Token::Newline,
             Token::Indent,
             Token::Identifier("other-proc".into()),
             Token::Newline,
             Token::End,
             Token::Newline,
+            Token::Identifier("proc".into()),
+            Token::Newline,
+            Token::Eof,
+        ];
+        let lines = vec![1, 1, 1, 2, 2, 2, 3, 3, 5, 5, 6];
+        let mut parser = Parser::new(toks).with_lines(lines);
+        let _ = parser.parse();
+        assert_eq!(parser.source_map().top_level, vec![1, 5]);
+        assert_eq!(parser.source_map().procedures["proc"], vec![2]);
+    }
+
+    #[test]
+    fn parse_procedure_call_arguments() {
+        let source = "weather-api \"north\" \"today\"\n";
+        let parsed = Parser::from_tokens(Scanner::new(source)).parse();
+        assert_eq!(
+            parsed[0].as_ref().unwrap(),
+            &Statement::ProcedureCall {
+                identifier: "weather-api".into(),
+                arguments: vec!["north".into(), "today".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_from_scanner() {
+        let source = "reminder \"eat\"\nreminder \"sleep\"\n";
+        let mut parser = Parser::from_tokens(Scanner::new(source));
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::Reminder("eat".into())
+        );
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::Reminder("sleep".into())
+        );
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn parse_reports_scanner_errors() {
+        let source = "reminder \"eat\"\nreminder 2d6d8 \"sleep\"\nreminder \"wake\"\n";
+        let parsed = Parser::from_tokens(Scanner::new(source)).parse();
+        assert_eq!(parsed.len(), 3);
+        assert!(matches!(parsed[1], Err(CrawlError::ScannerError { .. })));
+        assert_eq!(
+            parsed[2].as_ref().unwrap(),
+            &Statement::Reminder("wake".into())
+        );
+    }
+
+    #[test]
+    fn parse_reminder() {
+        let toks = vec![
+            Token::Reminder,
+            Token::Str("don't forget to eat".into()),
+            Token::Newline,
+            Token::Eof,
+        ];
+        let parsed = Parser::new(toks).parse();
+        assert_eq!(
+            parsed
+                .into_iter()
+                .map(|a| a.unwrap())
+                .collect::<Vec<Statement>>(),
+            vec![Statement::Reminder("don't forget to eat".into())],
+        )
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_statement_no_nl() {
+        let toks = vec![
+            Token::Reminder,
+            Token::Str("statements end with a newline".into()),
             Token::Eof,
         ];
-        let parsed: Vec<Statement> = Parser::new(toks)
-            .parse()
-            .into_iter()
-            .map(|t| t.unwrap())
-            .collect();
+        let _: Vec<Statement> = Parser::new(toks)
+            .parse()
+            .into_iter()
+            .map(|a| a.unwrap())
+            .collect();
+    }
+
+    #[test]
+    fn if_then() {
+        let toks = vec![
+            Token::If,
+            Token::Roll,
+            Token::Num(6),
+            Token::On,
+            Token::RollSpecifier(RollSpec::new(1, 6)),
+            Token::Plus,
+            Token::Num(1),
+            Token::Arrow,
+            Token::SetFact,
+            Token::Str("cool!".into()),
+        ];
+        let parsed = Parser::new(toks).if_then();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::IfThen {
+                antecedent: Antecedent::DiceRoll {
+                    target: Token::Num(6),
+                    roll_specifier: ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 6)),
+                        modifier: 1,
+                        terms: Vec::new(),
+                    },
+                },
+                consequent: Box::new(Statement::SetFact(CrawlStr::Str("cool!".into()))),
+            }
+        )
+    }
+
+    #[test]
+    fn matching_roll() {
+        let toks = vec![
+            Token::Roll,
+            Token::RollSpecifier(RollSpec::new(2, 20)),
+            Token::Minus,
+            Token::Num(2),
+            Token::Newline,
+            Token::Indent,
+            Token::Num(2),
+            Token::Arrow,
+            Token::SetFact,
+            Token::Str("you died".into()),
+            Token::Newline,
+            Token::Indent,
+            Token::NumRange(3, 40),
+            Token::Arrow,
+            Token::SetFact,
+            Token::Str("you're alright".into()),
+            Token::Newline,
+            Token::End,
+        ];
+        let parsed = Parser::new(toks).matching_roll();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::MatchingRoll {
+                roll_specifier: ModifiedRollSpecifier {
+                    base_roll_specifier: Token::RollSpecifier(RollSpec::new(2, 20)),
+                    modifier: -2,
+                    terms: Vec::new(),
+                },
+                arms: vec![
+                    MatchingRollArm {
+                        target: Token::Num(2),
+                        guard: None,
+                        consequent: Statement::SetFact(CrawlStr::Str("you died".into()))
+                    },
+                    MatchingRollArm {
+                        target: Token::NumRange(3, 40),
+                        guard: None,
+                        consequent: Statement::SetFact(CrawlStr::Str("you're alright".into()))
+                    },
+                ],
+                otherwise: None,
+            }
+        )
+    }
+
+    #[test]
+    fn matching_roll_guard() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "roll 1d6\n\t6 if fact? \"hex is forest\" => reminder \"bears\"\nend\n",
+        ));
+        let Statement::MatchingRoll { arms, .. } = parser.next().unwrap().unwrap() else {
+            panic!("expected a matching roll");
+        };
+        assert_eq!(
+            arms,
+            vec![MatchingRollArm {
+                target: Token::Num(6),
+                guard: Some(Antecedent::CheckFact("hex is forest".into())),
+                consequent: Statement::Reminder("bears".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn matching_roll_otherwise() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "roll 1d6\n\t6 => reminder \"bears\"\n\telse => reminder \"quiet\"\nend\n",
+        ));
+        let Statement::MatchingRoll { otherwise, .. } = parser.next().unwrap().unwrap() else {
+            panic!("expected a matching roll");
+        };
+        assert_eq!(
+            otherwise,
+            Some(Box::new(Statement::Reminder("quiet".into())))
+        );
+
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "roll 1d6\n\telse => reminder \"a\"\n\telse => reminder \"b\"\nend\n",
+        ));
+        assert!(parser.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn set_fact() {
+        let toks = vec![Token::SetFact, Token::Str("weather is nice".into())];
+        let parsed = Parser::new(toks).set_fact();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::SetFact(CrawlStr::Str("weather is nice".into()))
+        )
+    }
+
+    #[test]
+    fn set_pfact() {
+        let toks = vec![
+            Token::SetPersistentFact,
+            Token::Str("weather is nice".into()),
+        ];
+        let parsed = Parser::new(toks).set_persistent_fact();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::SetPersistentFact("weather is nice".into())
+        )
+    }
+
+    #[test]
+    fn clear_fact() {
+        let toks = vec![Token::ClearFact, Token::Str("weather is nice".into())];
+        let parsed = Parser::new(toks).clear_fact();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::ClearFact("weather is nice".into())
+        )
+    }
+
+    #[test]
+    fn clear_pfact() {
+        let toks = vec![
+            Token::ClearPersistentFact,
+            Token::Str("weather is nice".into()),
+        ];
+        let parsed = Parser::new(toks).clear_persistent_fact();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::ClearPersistentFact("weather is nice".into())
+        )
+    }
+
+    #[test]
+    fn clocks() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "create clock \"alarm\" 6\nadvance clock \"alarm\" by 1d3\nif clock \"alarm\" full => advance clock \"doom\" by 1\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                Statement::CreateClock {
+                    clock: "alarm".into(),
+                    segments: 6,
+                },
+                Statement::AdvanceClock {
+                    clock: "alarm".into(),
+                    amount: Amount::Roll(ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 3)),
+                        modifier: 0,
+                        terms: Vec::new(),
+                    }),
+                },
+                Statement::IfThen {
+                    antecedent: Antecedent::CheckClockFull("alarm".into()),
+                    consequent: Box::new(Statement::AdvanceClock {
+                        clock: "doom".into(),
+                        amount: Amount::Fixed(1),
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn resources() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "track \"torches\" 6\nspend 1 \"torches\"\nif depleted \"torches\" => reminder \"darkness\"\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                Statement::TrackResource {
+                    resource: "torches".into(),
+                    amount: 6,
+                },
+                Statement::SpendResource {
+                    resource: "torches".into(),
+                    amount: Amount::Fixed(1),
+                },
+                Statement::IfThen {
+                    antecedent: Antecedent::CheckResourceDepleted("torches".into()),
+                    consequent: Box::new(Statement::Reminder("darkness".into())),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn usage_dice() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "usage-die \"rations\" d8\nusage-die \"torches\" 1d6\nif fact? \"party is camping\" => roll usage \"rations\"\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                Statement::UsageDie {
+                    resource: "rations".into(),
+                    sides: 8,
+                },
+                Statement::UsageDie {
+                    resource: "torches".into(),
+                    sides: 6,
+                },
+                Statement::IfThen {
+                    antecedent: Antecedent::CheckFact("party is camping".into()),
+                    consequent: Box::new(Statement::UsageRoll("rations".into())),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn choice() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "choose \"Take the left or right passage?\"\n\t\"left\" => set-fact \"party went left\"\n\t\"right\" => flooded-cave\nend\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed,
+            vec![Statement::Choice {
+                question: "Take the left or right passage?".into(),
+                arms: vec![
+                    ChoiceArm {
+                        option: "left".into(),
+                        consequent: Statement::SetFact(CrawlStr::Str("party went left".into())),
+                    },
+                    ChoiceArm {
+                        option: "right".into(),
+                        consequent: Statement::ProcedureCall {
+                            identifier: "flooded-cave".into(),
+                            arguments: Vec::new(),
+                        },
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn let_binding() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "let damage = roll 2d6 + 3\nif roll damage on 1d20 - damage + 1 => reminder \"hit\"\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                Statement::Let {
+                    name: "damage".into(),
+                    value: Amount::Roll(ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(2, 6)),
+                        modifier: 3,
+                        terms: Vec::new(),
+                    }),
+                },
+                Statement::IfThen {
+                    antecedent: Antecedent::DiceRoll {
+                        target: Token::Identifier("damage".into()),
+                        roll_specifier: ModifiedRollSpecifier {
+                            base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 20)),
+                            modifier: 1,
+                            terms: vec![ModifierTerm {
+                                negated: true,
+                                value: ModifierValue::Variable("damage".into()),
+                                condition: None,
+                            }],
+                        },
+                    },
+                    consequent: Box::new(Statement::Reminder("hit".into())),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fact_modifier() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "roll 1d20 + fact \"fighter str-mod\" - 1\n\t20 => reminder \"crit\"\nend\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert!(matches!(
+            &parsed[0],
+            Statement::MatchingRoll { roll_specifier, .. }
+                if roll_specifier.modifier == -1
+                    && roll_specifier.terms
+                        == vec![ModifierTerm {
+                            negated: false,
+                            value: ModifierValue::Fact("fighter str-mod".into()),
+                            condition: None,
+                        }]
+        ));
+    }
+
+    #[test]
+    fn stat_modifier() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "load statblocks \"party.json\"\nif roll 15+ on 1d20 + stat \"Brofist\" \"STR\" => reminder \"hit\"\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(parsed[0], Statement::LoadStatblocks("party.json".into()));
+        assert!(matches!(
+            &parsed[1],
+            Statement::IfThen {
+                antecedent: Antecedent::DiceRoll { roll_specifier, .. },
+                ..
+            } if roll_specifier.terms
+                == vec![ModifierTerm {
+                    negated: false,
+                    value: ModifierValue::Stat {
+                        statblock: "Brofist".into(),
+                        stat: "STR".into(),
+                    },
+                    condition: None,
+                }]
+        ));
+    }
+
+    #[test]
+    fn save() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "save \"Brofist\" vs \"poison\"\n\tsuccess => reminder \"fine\"\n\telse => reminder \"poisoned\"\nend\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(
+            statements,
+            vec![Statement::Save {
+                statblock: "Brofist".into(),
+                save: "poison".into(),
+                arms: vec![CheckArm {
+                    degree: Degree::Success,
+                    consequent: Statement::Reminder("fine".into()),
+                }],
+                otherwise: Some(Box::new(Statement::Reminder("poisoned".into()))),
+            }]
+        );
+    }
+
+    #[test]
+    fn conditional_modifier() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "if roll 20 on 1d20 + 2 if fact? \"party has high ground\" + 1 => reminder \"hit\"\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed,
+            vec![Statement::IfThen {
+                antecedent: Antecedent::DiceRoll {
+                    target: Token::Num(20),
+                    roll_specifier: ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 20)),
+                        modifier: 1,
+                        terms: vec![ModifierTerm {
+                            negated: false,
+                            value: ModifierValue::Fixed(2),
+                            condition: Some(Box::new(Antecedent::CheckFact(
+                                "party has high ground".into()
+                            ))),
+                        }],
+                    },
+                },
+                consequent: Box::new(Statement::Reminder("hit".into())),
+            }]
+        );
+    }
+
+    #[test]
+    fn nested_procedure() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "procedure travel-turn\n\tprocedure forage\n\t\treminder \"roll wisdom\"\n\tend\n\tforage\nend\nreminder \"rest\"\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                Statement::Procedure {
+                    declaration: ProcedureDeclaration("travel-turn".into()),
+                    body: vec![
+                        Statement::Procedure {
+                            declaration: ProcedureDeclaration("forage".into()),
+                            body: vec![Statement::Reminder("roll wisdom".into())].into(),
+                        },
+                        Statement::ProcedureCall {
+                            identifier: "forage".into(),
+                            arguments: Vec::new(),
+                        },
+                    ]
+                    .into(),
+                },
+                Statement::Reminder("rest".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn return_value() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "procedure travel-dc\n\treturn roll 1d6 + 8\nend\nlet dc = travel-dc\nprocedure greeting\n\treturn \"hail\"\nend\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed[0],
+            Statement::Procedure {
+                declaration: ProcedureDeclaration("travel-dc".into()),
+                body: vec![Statement::Return(ReturnValue::Amount(Amount::Roll(
+                    ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 6)),
+                        modifier: 8,
+                        terms: Vec::new(),
+                    }
+                )))]
+                .into(),
+            }
+        );
+        assert_eq!(
+            parsed[1],
+            Statement::Let {
+                name: "dc".into(),
+                value: Amount::Call("travel-dc".into()),
+            }
+        );
+        assert_eq!(
+            parsed[2],
+            Statement::Procedure {
+                declaration: ProcedureDeclaration("greeting".into()),
+                body: vec![Statement::Return(ReturnValue::Str(CrawlStr::Str(
+                    "hail".into()
+                )))]
+                .into(),
+            }
+        );
+    }
+
+    #[test]
+    fn assertions() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "assert fact? \"party is lost\"\nassert-equal travel-dc 12\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                Statement::Assert(Antecedent::CheckFact("party is lost".into())),
+                Statement::AssertEqual {
+                    actual: ReturnValue::Amount(Amount::Call("travel-dc".into())),
+                    expected: ReturnValue::Amount(Amount::Fixed(12)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn generator() {
+        let toks = vec![
+            Token::Generator,
+            Token::Identifier("npc".into()),
+            Token::Str("{names} the {adjectives}".into()),
+        ];
+        let parsed = Parser::new(toks).generator();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::Generator {
+                name: "npc".into(),
+                template: "{names} the {adjectives}".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn oracle() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "oracle \"does the guard notice?\" very unlikely\noracle \"is it raining?\"\noracle \"is it dark?\" almost certain\noracle \"is it a trap?\" \"50/50\"\noracle \"is it dark?\" 50\n",
+        ));
+        let parsed = parser.parse();
+        assert_eq!(
+            parsed[0].as_ref().unwrap(),
+            &Statement::Oracle {
+                question: "does the guard notice?".into(),
+                likelihood: Likelihood::VeryUnlikely,
+            }
+        );
+        assert_eq!(
+            parsed[1].as_ref().unwrap(),
+            &Statement::Oracle {
+                question: "is it raining?".into(),
+                likelihood: Likelihood::Even,
+            }
+        );
+        assert_eq!(
+            parsed[2].as_ref().unwrap(),
+            &Statement::Oracle {
+                question: "is it dark?".into(),
+                likelihood: Likelihood::Named("almost certain".into()),
+            }
+        );
+        assert_eq!(
+            parsed[3].as_ref().unwrap(),
+            &Statement::Oracle {
+                question: "is it a trap?".into(),
+                likelihood: Likelihood::Named("50/50".into()),
+            }
+        );
+        assert!(parsed[4].is_err());
+    }
+
+    #[test]
+    fn reminder() {
+        let toks = vec![Token::Reminder, Token::Str("don't forget to eat".into())];
+        let parsed = Parser::new(toks).reminder();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::Reminder("don't forget to eat".into())
+        )
+    }
+
+    #[test]
+    fn dice_roll() {
+        let toks = vec![
+            Token::Roll,
+            Token::NumRange(1, 5),
+            Token::On,
+            Token::RollSpecifier(RollSpec::new(1, 12)),
+            Token::Plus,
+            Token::Num(5),
+        ];
+        let parsed = Parser::new(toks).dice_roll();
+        assert_eq!(
+            parsed.unwrap(),
+            Antecedent::DiceRoll {
+                target: Token::NumRange(1, 5),
+                roll_specifier: ModifiedRollSpecifier {
+                    base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 12)),
+                    modifier: 5,
+                    terms: Vec::new(),
+                }
+            }
+        )
+    }
+
+    #[test]
+    fn table_roll() {
+        let toks = vec![
+            Token::Roll,
+            Token::On,
+            Token::Table,
+            Token::Str("table-t1".into()),
+        ];
+        let parsed = Parser::new(toks).table_roll();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::TableRoll {
+                table: "table-t1".into(),
+                field: None,
+                tag: None,
+                excluding_seen: false,
+            }
+        )
+    }
+
+    #[test]
+    fn table_field_roll() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "roll on table \"encounters\" field \"treasure\"\nset-fact \"{}\" % roll on table \"encounters\" field \"monster\"\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        let field_roll = |field: &str| Statement::TableRoll {
+            table: "encounters".into(),
+            field: Some(field.into()),
+            tag: None,
+            excluding_seen: false,
+        };
+        assert_eq!(parsed[0], field_roll("treasure"));
+        assert!(matches!(
+            &parsed[1],
+            Statement::SetFact(CrawlStr::InterpolatedStr { expressions, .. })
+                if expressions[0] == field_roll("monster")
+        ));
+    }
+
+    #[test]
+    fn tagged_table_roll() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "roll on table \"encounters\" where tag \"night\"\nroll on table \"reaction\" where tag \"beast\"\n\t\"hostile\" => reminder \"ambush\"\nend\nroll on table \"encounters\" where \"night\"\n",
+        ));
+        let parsed: Vec<Result<Statement, CrawlError>> = parser.parse().into_iter().collect();
+        assert_eq!(
+            parsed[0].as_ref().unwrap(),
+            &Statement::TableRoll {
+                table: "encounters".into(),
+                field: None,
+                tag: Some("night".into()),
+                excluding_seen: false,
+            }
+        );
+        assert!(matches!(
+            parsed[1].as_ref().unwrap(),
+            Statement::MatchingTableRoll { tag: Some(tag), .. } if tag == "beast"
+        ));
+        assert!(parsed[2].is_err());
+
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "roll on table \"rumors\" where tag \"town\" excluding seen\n",
+        ));
+        assert!(matches!(
+            parser.parse()[0].as_ref().unwrap(),
+            Statement::TableRoll {
+                excluding_seen: true,
+                tag: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn load_table() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "load table \"weather\"\nload table \"crits\" no-clamp\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                Statement::LoadTable {
+                    table: "weather".into(),
+                    no_clamp: false,
+                },
+                Statement::LoadTable {
+                    table: "crits".into(),
+                    no_clamp: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn matching_table_roll() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "roll on table \"reaction\"\n\t\"hostile\" => reminder \"ambush\"\nend\nprocedure camp\n\troll on table \"weather\"\n\troll on table \"reaction\"\n\t\t\"friendly\" => reminder \"trade\"\n\tend\nend\n",
+        ));
+        let parsed: Vec<Statement> = parser.parse().into_iter().map(|s| s.unwrap()).collect();
+        let reaction = |pattern: &str, reminder: &str| Statement::MatchingTableRoll {
+            table: "reaction".into(),
+            tag: None,
+            excluding_seen: false,
+            arms: vec![TableRollArm {
+                pattern: pattern.into(),
+                consequent: Statement::Reminder(reminder.into()),
+            }],
+        };
+        assert_eq!(
+            parsed,
+            vec![
+                reaction("hostile", "ambush"),
+                Statement::Procedure {
+                    declaration: ProcedureDeclaration("camp".into()),
+                    body: vec![
+                        Statement::TableRoll {
+                            table: "weather".into(),
+                            field: None,
+                            tag: None,
+                            excluding_seen: false,
+                        },
+                        reaction("friendly", "trade"),
+                    ]
+                    .into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn matching_fact() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "match fact \"reaction is\"\n\t\"hostile\" => reminder \"ambush\"\n\telse => reminder \"parley\"\nend\n",
+        ));
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::MatchingFact {
+                fact: "reaction is".into(),
+                arms: vec![TableRollArm {
+                    pattern: "hostile".into(),
+                    consequent: Statement::Reminder("ambush".into()),
+                }],
+                otherwise: Some(Box::new(Statement::Reminder("parley".into()))),
+            }
+        );
+    }
+
+    #[test]
+    fn with_facts() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "with-facts\n\tset-fact \"party is hidden\"\n\treminder \"ambush\"\nend\n",
+        ));
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::WithFacts(vec![
+                Statement::SetFact(CrawlStr::Str("party is hidden".into())),
+                Statement::Reminder("ambush".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn rule() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "rule: if fact? \"weather is storm\" and persistent-fact? \"party is travelling\" => derive-fact \"travel is slowed\"\n",
+        ));
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::Rule(Rule {
+                conditions: vec![
+                    Antecedent::CheckFact("weather is storm".into()),
+                    Antecedent::CheckPersistentFact("party is travelling".into()),
+                ],
+                fact: "travel is slowed".into(),
+            })
+        );
+
+        // Rolls can't be re-checked as facts change.
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "rule: if roll 1d6 6 => derive-fact \"party is lucky\"\n",
+        ));
+        assert!(parser.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn report_facts() {
+        let mut parser = Parser::from_tokens(Scanner::new("report facts for \"party\"\n"));
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::ReportFacts("party".into())
+        );
+
+        let mut parser = Parser::from_tokens(Scanner::new("report \"party\"\n"));
+        assert!(parser.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn include() {
+        let toks = vec![Token::Include, Token::Str("procedures/travel.crawl".into())];
+        let parsed = Parser::new(toks).include();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::Include {
+                path: "procedures/travel.crawl".into(),
+                namespace: None,
+            }
+        )
+    }
+
+    #[test]
+    fn include_as() {
+        let toks = vec![
+            Token::Include,
+            Token::Str("hexcrawl.crawl".into()),
+            Token::As,
+            Token::Identifier("hex".into()),
+        ];
+        let parsed = Parser::new(toks).include();
+        assert_eq!(
+            parsed.unwrap(),
+            Statement::Include {
+                path: "hexcrawl.crawl".into(),
+                namespace: Some("hex".into()),
+            }
+        )
+    }
+
+    #[test]
+    fn crawl_version() {
+        let parse = |source: &str| {
+            let mut parser = Parser::from_tokens(Scanner::new(source));
+            let statements = parser.parse();
+            (statements, parser.warnings().to_vec())
+        };
+        let (major, minor) = GRAMMAR_VERSION;
+
+        let (statements, warnings) = parse(&format!("crawl-version {major}.{minor}\ncamp\n"));
+        assert!(matches!(
+            statements[..],
+            [Ok(Statement::ProcedureCall { .. })]
+        ));
+        assert!(warnings.is_empty());
+
+        let (statements, warnings) = parse("crawl-version 0.0\n\ncamp\n");
+        assert_eq!(statements.len(), 1);
+        assert_eq!(warnings.len(), 1);
+
+        let (statements, _) = parse(&format!("crawl-version {}.0\ncamp\n", major + 1));
+        assert!(matches!(
+            statements[0],
+            Err(CrawlError::VersionError { .. })
+        ));
+
+        let (statements, _) = parse(&format!("camp\ncrawl-version {major}.{minor}\n"));
+        assert!(statements[1].is_err());
+
+        // Errors in the version are pointed at its line.
+        let mut scanner = Scanner::new("crawl-version 9.9\nreminder \"x\"\n");
+        let tokens = scanner.tokens().into_iter().map(Result::unwrap).collect();
+        let mut parser = Parser::new(tokens).with_lines(scanner.lines().to_vec());
+        assert!(matches!(
+            parser.next(),
+            Some(Err(CrawlError::VersionError { .. }))
+        ));
+        assert_eq!(parser.statement_line(), Some(1));
+    }
+
+    #[test]
+    fn deprecations() {
+        let source = "let x = 2\nset-fact \"party is {}\" % x\nset-fact \"party met {}\" % roll on table \"npcs\"\n";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.tokens().into_iter().map(Result::unwrap).collect();
+        let mut parser = Parser::new(tokens)
+            .with_lines(scanner.lines().to_vec())
+            .with_spans(scanner.spans().to_vec());
+        assert!(parser.parse().iter().all(Result::is_ok));
+        let deprecations = parser.deprecations();
+        assert_eq!(
+            deprecations,
+            [Deprecation {
+                span: 19..36,
+                line: 2,
+                message: "interpolating with `%`".into(),
+                replacement: Some("\"party is {x}\"".into()),
+            }]
+        );
+        assert_eq!(&source[deprecations[0].span.clone()], "\"party is {}\" % x");
+    }
+
+    #[test]
+    fn inline_interpolation() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "set-fact \"{who} is {mood}\"\nset-fact \"party is {}\"\n",
+        ));
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::SetFact(CrawlStr::InterpolatedStr {
+                format_string: "{who} is {mood}".into(),
+                expressions: ["who", "mood"]
+                    .map(|identifier| Statement::ProcedureCall {
+                        identifier: identifier.into(),
+                        arguments: Vec::new(),
+                    })
+                    .into(),
+            })
+        );
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::SetFact(CrawlStr::Str("party is {}".into()))
+        );
+        assert!(parser.deprecations().is_empty());
+
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "set-fact \"rolled {last roll}\"\nset-fact \"rolled {}\" % last roll\n",
+        ));
+        for _ in 0..2 {
+            assert!(matches!(
+                parser.next().unwrap().unwrap(),
+                Statement::SetFact(CrawlStr::InterpolatedStr { expressions, .. })
+                    if expressions == [Statement::LastRoll]
+            ));
+        }
+        assert_eq!(
+            parser.deprecations()[0].replacement.as_deref(),
+            Some("\"rolled {last roll}\"")
+        );
+    }
+
+    #[test]
+    fn define_roll() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "define roll \"attack\" as 1d20 + 5\nif roll 15+ on \"attack\" + 1 => reminder \"hit\"\n",
+        ));
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::DefineRoll {
+                name: "attack".into(),
+                roll: ModifiedRollSpecifier {
+                    base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 20)),
+                    modifier: 5,
+                    terms: Vec::new(),
+                },
+            }
+        );
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::IfThen {
+                antecedent: Antecedent::DiceRoll {
+                    target: Token::OverOrEqual(15),
+                    roll_specifier: ModifiedRollSpecifier {
+                        base_roll_specifier: Token::Str("attack".into()),
+                        modifier: 1,
+                        terms: Vec::new(),
+                    },
+                },
+                consequent: Box::new(Statement::Reminder("hit".into())),
+            }
+        );
+    }
+
+    #[test]
+    fn modifier_stack() {
+        let parser = Parser::from_tokens(Scanner::new(
+            "apply modifier +1 to all rolls named \"attack\"\napply modifier -2 to all rolls\nremove modifier\nremove modifier from all rolls named \"attack\"\n",
+        ));
+        assert_eq!(
+            parser.map(Result::unwrap).collect::<Vec<_>>(),
+            vec![
+                Statement::ApplyModifier {
+                    modifier: 1,
+                    rolls: Some("attack".into()),
+                },
+                Statement::ApplyModifier {
+                    modifier: -2,
+                    rolls: None,
+                },
+                Statement::RemoveModifier(None),
+                Statement::RemoveModifier(Some("attack".into())),
+            ]
+        );
+        assert!(
+            Parser::from_tokens(Scanner::new("apply modifier 1 to all rolls\n"))
+                .next()
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn natural_roll() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "if critical on 1d20 => reminder \"crit\"\nif fumble on \"attack\" => reminder \"fumble\"\n",
+        ));
+        let natural_roll = |natural, base_roll_specifier| Antecedent::NaturalRoll {
+            natural,
+            roll_specifier: ModifiedRollSpecifier {
+                base_roll_specifier,
+                modifier: 0,
+                terms: Vec::new(),
+            },
+        };
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::IfThen {
+                antecedent: natural_roll(
+                    Natural::Critical,
+                    Token::RollSpecifier(RollSpec::new(1, 20))
+                ),
+                consequent: Box::new(Statement::Reminder("crit".into())),
+            }
+        );
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::IfThen {
+                antecedent: natural_roll(Natural::Fumble, Token::Str("attack".into())),
+                consequent: Box::new(Statement::Reminder("fumble".into())),
+            }
+        );
+    }
+
+    #[test]
+    fn check() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "check 1d20 + 4 vs dc\n\tcritical success => reminder \"crit\"\n\tsuccess => reminder \"hit\"\n\telse => reminder \"miss\"\nend\nif critical failure on 1d20 vs 15 => reminder \"ouch\"\n",
+        ));
+        let d20 = |modifier| ModifiedRollSpecifier {
+            base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 20)),
+            modifier,
+            terms: Vec::new(),
+        };
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::Check {
+                roll_specifier: d20(4),
+                dc: Amount::Call("dc".into()),
+                arms: vec![
+                    CheckArm {
+                        degree: Degree::CriticalSuccess,
+                        consequent: Statement::Reminder("crit".into()),
+                    },
+                    CheckArm {
+                        degree: Degree::Success,
+                        consequent: Statement::Reminder("hit".into()),
+                    },
+                ],
+                otherwise: Some(Box::new(Statement::Reminder("miss".into()))),
+            }
+        );
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::IfThen {
+                antecedent: Antecedent::DegreeOfSuccess {
+                    degree: Degree::CriticalFailure,
+                    roll_specifier: d20(0),
+                    dc: Amount::Fixed(15),
+                },
+                consequent: Box::new(Statement::Reminder("ouch".into())),
+            }
+        );
+    }
+
+    #[test]
+    fn money() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "add 2d6 gp to \"party treasure\"\nspend 15 sp\nspend x cp from \"party treasure\"\nspend 1 \"torches\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
         assert_eq!(
-            parsed,
-            vec![Statement::Procedure {
-                declaration: ProcedureDeclaration("proc".into()),
-                body: vec![Box::new(Statement::ProcedureCall("other-proc".into()))]
-            }]
+            statements,
+            vec![
+                Statement::AddMoney {
+                    amount: Amount::Roll(ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(2, 6)),
+                        modifier: 0,
+                        terms: vec![],
+                    }),
+                    coin: "gp".into(),
+                    purse: "party treasure".into(),
+                },
+                Statement::SpendMoney {
+                    amount: Amount::Fixed(15),
+                    coin: "sp".into(),
+                    purse: None,
+                },
+                Statement::SpendMoney {
+                    amount: Amount::Call("x".into()),
+                    coin: "cp".into(),
+                    purse: Some("party treasure".into()),
+                },
+                Statement::SpendResource {
+                    resource: "torches".into(),
+                    amount: Amount::Fixed(1),
+                },
+            ]
         );
     }
 
     #[test]
-    fn parse_reminder() {
-        let toks = vec![
-            Token::Reminder,
-            Token::Str("don't forget to eat".into()),
-            Token::Newline,
-            Token::Eof,
-        ];
-        let parsed = Parser::new(toks).parse();
+    fn name_roll() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "load names \"dwarf-names.csv\"\nroll name from \"dwarf-names.csv\"\nset-fact \"met {}\" % roll name from \"dwarf-names.csv\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
         assert_eq!(
-            parsed
-                .into_iter()
-                .map(|a| a.unwrap())
-                .collect::<Vec<Statement>>(),
-            vec![Statement::Reminder("don't forget to eat".into())],
-        )
+            statements,
+            vec![
+                Statement::LoadNames("dwarf-names.csv".into()),
+                Statement::NameRoll("dwarf-names.csv".into()),
+                Statement::SetFact(CrawlStr::InterpolatedStr {
+                    format_string: "met {}".into(),
+                    expressions: vec![Statement::NameRoll("dwarf-names.csv".into())],
+                }),
+            ]
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn parse_statement_no_nl() {
-        let toks = vec![
-            Token::Reminder,
-            Token::Str("statements end with a newline".into()),
-            Token::Eof,
-        ];
-        let _: Vec<Statement> = Parser::new(toks)
-            .parse()
-            .into_iter()
-            .map(|a| a.unwrap())
-            .collect();
+    fn date() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "advance date by 1d6 days\nif date is full-moon => advance date by 1 week\nif date is \"Midwinter\" => set-fact \"it's {current date}\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(
+            statements,
+            vec![
+                Statement::AdvanceDate {
+                    amount: Amount::Roll(ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 6)),
+                        modifier: 0,
+                        terms: vec![],
+                    }),
+                    unit: DateUnit::Days,
+                },
+                Statement::IfThen {
+                    antecedent: Antecedent::DateIs("full-moon".into()),
+                    consequent: Box::new(Statement::AdvanceDate {
+                        amount: Amount::Fixed(1),
+                        unit: DateUnit::Weeks,
+                    }),
+                },
+                Statement::IfThen {
+                    antecedent: Antecedent::DateIs("Midwinter".into()),
+                    consequent: Box::new(Statement::SetFact(CrawlStr::InterpolatedStr {
+                        format_string: "it's {current date}".into(),
+                        expressions: vec![Statement::CurrentDate],
+                    })),
+                },
+            ]
+        );
+        assert!(
+            Parser::from_tokens(Scanner::new("advance date by 2 fortnights\n"))
+                .any(|statement| statement.is_err())
+        );
     }
 
     #[test]
-    fn if_then() {
-        let toks = vec![
-            Token::If,
-            Token::Roll,
-            Token::Num(6),
-            Token::On,
-            Token::RollSpecifier("1d6".into()),
-            Token::Plus,
-            Token::Num(1),
-            Token::Arrow,
-            Token::SetFact,
-            Token::Str("cool!".into()),
-        ];
-        let parsed = Parser::new(toks).if_then();
+    fn awards() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "award 250 xp\naward treasure 1d6 x 100 gp\nreport xp\naward\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
         assert_eq!(
-            parsed.unwrap(),
-            Statement::IfThen {
-                antecedent: Antecedent::DiceRoll {
-                    target: Token::Num(6),
-                    roll_specifier: ModifiedRollSpecifier {
-                        base_roll_specifier: Token::RollSpecifier("1d6".into()),
-                        modifier: 1,
-                    },
+            statements,
+            vec![
+                Statement::AwardXp {
+                    amount: Amount::Fixed(250),
+                    multiplier: 1,
                 },
-                consequent: Box::new(Statement::SetFact(CrawlStr::Str("cool!".into()))),
-            }
-        )
+                Statement::AwardTreasure {
+                    amount: Amount::Roll(ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 6)),
+                        modifier: 0,
+                        terms: vec![],
+                    }),
+                    multiplier: 100,
+                    coin: "gp".into(),
+                },
+                Statement::ReportXp,
+                // Still a procedure call without an amount
+                Statement::ProcedureCall {
+                    identifier: "award".into(),
+                    arguments: vec![],
+                },
+            ]
+        );
+        assert!(
+            Parser::from_tokens(Scanner::new("award 250 gp\n")).any(|statement| statement.is_err())
+        );
     }
 
     #[test]
-    fn matching_roll() {
-        let toks = vec![
-            Token::Roll,
-            Token::RollSpecifier("2d20".into()),
-            Token::Minus,
-            Token::Num(2),
-            Token::Newline,
-            Token::Indent,
-            Token::Num(2),
-            Token::Arrow,
-            Token::SetFact,
-            Token::Str("you died".into()),
-            Token::Newline,
-            Token::Indent,
-            Token::NumRange(3, 40),
-            Token::Arrow,
-            Token::SetFact,
-            Token::Str("you're alright".into()),
-            Token::Newline,
-            Token::End,
-        ];
-        let parsed = Parser::new(toks).matching_roll();
+    fn lights() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "light torch\nadvance time by 3 turns\nif fact? \"torch is out\" => light lantern\nadvance time by 1 turn\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
         assert_eq!(
-            parsed.unwrap(),
-            Statement::MatchingRoll {
-                roll_specifier: ModifiedRollSpecifier {
-                    base_roll_specifier: Token::RollSpecifier("2d20".into()),
-                    modifier: -2,
+            statements,
+            vec![
+                Statement::Light("torch".into()),
+                Statement::AdvanceTime(Amount::Fixed(3)),
+                Statement::IfThen {
+                    antecedent: Antecedent::CheckFact("torch is out".into()),
+                    consequent: Box::new(Statement::Light("lantern".into())),
+                },
+                Statement::AdvanceTime(Amount::Fixed(1)),
+            ]
+        );
+        assert!(
+            Parser::from_tokens(Scanner::new("advance time by 3 hours\n"))
+                .any(|statement| statement.is_err())
+        );
+    }
+
+    #[test]
+    fn morale_check() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "morale check \"goblins\" 7\nif fact? \"goblins are hurt\" => morale check \"goblins\" fact \"goblins morale\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(
+            statements,
+            vec![
+                Statement::MoraleCheck {
+                    group: "goblins".into(),
+                    morale: ModifierValue::Fixed(7),
+                },
+                Statement::IfThen {
+                    antecedent: Antecedent::CheckFact("goblins are hurt".into()),
+                    consequent: Box::new(Statement::MoraleCheck {
+                        group: "goblins".into(),
+                        morale: ModifierValue::Fact("goblins morale".into()),
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_room() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "generate room\nif fact? \"door is open\" => generate room\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(statements[0], Statement::GenerateRoom);
+        assert!(matches!(
+            &statements[1],
+            Statement::IfThen { consequent, .. } if **consequent == Statement::GenerateRoom
+        ));
+    }
+
+    #[test]
+    fn rumors() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "add rumor \"the well is cursed\" truth hidden\nreveal rumor \"the well is cursed\" as true\nhear 1d3 rumors\nhear 1 rumor\nreport rumors\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(
+            statements,
+            vec![
+                Statement::AddRumor {
+                    rumor: "the well is cursed".into(),
+                    truth: None,
                 },
+                Statement::RevealRumor {
+                    rumor: "the well is cursed".into(),
+                    truth: Some(true),
+                },
+                Statement::HearRumors(Amount::Roll(ModifiedRollSpecifier {
+                    base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 3)),
+                    modifier: 0,
+                    terms: Vec::new(),
+                })),
+                Statement::HearRumors(Amount::Fixed(1)),
+                Statement::ReportRumors,
+            ]
+        );
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "reveal rumor \"the well is cursed\" as hidden\n",
+        ));
+        assert!(parser.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn downtime() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "downtime 2 weeks\n\t\"Brofist\" => carousing\n\t\"Ada\" => research training\nend\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(
+            statements,
+            vec![Statement::Downtime {
+                weeks: Amount::Fixed(2),
                 arms: vec![
-                    MatchingRollArm {
-                        target: Token::Num(2),
-                        consequent: Statement::SetFact(CrawlStr::Str("you died".into()))
+                    DowntimeArm {
+                        character: "Brofist".into(),
+                        activities: vec!["carousing".into()],
                     },
-                    MatchingRollArm {
-                        target: Token::NumRange(3, 40),
-                        consequent: Statement::SetFact(CrawlStr::Str("you're alright".into()))
+                    DowntimeArm {
+                        character: "Ada".into(),
+                        activities: vec!["research".into(), "training".into()],
                     },
-                ]
+                ],
+            }]
+        );
+        let mut parser =
+            Parser::from_tokens(Scanner::new("downtime 1 week\n\t\"Brofist\" =>\nend\n"));
+        assert!(parser.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn scene_check() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "scene check\nif fact? \"scene is over\" => scene check\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(statements[0], Statement::SceneCheck);
+        assert!(matches!(
+            &statements[1],
+            Statement::IfThen { consequent, .. } if **consequent == Statement::SceneCheck
+        ));
+    }
+
+    #[test]
+    fn pin_table() {
+        let parsed = Parser::from_tokens(Scanner::new(
+            "pin table \"weather\" to \"storm\" for 3 rolls\nif fact? \"omen\" => pin table \"weather\" to \"fog\" for 1 roll\npin table \"weather\" to \"storm\" for 0 rolls\n",
+        ))
+        .parse();
+        assert_eq!(
+            parsed[0].as_ref().unwrap(),
+            &Statement::PinTable {
+                table: "weather".into(),
+                entry: "storm".into(),
+                rolls: 3,
             }
-        )
+        );
+        assert!(matches!(
+            parsed[1].as_ref().unwrap(),
+            Statement::IfThen { consequent, .. }
+                if matches!(**consequent, Statement::PinTable { rolls: 1, .. })
+        ));
+        assert!(parsed[2].is_err());
     }
 
     #[test]
-    fn set_fact() {
-        let toks = vec![Token::SetFact, Token::Str("weather is nice".into())];
-        let parsed = Parser::new(toks).set_fact();
+    fn chaos_factor() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "increase chaos factor\ndecrease chaos factor\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
         assert_eq!(
-            parsed.unwrap(),
-            Statement::SetFact(CrawlStr::Str("weather is nice".into()))
-        )
+            statements,
+            [Statement::AdjustChaos(1), Statement::AdjustChaos(-1)]
+        );
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "set-fact \"chaos is {chaos factor}\"\nincrease chaos\n",
+        ));
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::SetFact(CrawlStr::InterpolatedStr {
+                format_string: "chaos is {chaos factor}".into(),
+                expressions: vec![Statement::ChaosFactor],
+            })
+        );
+        assert!(parser.next().unwrap().is_err());
     }
 
     #[test]
-    fn set_pfact() {
-        let toks = vec![
-            Token::SetPersistentFact,
-            Token::Str("weather is nice".into()),
-        ];
-        let parsed = Parser::new(toks).set_persistent_fact();
+    fn prices() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "load prices \"prices.csv\"\nprice \"torch\"\nhaggle roll \"plate mail\" at \"village\"\nif in stock? \"plate mail\" => reminder \"buy it\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
         assert_eq!(
-            parsed.unwrap(),
-            Statement::SetPersistentFact("weather is nice".into())
-        )
+            statements,
+            vec![
+                Statement::LoadPrices("prices.csv".into()),
+                Statement::Price {
+                    item: "torch".into(),
+                    settlement: None,
+                },
+                Statement::Haggle {
+                    item: "plate mail".into(),
+                    settlement: Some("village".into()),
+                },
+                Statement::IfThen {
+                    antecedent: Antecedent::InStock {
+                        item: "plate mail".into(),
+                        settlement: None,
+                    },
+                    consequent: Box::new(Statement::Reminder("buy it".into())),
+                },
+            ]
+        );
     }
 
     #[test]
-    fn clear_fact() {
-        let toks = vec![Token::ClearFact, Token::Str("weather is nice".into())];
-        let parsed = Parser::new(toks).clear_fact();
+    fn faction_fronts() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "create faction clock \"Cult of Orcus\" 6\nadvance faction fronts\nadvance faction fronts on table \"fronts\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
         assert_eq!(
-            parsed.unwrap(),
-            Statement::ClearFact("weather is nice".into())
-        )
+            statements,
+            vec![
+                Statement::CreateFront {
+                    faction: "Cult of Orcus".into(),
+                    segments: 6,
+                },
+                Statement::AdvanceFronts(FRONTS_TABLE.into()),
+                Statement::AdvanceFronts("fronts".into()),
+            ]
+        );
     }
 
     #[test]
-    fn clear_pfact() {
-        let toks = vec![
-            Token::ClearPersistentFact,
-            Token::Str("weather is nice".into()),
-        ];
-        let parsed = Parser::new(toks).clear_persistent_fact();
+    fn encounter() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "encounter \"forest\"\nroll 1d6\n    1 => encounter \"forest\"\nend\nencounter\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(statements[0], Statement::Encounter("forest".into()));
+        assert!(matches!(
+            &statements[1],
+            Statement::MatchingRoll { arms, .. }
+                if arms[0].consequent == Statement::Encounter("forest".into())
+        ));
         assert_eq!(
-            parsed.unwrap(),
-            Statement::ClearPersistentFact("weather is nice".into())
-        )
+            statements[2],
+            Statement::ProcedureCall {
+                identifier: "encounter".into(),
+                arguments: vec![],
+            }
+        );
     }
 
     #[test]
-    fn reminder() {
-        let toks = vec![Token::Reminder, Token::Str("don't forget to eat".into())];
-        let parsed = Parser::new(toks).reminder();
+    fn weather() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "advance weather\nif fact? \"party is camping\" => advance weather on table \"mountain weather\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
         assert_eq!(
-            parsed.unwrap(),
-            Statement::Reminder("don't forget to eat".into())
-        )
+            statements,
+            vec![
+                Statement::AdvanceWeather("weather".into()),
+                Statement::IfThen {
+                    antecedent: Antecedent::CheckFact("party is camping".into()),
+                    consequent: Box::new(Statement::AdvanceWeather("mountain weather".into())),
+                },
+            ]
+        );
     }
 
     #[test]
-    fn dice_roll() {
-        let toks = vec![
-            Token::Roll,
-            Token::NumRange(1, 5),
-            Token::On,
-            Token::RollSpecifier("1d12".into()),
-            Token::Plus,
-            Token::Num(5),
-        ];
-        let parsed = Parser::new(toks).dice_roll();
+    fn travel() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "travel \"forest\"\nif fact? \"party is rested\" => travel \"hills\"\ntravel\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
         assert_eq!(
-            parsed.unwrap(),
-            Antecedent::DiceRoll {
-                target: Token::NumRange(1, 5),
-                roll_specifier: ModifiedRollSpecifier {
-                    base_roll_specifier: Token::RollSpecifier("1d12".into()),
-                    modifier: 5,
-                }
+            statements,
+            vec![
+                Statement::Travel("forest".into()),
+                Statement::IfThen {
+                    antecedent: Antecedent::CheckFact("party is rested".into()),
+                    consequent: Box::new(Statement::Travel("hills".into())),
+                },
+                // Still a procedure call without a terrain
+                Statement::ProcedureCall {
+                    identifier: "travel".into(),
+                    arguments: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn inventory() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "add item \"rope\" to \"party\"\nif has item? \"rope\" in \"party\" => remove item \"rope\" from \"party\"\nreport inventory for \"party\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(
+            statements,
+            vec![
+                Statement::AddItem {
+                    item: "rope".into(),
+                    owner: "party".into(),
+                },
+                Statement::IfThen {
+                    antecedent: Antecedent::HasItem {
+                        item: "rope".into(),
+                        owner: "party".into(),
+                    },
+                    consequent: Box::new(Statement::RemoveItem {
+                        item: "rope".into(),
+                        owner: "party".into(),
+                    }),
+                },
+                Statement::ReportInventory("party".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn hit_points() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "track hp \"goblin\" 7\nif roll 15+ on 1d20 => damage \"goblin\" dmg\nif dead \"goblin\" => reminder \"loot\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(
+            statements[0],
+            Statement::TrackHitPoints {
+                entity: "goblin".into(),
+                amount: Amount::Fixed(7),
             }
-        )
+        );
+        let Statement::IfThen { consequent, .. } = &statements[1] else {
+            panic!("expected an if");
+        };
+        assert_eq!(
+            **consequent,
+            Statement::Damage {
+                entity: "goblin".into(),
+                amount: Amount::Call("dmg".into()),
+            }
+        );
+        let Statement::IfThen { antecedent, .. } = &statements[2] else {
+            panic!("expected an if");
+        };
+        assert_eq!(*antecedent, Antecedent::CheckDead("goblin".into()));
     }
 
     #[test]
-    fn table_roll() {
-        let toks = vec![
-            Token::Roll,
-            Token::On,
-            Token::Table,
-            Token::Str("table-t1".into()),
-        ];
-        let parsed = Parser::new(toks).table_roll();
-        assert_eq!(parsed.unwrap(), Statement::TableRoll("table-t1".into()))
+    fn combatants() {
+        let statements: Vec<Statement> = Parser::from_tokens(Scanner::new(
+            "add combatant \"goblin\" rolling 1d20 + 1\nsort combatants\nadvance turn\nif clock \"morale\" full => remove combatant \"goblin\"\nsort \"x\"\n",
+        ))
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(
+            statements,
+            vec![
+                Statement::AddCombatant {
+                    combatant: "goblin".into(),
+                    roll_specifier: ModifiedRollSpecifier {
+                        base_roll_specifier: Token::RollSpecifier(RollSpec::new(1, 20)),
+                        modifier: 1,
+                        terms: Vec::new(),
+                    },
+                },
+                Statement::SortCombatants,
+                Statement::AdvanceTurn,
+                Statement::IfThen {
+                    antecedent: Antecedent::CheckClockFull("morale".into()),
+                    consequent: Box::new(Statement::RemoveCombatant("goblin".into())),
+                },
+                // Still a procedure call, with anything but `combatants`
+                Statement::ProcedureCall {
+                    identifier: "sort".into(),
+                    arguments: vec!["x".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn move_roll() {
+        let mut parser = Parser::from_tokens(Scanner::new(
+            "move \"Act Under Pressure\" with +1 - x\n\ton 10+ => reminder \"you do it\"\n\ton 7-9 => reminder \"you flinch\"\n\ton 6- => reminder \"the MC moves\"\nend\nmove \"Help\"\n\ton 7+ => reminder \"+1\"\nend\n",
+        ));
+        let arm = |target, reminder: &str| MatchingRollArm {
+            target,
+            guard: None,
+            consequent: Statement::Reminder(reminder.into()),
+        };
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Statement::Move {
+                name: "Act Under Pressure".into(),
+                roll_specifier: ModifiedRollSpecifier {
+                    base_roll_specifier: Token::RollSpecifier(RollSpec::new(2, 6)),
+                    modifier: 1,
+                    terms: vec![ModifierTerm {
+                        negated: true,
+                        value: ModifierValue::Variable("x".into()),
+                        condition: None,
+                    }],
+                },
+                arms: vec![
+                    arm(Token::OverOrEqual(10), "you do it"),
+                    arm(Token::NumRange(7, 9), "you flinch"),
+                    arm(Token::UnderOrEqual(6), "the MC moves"),
+                ],
+                otherwise: None,
+            }
+        );
+        let Statement::Move { roll_specifier, .. } = parser.next().unwrap().unwrap() else {
+            panic!("expected a move");
+        };
+        assert_eq!(roll_specifier.modifier, 0);
+
+        // Arms need their `on`, and modifiers their sign.
+        assert!(
+            Parser::from_tokens(Scanner::new("move \"Help\"\n\t7+ => reminder \"x\"\nend\n"))
+                .any(|statement| statement.is_err())
+        );
+        assert!(
+            Parser::from_tokens(Scanner::new(
+                "move \"Help\" with 1\n\ton 7+ => reminder \"x\"\nend\n"
+            ))
+            .any(|statement| statement.is_err())
+        );
     }
 }
@@ -0,0 +1,203 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+
+use crate::dice::DicePool;
+use crate::error::CrawlError;
+
+/// Asks the player to pick one of a `choose` statement's options.
+pub trait Prompt: Send {
+    /// The index of the option chosen.
+    fn choose(&mut self, question: &str, options: &[String]) -> Result<usize, CrawlError>;
+}
+
+/// Asks what physical dice came up as, for groups who roll their own.
+pub trait DicePrompt: Send {
+    /// The face each die in `dice` came up on, in order.
+    fn enter_faces(&mut self, dice: &DicePool) -> Result<Vec<i32>, CrawlError>;
+}
+
+/// Asks over line-based input and output, like a terminal. Options can be picked by number or by
+/// name.
+pub struct TerminalPrompt<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> TerminalPrompt<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        TerminalPrompt { input, output }
+    }
+
+    fn ask(&mut self, question: &str, options: &[String]) -> io::Result<Option<usize>> {
+        writeln!(self.output, "{question}")?;
+        for (idx, option) in options.iter().enumerate() {
+            writeln!(self.output, "  {}. {option}", idx + 1)?;
+        }
+        loop {
+            write!(self.output, "> ")?;
+            self.output.flush()?;
+
+            let mut answer = String::new();
+            if self.input.read_line(&mut answer)? == 0 {
+                return Ok(None);
+            }
+            let answer = answer.trim();
+            let chosen = match answer.parse::<usize>() {
+                Ok(number) => number.checked_sub(1).filter(|idx| *idx < options.len()),
+                Err(_) => options.iter().position(|option| option == answer),
+            };
+            match chosen {
+                Some(idx) => return Ok(Some(idx)),
+                None => writeln!(self.output, "pick one of the options above")?,
+            }
+        }
+    }
+
+    fn ask_faces(&mut self, dice: &DicePool) -> io::Result<Option<Vec<i32>>> {
+        loop {
+            write!(self.output, "roll {dice}: ")?;
+            self.output.flush()?;
+
+            let mut answer = String::new();
+            if self.input.read_line(&mut answer)? == 0 {
+                return Ok(None);
+            }
+            let faces: Result<Vec<i32>, _> = answer.split_whitespace().map(str::parse).collect();
+            match faces {
+                Ok(faces) if dice.accepts_faces(&faces) => return Ok(Some(faces)),
+                _ => writeln!(
+                    self.output,
+                    "enter what each die came up as, separated by spaces"
+                )?,
+            }
+        }
+    }
+
+    fn choice(&mut self, question: &str, options: &[String]) -> Result<usize, CrawlError> {
+        match self.ask(question, options) {
+            Ok(Some(idx)) => Ok(idx),
+            Ok(None) => Err(CrawlError::InterpreterError {
+                reason: format!("no answer to {question:?}"),
+            }),
+            Err(error) => Err(CrawlError::InterpreterError {
+                reason: format!("failed to ask {question:?} ({error})"),
+            }),
+        }
+    }
+
+    fn faces(&mut self, dice: &DicePool) -> Result<Vec<i32>, CrawlError> {
+        match self.ask_faces(dice) {
+            Ok(Some(faces)) => Ok(faces),
+            Ok(None) => Err(CrawlError::InterpreterError {
+                reason: format!("no faces entered for {dice}"),
+            }),
+            Err(error) => Err(CrawlError::InterpreterError {
+                reason: format!("failed to ask for {dice} ({error})"),
+            }),
+        }
+    }
+}
+
+impl<R: BufRead + Send, W: Write + Send> DicePrompt for TerminalPrompt<R, W> {
+    fn enter_faces(&mut self, dice: &DicePool) -> Result<Vec<i32>, CrawlError> {
+        self.faces(dice)
+    }
+}
+
+impl<R: BufRead + Send, W: Write + Send> Prompt for TerminalPrompt<R, W> {
+    fn choose(&mut self, question: &str, options: &[String]) -> Result<usize, CrawlError> {
+        self.choice(question, options)
+    }
+}
+
+/// Asks on stdin and stdout. Stdin is only locked while waiting for an answer, so it can be
+/// shared with the REPL.
+#[derive(Debug, Default)]
+pub struct StdinPrompt;
+
+impl Prompt for StdinPrompt {
+    fn choose(&mut self, question: &str, options: &[String]) -> Result<usize, CrawlError> {
+        TerminalPrompt::new(io::stdin().lock(), io::stdout()).choice(question, options)
+    }
+}
+
+impl DicePrompt for StdinPrompt {
+    fn enter_faces(&mut self, dice: &DicePool) -> Result<Vec<i32>, CrawlError> {
+        TerminalPrompt::new(io::stdin().lock(), io::stdout()).faces(dice)
+    }
+}
+
+/// Answers with options given up front, in order, by name.
+#[derive(Debug, Default)]
+pub struct ScriptedPrompt {
+    answers: VecDeque<String>,
+}
+
+impl ScriptedPrompt {
+    pub fn new(answers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        ScriptedPrompt {
+            answers: answers.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Prompt for ScriptedPrompt {
+    fn choose(&mut self, question: &str, options: &[String]) -> Result<usize, CrawlError> {
+        let answer = self
+            .answers
+            .pop_front()
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("no answer to {question:?}"),
+            })?;
+        options
+            .iter()
+            .position(|option| *option == answer)
+            .ok_or_else(|| CrawlError::InterpreterError {
+                reason: format!("{answer:?} isn't an option for {question:?}"),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::Die;
+
+    fn options() -> Vec<String> {
+        vec!["left".into(), "right".into()]
+    }
+
+    #[test]
+    fn terminal_prompt() {
+        let mut output = Vec::new();
+        let mut prompt = TerminalPrompt::new("3\nright\n".as_bytes(), &mut output);
+        assert_eq!(prompt.choose("Which passage?", &options()).unwrap(), 1);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Which passage?\n  1. left\n  2. right\n"));
+        assert!(output.contains("pick one of the options above"));
+
+        let mut prompt = TerminalPrompt::new("1\n".as_bytes(), io::sink());
+        assert_eq!(prompt.choose("Which passage?", &options()).unwrap(), 0);
+        let mut prompt = TerminalPrompt::new("".as_bytes(), io::sink());
+        assert!(prompt.choose("Which passage?", &options()).is_err());
+    }
+
+    #[test]
+    fn enter_faces() {
+        let dice = DicePool::new(vec![Die(6), Die(6)]);
+        let mut output = Vec::new();
+        let mut prompt = TerminalPrompt::new("7 1\nfour\n4 1\n".as_bytes(), &mut output);
+        assert_eq!(prompt.enter_faces(&dice).unwrap(), vec![4, 1]);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("roll 2d6: "));
+        assert_eq!(output.matches("enter what each die came up as").count(), 2);
+    }
+
+    #[test]
+    fn scripted_prompt() {
+        let mut prompt = ScriptedPrompt::new(["right", "up"]);
+        assert_eq!(prompt.choose("Which passage?", &options()).unwrap(), 1);
+        assert!(prompt.choose("Which passage?", &options()).is_err());
+        assert!(prompt.choose("Which passage?", &options()).is_err());
+    }
+}
@@ -0,0 +1,1074 @@
+use std::collections::HashMap;
+
+use crate::error::CrawlError;
+use crate::interpreter::{AppliedModifier, StatementRecord};
+use crate::journal::{initiative_order, inventory, table_title};
+use crate::locale::{fill, read_strings};
+use crate::scenes::RandomEvent;
+
+// Session records written back out as prose, a sentence each, for pasting into a recap. Each
+// kind of record has a template its fields are filled into by name, like `{table}`, and records
+// nested in a procedure call or a consequent follow their parent's sentence. Bookkeeping, like
+// defining procedures or loading tables, has an empty template and is left out.
+
+// Record kinds and their templates, with the fields each can use.
+const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    // {combatant}, {initiative}
+    (
+        "add_combatant",
+        "{combatant} joined the fight, with initiative {initiative}.",
+    ),
+    // {item}, {owner}, {count}
+    ("add_item", "{owner} got {item}."),
+    // {purse}, {added}, {balance}
+    ("add_money", "{purse} got {added}."),
+    // {combatant}, {round}
+    ("advance_turn", "Round {round}: {combatant}'s turn."),
+    // {from}, {to}
+    ("advance_weather", "The weather turned from {from} to {to}."),
+    // {days}, {date}
+    ("advance_date", "{days} days passed, to {date}."),
+    // {date}
+    ("current_date", ""),
+    // {chaos}
+    ("chaos_factor", ""),
+    // {from}, {to}
+    ("adjust_chaos", "The chaos factor went from {from} to {to}."),
+    // {rolled}, {chaos}, {outcome}
+    ("scene_check", "The scene was {outcome}."),
+    // {rolled}, {chaos}, {outcome}, {focus}, {meaning}
+    (
+        "scene_check_interrupted",
+        "The scene was interrupted: {focus}, {meaning}.",
+    ),
+    // {clock}, {filled}, {segments}
+    (
+        "advance_clock",
+        "The {clock} clock advanced to {filled}/{segments}.",
+    ),
+    // {modifier}, {rolls}
+    ("apply_modifier", "Applied {modifier} to {rolls}."),
+    // {assertion}
+    ("assert", ""),
+    // {campaign}
+    ("campaign", "Switched to the {campaign} campaign."),
+    // {result}
+    ("check_fact", ""),
+    // {question}, {chosen}
+    ("choice", "{question} {chosen}."),
+    // {fact}
+    ("clear_fact", "No longer {fact}."),
+    // {clock}, {segments}
+    ("create_clock", "Started the {clock} clock at 0/{segments}."),
+    // {faction}, {segments}
+    (
+        "create_front",
+        "Started the {faction} front at 0/{segments}.",
+    ),
+    // {rumor}
+    ("add_rumor", ""),
+    // {rumor}, {truth}
+    (
+        "reveal_rumor",
+        "The rumor that {rumor} turned out to be {truth}.",
+    ),
+    // {rumors}, empty if there were none left to hear
+    ("hear_rumors", "Heard rumors: {rumors}."),
+    // {rumors}
+    ("rumor_report", ""),
+    // {fronts}
+    ("advance_fronts", "The factions' fronts advanced: {fronts}."),
+    // {fronts}, {completed}
+    (
+        "advance_fronts_completed",
+        "The factions' fronts advanced: {fronts}, and the {completed} completed.",
+    ),
+    // {antecedent}, only when it didn't hold
+    ("if_then", ""),
+    // {name}, {value}
+    ("let", ""),
+    // {table}
+    ("load_table", ""),
+    // {names}
+    ("load_names", ""),
+    // {file}, {statblocks}
+    ("load_statblocks", ""),
+    // {prices}
+    ("load_prices", ""),
+    // {weeks}, {date}, {reports}
+    (
+        "downtime",
+        "{weeks} weeks of downtime passed, to {date}: {reports}.",
+    ),
+    // {item}, {price}
+    ("price", ""),
+    // {item}, {total}, {percent}, {price}
+    ("haggle", "Haggled over {item}, for {price}."),
+    // {names}, {name}
+    ("name_roll", "Named {name}."),
+    // {target}
+    ("matching_roll", ""),
+    // {total}, {dc}, {degree}
+    ("check", "Rolled {total} against {dc}, a {degree}."),
+    // {statblock}, {save}, {total}, {target}, {degree}
+    (
+        "save",
+        "{statblock} rolled {total} against {target} to save vs {save}, a {degree}.",
+    ),
+    // {move}, {total}, {target}
+    ("move", "Made the move {move}, rolling {total}."),
+    // {fact}, {values}
+    ("matching_fact", ""),
+    // {total}
+    ("nontargeted_roll", "Rolled {total}."),
+    // {question}, {likelihood}, {rolled}, {answer}
+    (
+        "oracle",
+        "Asked the oracle {question} ({likelihood}, rolled {rolled}): {answer}.",
+    ),
+    // {xp}, {session}, {campaign}
+    ("award_xp", "Earned {xp} XP."),
+    // {treasure}, {session}, {campaign}
+    ("award_treasure", "Won {treasure} in treasure."),
+    // {party_size}, {session_xp}, {campaign_xp}, {xp_share}, {session_treasure}, {treasure_share}
+    ("xp_report", ""),
+    // {light}, {turns}
+    ("light", "Lit a {light}."),
+    // {by}, {turns}
+    ("advance_time", "{by} turns passed."),
+    // {by}, {turns}, {guttered}
+    (
+        "advance_time_guttered",
+        "{by} turns passed, and the {guttered} guttered out.",
+    ),
+    // {group}, {total}, {morale}
+    ("morale_held", "{group} held their nerve."),
+    // {group}, {total}, {morale}
+    ("morale_broke", "{group} broke and fled."),
+    // {terrain}, {creature}, {number}, {reaction}, {distance}
+    ("encounter", "Met {creature}, {reaction}, {distance} away."),
+    // {number}, {shape}, {exits}, {contents}
+    (
+        "generate_room",
+        "Came to room {number}, {shape}, and found {contents}.",
+    ),
+    // {terrain}, {events}, {lost}, {encounter}, {weather}, {discovery}
+    ("travel", "Travelled through {terrain}: {events}."),
+    // {procedure}
+    ("procedure_call", "{procedure}:"),
+    // {procedure}
+    ("procedure_definition", ""),
+    // {fact}
+    ("rule_definition", ""),
+    // {generator}
+    ("generator_definition", ""),
+    // {roll}
+    ("roll_definition", ""),
+    // {item}, {owner}, {count}
+    ("remove_item", "{owner} lost {item}."),
+    // {owner}, {items}
+    ("inventory_report", "{owner} has {items}."),
+    // {combatant}
+    ("remove_combatant", "{combatant} left the fight."),
+    // {combatants}
+    ("sort_combatants", "Initiative order: {combatants}."),
+    // {modifier}, {rolls}
+    ("remove_modifier", "Removed {modifier} from {rolls}."),
+    // {generator}, {value}
+    ("generated", "{generator}: {value}."),
+    // {path}
+    ("include", ""),
+    // {function}, {value}
+    ("host_call", "{function}: {value}."),
+    // {reminder}
+    ("reminder", ""),
+    // {entity}, {facts}
+    ("fact_report", "{entity}: {facts}."),
+    // {value}
+    ("return", ""),
+    // {fact}
+    ("set_fact", "{fact}."),
+    // {purse}, {spent}, {balance}
+    ("spend_money", "{purse} spent {spent}."),
+    // {resource}, {spent}, {remaining}
+    (
+        "spend_resource",
+        "Spent {spent} {resource}, {remaining} left.",
+    ),
+    // {entity}, {damage}, {remaining}
+    (
+        "damage",
+        "{entity} took {damage} damage, {remaining} hp left.",
+    ),
+    // {entity}, {hit_points}
+    ("track_hit_points", ""),
+    // {resource}, {remaining}
+    ("track_resource", "Tracking {resource}: {remaining}."),
+    // {resource}, {sides}
+    ("usage_die", ""),
+    // {resource}, {rolled}, {die}
+    ("usage_roll", "Rolled {rolled} for {resource} usage: {die}."),
+    ("with_facts", ""),
+    // {table}, {result}, also for table rolls with arms
+    ("table_roll", "Rolled on {table}: {result}."),
+    // {table}, {result}
+    ("table_roll_pinned", "Rolled on {table}: {result}."),
+    // {table}, {entry}, {rolls}
+    ("pin_table", ""),
+    // {table}, {entry}, {rolls}
+    ("pin_table_once", ""),
+];
+
+/// Writes records as prose, from a template for each kind of record.
+#[derive(Clone, Debug)]
+pub struct Recap {
+    templates: HashMap<String, String>,
+}
+
+impl Default for Recap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recap {
+    pub fn new() -> Self {
+        Recap {
+            templates: DEFAULT_TEMPLATES
+                .iter()
+                .map(|(kind, template)| (kind.to_string(), template.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Write records of `kind`, like `table_roll`, with `template`. An empty template leaves
+    /// them out, though records nested in them are still written.
+    pub fn with_template(mut self, kind: impl Into<String>, template: impl Into<String>) -> Self {
+        self.templates.insert(kind.into(), template.into());
+        self
+    }
+
+    /// Templates from a file, a line each like `table_roll: "📜 {table}: {result}"`, over the
+    /// ones there are. Blank lines, and lines starting with `#`, are skipped.
+    pub fn with_templates(mut self, source: &str) -> Result<Self, CrawlError> {
+        let templates = read_strings(source, |kind| self.templates.contains_key(kind))?;
+        self.templates.extend(templates);
+        Ok(self)
+    }
+
+    /// The record kinds there are templates for.
+    pub fn kinds() -> impl Iterator<Item = &'static str> {
+        DEFAULT_TEMPLATES.iter().map(|(kind, _)| *kind)
+    }
+
+    /// A paragraph for each record.
+    pub fn render<'a>(&self, records: impl IntoIterator<Item = &'a StatementRecord>) -> String {
+        records
+            .into_iter()
+            .map(|record| self.render_record(record))
+            .filter(|paragraph| !paragraph.is_empty())
+            .map(|paragraph| paragraph + "\n")
+            .collect()
+    }
+
+    /// A record and the records nested in it, as sentences run together.
+    pub fn render_record(&self, record: &StatementRecord) -> String {
+        self.sentences(record).join(" ")
+    }
+
+    /// The sentence for a record, followed by those for the records nested in it, leaving out
+    /// any with empty templates.
+    pub fn sentences(&self, record: &StatementRecord) -> Vec<String> {
+        let mut sentences = Vec::new();
+        self.write_record(record, &mut sentences);
+        sentences
+    }
+
+    fn write_record(&self, record: &StatementRecord, sentences: &mut Vec<String>) {
+        let (kind, fields) = fields(record);
+        let sentence = self.fill(kind, &fields);
+        if !sentence.is_empty() {
+            sentences.push(sentence);
+        }
+        for nested in nested(record) {
+            self.write_record(nested, sentences);
+        }
+    }
+
+    fn fill(&self, kind: &str, fields: &[(&str, String)]) -> String {
+        let Some(template) = self.templates.get(kind) else {
+            return String::new();
+        };
+        let fields: Vec<(&str, &str)> = fields
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        let sentence = fill(template, &fields);
+        capitalize(&sentence)
+    }
+}
+
+// A record's kind, and the fields its template can use.
+fn fields(record: &StatementRecord) -> (&'static str, Vec<(&'static str, String)>) {
+    match record {
+        StatementRecord::AdvanceClock {
+            clock,
+            filled,
+            segments,
+        } => (
+            "advance_clock",
+            vec![
+                ("clock", clock.clone()),
+                ("filled", filled.to_string()),
+                ("segments", segments.to_string()),
+            ],
+        ),
+        StatementRecord::AwardXp {
+            xp,
+            session,
+            campaign,
+        } => (
+            "award_xp",
+            vec![
+                ("xp", xp.to_string()),
+                ("session", session.to_string()),
+                ("campaign", campaign.to_string()),
+            ],
+        ),
+        StatementRecord::AwardTreasure {
+            treasure,
+            session,
+            campaign,
+        } => (
+            "award_treasure",
+            vec![
+                ("treasure", treasure.clone()),
+                ("session", session.clone()),
+                ("campaign", campaign.clone()),
+            ],
+        ),
+        StatementRecord::XpReport {
+            party_size,
+            session_xp,
+            campaign_xp,
+            xp_share,
+            session_treasure,
+            treasure_share,
+        } => (
+            "xp_report",
+            vec![
+                ("party_size", party_size.to_string()),
+                ("session_xp", session_xp.to_string()),
+                ("campaign_xp", campaign_xp.to_string()),
+                ("xp_share", xp_share.to_string()),
+                ("session_treasure", session_treasure.clone()),
+                ("treasure_share", treasure_share.clone()),
+            ],
+        ),
+        StatementRecord::Light { light, turns } => (
+            "light",
+            vec![("light", light.clone()), ("turns", turns.to_string())],
+        ),
+        StatementRecord::AdvanceTime {
+            by,
+            turns,
+            guttered,
+        } => (
+            if guttered.is_empty() {
+                "advance_time"
+            } else {
+                "advance_time_guttered"
+            },
+            vec![
+                ("by", by.to_string()),
+                ("turns", turns.to_string()),
+                ("guttered", guttered.join(" and ")),
+            ],
+        ),
+        StatementRecord::MoraleCheck {
+            group,
+            total,
+            morale,
+            held,
+        } => (
+            if *held { "morale_held" } else { "morale_broke" },
+            vec![
+                ("group", group.clone()),
+                ("total", total.to_string()),
+                ("morale", morale.to_string()),
+            ],
+        ),
+        StatementRecord::Encounter {
+            terrain,
+            creature,
+            number,
+            reaction,
+            distance,
+        } => (
+            "encounter",
+            vec![
+                ("terrain", terrain.clone()),
+                ("creature", creature.clone()),
+                (
+                    "number",
+                    number.map(|number| number.to_string()).unwrap_or_default(),
+                ),
+                ("reaction", reaction.clone()),
+                ("distance", distance.clone()),
+            ],
+        ),
+        StatementRecord::GenerateRoom {
+            number,
+            shape,
+            exits,
+            contents,
+        } => (
+            "generate_room",
+            vec![
+                ("number", number.to_string()),
+                ("shape", shape.clone()),
+                ("exits", exits.clone()),
+                ("contents", contents.clone()),
+            ],
+        ),
+        StatementRecord::AdvanceWeather { from, to } => (
+            "advance_weather",
+            vec![("from", from.clone()), ("to", to.clone())],
+        ),
+        StatementRecord::AdvanceDate { days, date } => (
+            "advance_date",
+            vec![("days", days.to_string()), ("date", date.clone())],
+        ),
+        StatementRecord::CurrentDate(date) => ("current_date", vec![("date", date.clone())]),
+        StatementRecord::ChaosFactor(chaos) => ("chaos_factor", vec![("chaos", chaos.to_string())]),
+        StatementRecord::SceneCheck {
+            rolled,
+            chaos,
+            outcome,
+            event,
+        } => {
+            let mut fields = vec![
+                ("rolled", rolled.to_string()),
+                ("chaos", chaos.to_string()),
+                ("outcome", outcome.to_string()),
+            ];
+            match event {
+                Some(RandomEvent { focus, meaning }) => {
+                    fields.push(("focus", focus.clone()));
+                    fields.push(("meaning", meaning.clone()));
+                    ("scene_check_interrupted", fields)
+                }
+                None => ("scene_check", fields),
+            }
+        }
+        StatementRecord::AdjustChaos { from, to } => (
+            "adjust_chaos",
+            vec![("from", from.to_string()), ("to", to.to_string())],
+        ),
+        StatementRecord::PinTable {
+            table,
+            entry,
+            rolls,
+        } => (
+            if *rolls == 1 {
+                "pin_table_once"
+            } else {
+                "pin_table"
+            },
+            vec![
+                ("table", table_title(table).into()),
+                ("entry", entry.clone()),
+                ("rolls", rolls.to_string()),
+            ],
+        ),
+        StatementRecord::ApplyModifier(applied) => ("apply_modifier", modifier_fields(applied)),
+        StatementRecord::Assert(assertion) => ("assert", vec![("assertion", assertion.clone())]),
+        StatementRecord::Campaign(campaign) => ("campaign", vec![("campaign", campaign.clone())]),
+        StatementRecord::CheckFact(result) | StatementRecord::CheckPersistentFact(result) => {
+            ("check_fact", vec![("result", yes_no(*result).into())])
+        }
+        StatementRecord::Choice {
+            question, chosen, ..
+        } => (
+            "choice",
+            vec![("question", question.clone()), ("chosen", chosen.clone())],
+        ),
+        StatementRecord::ClearFact(fact) | StatementRecord::ClearPersistentFact(fact) => {
+            ("clear_fact", vec![("fact", fact.clone())])
+        }
+        StatementRecord::AddRumor(rumor) => ("add_rumor", vec![("rumor", rumor.clone())]),
+        StatementRecord::RevealRumor { rumor, truth } => (
+            "reveal_rumor",
+            vec![("rumor", rumor.clone()), ("truth", truth.to_string())],
+        ),
+        StatementRecord::HearRumors(heard) => ("hear_rumors", vec![("rumors", heard.join("; "))]),
+        StatementRecord::RumorReport(rumors) => {
+            let rumors: Vec<&str> = rumors.iter().map(|(rumor, _)| rumor.as_str()).collect();
+            ("rumor_report", vec![("rumors", rumors.join("; "))])
+        }
+        StatementRecord::CreateFront { faction, segments } => (
+            "create_front",
+            vec![
+                ("faction", faction.clone()),
+                ("segments", segments.to_string()),
+            ],
+        ),
+        StatementRecord::AdvanceFronts(advanced) => {
+            let completed: Vec<&str> = advanced
+                .iter()
+                .filter(|front| front.is_complete())
+                .map(|front| front.faction.as_str())
+                .collect();
+            let fronts: Vec<String> = advanced
+                .iter()
+                .map(|front| format!("{} {}/{}", front.faction, front.filled, front.segments))
+                .collect();
+            (
+                if completed.is_empty() {
+                    "advance_fronts"
+                } else {
+                    "advance_fronts_completed"
+                },
+                vec![
+                    ("fronts", fronts.join(", ")),
+                    ("completed", completed.join(" and ")),
+                ],
+            )
+        }
+        StatementRecord::CreateClock { clock, segments } => (
+            "create_clock",
+            vec![("clock", clock.clone()), ("segments", segments.to_string())],
+        ),
+        // One that held is told by its consequent.
+        StatementRecord::IfThen {
+            consequent: Some(_),
+            ..
+        } => ("", Vec::new()),
+        StatementRecord::IfThen {
+            antecedent,
+            consequent: None,
+        } => ("if_then", vec![("antecedent", yes_no(*antecedent).into())]),
+        StatementRecord::Let { name, value } => (
+            "let",
+            vec![("name", name.clone()), ("value", value.to_string())],
+        ),
+        StatementRecord::LoadTable(table) => {
+            ("load_table", vec![("table", table_title(table).into())])
+        }
+        StatementRecord::LoadNames(names) => {
+            ("load_names", vec![("names", table_title(names).into())])
+        }
+        StatementRecord::LoadPrices(prices) => {
+            ("load_prices", vec![("prices", table_title(prices).into())])
+        }
+        StatementRecord::Price { item, price } => (
+            "price",
+            vec![("item", item.clone()), ("price", price.clone())],
+        ),
+        StatementRecord::Haggle {
+            item,
+            total,
+            percent,
+            price,
+        } => (
+            "haggle",
+            vec![
+                ("item", item.clone()),
+                ("total", total.to_string()),
+                ("percent", percent.to_string()),
+                ("price", price.clone()),
+            ],
+        ),
+        StatementRecord::LoadStatblocks { file, statblocks } => (
+            "load_statblocks",
+            vec![
+                ("file", file.clone()),
+                ("statblocks", statblocks.join(", ")),
+            ],
+        ),
+        StatementRecord::NameRoll { names, name } => (
+            "name_roll",
+            vec![("names", table_title(names).into()), ("name", name.clone())],
+        ),
+        StatementRecord::MatchingRoll { matched_target, .. } => {
+            let target = match matched_target {
+                Some(target) => format!("{target:?}"),
+                None => "nothing".into(),
+            };
+            ("matching_roll", vec![("target", target)])
+        }
+        StatementRecord::Move {
+            name,
+            total,
+            matched_target,
+            ..
+        } => {
+            let target = match matched_target {
+                Some(target) => format!("{target:?}"),
+                None => "nothing".into(),
+            };
+            (
+                "move",
+                vec![
+                    ("move", name.clone()),
+                    ("total", total.to_string()),
+                    ("target", target),
+                ],
+            )
+        }
+        StatementRecord::Check {
+            total, dc, degree, ..
+        } => (
+            "check",
+            vec![
+                ("total", total.to_string()),
+                ("dc", dc.to_string()),
+                ("degree", degree.to_string()),
+            ],
+        ),
+        StatementRecord::Save {
+            statblock,
+            save,
+            total,
+            target,
+            degree,
+            ..
+        } => (
+            "save",
+            vec![
+                ("statblock", statblock.clone()),
+                ("save", save.clone()),
+                ("total", total.to_string()),
+                ("target", target.to_string()),
+                ("degree", degree.to_string()),
+            ],
+        ),
+        StatementRecord::MatchingFact { fact, values, .. } => {
+            let values = match values.as_slice() {
+                [] => "not set".into(),
+                values => values.join("; "),
+            };
+            (
+                "matching_fact",
+                vec![("fact", fact.clone()), ("values", values)],
+            )
+        }
+        StatementRecord::MatchingTableRoll {
+            table,
+            entry,
+            pinned,
+            ..
+        }
+        | StatementRecord::TableRoll {
+            table,
+            entry,
+            pinned,
+        } => {
+            let result = if entry.is_empty() {
+                "no result".into()
+            } else {
+                entry.clone()
+            };
+            (
+                if *pinned {
+                    "table_roll_pinned"
+                } else {
+                    "table_roll"
+                },
+                vec![("table", table_title(table).into()), ("result", result)],
+            )
+        }
+        StatementRecord::NontargetedRoll(total) => {
+            ("nontargeted_roll", vec![("total", total.to_string())])
+        }
+        StatementRecord::Oracle {
+            question,
+            likelihood,
+            rolled,
+            answer,
+        } => (
+            "oracle",
+            vec![
+                ("question", question.clone()),
+                ("likelihood", likelihood.to_string()),
+                ("rolled", rolled.to_string()),
+                ("answer", yes_no(*answer).into()),
+            ],
+        ),
+        StatementRecord::Travel {
+            terrain,
+            lost,
+            encounter,
+            weather,
+            discovery,
+        } => {
+            let mut events = Vec::new();
+            if *lost {
+                events.push("got lost".to_string());
+            }
+            events.extend(encounter.iter().map(|encounter| format!("met {encounter}")));
+            events.extend(weather.iter().map(|weather| format!("weather {weather}")));
+            events.extend(
+                discovery
+                    .iter()
+                    .map(|discovery| format!("found {discovery}")),
+            );
+            if events.is_empty() {
+                events.push("nothing happened".into());
+            }
+            (
+                "travel",
+                vec![
+                    ("terrain", terrain.clone()),
+                    ("events", events.join(", ")),
+                    ("lost", yes_no(*lost).into()),
+                    ("encounter", encounter.clone().unwrap_or_default()),
+                    ("weather", weather.clone().unwrap_or_default()),
+                    ("discovery", discovery.clone().unwrap_or_default()),
+                ],
+            )
+        }
+        StatementRecord::ProcedureCall { identifier, .. } => {
+            ("procedure_call", vec![("procedure", identifier.clone())])
+        }
+        StatementRecord::ProcedureDefinition(identifier) => (
+            "procedure_definition",
+            vec![("procedure", identifier.clone())],
+        ),
+        StatementRecord::RuleDefinition(fact) => ("rule_definition", vec![("fact", fact.clone())]),
+        StatementRecord::GeneratorDefinition(generator) => (
+            "generator_definition",
+            vec![("generator", generator.clone())],
+        ),
+        StatementRecord::RollDefinition(roll) => ("roll_definition", vec![("roll", roll.clone())]),
+        StatementRecord::RemoveModifier(applied) => ("remove_modifier", modifier_fields(applied)),
+        StatementRecord::Generated { generator, value } => (
+            "generated",
+            vec![("generator", generator.clone()), ("value", value.clone())],
+        ),
+        StatementRecord::Include { path, .. } => ("include", vec![("path", path.clone())]),
+        StatementRecord::HostCall { identifier, value } => (
+            "host_call",
+            vec![("function", identifier.clone()), ("value", value.clone())],
+        ),
+        StatementRecord::Reminder(reminder) => ("reminder", vec![("reminder", reminder.clone())]),
+        StatementRecord::FactReport { entity, facts } => {
+            let facts = if facts.is_empty() {
+                "nothing known".into()
+            } else {
+                facts
+                    .iter()
+                    .map(|(attribute, value)| format!("{attribute} {value}"))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            };
+            (
+                "fact_report",
+                vec![("entity", entity.clone()), ("facts", facts)],
+            )
+        }
+        StatementRecord::Return(value) => ("return", vec![("value", value.clone())]),
+        StatementRecord::SetFact(fact) | StatementRecord::SetPersistentFact(fact) => {
+            ("set_fact", vec![("fact", fact.clone())])
+        }
+        StatementRecord::SpendResource {
+            resource,
+            spent,
+            remaining,
+        } => (
+            "spend_resource",
+            vec![
+                ("resource", resource.clone()),
+                ("spent", spent.to_string()),
+                ("remaining", remaining.to_string()),
+            ],
+        ),
+        StatementRecord::Damage {
+            entity,
+            damage,
+            remaining,
+        } => (
+            "damage",
+            vec![
+                ("entity", entity.clone()),
+                ("damage", damage.to_string()),
+                ("remaining", remaining.to_string()),
+            ],
+        ),
+        StatementRecord::TrackHitPoints { entity, hit_points } => (
+            "track_hit_points",
+            vec![
+                ("entity", entity.clone()),
+                ("hit_points", hit_points.to_string()),
+            ],
+        ),
+        StatementRecord::TrackResource {
+            resource,
+            remaining,
+        } => (
+            "track_resource",
+            vec![
+                ("resource", resource.clone()),
+                ("remaining", remaining.to_string()),
+            ],
+        ),
+        StatementRecord::UsageDie { resource, sides } => (
+            "usage_die",
+            vec![("resource", resource.clone()), ("sides", sides.to_string())],
+        ),
+        StatementRecord::UsageRoll {
+            resource,
+            rolled,
+            die,
+        } => {
+            let die = match die {
+                Some(die) => format!("now d{die}"),
+                None => "used up".into(),
+            };
+            (
+                "usage_roll",
+                vec![
+                    ("resource", resource.clone()),
+                    ("rolled", rolled.to_string()),
+                    ("die", die),
+                ],
+            )
+        }
+        StatementRecord::WithFacts(_) => ("with_facts", Vec::new()),
+        StatementRecord::Downtime {
+            weeks,
+            date,
+            reports,
+            ..
+        } => {
+            let reports: Vec<String> = reports
+                .iter()
+                .map(|report| format!("{} spent {}", report.character, report.spent))
+                .collect();
+            (
+                "downtime",
+                vec![
+                    ("weeks", weeks.to_string()),
+                    ("date", date.clone()),
+                    ("reports", reports.join(", ")),
+                ],
+            )
+        }
+        StatementRecord::AddCombatant {
+            combatant,
+            initiative,
+        } => (
+            "add_combatant",
+            vec![
+                ("combatant", combatant.clone()),
+                ("initiative", initiative.to_string()),
+            ],
+        ),
+        StatementRecord::AdvanceTurn { combatant, round } => (
+            "advance_turn",
+            vec![
+                ("combatant", combatant.clone()),
+                ("round", round.to_string()),
+            ],
+        ),
+        StatementRecord::AddItem { item, owner, count } => {
+            ("add_item", item_fields(item, owner, *count))
+        }
+        StatementRecord::AddMoney {
+            purse,
+            added,
+            balance,
+        } => (
+            "add_money",
+            vec![
+                ("purse", purse.clone()),
+                ("added", added.clone()),
+                ("balance", balance.clone()),
+            ],
+        ),
+        StatementRecord::SpendMoney {
+            purse,
+            spent,
+            balance,
+        } => (
+            "spend_money",
+            vec![
+                ("purse", purse.clone()),
+                ("spent", spent.clone()),
+                ("balance", balance.clone()),
+            ],
+        ),
+        StatementRecord::RemoveItem { item, owner, count } => {
+            ("remove_item", item_fields(item, owner, *count))
+        }
+        StatementRecord::InventoryReport { owner, items } => {
+            let items = if items.is_empty() {
+                "nothing".into()
+            } else {
+                inventory(items)
+            };
+            (
+                "inventory_report",
+                vec![("owner", owner.clone()), ("items", items)],
+            )
+        }
+        StatementRecord::RemoveCombatant(combatant) => {
+            ("remove_combatant", vec![("combatant", combatant.clone())])
+        }
+        StatementRecord::SortCombatants(combatants) => (
+            "sort_combatants",
+            vec![("combatants", initiative_order(combatants))],
+        ),
+    }
+}
+
+fn modifier_fields(applied: &AppliedModifier) -> Vec<(&'static str, String)> {
+    let rolls = match &applied.rolls {
+        Some(name) => format!("rolls named {name}"),
+        None => "all rolls".into(),
+    };
+    vec![
+        ("modifier", format!("{:+}", applied.modifier)),
+        ("rolls", rolls),
+    ]
+}
+
+fn item_fields(item: &str, owner: &str, count: i32) -> Vec<(&'static str, String)> {
+    vec![
+        ("item", item.into()),
+        ("owner", owner.into()),
+        ("count", count.to_string()),
+    ]
+}
+
+// The records nested in a record, in the order they happened.
+fn nested(record: &StatementRecord) -> Vec<&StatementRecord> {
+    match record {
+        StatementRecord::Choice { consequent, .. } => vec![consequent],
+        StatementRecord::IfThen {
+            consequent: Some(consequent),
+            ..
+        }
+        | StatementRecord::MatchingRoll {
+            consequent: Some(consequent),
+            ..
+        }
+        | StatementRecord::Check {
+            consequent: Some(consequent),
+            ..
+        }
+        | StatementRecord::Save {
+            consequent: Some(consequent),
+            ..
+        }
+        | StatementRecord::Move {
+            consequent: Some(consequent),
+            ..
+        }
+        | StatementRecord::MatchingFact {
+            consequent: Some(consequent),
+            ..
+        }
+        | StatementRecord::MatchingTableRoll {
+            consequent: Some(consequent),
+            ..
+        } => vec![consequent],
+        StatementRecord::ProcedureCall { records, .. }
+        | StatementRecord::Downtime { records, .. }
+        | StatementRecord::Include { records, .. }
+        | StatementRecord::WithFacts(records) => records.iter().map(Box::as_ref).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+fn capitalize(sentence: &str) -> String {
+    let mut chars = sentence.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day() -> StatementRecord {
+        StatementRecord::ProcedureCall {
+            identifier: "day".into(),
+            records: vec![
+                Box::new(StatementRecord::TableRoll {
+                    table: "examples/weather.csv".into(),
+                    entry: "fog seeps up through the dirt".into(),
+                    pinned: false,
+                }),
+                Box::new(StatementRecord::IfThen {
+                    antecedent: true,
+                    consequent: Some(Box::new(StatementRecord::SetFact("party is lost".into()))),
+                }),
+                Box::new(StatementRecord::IfThen {
+                    antecedent: false,
+                    consequent: None,
+                }),
+                Box::new(StatementRecord::Reminder("eat rations".into())),
+            ],
+        }
+    }
+
+    #[test]
+    fn prose() {
+        let records = [
+            StatementRecord::LoadTable("examples/weather.csv".into()),
+            day(),
+            StatementRecord::NontargetedRoll(7),
+        ];
+        assert_eq!(
+            Recap::new().render(&records),
+            "Day: Rolled on weather: fog seeps up through the dirt. Party is lost.\nRolled 7.\n"
+        );
+    }
+
+    #[test]
+    fn templates() {
+        let recap = Recap::new()
+            .with_template("procedure_call", "")
+            .with_template("table_roll", "The {table} was {result}.")
+            .with_template("if_then", "Nothing happened.")
+            .with_template("reminder", "({reminder})");
+        assert_eq!(
+            recap.render_record(&day()),
+            "The weather was fog seeps up through the dirt. Party is lost. Nothing happened. \
+            (eat rations)"
+        );
+        assert!(Recap::kinds().all(|kind| recap.templates.contains_key(kind)));
+    }
+
+    #[test]
+    fn template_file() {
+        let recap = Recap::new()
+            .with_templates(
+                "# table-side shorthand\n\ntable_roll: \"📜 {table}: {result}\"\nset_fact: \"\\\"{fact}\\\"\"\n",
+            )
+            .unwrap();
+        assert_eq!(
+            recap.sentences(&day()),
+            [
+                "Day:",
+                "📜 weather: fog seeps up through the dirt",
+                "\"party is lost\""
+            ]
+        );
+
+        for source in [
+            "table_roll \"{table}\"",
+            "table-roll: \"{table}\"",
+            "table_roll: {table}",
+        ] {
+            assert!(Recap::new().with_templates(source).is_err());
+        }
+    }
+}
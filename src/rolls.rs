@@ -1,54 +1,46 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
 use crate::error::CrawlError;
 
 // TODO: confusing to have rolls.rs and dice.rs
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum RollTarget {
     Num(i32),
     NumRange(i32, i32),
     OverOrEqual(i32),
 }
 
+impl fmt::Display for RollTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RollTarget::Num(n) => write!(f, "{n}"),
+            RollTarget::NumRange(min, max) => write!(f, "{min}-{max}"),
+            RollTarget::OverOrEqual(n) => write!(f, "{n}+"),
+        }
+    }
+}
+
 impl TryFrom<&str> for RollTarget {
     type Error = CrawlError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let mut s = value.split('-').collect::<Vec<&str>>();
-        match s.len() {
-            1 => {
-                let mut is_roll_over = false;
-                if let Some(n) = s.last_mut() {
-                    if n.ends_with('+') {
-                        is_roll_over = true;
-                        *n = n.trim_matches('+');
-                    }
-                }
-
-                let num = s
-                    .first()
-                    .expect("roll target should be a value")
-                    .parse::<i32>()
-                    .expect("roll target should be a number");
-
-                if is_roll_over {
-                    return Ok(RollTarget::OverOrEqual(num));
-                }
-
-                Ok(RollTarget::Num(num))
-            }
-            2 => {
-                let range_min = s
-                    .first()
-                    .expect("range min should be a value")
-                    .parse::<i32>()
-                    .expect("range min should be a number");
-                let range_max = s
-                    .last()
-                    .expect("range max should be a value")
-                    .parse::<i32>()
-                    .expect("range max should be a number");
-
-                Ok(RollTarget::NumRange(range_min, range_max))
+        let number = |n: &str| {
+            n.trim()
+                .parse::<i32>()
+                .map_err(|_| CrawlError::InterpreterError {
+                    reason: format!("cannot convert {value:?} to RollTarget"),
+                })
+        };
+        match value.split('-').collect::<Vec<&str>>()[..] {
+            [n] => match n.strip_suffix('+') {
+                Some(n) => Ok(RollTarget::OverOrEqual(number(n)?)),
+                None => Ok(RollTarget::Num(number(n)?)),
+            },
+            [range_min, range_max] => {
+                Ok(RollTarget::NumRange(number(range_min)?, number(range_max)?))
             }
             // TODO: not an interpreter error (can happen in scanner or interpreter)
             _ => Err(CrawlError::InterpreterError {
@@ -65,3 +57,83 @@ impl TryFrom<String> for RollTarget {
         Self::try_from(value.as_str())
     }
 }
+
+/// How well a roll went against a DC: meeting it is a success, and beating it by 10 or more a
+/// critical success, and likewise missing it a failure, and by 10 or more a critical failure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Degree {
+    CriticalFailure,
+    Failure,
+    Success,
+    CriticalSuccess,
+}
+
+impl Degree {
+    pub fn of(total: i32, dc: i32) -> Self {
+        let margin = i64::from(total) - i64::from(dc);
+        match margin {
+            ..=-10 => Degree::CriticalFailure,
+            -9..=-1 => Degree::Failure,
+            0..=9 => Degree::Success,
+            _ => Degree::CriticalSuccess,
+        }
+    }
+
+    /// Whether this degree counts as `wanted`: any success counts as a success, and any failure
+    /// as a failure, but only a critical one as critical.
+    pub fn satisfies(self, wanted: Degree) -> bool {
+        match wanted {
+            Degree::Success => self >= Degree::Success,
+            Degree::Failure => self <= Degree::Failure,
+            _ => self == wanted,
+        }
+    }
+
+    /// Its name in snake case, like `critical_success`.
+    pub fn key(self) -> &'static str {
+        match self {
+            Degree::CriticalFailure => "critical_failure",
+            Degree::Failure => "failure",
+            Degree::Success => "success",
+            Degree::CriticalSuccess => "critical_success",
+        }
+    }
+}
+
+impl fmt::Display for Degree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.key().replace('_', " "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str() {
+        assert_eq!(RollTarget::try_from("4").unwrap(), RollTarget::Num(4));
+        assert_eq!(
+            RollTarget::try_from("2-5").unwrap(),
+            RollTarget::NumRange(2, 5)
+        );
+        assert_eq!(
+            RollTarget::try_from("13+").unwrap(),
+            RollTarget::OverOrEqual(13)
+        );
+        assert!(RollTarget::try_from("rain").is_err());
+        assert!(RollTarget::try_from("1-").is_err());
+    }
+
+    #[test]
+    fn degrees() {
+        assert_eq!(Degree::of(5, 15), Degree::CriticalFailure);
+        assert_eq!(Degree::of(14, 15), Degree::Failure);
+        assert_eq!(Degree::of(15, 15), Degree::Success);
+        assert_eq!(Degree::of(25, 15), Degree::CriticalSuccess);
+        assert!(Degree::CriticalSuccess.satisfies(Degree::Success));
+        assert!(!Degree::Success.satisfies(Degree::CriticalSuccess));
+        assert!(Degree::CriticalFailure.satisfies(Degree::Failure));
+        assert_eq!(Degree::CriticalFailure.to_string(), "critical failure");
+    }
+}
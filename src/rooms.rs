@@ -0,0 +1,13 @@
+// `generate room` rolls a room as the party comes to it, on tables for its shape, its exits, and
+// what's in it. Rooms are numbered in the order they're generated, so exploring builds the
+// dungeon's key as it goes. There's no map kept, only the key.
+
+/// The table a room's shape is rolled on.
+pub const SHAPES: &str = "room shapes";
+/// The table a room's exits are rolled on.
+pub const EXITS: &str = "room exits";
+/// The table what's in a room is rolled on.
+pub const CONTENTS: &str = "room contents";
+
+/// The tables `generate room` rolls on, in order.
+pub const TABLES: [&str; 3] = [SHAPES, EXITS, CONTENTS];
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+// Rumors the party might hear in town, kept for the campaign. Each is true or false, or hidden
+// until it's decided when it's revealed, and its truth is left out of records and reports until
+// then, so a journal shared with the players doesn't give it away.
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rumor {
+    pub text: String,
+    pub truth: Option<bool>,
+    pub heard: bool,
+    pub revealed: bool,
+}
+
+impl Rumor {
+    pub fn new(text: &str, truth: Option<bool>) -> Self {
+        Rumor {
+            text: text.into(),
+            truth,
+            heard: false,
+            revealed: false,
+        }
+    }
+
+    /// Its truth, once it's been revealed.
+    pub fn known_truth(&self) -> Option<bool> {
+        if self.revealed { self.truth } else { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truth_is_kept_until_revealed() {
+        let mut rumor = Rumor::new("the well is cursed", Some(false));
+        assert_eq!(rumor.known_truth(), None);
+        rumor.revealed = true;
+        assert_eq!(rumor.known_truth(), Some(false));
+    }
+}
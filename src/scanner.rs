@@ -2,72 +2,178 @@
 * Largely ripped from Robert Nystrom's *Crafting Interpreters*
 */
 
+use crate::dice::RollSpec;
 use crate::error::CrawlError;
 use crate::rolls::RollTarget;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
 const EOF_CHAR: char = '\0';
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Token {
+    Add,
+    Advance,
+    Apply,
     Arrow,
+    As,
+    Assert,
+    AssertEqual,
+    By,
+    Campaign,
+    Check,
+    Choose,
     ClearFact,
     ClearPersistentFact,
+    Clock,
+    Colon,
+    Create,
+    CrawlVersion,
+    Define,
+    Depleted,
+    DeriveFact,
+    // A single die, like `d8`
+    Die(i32),
+    Else,
     End,
     Eof,
+    Equals,
     FactTest,
+    Full,
+    Generator,
     Identifier(String),
     If,
+    Include,
     Indent,
+    Let,
     Load,
+    Match,
     Minus,
+    Move,
     Newline,
     Num(i32),
     NumRange(i32, i32),
+    // A roll target met by this number or more, like `15+`
+    OverOrEqual(i32),
+    // A roll target met by this number or less, like `6-`
+    UnderOrEqual(i32),
     On,
+    Oracle,
     PersistentFactTest,
     Percent,
     Plus,
     Procedure,
     Reminder,
+    Remove,
+    Report,
+    Rule,
+    Return,
     Roll,
-    RollSpecifier(String),
+    RollSpecifier(RollSpec),
     SetFact,
     SetPersistentFact,
+    Spend,
     Str(String),
     Table,
+    Track,
+    Usage,
+    UsageDie,
+    // A language version, like `0.2`
+    Version(u32, u32),
+    WithFacts,
 }
 
+/// Scans directly over the source text - lexemes are slices of it, and only the text a token
+/// keeps (identifiers, strings, roll specifiers) is copied.
+/// Every keyword, for editors to complete.
+pub const KEYWORDS: &[&str] = &[
+    "add",
+    "advance",
+    "apply",
+    "as",
+    "assert",
+    "assert-equal",
+    "by",
+    "campaign",
+    "check",
+    "choose",
+    "clear-fact",
+    "clear-persistent-fact",
+    "clock",
+    "create",
+    "define",
+    "depleted",
+    "derive-fact",
+    "else",
+    "end",
+    "fact?",
+    "full",
+    "generator",
+    "if",
+    "include",
+    "let",
+    "load",
+    "match",
+    "move",
+    "on",
+    "oracle",
+    "persistent-fact?",
+    "procedure",
+    "reminder",
+    "remove",
+    "report",
+    "return",
+    "roll",
+    "rule",
+    "set-fact",
+    "set-persistent-fact",
+    "spend",
+    "table",
+    "track",
+    "usage",
+    "usage-die",
+    "with-facts",
+];
+
 #[derive(Debug)]
-pub struct Scanner {
-    source: Vec<char>,
-    position: usize, // The character to be scanned
+pub struct Scanner<'a> {
+    source: &'a str,
+    position: usize, // Byte offset of the character to be scanned
     line: usize,
-    start: usize, // The start of the current lexeme
+    start: usize, // Byte offset of the start of the current lexeme
+    // The line each scanned token starts on
+    lines: Vec<usize>,
+    // The bytes of the source each scanned token came from
+    spans: Vec<Range<usize>>,
+    finished: bool,
 }
 
-impl Scanner {
-    pub fn new(source: Vec<char>) -> Self {
+impl<'a> Scanner<'a> {
+    pub fn new(source: &'a str) -> Self {
         Scanner {
             source,
             position: 0,
             line: 1,
             start: 0,
+            lines: Vec::new(),
+            spans: Vec::new(),
+            finished: false,
         }
     }
 
+    /// Scan everything at once. Iterate over the scanner instead to scan a token at a time.
     pub fn tokens(&mut self) -> Vec<Result<Token, CrawlError>> {
-        if self.is_at_end() {
-            return Vec::new();
-        }
+        self.collect()
+    }
 
-        let mut toks = Vec::new();
-        while !self.is_at_end() {
-            self.start = self.position;
-            toks.push(self.next_token());
-        }
-        toks.push(Ok(Token::Eof));
+    /// The line each token scanned so far starts on, in the same order.
+    pub fn lines(&self) -> &[usize] {
+        &self.lines
+    }
 
-        toks
+    /// The byte range of the source each token scanned so far came from, in the same order.
+    pub fn spans(&self) -> &[Range<usize>] {
+        &self.spans
     }
 
     fn next_token(&mut self) -> Result<Token, CrawlError> {
@@ -106,12 +212,7 @@ impl Scanner {
                     if self.match_and_consume('>') {
                         return Ok(Token::Arrow);
                     }
-                    return Err(CrawlError::ScannerError {
-                        position: self.position,
-                        line: self.line,
-                        lexeme: self.source[self.start..self.position].iter().collect(),
-                        reason: "expected '>' after '='".into(),
-                    });
+                    return Ok(Token::Equals);
                 }
 
                 '+' => return Ok(Token::Plus),
@@ -120,13 +221,15 @@ impl Scanner {
 
                 '%' => return Ok(Token::Percent),
 
+                ':' => return Ok(Token::Colon),
+
                 c => {
                     return Err(CrawlError::ScannerError {
                         position: self.position,
                         line: self.line,
                         lexeme: c.into(),
                         reason: "unexpected character".into(),
-                    })
+                    });
                 }
             }
         }
@@ -136,6 +239,7 @@ impl Scanner {
         let mut next_ch = self.curr_char();
         let mut is_dice_roll = false;
         let mut is_roll_range = false;
+        let mut is_version = false;
         while !self.is_at_end() {
             match next_ch {
                 'd' => {
@@ -143,47 +247,110 @@ impl Scanner {
                         return Err(CrawlError::ScannerError {
                             position: self.position,
                             line: self.line,
-                            lexeme: self.source[self.start..self.position]
-                                .iter()
-                                .collect::<String>(),
-                            reason: "roll specifier must be NUMBER 'd' NUMBER".into(),
+                            lexeme: self.lexeme().into(),
+                            reason: "roll specifier must be NUMBER 'd' NUMBER, both at least 1"
+                                .into(),
                         });
                     }
                     is_dice_roll = true;
                 }
                 '-' => is_roll_range = true,
+                '.' if self.peek_next().is_ascii_digit() => is_version = true,
                 nch if nch.is_numeric() => {}
                 _ => break,
             }
             self.advance();
             next_ch = self.curr_char();
         }
-        let lexeme = self.source[self.start..self.position]
-            .iter()
-            .collect::<String>();
+        let lexeme = self.lexeme();
+        if is_version {
+            let version = match lexeme.split_once('.') {
+                Some((major, minor)) if !is_dice_roll && !is_roll_range => {
+                    major.parse().ok().zip(minor.parse().ok())
+                }
+                _ => None,
+            };
+            return match version {
+                Some((major, minor)) => Ok(Token::Version(major, minor)),
+                None => Err(CrawlError::ScannerError {
+                    position: self.position,
+                    line: self.line,
+                    lexeme: lexeme.into(),
+                    reason: "version must be NUMBER '.' NUMBER".into(),
+                }),
+            };
+        }
         match (is_dice_roll, is_roll_range) {
-            (true, false) => Ok(Token::RollSpecifier(lexeme)),
+            (true, false) => match lexeme.parse() {
+                Ok(spec) => Ok(Token::RollSpecifier(spec)),
+                Err(_) => Err(CrawlError::ScannerError {
+                    position: self.position,
+                    line: self.line,
+                    lexeme: lexeme.into(),
+                    reason: "roll specifier must be NUMBER 'd' NUMBER".into(),
+                }),
+            },
+            // Likewise a trailing `-`, which is otherwise an unfinished range.
+            (false, true)
+                if lexeme.ends_with('-')
+                    && (self.curr_char().is_whitespace() || self.curr_char() == EOF_CHAR) =>
+            {
+                match lexeme[..lexeme.len() - 1].parse() {
+                    Ok(n) => Ok(Token::UnderOrEqual(n)),
+                    Err(_) => Err(CrawlError::ScannerError {
+                        position: self.position,
+                        line: self.line,
+                        lexeme: lexeme.into(),
+                        reason: "invalid roll target".into(),
+                    }),
+                }
+            }
             (false, true) => {
                 if let Ok(RollTarget::NumRange(range_min, range_max)) =
-                    RollTarget::try_from(lexeme.clone())
+                    RollTarget::try_from(lexeme.to_string())
                 {
                     Ok(Token::NumRange(range_min, range_max))
                 } else {
                     Err(CrawlError::ScannerError {
                         position: self.position,
                         line: self.line,
-                        lexeme,
+                        lexeme: lexeme.into(),
                         reason: "invalid roll target".into(),
                     })
                 }
             }
-            (false, false) => Ok(Token::Num(
-                lexeme.parse::<i32>().expect("should be a number"),
-            )),
+            // A trailing `+` makes it a roll target, but not one followed by more of a modifier.
+            (false, false)
+                if self.curr_char() == '+'
+                    && (self.peek_next().is_whitespace() || self.peek_next() == EOF_CHAR) =>
+            {
+                match lexeme.parse() {
+                    Ok(n) => {
+                        self.advance();
+                        Ok(Token::OverOrEqual(n))
+                    }
+                    Err(_) => Err(CrawlError::ScannerError {
+                        position: self.position,
+                        line: self.line,
+                        lexeme: lexeme.into(),
+                        reason: "invalid roll target".into(),
+                    }),
+                }
+            }
+            (false, false) => match lexeme.parse() {
+                Ok(n) => Ok(Token::Num(n)),
+                // Too big, or numeric characters that aren't digits, like `½`
+                Err(_) => Err(CrawlError::ScannerError {
+                    position: self.position,
+                    line: self.line,
+                    lexeme: lexeme.into(),
+                    reason: "invalid number".into(),
+                }),
+            },
             (true, true) => Err(CrawlError::ScannerError {
                 position: self.position,
                 line: self.line,
-                lexeme,
+                lexeme: lexeme.into(),
                 reason: "can't be a dice roll and dice range".into(),
             }),
         }
@@ -215,74 +382,133 @@ impl Scanner {
             return Err(CrawlError::ScannerError {
                 position: self.position,
                 line: self.line,
-                lexeme: self.source[self.start..self.position].iter().collect(),
+                lexeme: self.lexeme().into(),
                 reason: "unterminated string, expected closing '\"'".into(),
             });
         }
         // pass closing "
         self.advance();
         Ok(Token::Str(
-            self.source[self.start + 1..self.position - 1]
-                .iter()
-                .collect(),
+            self.source[self.start + 1..self.position - 1].into(),
         ))
     }
 
     fn scan_symbol(&mut self) -> Result<Token, CrawlError> {
+        if self.lexeme() == "d" && self.curr_char().is_numeric() {
+            return self.scan_die();
+        }
+
         let mut next_ch = self.curr_char();
-        while !self.is_at_end() && (next_ch.is_alphabetic() || next_ch == '-' || next_ch == '?') {
+        // '.' separates a namespace from the procedure in it
+        while !self.is_at_end()
+            && (next_ch.is_alphabetic() || next_ch == '-' || next_ch == '?' || next_ch == '.')
+        {
             self.advance();
             next_ch = self.curr_char();
         }
-        let lexeme: String = self.source[self.start..self.position].iter().collect();
-        match Self::token_for_keyword(&lexeme) {
+        let lexeme = self.lexeme();
+        match Self::token_for_keyword(lexeme) {
             Some(token) => Ok(token),
-            None => Ok(Token::Identifier(lexeme)),
+            None => Ok(Token::Identifier(lexeme.into())),
+        }
+    }
+
+    fn scan_die(&mut self) -> Result<Token, CrawlError> {
+        while !self.is_at_end() && self.curr_char().is_numeric() {
+            self.advance();
+        }
+        match self.lexeme()[1..].parse() {
+            Ok(sides) => Ok(Token::Die(sides)),
+            Err(_) => Err(CrawlError::ScannerError {
+                position: self.position,
+                line: self.line,
+                lexeme: self.lexeme().into(),
+                reason: "die must be 'd' NUMBER".into(),
+            }),
         }
     }
 
     fn token_for_keyword(lexeme: &str) -> Option<Token> {
         match lexeme {
+            "add" => Some(Token::Add),
+            "advance" => Some(Token::Advance),
+            "apply" => Some(Token::Apply),
+            "as" => Some(Token::As),
+            "assert" => Some(Token::Assert),
+            "assert-equal" => Some(Token::AssertEqual),
+            "by" => Some(Token::By),
+            "campaign" => Some(Token::Campaign),
+            "check" => Some(Token::Check),
+            "choose" => Some(Token::Choose),
             "clear-fact" => Some(Token::ClearFact),
             "clear-persistent-fact" => Some(Token::ClearPersistentFact),
+            "clock" => Some(Token::Clock),
+            "crawl-version" => Some(Token::CrawlVersion),
+            "create" => Some(Token::Create),
+            "define" => Some(Token::Define),
+            "depleted" => Some(Token::Depleted),
+            "derive-fact" => Some(Token::DeriveFact),
+            "else" => Some(Token::Else),
             "end" => Some(Token::End),
             "fact?" => Some(Token::FactTest),
+            "full" => Some(Token::Full),
+            "generator" => Some(Token::Generator),
             "if" => Some(Token::If),
+            "include" => Some(Token::Include),
+            "let" => Some(Token::Let),
             "load" => Some(Token::Load),
+            "match" => Some(Token::Match),
+            "move" => Some(Token::Move),
             "on" => Some(Token::On),
+            "oracle" => Some(Token::Oracle),
+            "persistent-fact?" => Some(Token::PersistentFactTest),
             "procedure" => Some(Token::Procedure),
             "reminder" => Some(Token::Reminder),
+            "remove" => Some(Token::Remove),
+            "report" => Some(Token::Report),
+            "return" => Some(Token::Return),
             "roll" => Some(Token::Roll),
+            "rule" => Some(Token::Rule),
             "set-fact" => Some(Token::SetFact),
             "set-persistent-fact" => Some(Token::SetPersistentFact),
+            "spend" => Some(Token::Spend),
             "table" => Some(Token::Table),
+            "track" => Some(Token::Track),
+            "usage" => Some(Token::Usage),
+            "usage-die" => Some(Token::UsageDie),
+            "with-facts" => Some(Token::WithFacts),
             _ => None,
         }
     }
 
+    fn lexeme(&self) -> &'a str {
+        &self.source[self.start..self.position]
+    }
+
     fn curr_char(&mut self) -> char {
-        if self.is_at_end() {
-            return EOF_CHAR;
-        }
-        self.source[self.position]
+        self.peek()
     }
 
     fn advance(&mut self) {
-        self.position += 1;
+        // Past the end, step over the EOF_CHAR that's "there" like any other one-byte character.
+        self.position += self.peek().len_utf8();
     }
 
     fn peek(&self) -> char {
         if self.is_at_end() {
             return EOF_CHAR;
         }
-        self.source[self.position]
+        self.source[self.position..].chars().next().unwrap()
     }
 
     fn peek_next(&self) -> char {
-        if self.position + 1 >= self.source.len() {
+        if self.is_at_end() {
             return EOF_CHAR;
         }
-        self.source[self.position + 1]
+        self.source[self.position..]
+            .chars()
+            .nth(1)
+            .unwrap_or(EOF_CHAR)
     }
 
     fn match_and_consume(&mut self, ch: char) -> bool {
@@ -299,13 +525,40 @@ impl Scanner {
     }
 }
 
+impl Iterator for Scanner<'_> {
+    type Item = Result<Token, CrawlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        if self.is_at_end() {
+            self.finished = true;
+            // Empty source has no tokens at all, not even Eof.
+            if self.source.is_empty() {
+                return None;
+            }
+            self.lines.push(self.line);
+            self.spans.push(self.position..self.position);
+            return Some(Ok(Token::Eof));
+        }
+
+        self.start = self.position;
+        self.lines.push(self.line);
+        let token = self.next_token();
+        // Whitespace before the token isn't part of it.
+        self.spans.push(self.start..self.position);
+        Some(token)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn scan_if_then() {
-        let source = "if \"Hi\" => 5".chars().collect();
+        let source = "if \"Hi\" => 5";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(
@@ -322,7 +575,7 @@ mod tests {
 
     #[test]
     fn scan_proc_decl() {
-        let source = "procedure proc".chars().collect();
+        let source = "procedure proc";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(
@@ -337,15 +590,73 @@ mod tests {
 
     #[test]
     fn scan_proc_call() {
-        let source = "proc".chars().collect();
+        let source = "proc";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(toks, vec![Token::Identifier("proc".into()), Token::Eof]);
     }
 
+    #[test]
+    fn scan_version() {
+        let toks: Vec<Token> = Scanner::new("crawl-version 0.12\n")
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            toks,
+            vec![
+                Token::CrawlVersion,
+                Token::Version(0, 12),
+                Token::Newline,
+                Token::Eof
+            ]
+        );
+        assert!(Scanner::new("1.2.3").any(|token| token.is_err()));
+        assert!(Scanner::new("1d6.2").any(|token| token.is_err()));
+    }
+
+    #[test]
+    fn scan_under_or_equal() {
+        let toks: Vec<Token> = Scanner::new("on 6- => 7-9 6-")
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            toks,
+            vec![
+                Token::On,
+                Token::UnderOrEqual(6),
+                Token::Arrow,
+                Token::NumRange(7, 9),
+                Token::UnderOrEqual(6),
+                Token::Eof
+            ]
+        );
+        assert!(Scanner::new("6-x").any(|token| token.is_err()));
+    }
+
+    #[test]
+    fn scan_over_or_equal() {
+        let toks: Vec<Token> = Scanner::new("roll 15+ on 1d20 + 5+2")
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            toks,
+            vec![
+                Token::Roll,
+                Token::OverOrEqual(15),
+                Token::On,
+                Token::RollSpecifier(RollSpec::new(1, 20)),
+                Token::Plus,
+                Token::Num(5),
+                Token::Plus,
+                Token::Num(2),
+                Token::Eof
+            ]
+        );
+    }
+
     #[test]
     fn scan_roll_range() {
-        let source = "roll 2-10".chars().collect();
+        let source = "roll 2-10";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(toks, vec![Token::Roll, Token::NumRange(2, 10), Token::Eof]);
@@ -353,9 +664,7 @@ mod tests {
 
     #[test]
     fn scan_expr() {
-        let source = "roll 1-3 on 1d6 + 1 => set-fact \"party is lost\""
-            .chars()
-            .collect();
+        let source = "roll 1-3 on 1d6 + 1 => set-fact \"party is lost\"";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(
@@ -364,7 +673,7 @@ mod tests {
                 Token::Roll,
                 Token::NumRange(1, 3),
                 Token::On,
-                Token::RollSpecifier("1d6".into()),
+                Token::RollSpecifier(RollSpec::new(1, 6)),
                 Token::Plus,
                 Token::Num(1),
                 Token::Arrow,
@@ -377,7 +686,7 @@ mod tests {
 
     #[test]
     fn scan_roll() {
-        let source = "roll 99 on 3d100".chars().collect();
+        let source = "roll 99 on 3d100";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(
@@ -386,7 +695,7 @@ mod tests {
                 Token::Roll,
                 Token::Num(99),
                 Token::On,
-                Token::RollSpecifier("3d100".into()),
+                Token::RollSpecifier(RollSpec::new(3, 100)),
                 Token::Eof,
             ]
         )
@@ -394,9 +703,7 @@ mod tests {
 
     #[test]
     fn scan_concat() {
-        let source = "set-fact \"weather is \" + roll on table \"weather\""
-            .chars()
-            .collect();
+        let source = "set-fact \"weather is \" + roll on table \"weather\"";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(
@@ -416,16 +723,14 @@ mod tests {
 
     #[test]
     fn scan_matching_roll() {
-        let source = "roll 2d6\n\t2-4 => set-fact \"encounter is hostile\"\n\t5-8 => set-fact \"encounter is neutral\"\nend"
-        .chars()
-        .collect();
+        let source = "roll 2d6\n\t2-4 => set-fact \"encounter is hostile\"\n\t5-8 => set-fact \"encounter is neutral\"\nend";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(
             toks,
             vec![
                 Token::Roll,
-                Token::RollSpecifier("2d6".into()),
+                Token::RollSpecifier(RollSpec::new(2, 6)),
                 Token::Newline,
                 Token::Indent,
                 Token::NumRange(2, 4),
@@ -447,9 +752,7 @@ mod tests {
 
     #[test]
     fn scan_interpolated_str() {
-        let source = "set-fact \"encounter distance {}\" % roll 1d6"
-            .chars()
-            .collect();
+        let source = "set-fact \"encounter distance {}\" % roll 1d6";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(
@@ -459,7 +762,7 @@ mod tests {
                 Token::Str("encounter distance {}".into()),
                 Token::Percent,
                 Token::Roll,
-                Token::RollSpecifier("1d6".into()),
+                Token::RollSpecifier(RollSpec::new(1, 6)),
                 Token::Eof,
             ]
         )
@@ -467,9 +770,7 @@ mod tests {
 
     #[test]
     fn scan_proc_def() {
-        let source = "procedure proc\n\troll on table \"table\"\nend"
-            .chars()
-            .collect();
+        let source = "procedure proc\n\troll on table \"table\"\nend";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(
@@ -492,7 +793,7 @@ mod tests {
 
     #[test]
     fn tokens_valid_once() {
-        let source = "roll 2-10".chars().collect();
+        let source = "roll 2-10";
         let mut scanner = Scanner::new(source);
         let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
         assert_eq!(toks, vec![Token::Roll, Token::NumRange(2, 10), Token::Eof]);
@@ -501,10 +802,123 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "expected '>' after '='")]
-    fn incomplete_arrow() {
-        let source = "= 5".chars().collect();
+    fn iterate_tokens() {
+        let mut scanner = Scanner::new("roll 2-10");
+        assert_eq!(scanner.next().unwrap().unwrap(), Token::Roll);
+        assert_eq!(scanner.lines(), &[1]);
+        assert_eq!(
+            scanner.map(|t| t.unwrap()).collect::<Vec<Token>>(),
+            vec![Token::NumRange(2, 10), Token::Eof]
+        );
+    }
+
+    #[test]
+    fn scan_multibyte() {
+        let mut scanner = Scanner::new("réveil\nreminder \"café ☕\"");
+        let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
+        assert_eq!(
+            toks,
+            vec![
+                Token::Identifier("réveil".into()),
+                Token::Newline,
+                Token::Reminder,
+                Token::Str("café ☕".into()),
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_namespaces() {
+        let mut scanner = Scanner::new("include \"hexcrawl.crawl\" as hex\nhex.travel-turn");
+        let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
+        assert_eq!(
+            toks,
+            vec![
+                Token::Include,
+                Token::Str("hexcrawl.crawl".into()),
+                Token::As,
+                Token::Identifier("hex".into()),
+                Token::Newline,
+                Token::Identifier("hex.travel-turn".into()),
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_die() {
+        let mut scanner = Scanner::new("usage-die \"rations\" d8\nroll usage \"rations\"");
+        let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
+        assert_eq!(
+            toks,
+            vec![
+                Token::UsageDie,
+                Token::Str("rations".into()),
+                Token::Die(8),
+                Token::Newline,
+                Token::Roll,
+                Token::Usage,
+                Token::Str("rations".into()),
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn token_spans() {
+        let source = "roll 1d6\n  \"rain\"";
         let mut scanner = Scanner::new(source);
+        scanner.tokens();
+        let lexemes: Vec<&str> = scanner
+            .spans()
+            .iter()
+            .map(|span| &source[span.clone()])
+            .collect();
+        assert_eq!(lexemes, vec!["roll", "1d6", "\n", "\"rain\"", ""]);
+    }
+
+    #[test]
+    fn keywords() {
+        for keyword in KEYWORDS {
+            assert!(Scanner::token_for_keyword(keyword).is_some(), "{keyword}");
+        }
+    }
+
+    #[test]
+    fn token_lines() {
+        let source = "procedure proc\n\troll on table \"table\"\nend";
+        let mut scanner = Scanner::new(source);
+        let _ = scanner.tokens();
+        assert_eq!(scanner.lines(), &[1, 1, 1, 2, 2, 2, 2, 2, 2, 3, 3]);
+    }
+
+    #[test]
+    fn scan_let() {
+        let mut scanner = Scanner::new("let damage = roll 2d6\nroll 1d20 => x");
+        let toks: Vec<Token> = scanner.tokens().into_iter().map(|t| t.unwrap()).collect();
+        assert_eq!(
+            toks,
+            vec![
+                Token::Let,
+                Token::Identifier("damage".into()),
+                Token::Equals,
+                Token::Roll,
+                Token::RollSpecifier(RollSpec::new(2, 6)),
+                Token::Newline,
+                Token::Roll,
+                Token::RollSpecifier(RollSpec::new(1, 20)),
+                Token::Arrow,
+                Token::Identifier("x".into()),
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "roll specifier must be NUMBER 'd' NUMBER")]
+    fn malformed_roll_specifier() {
+        let mut scanner = Scanner::new("roll 2d6d8");
         let _ = scanner
             .tokens()
             .into_iter()
@@ -515,7 +929,7 @@ mod tests {
     #[test]
     #[should_panic(expected = "unterminated string")]
     fn unterminated_string() {
-        let source = "\"Unterminated string".chars().collect();
+        let source = "\"Unterminated string";
         let mut scanner = Scanner::new(source);
         let _ = scanner
             .tokens()
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use serde::Serialize;
+
+// `scene check` rolls a d10 against the chaos factor as a scene starts, like Mythic. Over it, the
+// scene goes as expected. On it or under, an odd roll alters the scene, and an even one interrupts
+// it with a random event, rolled on the event focus table for what it's about and the event
+// meaning table for what happens.
+
+/// The table an interrupting event's focus is rolled on.
+pub const EVENT_FOCUS: &str = "event focus";
+/// The table an interrupting event's meaning is rolled on.
+pub const EVENT_MEANING: &str = "event meaning";
+
+/// The tables `scene check` can roll on, in order.
+pub const TABLES: [&str; 2] = [EVENT_FOCUS, EVENT_MEANING];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum SceneOutcome {
+    Expected,
+    Altered,
+    Interrupted,
+}
+
+impl SceneOutcome {
+    /// How a scene goes on a d10 roll of `rolled` at the chaos factor `chaos`.
+    pub fn of(rolled: i32, chaos: i32) -> Self {
+        if rolled > chaos {
+            SceneOutcome::Expected
+        } else if rolled % 2 == 1 {
+            SceneOutcome::Altered
+        } else {
+            SceneOutcome::Interrupted
+        }
+    }
+
+    /// The name of the outcome in locale keys, like `scene.altered`.
+    pub fn key(self) -> &'static str {
+        match self {
+            SceneOutcome::Expected => "expected",
+            SceneOutcome::Altered => "altered",
+            SceneOutcome::Interrupted => "interrupted",
+        }
+    }
+}
+
+impl fmt::Display for SceneOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+/// The event that interrupts a scene.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RandomEvent {
+    pub focus: String,
+    pub meaning: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome() {
+        assert_eq!(SceneOutcome::of(6, 5), SceneOutcome::Expected);
+        assert_eq!(SceneOutcome::of(5, 5), SceneOutcome::Altered);
+        assert_eq!(SceneOutcome::of(4, 5), SceneOutcome::Interrupted);
+        assert_eq!(SceneOutcome::of(10, 9), SceneOutcome::Expected);
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::mem;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::lang::Crawl;
+use crate::output::{BufferSink, Output};
+use crate::prompt::ScriptedPrompt;
+
+// Runs a script many times over to see how its outcomes fall out: which facts end up true, and
+// what tables come up with. Runs are split across threads, each run with its own session seeded
+// from the simulation's seed and the run's number, so the outcomes are the same however many
+// threads there are.
+
+/// A script to run many times.
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    script: Script,
+    runs: u64,
+    threads: usize,
+    seed: u64,
+}
+
+#[derive(Clone, Debug)]
+enum Script {
+    Source(String),
+    // Read fresh for each run, so includes are found relative to it
+    File(PathBuf),
+}
+
+/// How often each outcome came up over a simulation's runs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outcomes {
+    pub runs: u64,
+    // Runs that errored, including on a `choose`, which nobody's there to answer
+    pub failed: u64,
+    // How many runs each fact was true at the end of
+    pub facts: HashMap<String, u64>,
+    // How many times each table came up with each entry, over every run
+    pub table_rolls: HashMap<String, HashMap<String, u64>>,
+}
+
+impl Outcomes {
+    /// The share of runs `fact` was true at the end of.
+    pub fn fact_probability(&self, fact: &str) -> f64 {
+        if self.runs == 0 {
+            return 0.0;
+        }
+        self.facts.get(fact).copied().unwrap_or(0) as f64 / self.runs as f64
+    }
+
+    fn merge(&mut self, other: Outcomes) {
+        self.runs += other.runs;
+        self.failed += other.failed;
+        for (fact, count) in other.facts {
+            *self.facts.entry(fact).or_default() += count;
+        }
+        for (table, entries) in other.table_rolls {
+            let counts = self.table_rolls.entry(table).or_default();
+            for (entry, count) in entries {
+                *counts.entry(entry).or_default() += count;
+            }
+        }
+    }
+}
+
+const DEFAULT_RUNS: u64 = 10_000;
+
+impl Simulation {
+    pub fn new(source: impl Into<String>) -> Self {
+        Simulation::with_script(Script::Source(source.into()))
+    }
+
+    /// Simulate the script at `path`.
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        Simulation::with_script(Script::File(path.into()))
+    }
+
+    fn with_script(script: Script) -> Self {
+        Simulation {
+            script,
+            runs: DEFAULT_RUNS,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            seed: rand::random(),
+        }
+    }
+
+    pub fn with_runs(mut self, runs: u64) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// Split the runs across this many threads (at least one). Defaults to one per core.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Seed the runs, so the simulation comes out the same every time.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn run(&self) -> Outcomes {
+        let threads = (self.threads as u64).min(self.runs).max(1);
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|thread| {
+                    // Each thread takes every `threads`th run, starting from its own number.
+                    scope.spawn(move || {
+                        let mut outcomes = Outcomes::default();
+                        for run in (thread..self.runs).step_by(threads as usize) {
+                            outcomes.merge(self.run_once(run));
+                        }
+                        outcomes
+                    })
+                })
+                .collect();
+            let mut outcomes = Outcomes::default();
+            for handle in handles {
+                outcomes.merge(handle.join().expect("simulation thread panicked"));
+            }
+            outcomes
+        })
+    }
+
+    fn run_once(&self, run: u64) -> Outcomes {
+        let output = BufferSink::new();
+        let mut crawl = Crawl::new()
+            .with_seed(self.seed.wrapping_add(run))
+            .with_output(Box::new(output.clone()))
+            .with_prompt(Box::new(ScriptedPrompt::default()));
+        let table_rolls: Arc<Mutex<HashMap<String, HashMap<String, u64>>>> = Arc::default();
+        {
+            let table_rolls = Arc::clone(&table_rolls);
+            crawl.on_table_roll(move |table, entry| {
+                *table_rolls
+                    .lock()
+                    .unwrap()
+                    .entry(table.into())
+                    .or_default()
+                    .entry(entry.into())
+                    .or_default() += 1;
+            });
+        }
+
+        let read = match &self.script {
+            Script::Source(source) => {
+                crawl.execute(source);
+                true
+            }
+            Script::File(path) => crawl.execute_file(path).is_ok(),
+        };
+        let errored = output
+            .take()
+            .iter()
+            .any(|output| matches!(output, Output::Error(_)));
+
+        let snapshot = crawl.snapshot();
+        let facts = snapshot
+            .local_facts
+            .iter()
+            .chain(snapshot.persistent_facts.iter())
+            .map(|fact| (fact.to_string(), 1))
+            .collect();
+        Outcomes {
+            runs: 1,
+            failed: u64::from(errored || !read),
+            facts,
+            table_rolls: mem::take(&mut table_rolls.lock().unwrap()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "load table \"examples/table.csv\"\nroll on table \"examples/table.csv\"\nif roll 1-3 on 1d6 => set-fact \"party is lost\"\n";
+
+    #[test]
+    fn outcomes() {
+        let outcomes = Simulation::new(SOURCE).with_runs(1000).with_seed(7).run();
+        assert_eq!(outcomes.runs, 1000);
+        assert_eq!(outcomes.failed, 0);
+        assert!((outcomes.fact_probability("party is lost") - 0.5).abs() < 0.05);
+        let rolls: u64 = outcomes.table_rolls["examples/table.csv"].values().sum();
+        assert_eq!(rolls, 1000);
+    }
+
+    #[test]
+    fn same_however_many_threads() {
+        let simulation = Simulation::new(SOURCE).with_runs(100).with_seed(3);
+        assert_eq!(
+            simulation.clone().with_threads(1).run(),
+            simulation.with_threads(4).run()
+        );
+    }
+
+    #[test]
+    fn failed_runs() {
+        let outcomes =
+            Simulation::new("choose \"Which way?\"\n\t\"left\" => reminder \"cold\"\nend\n")
+                .with_runs(3)
+                .run();
+        assert_eq!(outcomes.failed, 3);
+    }
+}
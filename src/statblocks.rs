@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+// Statblocks are loaded from a file with `load statblocks "party.toml"`, and their stats used as
+// modifiers, like `roll 15+ on 1d20 + stat "Brofist" "STR"`. A TOML file lists them as
+//
+//     [[statblocks]]
+//     name = "Brofist"
+//     stats = { STR = 2, DEX = -1 }
+//     saves = { poison = 12 }
+//
+// and a JSON file as an array of the same objects.
+
+/// A character or creature's name, stats, and saving throws.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Statblock {
+    pub name: String,
+    #[serde(default)]
+    pub stats: HashMap<String, i32>,
+    #[serde(default)]
+    pub saves: HashMap<String, i32>,
+}
+
+#[derive(Deserialize)]
+struct StatblockFile {
+    statblocks: Vec<Statblock>,
+}
+
+/// The statblocks in `text`, read from the file `key`: TOML if it ends in `.toml`, and JSON
+/// otherwise.
+pub fn parse(key: &str, text: &str) -> Result<Vec<Statblock>, Box<dyn Error>> {
+    if key.ends_with(".toml") {
+        Ok(toml::from_str::<StatblockFile>(text)?.statblocks)
+    } else {
+        Ok(serde_json::from_str(text)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_toml_and_json() {
+        let toml = "[[statblocks]]\nname = \"Brofist\"\nstats = { STR = 2 }\n\n\
+                    [[statblocks]]\nname = \"Goblin\"\n";
+        let statblocks = parse("party.toml", toml).unwrap();
+        assert_eq!(statblocks.len(), 2);
+        assert_eq!(statblocks[0].stats["STR"], 2);
+        assert!(statblocks[1].saves.is_empty());
+
+        let json = r#"[{"name": "Brofist", "stats": {"STR": 2}, "saves": {"poison": 12}}]"#;
+        let statblocks = parse("party.json", json).unwrap();
+        assert_eq!(statblocks[0].saves["poison"], 12);
+
+        assert!(parse("party.json", toml).is_err());
+    }
+}
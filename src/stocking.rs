@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::dice::{DicePool, DiceRoll, Die};
+use crate::error::CrawlError;
+use crate::interpreter::Interpreter;
+use crate::tables::Table;
+
+// Stocks a dungeon's rooms ahead of play, the way a system's rules do. Each room's contents are
+// rolled on a "contents" table, a room with a monster in it rolls on "monsters", and a room may
+// have treasure, rolled on "treasure", on a chance in 6 given by the contents table's `treasure`
+// column. Each system's tables are built in, and any of them can be replaced.
+
+/// A game system whose rules, and tables, rooms are stocked by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum System {
+    // B/X: empty, monster, special, or trap, with treasure likeliest with a monster
+    Bx,
+}
+
+impl FromStr for System {
+    type Err = CrawlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bx" => Ok(System::Bx),
+            _ => Err(CrawlError::InterpreterError {
+                reason: format!("no system called {s:?} to stock rooms by"),
+            }),
+        }
+    }
+}
+
+impl System {
+    // The system's contents, monsters, and treasure tables, as CSV.
+    fn tables(self) -> [(&'static str, &'static str); 3] {
+        match self {
+            System::Bx => [
+                (CONTENTS, BX_CONTENTS),
+                (MONSTERS, BX_MONSTERS),
+                (TREASURE, BX_TREASURE),
+            ],
+        }
+    }
+}
+
+pub const CONTENTS: &str = "contents";
+pub const MONSTERS: &str = "monsters";
+pub const TREASURE: &str = "treasure";
+
+const BX_CONTENTS: &str = "roll,contents,treasure
+1-2,empty,1
+3-4,monster,3
+5,special,0
+6,trap,2
+";
+
+const BX_MONSTERS: &str = "1,{1d8} acolytes
+2,{1d8} bandits
+3,{1d8} fire beetles
+4,{1d6} dwarves
+5,{1d6} gnomes
+6,{2d4} goblins
+7,a green slime
+8,{3d6} halflings
+9,{1d10} killer bees
+10,{4d4} kobolds
+11,{1d6} gecko lizards
+12,{2d4} orcs
+13,{1d10} giant shrews
+14,{3d4} skeletons
+15,a spitting cobra
+16,{1d2} crab spiders
+17,{3d6} sprites
+18,{1d10} stirges
+19,{1d8} traders
+20,{2d6} wolves
+";
+
+const BX_TREASURE: &str = "1-2,{1d6}00 sp
+3-4,\"{1d6}00 sp, {1d6}0 gp\"
+5,\"{1d6}00 sp, {1d6}0 gp, {1d6} gems\"
+6,\"{1d6}00 sp, {1d6}0 gp, a piece of jewellery\"
+";
+
+/// A stocked room, for the dungeon's key.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Room {
+    pub number: usize,
+    pub contents: String,
+    pub monster: Option<String>,
+    pub treasure: Option<String>,
+}
+
+/// How to stock a dungeon: by which system, with which tables.
+#[derive(Clone, Debug)]
+pub struct Stocking {
+    system: System,
+    // Tables replacing the system's own
+    tables: HashMap<String, Table>,
+    seed: Option<u64>,
+}
+
+impl Stocking {
+    pub fn new(system: System) -> Self {
+        Stocking {
+            system,
+            tables: HashMap::new(),
+            seed: None,
+        }
+    }
+
+    /// Roll on `table` in place of the system's table called `name`: contents, monsters, or
+    /// treasure.
+    pub fn with_table(mut self, name: &str, table: Table) -> Self {
+        self.tables.insert(name.into(), table);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Stock rooms numbered from 1 to `rooms`.
+    pub fn stock(&self, rooms: usize) -> Result<Vec<Room>, CrawlError> {
+        let mut interpreter = Interpreter::new();
+        if let Some(seed) = self.seed {
+            interpreter = interpreter.with_seed(seed);
+        }
+        for (name, csv) in self.system.tables() {
+            let table = match self.tables.get(name) {
+                Some(table) => table.clone(),
+                None => Table::from_reader(csv.as_bytes()).expect("built-in tables are valid"),
+            };
+            interpreter.add_table(name, table);
+        }
+
+        let d6 = DiceRoll::new(DicePool::new(vec![Die(6)]), 0);
+        (1..=rooms)
+            .map(|number| {
+                let (entries, contents) = interpreter.roll_table(CONTENTS)?;
+                let monster = if entries.iter().any(|entry| entry.matches("monster")) {
+                    Some(interpreter.roll_table(MONSTERS)?.1.join("; "))
+                } else {
+                    None
+                };
+                let chance = entries
+                    .iter()
+                    .filter_map(|entry| entry.field(TREASURE)?.parse::<i32>().ok())
+                    .max()
+                    .unwrap_or(0);
+                let treasure = if chance > 0 && interpreter.roll_dice(&d6)? <= chance {
+                    Some(interpreter.roll_table(TREASURE)?.1.join("; "))
+                } else {
+                    None
+                };
+                Ok(Room {
+                    number,
+                    contents: contents.join("; "),
+                    monster,
+                    treasure,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stock() {
+        let rooms = Stocking::new(System::Bx).with_seed(7).stock(12).unwrap();
+        assert_eq!(rooms.len(), 12);
+        for (idx, room) in rooms.iter().enumerate() {
+            assert_eq!(room.number, idx + 1);
+            assert!(["empty", "monster", "special", "trap"].contains(&room.contents.as_str()));
+            assert_eq!(room.monster.is_some(), room.contents == "monster");
+            if room.contents == "special" {
+                assert_eq!(room.treasure, None);
+            }
+        }
+        // Rolls are embedded in the built-in tables.
+        assert!(
+            rooms
+                .iter()
+                .flat_map(|room| &room.monster)
+                .all(|monster| !monster.contains('{'))
+        );
+
+        let lair = Table::from_reader("roll,contents,treasure\n1,monster,6\n".as_bytes()).unwrap();
+        let rooms = Stocking::new(System::Bx)
+            .with_table(CONTENTS, lair)
+            .stock(3)
+            .unwrap();
+        assert!(rooms.iter().all(|room| room.treasure.is_some()));
+        assert!("odnd".parse::<System>().is_err());
+    }
+}
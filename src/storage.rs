@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::error::CrawlError;
+
+/// Where tables are loaded from and persistent facts are kept, so crawl can run somewhere
+/// without a filesystem.
+pub trait Storage: Send {
+    /// `None` if nothing is stored under `key`.
+    fn read(&mut self, key: &str) -> Result<Option<String>, CrawlError>;
+    fn write(&mut self, key: &str, value: &str) -> Result<(), CrawlError>;
+}
+
+/// Storage read without blocking, for `Crawl::execute_async`. Keys are table names, so they can be
+/// files, URLs, or anything else the implementation knows how to fetch.
+#[cfg(feature = "async")]
+pub trait AsyncStorage {
+    /// `None` if nothing is stored under `key`.
+    fn read(&mut self, key: &str) -> impl Future<Output = Result<Option<String>, CrawlError>>;
+}
+
+/// Keys are file paths, relative to `root`.
+#[derive(Debug)]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileStorage { root: root.into() }
+    }
+}
+
+impl Storage for FileStorage {
+    fn read(&mut self, key: &str) -> Result<Option<String>, CrawlError> {
+        match fs::read_to_string(self.root.join(key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(CrawlError::InterpreterError {
+                reason: format!("failed to read {key} ({error})"),
+            }),
+        }
+    }
+
+    fn write(&mut self, key: &str, value: &str) -> Result<(), CrawlError> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| CrawlError::InterpreterError {
+                reason: format!("failed to create the directory for {key} ({error})"),
+            })?;
+        }
+        fs::write(path, value).map_err(|error| CrawlError::InterpreterError {
+            reason: format!("failed to write {key} ({error})"),
+        })
+    }
+}
+
+/// Where `key` is kept for `campaign`, so each campaign's world state has a directory of its own.
+pub fn campaign_key(campaign: &str, key: &str) -> String {
+    format!("campaigns/{campaign}/{key}")
+}
+
+/// Campaign names are used as directory names, so can't be empty or a path.
+pub fn check_campaign_name(campaign: &str) -> Result<(), CrawlError> {
+    if campaign.is_empty() || campaign == "." || campaign == ".." || campaign.contains(['/', '\\'])
+    {
+        return Err(CrawlError::InterpreterError {
+            reason: format!("{campaign:?} can't be used as a campaign name"),
+        });
+    }
+    Ok(())
+}
+
+/// Keeps values in memory. Clones share the same values, like `BufferSink`.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage(Arc<Mutex<HashMap<String, String>>>);
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&mut self, key: &str) -> Result<Option<String>, CrawlError> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    fn write(&mut self, key: &str, value: &str) -> Result<(), CrawlError> {
+        self.0.lock().unwrap().insert(key.into(), value.into());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_storage() {
+        let mut storage = MemoryStorage::new();
+        let mut shared = storage.clone();
+        storage.write("weather", "1,clear").unwrap();
+        assert_eq!(shared.read("weather").unwrap().as_deref(), Some("1,clear"));
+        assert_eq!(shared.read("encounters").unwrap(), None);
+    }
+
+    #[test]
+    fn campaign_names() {
+        assert_eq!(
+            campaign_key("greyhawk", "persistent-facts.json"),
+            "campaigns/greyhawk/persistent-facts.json"
+        );
+        assert!(check_campaign_name("west marches").is_ok());
+        for name in ["", "..", "a/b", "a\\b"] {
+            assert!(check_campaign_name(name).is_err());
+        }
+    }
+
+    #[test]
+    fn file_storage() {
+        let mut storage = FileStorage::new("examples");
+        assert!(storage.read("table.csv").unwrap().is_some());
+        assert_eq!(storage.read("missing.csv").unwrap(), None);
+    }
+}
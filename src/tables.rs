@@ -1,15 +1,75 @@
-use std::{collections::HashMap, error::Error, fs::File};
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fs::File,
+    io,
+};
+
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    dice::{DicePool, DiceRoll, Die},
+    dice::{DicePool, DiceRoll, Die, RollSpec},
     error::CrawlError,
     rolls::RollTarget,
 };
 
-#[derive(Debug, PartialEq, Clone)]
+/// Entries with this text stand for rolling twice more on the table, and taking both.
+pub const ROLL_TWICE: &str = "roll twice";
+
+// How many more roll-twice entries can come up before giving up - on a table with nothing
+// else, rolling would never finish.
+const MAX_REROLLS: usize = 100;
+// How many times an excluded entry can come up before giving up, which is more, since it's
+// likelier to keep coming up as more of a table is excluded.
+const MAX_EXCLUDED_REROLLS: usize = 1000;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TableEntry {
     roll_target: RollTarget,
     pub value: String,
+    // Words to match the entry by, besides its text, like "hostile"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Named columns, on tables with a header row
+    #[serde(default)]
+    pub fields: Vec<(String, String)>,
+}
+
+impl TableEntry {
+    pub fn new(roll_target: RollTarget, value: impl Into<String>) -> Self {
+        TableEntry {
+            roll_target,
+            value: value.into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_tags(mut self, tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn roll_target(&self) -> &RollTarget {
+        &self.roll_target
+    }
+
+    pub fn is_roll_twice(&self) -> bool {
+        self.value.eq_ignore_ascii_case(ROLL_TWICE)
+    }
+
+    /// The entry's value in the column named `name`.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether `pattern` is the entry's text or one of its tags.
+    pub fn matches(&self, pattern: &str) -> bool {
+        self.value == pattern || self.tags.iter().any(|tag| tag == pattern)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -23,14 +83,33 @@ impl<'a> TableRollResult<'a> {
     }
 }
 
-#[derive(Debug)]
+/// What an unclamped roll off the end of a table comes up with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OffTable {
+    #[default]
+    Error,
+    NoResult,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Table {
     entries: Vec<TableEntry>,
     roll_targets: HashMap<i32, usize>,
     min_target: i32,
     max_target: i32,
+    #[serde(default = "clamp_by_default")]
     clamp_to_min: bool,
+    #[serde(default = "clamp_by_default")]
     clamp_to_max: bool,
+    #[serde(default)]
+    off_table: OffTable,
+    // Rolled on the table instead of its default die
+    #[serde(default)]
+    dice: Option<RollSpec>,
+}
+
+fn clamp_by_default() -> bool {
+    true
 }
 
 impl Table {
@@ -44,65 +123,272 @@ impl Table {
             max_target,
             clamp_to_min: true,
             clamp_to_max: true,
+            off_table: OffTable::Error,
+            dice: None,
+        }
+    }
+
+    pub fn entries(&self) -> &[TableEntry] {
+        &self.entries
+    }
+
+    /// Roll `dice` on the table, instead of a die as big as its highest target.
+    pub fn with_dice(mut self, dice: RollSpec) -> Self {
+        self.dice = Some(dice);
+        self
+    }
+
+    /// Whether totals under the lowest target, and over the highest, land on those entries.
+    pub fn with_clamp(mut self, clamp_to_min: bool, clamp_to_max: bool) -> Self {
+        self.clamp_to_min = clamp_to_min;
+        self.clamp_to_max = clamp_to_max;
+        self
+    }
+
+    pub fn with_off_table(mut self, off_table: OffTable) -> Self {
+        self.off_table = off_table;
+        self
+    }
+
+    /// The index of the entry a roll total lands on, after clamping off-table totals.
+    fn entry_index(&self, total: i32) -> Option<usize> {
+        match self.roll_targets.get(&total) {
+            Some(entry_idx) => Some(*entry_idx),
+            None if total < self.min_target && self.clamp_to_min => {
+                self.roll_targets.get(&self.min_target).copied()
+            }
+            None if total > self.max_target && self.clamp_to_max => {
+                self.roll_targets.get(&self.max_target).copied()
+            }
+            None => None,
+        }
+    }
+
+    /// The table of just the entries tagged `tag`, each taking up as many targets as before,
+    /// one after another from 1, so it's rolled with a die as big as all of them together.
+    pub fn tagged(&self, tag: &str) -> Result<Table, CrawlError> {
+        let mut entries = Vec::new();
+        let mut next_target = 1;
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| entry.tags.iter().any(|t| t == tag))
+        {
+            let width = match entry.roll_target {
+                RollTarget::NumRange(low, high) => high - low + 1,
+                RollTarget::Num(_) | RollTarget::OverOrEqual(_) => 1,
+            };
+            let roll_target = match width {
+                1 => RollTarget::Num(next_target),
+                _ => RollTarget::NumRange(next_target, next_target + width - 1),
+            };
+            next_target += width;
+            entries.push(TableEntry {
+                roll_target,
+                ..entry.clone()
+            });
+        }
+        if entries.is_empty() {
+            return Err(table_error(format!("no entries are tagged {tag:?}")));
         }
+        Ok(Table::from(entries)
+            .with_clamp(self.clamp_to_min, self.clamp_to_max)
+            .with_off_table(self.off_table))
     }
 
-    fn get_value_for_target(&self, target: &i32) -> Result<TableRollResult, CrawlError> {
-        if let Some(entry_idx) = self.roll_targets.get(target) {
-            Ok(TableRollResult::new(self.entries.get(*entry_idx).unwrap()))
-        } else {
-            Err(CrawlError::InterpreterError {
-                reason: format!("target {target:?} not a valid index for table"),
-            })
+    /// The entry a roll total lands on.
+    pub fn lookup(&self, total: i32) -> Result<TableRollResult<'_>, CrawlError> {
+        match self.entry_index(total) {
+            Some(entry_idx) => Ok(TableRollResult::new(self.entries.get(entry_idx).unwrap())),
+            None => Err(CrawlError::InterpreterError {
+                reason: format!("roll {total} not a valid index for table"),
+            }),
         }
     }
 
-    pub fn roll(&self, dice: &DiceRoll) -> Result<TableRollResult, CrawlError> {
-        let roll_result = dice.roll();
-        let roll_value = self.roll_targets.get(&roll_result.total);
-        match roll_value {
-            Some(entry_idx) => Ok(TableRollResult::new(self.entries.get(*entry_idx).unwrap())),
-            None => {
-                if roll_result.total < self.min_target && self.clamp_to_min {
-                    return self.get_value_for_target(&self.min_target);
-                } else if roll_result.total > self.max_target && self.clamp_to_max {
-                    return self.get_value_for_target(&self.max_target);
-                } else {
-                    Err(CrawlError::InterpreterError {
-                        reason: format!("roll {roll_result:?} not a valid index for table"),
-                    })
+    pub fn roll(&self, dice: &DiceRoll) -> Result<TableRollResult<'_>, CrawlError> {
+        self.lookup(dice.roll().checked_total()?)
+    }
+
+    /// The entry a roll total lands on, or nothing if it's off the table and the table says
+    /// off-table rolls come up with no result.
+    fn lookup_entry(&self, total: i32) -> Result<Option<&TableEntry>, CrawlError> {
+        match self.entry_index(total) {
+            Some(entry_idx) => Ok(self.entries.get(entry_idx)),
+            None if self.off_table == OffTable::NoResult => Ok(None),
+            None => self.lookup(total).map(|result| Some(result.entry)),
+        }
+    }
+
+    /// The entries landed on with totals from `roll`. A roll-twice entry is replaced by two
+    /// more, rolling past any further roll-twice entries. Off-table rolls with no result
+    /// don't land on any entry.
+    pub fn roll_entries(
+        &self,
+        roll: impl FnMut() -> Result<i32, CrawlError>,
+    ) -> Result<Vec<&TableEntry>, CrawlError> {
+        self.roll_entries_excluding(roll, &[])
+    }
+
+    /// The entries landed on with totals from `roll`, as with `roll_entries`, but rerolling
+    /// any entry whose text is in `excluded`.
+    pub fn roll_entries_excluding(
+        &self,
+        mut roll: impl FnMut() -> Result<i32, CrawlError>,
+        excluded: &[String],
+    ) -> Result<Vec<&TableEntry>, CrawlError> {
+        let mut excluded_rerolls = 0;
+        let mut next = || -> Result<Option<&TableEntry>, CrawlError> {
+            loop {
+                let entry = self.lookup_entry(roll()?)?;
+                if !entry.is_some_and(|entry| excluded.contains(&entry.value)) {
+                    return Ok(entry);
+                }
+                if excluded_rerolls == MAX_EXCLUDED_REROLLS {
+                    return Err(table_error(format!(
+                        "kept landing on excluded entries after {MAX_EXCLUDED_REROLLS} rerolls"
+                    )));
                 }
+                excluded_rerolls += 1;
             }
+        };
+        let first = match next()? {
+            Some(entry) => entry,
+            None => return Ok(Vec::new()),
+        };
+        if !first.is_roll_twice() {
+            return Ok(vec![first]);
         }
+
+        let mut entries = Vec::new();
+        let mut rerolls = 0;
+        while entries.len() < 2 {
+            let entry = next()?;
+            if let Some(entry) = entry.filter(|entry| !entry.is_roll_twice()) {
+                entries.push(entry);
+            } else if rerolls < MAX_REROLLS {
+                rerolls += 1;
+            } else {
+                return Err(CrawlError::InterpreterError {
+                    reason: format!("kept landing on {ROLL_TWICE:?} after {MAX_REROLLS} rerolls"),
+                });
+            }
+        }
+        Ok(entries)
     }
 
-    pub fn auto_roll(&self) -> Result<TableRollResult, CrawlError> {
-        let dice = vec![Die(self.max_target)];
+    /// The dice used when rolling on the table without specifying any: the table's own, or
+    /// a single die with as many sides as the table's highest target.
+    pub fn auto_dice(&self) -> DiceRoll {
+        let dice = match self.dice {
+            Some(spec) => vec![Die(spec.n_sides); spec.n_dice],
+            None => vec![Die(self.max_target)],
+        };
         let dice_pool = DicePool::new(dice);
-        let roll = DiceRoll::new(dice_pool, 0);
-        self.roll(&roll)
+        DiceRoll::new(dice_pool, 0)
+    }
+
+    pub fn auto_roll(&self) -> Result<TableRollResult<'_>, CrawlError> {
+        self.roll(&self.auto_dice())
+    }
+
+    /// The probability of landing on each entry when rolling `dice` on the table, in entry
+    /// order. If some totals land off the table (and aren't clamped), the probabilities sum to
+    /// less than one.
+    pub fn distribution(&self, dice: &DiceRoll) -> Result<Vec<(&TableEntry, f64)>, CrawlError> {
+        let mut probabilities = vec![0.0; self.entries.len()];
+        for (total, probability) in dice.distribution()? {
+            // Totals too big to roll on a table don't land anywhere.
+            let entry_idx = i32::try_from(total)
+                .ok()
+                .and_then(|total| self.entry_index(total));
+            if let Some(entry_idx) = entry_idx {
+                probabilities[entry_idx] += probability;
+            }
+        }
+        Ok(self.entries.iter().zip(probabilities).collect())
     }
 
     // TODO: load from table paths + without extension
     pub fn load(filepath: &str) -> Result<Self, Box<dyn Error>> {
-        let file = File::open(filepath)?;
+        Self::from_reader(File::open(filepath)?)
+    }
+
+    /// Read a table from CSV rows of roll target, entry, and optionally space-separated tags.
+    /// A header row, like `roll,monster,number appearing,tags`, names the columns instead: the
+    /// first column after the roll target is the entry, and a `tags` column holds its tags.
+    /// Rows of `clamp,both|min|max|none` and `off-table,error|no-result` set how rolls off the
+    /// end of the table are handled.
+    pub fn from_reader(reader: impl io::Read) -> Result<Self, Box<dyn Error>> {
         let mut rdr = csv::ReaderBuilder::new()
             .has_headers(false)
-            .from_reader(file);
+            .flexible(true)
+            .from_reader(reader);
 
         let mut entries = Vec::new();
+        let mut clamp = (true, true);
+        let mut off_table = OffTable::Error;
+        let mut header: Option<Vec<String>> = None;
         for result in rdr.records() {
             let record = result?;
             let roll_target = record.get(0).unwrap();
             let value = record.get(1).unwrap();
+            match roll_target {
+                "clamp" => {
+                    clamp = match value {
+                        "both" => (true, true),
+                        "min" => (true, false),
+                        "max" => (false, true),
+                        "none" => (false, false),
+                        _ => return Err(format!("unknown clamp {value:?}").into()),
+                    };
+                    continue;
+                }
+                "off-table" => {
+                    off_table = match value {
+                        "error" => OffTable::Error,
+                        "no-result" => OffTable::NoResult,
+                        _ => return Err(format!("unknown off-table {value:?}").into()),
+                    };
+                    continue;
+                }
+                _ => {}
+            }
+            let roll_target = match RollTarget::try_from(roll_target) {
+                Ok(roll_target) => roll_target,
+                Err(_) if entries.is_empty() && header.is_none() => {
+                    header = Some(record.iter().skip(1).map(Into::into).collect());
+                    continue;
+                }
+                Err(error) => return Err(error.into()),
+            };
+            let fields: Vec<(String, String)> = match &header {
+                Some(names) => names
+                    .iter()
+                    .cloned()
+                    .zip(record.iter().skip(1).map(Into::into))
+                    .collect(),
+                None => Vec::new(),
+            };
+            let tags = match &header {
+                Some(_) => fields
+                    .iter()
+                    .find(|(name, _)| name == "tags")
+                    .map_or("", |(_, tags)| tags.as_str()),
+                None => record.get(2).unwrap_or_default(),
+            };
             let entry = TableEntry {
-                roll_target: roll_target.try_into().unwrap(),
+                roll_target,
                 value: value.into(),
+                tags: tags.split_whitespace().map(Into::into).collect(),
+                fields,
             };
             entries.push(entry);
         }
 
-        Ok(Self::from(entries))
+        Ok(Self::from(entries)
+            .with_clamp(clamp.0, clamp.1)
+            .with_off_table(off_table))
     }
 }
 
@@ -127,6 +413,114 @@ impl From<Vec<TableEntry>> for Table {
     }
 }
 
+#[derive(Debug)]
+enum BuilderTarget {
+    Target(String),
+    // How many targets the entry takes up, following on from the entries before it
+    Weight(i32),
+}
+
+/// Builds a table in code, checking when it's built that every target is a number or range,
+/// and that each total from the lowest target to the highest lands on exactly one entry.
+///
+/// ```
+/// use crawl::tables::TableBuilder;
+///
+/// let weather = TableBuilder::new()
+///     .entry("1-3", "rain")
+///     .weighted("snow", 2)
+///     .weighted("sleet", 1)
+///     .build()
+///     .unwrap();
+/// assert_eq!(weather.lookup(5).unwrap().entry.value, "snow");
+/// ```
+#[derive(Debug, Default)]
+pub struct TableBuilder {
+    entries: Vec<(BuilderTarget, String)>,
+    dice: Option<String>,
+}
+
+impl TableBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry on a roll target like `"4"`, `"1-3"`, or `"13+"`.
+    pub fn entry(mut self, target: &str, value: impl Into<String>) -> Self {
+        self.entries
+            .push((BuilderTarget::Target(target.into()), value.into()));
+        self
+    }
+
+    /// Add an entry taking up the next `weight` targets after the entries so far.
+    pub fn weighted(mut self, value: impl Into<String>, weight: i32) -> Self {
+        self.entries
+            .push((BuilderTarget::Weight(weight), value.into()));
+        self
+    }
+
+    /// Roll the table on dice like `"2d6"`.
+    pub fn die(mut self, dice: &str) -> Self {
+        self.dice = Some(dice.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Table, CrawlError> {
+        let mut entries = Vec::new();
+        let mut targets = BTreeMap::new();
+        let mut next_target = 1;
+        for (target, value) in self.entries {
+            let roll_target = match target {
+                BuilderTarget::Target(target) => RollTarget::try_from(target.as_str())?,
+                BuilderTarget::Weight(1) => RollTarget::Num(next_target),
+                BuilderTarget::Weight(weight) if weight > 1 => {
+                    RollTarget::NumRange(next_target, next_target + weight - 1)
+                }
+                BuilderTarget::Weight(weight) => {
+                    return Err(table_error(format!(
+                        "{value:?} needs a weight of at least 1, not {weight}"
+                    )));
+                }
+            };
+            let (low, high) = match roll_target {
+                RollTarget::Num(n) | RollTarget::OverOrEqual(n) => (n, n),
+                RollTarget::NumRange(low, high) => (low, high),
+            };
+            if low > high {
+                return Err(table_error(format!(
+                    "{value:?} is on {roll_target}, backwards"
+                )));
+            }
+            for target in low..=high {
+                if let Some(other) = targets.insert(target, value.clone()) {
+                    return Err(table_error(format!(
+                        "{target} is on both {other:?} and {value:?}"
+                    )));
+                }
+            }
+            next_target = next_target.max(high + 1);
+            entries.push(TableEntry::new(roll_target, value));
+        }
+
+        let (Some(low), Some(high)) = (targets.keys().next(), targets.keys().next_back()) else {
+            return Err(table_error("table has no entries".into()));
+        };
+        if let Some(missing) = (*low..=*high).find(|target| !targets.contains_key(target)) {
+            return Err(table_error(format!("nothing is on {missing}")));
+        }
+
+        let table = Table::from(entries);
+        match self.dice {
+            Some(dice) => Ok(table.with_dice(dice.parse()?)),
+            None => Ok(table),
+        }
+    }
+}
+
+fn table_error(reason: String) -> CrawlError {
+    CrawlError::InterpreterError { reason }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::dice::{DicePool, Die};
@@ -138,10 +532,14 @@ mod tests {
         let low_entry = TableEntry {
             roll_target: RollTarget::NumRange(1, 6),
             value: "1-6".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
         let high_entry = TableEntry {
             roll_target: RollTarget::NumRange(7, 12),
             value: "7-12".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
         let table = Table::from(vec![low_entry.clone(), high_entry.clone()]);
 
@@ -159,10 +557,14 @@ mod tests {
         let zero_entry = TableEntry {
             roll_target: RollTarget::Num(0),
             value: "zero".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
         let one_entry = TableEntry {
             roll_target: RollTarget::Num(1),
             value: "one".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
 
         let table = Table::from(vec![zero_entry.clone(), one_entry.clone()]);
@@ -178,10 +580,14 @@ mod tests {
         let zero_entry = TableEntry {
             roll_target: RollTarget::Num(0),
             value: "zero".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
         let one_entry = TableEntry {
             roll_target: RollTarget::Num(1),
             value: "one".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
 
         let table = Table::from(vec![zero_entry.clone(), one_entry.clone()]);
@@ -197,10 +603,14 @@ mod tests {
         let zero_entry = TableEntry {
             roll_target: RollTarget::Num(0),
             value: "zero".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
         let one_entry = TableEntry {
             roll_target: RollTarget::Num(1),
             value: "one".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
 
         let table = Table::from(vec![zero_entry.clone(), one_entry.clone()]);
@@ -211,6 +621,32 @@ mod tests {
         assert_eq!(result, TableRollResult { entry: &one_entry });
     }
 
+    #[test]
+    fn distribution() {
+        let low_entry = TableEntry {
+            roll_target: RollTarget::NumRange(1, 2),
+            value: "low".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
+        };
+        let high_entry = TableEntry {
+            roll_target: RollTarget::OverOrEqual(3),
+            value: "high".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
+        };
+        let table = Table::from(vec![low_entry.clone(), high_entry.clone()]);
+
+        // 1d6 lands on 3+ four times out of six, via clamping.
+        let dice = DiceRoll::new(DicePool::new(vec![Die(6)]), 0);
+        let distribution = table.distribution(&dice).unwrap();
+        assert_eq!(distribution.len(), 2);
+        assert_eq!(distribution[0].0, &low_entry);
+        assert!((distribution[0].1 - 2.0 / 6.0).abs() < 1e-9);
+        assert_eq!(distribution[1].0, &high_entry);
+        assert!((distribution[1].1 - 4.0 / 6.0).abs() < 1e-9);
+    }
+
     #[test]
     fn from_csv() {
         // TODO: test fixtures location
@@ -222,10 +658,121 @@ mod tests {
         let entry = TableEntry {
             roll_target: RollTarget::NumRange(7, 12),
             value: "total darkness and dread".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
         assert_eq!(result, TableRollResult { entry: &entry });
     }
 
+    #[test]
+    fn tags_from_csv() {
+        let table = Table::from_reader("1,wolf,hostile beast\n2,peasant\n".as_bytes()).unwrap();
+        let wolf = table.lookup(1).unwrap().entry;
+        assert_eq!(wolf.tags, vec!["hostile", "beast"]);
+        assert!(wolf.matches("wolf") && wolf.matches("beast"));
+        assert!(!wolf.matches("peasant"));
+        assert!(table.lookup(2).unwrap().entry.tags.is_empty());
+    }
+
+    #[test]
+    fn tagged() {
+        let table =
+            Table::from_reader("1-2,wolf,night\n3,peasant,day\n4-6,ghoul,night\n".as_bytes())
+                .unwrap();
+        let night = table.tagged("night").unwrap();
+        assert_eq!(night.auto_dice().to_string(), "1d5");
+        assert_eq!(night.lookup(2).unwrap().entry.value, "wolf");
+        assert_eq!(night.lookup(3).unwrap().entry.value, "ghoul");
+        assert_eq!(
+            night.lookup(5).unwrap().entry.roll_target(),
+            &RollTarget::NumRange(3, 5)
+        );
+        assert!(table.tagged("dusk").is_err());
+    }
+
+    #[test]
+    fn excluding() {
+        let table = Table::from_reader("1,wolf\n2,orc\n3,roll twice\n".as_bytes()).unwrap();
+        let excluded = vec!["wolf".to_string()];
+        let mut totals = vec![1, 3, 1, 2, 2].into_iter();
+        let entries = table
+            .roll_entries_excluding(|| Ok(totals.next().unwrap()), &excluded)
+            .unwrap();
+        let values: Vec<&str> = entries.iter().map(|entry| entry.value.as_str()).collect();
+        assert_eq!(values, vec!["orc", "orc"]);
+        assert!(table.roll_entries_excluding(|| Ok(1), &excluded).is_err());
+    }
+
+    #[test]
+    fn roll_twice() {
+        let table = Table::from_reader("1,wolf\n2,orc\n3,roll twice\n".as_bytes()).unwrap();
+        let mut totals = vec![3, 3, 2, 1].into_iter();
+        let entries = table.roll_entries(|| Ok(totals.next().unwrap())).unwrap();
+        let values: Vec<&str> = entries.iter().map(|entry| entry.value.as_str()).collect();
+        assert_eq!(values, vec!["orc", "wolf"]);
+
+        let table = Table::from_reader("1,roll twice\n".as_bytes()).unwrap();
+        assert!(table.roll_entries(|| Ok(1)).is_err());
+    }
+
+    #[test]
+    fn clamping_from_csv() {
+        let csv = "clamp,min\n2,orc\n3,wolf\n";
+        let table = Table::from_reader(csv.as_bytes()).unwrap();
+        assert_eq!(table.lookup(1).unwrap().entry.value, "orc");
+        assert!(table.lookup(4).is_err());
+        assert!(table.roll_entries(|| Ok(4)).is_err());
+
+        let csv = "clamp,none\noff-table,no-result\n2,orc\n3,wolf\n";
+        let table = Table::from_reader(csv.as_bytes()).unwrap();
+        assert!(table.roll_entries(|| Ok(1)).unwrap().is_empty());
+
+        assert!(Table::from_reader("clamp,sometimes\n1,orc\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn builder() {
+        let table = TableBuilder::new()
+            .entry("2-4", "calm")
+            .weighted("squall", 3)
+            .entry("8+", "storm")
+            .die("2d6")
+            .build()
+            .unwrap();
+        assert_eq!(table.lookup(7).unwrap().entry.value, "squall");
+        assert_eq!(table.lookup(12).unwrap().entry.value, "storm");
+        assert_eq!(table.auto_dice().max(), 12);
+
+        let invalid = [
+            TableBuilder::new(),
+            TableBuilder::new().entry("one", "calm"),
+            TableBuilder::new()
+                .entry("1-3", "calm")
+                .entry("3", "squall"),
+            TableBuilder::new().entry("1", "calm").entry("3", "squall"),
+            TableBuilder::new().entry("3-1", "calm"),
+            TableBuilder::new().weighted("calm", 0),
+            TableBuilder::new().entry("1", "calm").die("2x6"),
+        ];
+        for builder in invalid {
+            assert!(builder.build().is_err());
+        }
+    }
+
+    #[test]
+    fn fields_from_csv() {
+        let csv = "roll,monster,number appearing,tags\n1,goblins,2d4,hostile\n2,merchant,1\n";
+        let table = Table::from_reader(csv.as_bytes()).unwrap();
+        let goblins = table.lookup(1).unwrap().entry;
+        assert_eq!(goblins.value, "goblins");
+        assert_eq!(goblins.field("number appearing"), Some("2d4"));
+        assert_eq!(goblins.tags, vec!["hostile"]);
+        assert_eq!(table.lookup(2).unwrap().entry.field("tags"), None);
+        assert_eq!(goblins.field("treasure"), None);
+
+        assert!(Table::from_reader("1,goblins\nroll,monster\n".as_bytes()).is_err());
+    }
+
     #[test]
     fn over_target_from_csv() {
         let table = Table::load("examples/table.csv").unwrap();
@@ -236,6 +783,8 @@ mod tests {
         let entry = TableEntry {
             roll_target: RollTarget::OverOrEqual(13),
             value: "fog seeps up through the dirt".into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
         };
         assert_eq!(result, TableRollResult { entry: &entry })
     }
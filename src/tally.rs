@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// XP and treasure awarded, with treasure in the smallest coin. A tally is kept for the session,
+/// and one for the campaign, which is saved with the persistent facts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tally {
+    pub xp: i32,
+    pub treasure: i32,
+}
+
+impl Tally {
+    pub fn is_empty(&self) -> bool {
+        *self == Tally::default()
+    }
+}
+
+/// Each of `party_size` characters' share of `amount`, rounded down, as awards are.
+pub fn share(amount: i32, party_size: i32) -> i32 {
+    amount / party_size.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares() {
+        assert_eq!(share(250, 4), 62);
+        assert_eq!(share(250, 0), 250);
+        assert!(Tally::default().is_empty());
+        assert!(!Tally { xp: 0, treasure: 1 }.is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dice::RollOutcome;
+use crate::error::CrawlError;
+
+/// One line of a transcript. A transcript starts with the session's seed, followed by each
+/// executed source and the rolls it made.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum TranscriptEvent {
+    Session { seed: u64 },
+    Execute { source: String },
+    Roll(RollOutcome),
+}
+
+/// A machine-readable record of a session, stored as JSON lines, that can be replayed to
+/// reproduce every roll.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transcript {
+    events: Vec<TranscriptEvent>,
+}
+
+impl Transcript {
+    pub fn new(seed: u64) -> Self {
+        Transcript {
+            events: vec![TranscriptEvent::Session { seed }],
+        }
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.events.iter().find_map(|event| match event {
+            TranscriptEvent::Session { seed } => Some(*seed),
+            _ => None,
+        })
+    }
+
+    pub fn events(&self) -> &[TranscriptEvent] {
+        &self.events
+    }
+
+    pub fn record_execution(&mut self, source: &str, rolls: &[RollOutcome]) {
+        self.events.push(TranscriptEvent::Execute {
+            source: source.into(),
+        });
+        self.events
+            .extend(rolls.iter().cloned().map(TranscriptEvent::Roll));
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = &str> {
+        self.events.iter().filter_map(|event| match event {
+            TranscriptEvent::Execute { source } => Some(source.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn rolls(&self) -> impl Iterator<Item = &RollOutcome> {
+        self.events.iter().filter_map(|event| match event {
+            TranscriptEvent::Roll(outcome) => Some(outcome),
+            _ => None,
+        })
+    }
+
+    pub fn write_jsonl(&self, mut writer: impl Write) -> Result<(), CrawlError> {
+        for event in &self.events {
+            let line =
+                serde_json::to_string(event).map_err(|error| CrawlError::InterpreterError {
+                    reason: format!("failed to serialize transcript ({error})"),
+                })?;
+            writeln!(writer, "{line}").map_err(|error| CrawlError::InterpreterError {
+                reason: format!("failed to write transcript ({error})"),
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn read_jsonl(reader: impl BufRead) -> Result<Self, CrawlError> {
+        let mut events = Vec::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line.map_err(|error| CrawlError::InterpreterError {
+                reason: format!("failed to read transcript ({error})"),
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event =
+                serde_json::from_str(&line).map_err(|error| CrawlError::InterpreterError {
+                    reason: format!("invalid transcript line {} ({error})", idx + 1),
+                })?;
+            events.push(event);
+        }
+        Ok(Transcript { events })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jsonl_round_trip() {
+        let mut transcript = Transcript::new(42);
+        transcript.record_execution(
+            "roll 1d6\n",
+            &[RollOutcome {
+                dice: "1d6+0".into(),
+                faces: vec![4],
+                total: 4,
+            }],
+        );
+
+        let mut jsonl = Vec::new();
+        transcript.write_jsonl(&mut jsonl).unwrap();
+        let jsonl = String::from_utf8(jsonl).unwrap();
+        assert_eq!(
+            jsonl,
+            "{\"event\":\"session\",\"seed\":42}\n\
+            {\"event\":\"execute\",\"source\":\"roll 1d6\\n\"}\n\
+            {\"event\":\"roll\",\"dice\":\"1d6+0\",\"faces\":[4],\"total\":4}\n"
+        );
+
+        let read = Transcript::read_jsonl(jsonl.as_bytes()).unwrap();
+        assert_eq!(read, transcript);
+        assert_eq!(read.seed(), Some(42));
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+// A travel turn runs the usual sequence for the terrain being crossed: a check for getting lost,
+// a check for an encounter, the weather, and a check for a discovery. What's encountered, the
+// weather, and what's found are rolled on the terrain's tables, named like "forest encounters",
+// "forest weather", and "forest discoveries". A step is skipped if its table isn't loaded.
+
+/// The fact set while the party is lost, and cleared once a travel turn finds the way again.
+pub const LOST_FACT: &str = "party is lost";
+
+/// The chances, in 6, of each check on a travel turn coming up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TerrainChances {
+    pub lost: i32,
+    pub encounter: i32,
+    pub discovery: i32,
+}
+
+impl Default for TerrainChances {
+    fn default() -> Self {
+        TerrainChances {
+            lost: 1,
+            encounter: 1,
+            discovery: 1,
+        }
+    }
+}
+
+/// How likely travel turns are to get lost, meet something, or find something, by terrain.
+/// Terrain without chances of its own has a 1 in 6 chance of each.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Travel {
+    terrains: HashMap<String, TerrainChances>,
+}
+
+impl Travel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_terrain(mut self, terrain: &str, chances: TerrainChances) -> Self {
+        self.terrains.insert(terrain.into(), chances);
+        self
+    }
+
+    pub fn chances(&self, terrain: &str) -> TerrainChances {
+        self.terrains.get(terrain).copied().unwrap_or_default()
+    }
+}
+
+pub fn encounter_table(terrain: &str) -> String {
+    format!("{terrain} encounters")
+}
+
+pub fn weather_table(terrain: &str) -> String {
+    format!("{terrain} weather")
+}
+
+pub fn discovery_table(terrain: &str) -> String {
+    format!("{terrain} discoveries")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chances() {
+        let swamp = TerrainChances {
+            lost: 3,
+            encounter: 3,
+            discovery: 1,
+        };
+        let travel = Travel::new().with_terrain("swamp", swamp);
+        assert_eq!(travel.chances("swamp"), swamp);
+        assert_eq!(travel.chances("clear"), TerrainChances::default());
+        assert_eq!(encounter_table("swamp"), "swamp encounters");
+    }
+}
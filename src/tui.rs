@@ -0,0 +1,363 @@
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Flex, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame, Terminal};
+
+use crate::error::CrawlError;
+use crate::journal::render_record;
+use crate::lang::Crawl;
+use crate::locale::Locale;
+use crate::output::{BufferSink, Output};
+use crate::prompt::Prompt;
+
+// A terminal UI for running sessions, from `crawl tui`. A script is run, and then statements are
+// typed in and run one at a time, like the REPL. Panes show the session as it stands:
+// procedures, with the ones just run marked, a log of everything that's happened, facts, and
+// loaded tables. `choose` asks in a popup.
+
+/// A session, and what's shown of it.
+pub struct Tui {
+    crawl: Crawl,
+    output: BufferSink,
+    // Procedures called by the last thing run, in the order they were called
+    called: Arc<Mutex<Vec<String>>>,
+    log: Vec<Line<'static>>,
+    // How many lines the log is scrolled up from its end
+    scroll: usize,
+    input: String,
+    procedures: Vec<String>,
+    facts: Vec<String>,
+    tables: Vec<String>,
+}
+
+// How many lines Page Up and Page Down scroll the log
+const PAGE: usize = 10;
+
+impl Tui {
+    pub fn new(crawl: Crawl) -> Self {
+        let output = BufferSink::new();
+        let mut crawl = crawl
+            .with_output(Box::new(output.clone()))
+            .with_prompt(Box::new(PopupPrompt));
+        let called = Arc::new(Mutex::new(Vec::new()));
+        {
+            let called = Arc::clone(&called);
+            crawl.on_procedure_call(move |procedure| called.lock().unwrap().push(procedure.into()));
+        }
+        let mut tui = Tui {
+            crawl,
+            output,
+            called,
+            log: Vec::new(),
+            scroll: 0,
+            input: String::new(),
+            procedures: Vec::new(),
+            facts: Vec::new(),
+            tables: Vec::new(),
+        };
+        tui.refresh();
+        tui
+    }
+
+    /// Run `source` in the session, adding what it does to the log.
+    pub fn execute(&mut self, source: &str) {
+        self.called.lock().unwrap().clear();
+        self.crawl.execute(source);
+        self.refresh();
+    }
+
+    pub fn execute_file(&mut self, path: impl AsRef<Path>) {
+        self.called.lock().unwrap().clear();
+        if let Err(error) = self.crawl.execute_file(&path) {
+            let path = path.as_ref().display();
+            self.log
+                .push(Line::from(format!("error: failed to read {path} ({error})")).red());
+        }
+        self.refresh();
+    }
+
+    /// Take over the terminal, running the script at `path` and then whatever's typed in, until
+    /// Esc or Ctrl-C.
+    pub fn run(mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut terminal = ratatui::init();
+        self.execute_file(path);
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(());
+                }
+                KeyCode::Enter if !self.input.trim().is_empty() => {
+                    let input = mem::take(&mut self.input);
+                    self.log.push(Line::from(format!(">> {input}")).dim());
+                    self.execute(&format!("{input}\n"));
+                    // A popup may have drawn over the screen.
+                    terminal.clear()?;
+                }
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::PageUp => {
+                    self.scroll = (self.scroll + PAGE).min(self.log.len().saturating_sub(1));
+                }
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(PAGE),
+                KeyCode::Char(ch) => self.input.push(ch),
+                _ => {}
+            }
+        }
+    }
+
+    // Pick up the output and state left by the last thing run.
+    fn refresh(&mut self) {
+        for output in self.output.take() {
+            match output {
+                Output::Record(record) => self.log.extend(
+                    render_record(&record, false, &Locale::default())
+                        .lines()
+                        .map(|line| Line::from(line.to_string())),
+                ),
+                Output::Reminder(reminder) => {
+                    self.log
+                        .push(Line::from(format!("! {reminder}")).yellow().bold());
+                }
+                Output::Warning(warning) => {
+                    self.log
+                        .push(Line::from(format!("warning: {warning}")).yellow());
+                }
+                Output::Error(error) => self.log.push(Line::from(format!("error: {error}")).red()),
+            }
+        }
+        self.scroll = 0;
+
+        let snapshot = self.crawl.snapshot();
+        self.procedures = snapshot.procedures.into_keys().collect();
+        self.procedures.sort();
+        self.facts = snapshot
+            .local_facts
+            .iter()
+            .map(ToString::to_string)
+            .chain(
+                snapshot
+                    .persistent_facts
+                    .iter()
+                    .map(|fact| format!("{fact} (persistent)")),
+            )
+            .collect();
+        self.facts.sort();
+        self.tables = self.crawl.table_names().map(String::from).collect();
+        self.tables.sort();
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, input] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(frame.area());
+        let [procedures, log, state] = Layout::horizontal([
+            Constraint::Percentage(20),
+            Constraint::Percentage(50),
+            Constraint::Percentage(30),
+        ])
+        .areas(main);
+        let [facts, tables] =
+            Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(state);
+
+        let called = self.called.lock().unwrap();
+        let items = self.procedures.iter().map(|procedure| {
+            if called.contains(procedure) {
+                ListItem::new(format!("> {procedure}")).bold()
+            } else {
+                ListItem::new(format!("  {procedure}"))
+            }
+        });
+        frame.render_widget(
+            List::new(items).block(Block::bordered().title(" Procedures ")),
+            procedures,
+        );
+
+        // The end of the log, or as far up it as it's scrolled
+        let height = log.height.saturating_sub(2) as usize;
+        let end = self.log.len() - self.scroll.min(self.log.len());
+        let start = end.saturating_sub(height);
+        frame.render_widget(
+            Paragraph::new(self.log[start..end].to_vec()).block(Block::bordered().title(" Log ")),
+            log,
+        );
+
+        frame.render_widget(list(&self.facts, " Facts "), facts);
+        frame.render_widget(list(&self.tables, " Tables "), tables);
+
+        frame.render_widget(
+            Paragraph::new(format!(">> {}", self.input)).block(
+                Block::bordered()
+                    .title(" Run a statement (Enter), scroll (PgUp/PgDn), quit (Esc) "),
+            ),
+            input,
+        );
+        let cursor = input.x + 4 + self.input.chars().count() as u16;
+        frame.set_cursor_position((cursor.min(input.right().saturating_sub(2)), input.y + 1));
+    }
+}
+
+fn list<'a>(items: &'a [String], title: &'a str) -> List<'a> {
+    List::new(items.iter().map(String::as_str)).block(Block::bordered().title(title))
+}
+
+/// Asks `choose` questions in a popup, picking options with the arrow keys and Enter, or by
+/// number.
+#[derive(Debug, Default)]
+pub struct PopupPrompt;
+
+impl PopupPrompt {
+    fn ask(&mut self, question: &str, options: &[String]) -> io::Result<Option<usize>> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        let mut state = ListState::default().with_selected(Some(0));
+        loop {
+            terminal.draw(|frame| draw_popup(frame, question, options, &mut state))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some(chosen) = choice(key, options.len(), &mut state) {
+                return Ok(chosen);
+            }
+        }
+    }
+}
+
+impl Prompt for PopupPrompt {
+    fn choose(&mut self, question: &str, options: &[String]) -> Result<usize, CrawlError> {
+        match self.ask(question, options) {
+            Ok(Some(idx)) => Ok(idx),
+            Ok(None) => Err(CrawlError::InterpreterError {
+                reason: format!("no answer to {question:?}"),
+            }),
+            Err(error) => Err(CrawlError::InterpreterError {
+                reason: format!("failed to ask {question:?} ({error})"),
+            }),
+        }
+    }
+}
+
+// What a key press picks: an option, nothing if it's Esc, or `None` to keep asking.
+fn choice(key: KeyEvent, options: usize, state: &mut ListState) -> Option<Option<usize>> {
+    match key.code {
+        KeyCode::Up => state.select_previous(),
+        KeyCode::Down => state.select(
+            state
+                .selected()
+                .map(|idx| (idx + 1).min(options.saturating_sub(1))),
+        ),
+        KeyCode::Enter => return Some(state.selected()),
+        KeyCode::Esc => return Some(None),
+        KeyCode::Char(ch) => {
+            let idx = ch.to_digit(10)?.checked_sub(1)? as usize;
+            return (idx < options).then_some(Some(idx));
+        }
+        _ => {}
+    }
+    None
+}
+
+fn draw_popup(frame: &mut Frame, question: &str, options: &[String], state: &mut ListState) {
+    let height = options.len() as u16 + 2;
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::horizontal([Constraint::Percentage(50)])
+        .flex(Flex::Center)
+        .areas(area);
+    let items = options
+        .iter()
+        .enumerate()
+        .map(|(idx, option)| format!("{}. {option}", idx + 1));
+    let list = List::new(items)
+        .block(Block::bordered().title(format!(" {question} ")))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, state);
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    fn screen(tui: &Tui) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 16)).unwrap();
+        terminal.draw(|frame| tui.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    }
+
+    #[test]
+    fn panes() {
+        let mut tui = Tui::new(Crawl::new().with_seed(0));
+        tui.execute(
+            "load table \"examples/table.csv\"\nprocedure camp\n\treminder \"eat rations\"\n\tset-fact \"party is rested\"\nend\n",
+        );
+        tui.execute("camp\n");
+        let screen = screen(&tui);
+        assert!(screen.contains("> camp"));
+        assert!(screen.contains("! eat rations"));
+        assert!(screen.contains("party is rested"));
+        assert!(screen.contains("examples/table.csv"));
+    }
+
+    #[test]
+    fn scrolling() {
+        let mut tui = Tui::new(Crawl::new());
+        for idx in 0..30 {
+            tui.execute(&format!("reminder \"line {idx}\"\n"));
+        }
+        assert!(screen(&tui).contains("line 29"));
+        tui.scroll = 20;
+        let screen = screen(&tui);
+        assert!(screen.contains("line 9"));
+        assert!(!screen.contains("line 29"));
+    }
+
+    #[test]
+    fn popup_choice() {
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut state = ListState::default().with_selected(Some(0));
+        assert_eq!(choice(press(KeyCode::Down), 2, &mut state), None);
+        assert_eq!(choice(press(KeyCode::Down), 2, &mut state), None);
+        assert_eq!(choice(press(KeyCode::Enter), 2, &mut state), Some(Some(1)));
+        assert_eq!(
+            choice(press(KeyCode::Char('1')), 2, &mut state),
+            Some(Some(0))
+        );
+        assert_eq!(choice(press(KeyCode::Char('3')), 2, &mut state), None);
+        assert_eq!(choice(press(KeyCode::Esc), 2, &mut state), Some(None));
+    }
+}
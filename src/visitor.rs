@@ -0,0 +1,275 @@
+use crate::parser::{Amount, Antecedent, CrawlStr, ModifiedRollSpecifier, ReturnValue, Statement};
+
+// Walks parsed scripts, for tools like linters and doc generators. Implement the `visit_`
+// methods for what's of interest, calling the matching `walk_` function to carry on into what's
+// nested in it. The defaults only walk, so a visitor that implements nothing visits everything.
+
+pub trait Visitor {
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_antecedent(&mut self, antecedent: &Antecedent) {
+        walk_antecedent(self, antecedent);
+    }
+
+    fn visit_amount(&mut self, amount: &Amount) {
+        walk_amount(self, amount);
+    }
+}
+
+/// Visit everything nested in `statement`: consequents, bodies, antecedents, amounts, and the
+/// expressions interpolated into strings.
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::AdvanceClock { amount, .. }
+        | Statement::AdvanceDate { amount, .. }
+        | Statement::AdvanceTime(amount)
+        | Statement::AwardXp { amount, .. }
+        | Statement::AwardTreasure { amount, .. }
+        | Statement::Let { value: amount, .. }
+        | Statement::SpendResource { amount, .. }
+        | Statement::AddMoney { amount, .. }
+        | Statement::SpendMoney { amount, .. }
+        | Statement::TrackHitPoints { amount, .. }
+        | Statement::Damage { amount, .. }
+        | Statement::HearRumors(amount)
+        | Statement::Downtime { weeks: amount, .. } => visitor.visit_amount(amount),
+        Statement::Assert(antecedent) => visitor.visit_antecedent(antecedent),
+        Statement::AssertEqual { actual, expected } => {
+            walk_value(visitor, actual);
+            walk_value(visitor, expected);
+        }
+        Statement::Choice { arms, .. } => {
+            for arm in arms {
+                visitor.visit_statement(&arm.consequent);
+            }
+        }
+        Statement::IfThen {
+            antecedent,
+            consequent,
+        } => {
+            visitor.visit_antecedent(antecedent);
+            visitor.visit_statement(consequent);
+        }
+        Statement::DefineRoll { roll, .. }
+        | Statement::AddCombatant {
+            roll_specifier: roll,
+            ..
+        } => walk_roll(visitor, roll),
+        Statement::MatchingRoll {
+            roll_specifier,
+            arms,
+            otherwise,
+        }
+        | Statement::Move {
+            roll_specifier,
+            arms,
+            otherwise,
+            ..
+        } => {
+            walk_roll(visitor, roll_specifier);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    visitor.visit_antecedent(guard);
+                }
+                visitor.visit_statement(&arm.consequent);
+            }
+            if let Some(otherwise) = otherwise {
+                visitor.visit_statement(otherwise);
+            }
+        }
+        Statement::Check {
+            roll_specifier,
+            dc,
+            arms,
+            otherwise,
+        } => {
+            walk_roll(visitor, roll_specifier);
+            walk_amount(visitor, dc);
+            for arm in arms {
+                visitor.visit_statement(&arm.consequent);
+            }
+            if let Some(otherwise) = otherwise {
+                visitor.visit_statement(otherwise);
+            }
+        }
+        Statement::Save {
+            arms, otherwise, ..
+        } => {
+            for arm in arms {
+                visitor.visit_statement(&arm.consequent);
+            }
+            if let Some(otherwise) = otherwise {
+                visitor.visit_statement(otherwise);
+            }
+        }
+        Statement::MatchingFact {
+            arms, otherwise, ..
+        } => {
+            for arm in arms {
+                visitor.visit_statement(&arm.consequent);
+            }
+            if let Some(otherwise) = otherwise {
+                visitor.visit_statement(otherwise);
+            }
+        }
+        Statement::MatchingTableRoll { arms, .. } => {
+            for arm in arms {
+                visitor.visit_statement(&arm.consequent);
+            }
+        }
+        Statement::NontargetedRoll(roll_specifier) => walk_roll(visitor, roll_specifier),
+        Statement::Procedure { body, .. } => {
+            for statement in body.iter() {
+                visitor.visit_statement(statement);
+            }
+        }
+        Statement::Return(value) => walk_value(visitor, value),
+        Statement::Rule(rule) => {
+            for condition in &rule.conditions {
+                visitor.visit_antecedent(condition);
+            }
+        }
+        Statement::SetFact(fact) => walk_str(visitor, fact),
+        Statement::WithFacts(body) => {
+            for statement in body {
+                visitor.visit_statement(statement);
+            }
+        }
+        Statement::AddItem { .. }
+        | Statement::AdvanceTurn
+        | Statement::AdvanceWeather(_)
+        | Statement::ApplyModifier { .. }
+        | Statement::Campaign(_)
+        | Statement::ClearFact(_)
+        | Statement::ClearPersistentFact(_)
+        | Statement::CreateClock { .. }
+        | Statement::CreateFront { .. }
+        | Statement::AdvanceFronts(_)
+        | Statement::AddRumor { .. }
+        | Statement::RevealRumor { .. }
+        | Statement::ReportRumors
+        | Statement::CurrentDate
+        | Statement::ChaosFactor
+        | Statement::AdjustChaos(_)
+        | Statement::SceneCheck
+        | Statement::PinTable { .. }
+        | Statement::Generator { .. }
+        | Statement::Include { .. }
+        | Statement::LastRoll
+        | Statement::LoadTable { .. }
+        | Statement::LoadNames(_)
+        | Statement::LoadStatblocks(_)
+        | Statement::NameRoll(_)
+        | Statement::Oracle { .. }
+        | Statement::ProcedureCall { .. }
+        | Statement::Reminder(_)
+        | Statement::RemoveCombatant(_)
+        | Statement::RemoveItem { .. }
+        | Statement::RemoveModifier(_)
+        | Statement::ReportFacts(_)
+        | Statement::ReportInventory(_)
+        | Statement::SetPersistentFact(_)
+        | Statement::SortCombatants
+        | Statement::Travel(_)
+        | Statement::MoraleCheck { .. }
+        | Statement::Light(_)
+        | Statement::ReportXp
+        | Statement::Encounter(_)
+        | Statement::GenerateRoom
+        | Statement::LoadPrices(_)
+        | Statement::Price { .. }
+        | Statement::Haggle { .. }
+        | Statement::TableRoll { .. }
+        | Statement::TrackResource { .. }
+        | Statement::UsageDie { .. }
+        | Statement::UsageRoll(_) => {}
+    }
+}
+
+/// Visit the conditions on a dice roll's modifiers.
+pub fn walk_antecedent<V: Visitor + ?Sized>(visitor: &mut V, antecedent: &Antecedent) {
+    match antecedent {
+        Antecedent::DiceRoll { roll_specifier, .. }
+        | Antecedent::NaturalRoll { roll_specifier, .. } => walk_roll(visitor, roll_specifier),
+        Antecedent::DegreeOfSuccess {
+            roll_specifier, dc, ..
+        } => {
+            walk_roll(visitor, roll_specifier);
+            walk_amount(visitor, dc);
+        }
+        _ => {}
+    }
+}
+
+/// Visit the conditions on a roll's modifiers, if the amount is rolled.
+pub fn walk_amount<V: Visitor + ?Sized>(visitor: &mut V, amount: &Amount) {
+    if let Amount::Roll(roll_specifier) = amount {
+        walk_roll(visitor, roll_specifier);
+    }
+}
+
+fn walk_roll<V: Visitor + ?Sized>(visitor: &mut V, roll_specifier: &ModifiedRollSpecifier) {
+    for term in &roll_specifier.terms {
+        if let Some(condition) = &term.condition {
+            visitor.visit_antecedent(condition);
+        }
+    }
+}
+
+fn walk_value<V: Visitor + ?Sized>(visitor: &mut V, value: &ReturnValue) {
+    match value {
+        ReturnValue::Amount(amount) => visitor.visit_amount(amount),
+        ReturnValue::Str(s) => walk_str(visitor, s),
+    }
+}
+
+fn walk_str<V: Visitor + ?Sized>(visitor: &mut V, s: &CrawlStr) {
+    if let CrawlStr::InterpolatedStr { expressions, .. } = s {
+        for expression in expressions {
+            visitor.visit_statement(expression);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    // Every fact a script checks, wherever the check is.
+    #[derive(Default)]
+    struct FactChecks(Vec<String>);
+
+    impl Visitor for FactChecks {
+        fn visit_antecedent(&mut self, antecedent: &Antecedent) {
+            if let Antecedent::CheckFact(fact) = antecedent {
+                self.0.push(fact.clone());
+            }
+            walk_antecedent(self, antecedent);
+        }
+    }
+
+    #[test]
+    fn walk() {
+        let source = "procedure camp\n\tif fact? \"party is lost\" => reminder \"forage\"\n\troll 1d6 + 1 if fact? \"party is rested\"\n\t\t6 if fact? \"weather is clear\" => reminder \"stars\"\n\tend\nend\nrule: if fact? \"weather is storm\" => derive-fact \"travel is slowed\"\n";
+        let ast: Vec<Statement> = Parser::from_tokens(Scanner::new(source))
+            .map(Result::unwrap)
+            .collect();
+        let mut checks = FactChecks::default();
+        for statement in &ast {
+            checks.visit_statement(statement);
+        }
+        assert_eq!(
+            checks.0,
+            vec![
+                "party is lost",
+                "party is rested",
+                "weather is clear",
+                "weather is storm"
+            ]
+        );
+    }
+}
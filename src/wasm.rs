@@ -0,0 +1,95 @@
+//! JavaScript bindings, so crawl scripts can be run from a browser.
+//!
+//! ```js
+//! const crawl = new Crawl(
+//!     (key) => localStorage.getItem(key),
+//!     (key, value) => localStorage.setItem(key, value),
+//! );
+//! const output = JSON.parse(crawl.execute('set-persistent-fact "party is lost"'));
+//! ```
+
+use js_sys::Function;
+use send_wrapper::SendWrapper;
+use wasm_bindgen::prelude::*;
+
+use crate::error::CrawlError;
+use crate::interpreter::StateSnapshot;
+use crate::lang::Crawl;
+use crate::output::BufferSink;
+use crate::storage::Storage;
+
+/// Storage backed by a pair of JavaScript callbacks: `read(key)` returning a string, or
+/// `null`/`undefined` if there's nothing stored, and `write(key, value)`. JavaScript functions
+/// can't be sent between threads, so using them from any thread but the one that made the storage
+/// panics.
+struct JsStorage {
+    read: SendWrapper<Function>,
+    write: SendWrapper<Function>,
+}
+
+impl Storage for JsStorage {
+    fn read(&mut self, key: &str) -> Result<Option<String>, CrawlError> {
+        let value = self
+            .read
+            .call1(&JsValue::NULL, &JsValue::from_str(key))
+            .map_err(|error| storage_error(key, error))?;
+        Ok(value.as_string())
+    }
+
+    fn write(&mut self, key: &str, value: &str) -> Result<(), CrawlError> {
+        self.write
+            .call2(
+                &JsValue::NULL,
+                &JsValue::from_str(key),
+                &JsValue::from_str(value),
+            )
+            .map_err(|error| storage_error(key, error))?;
+        Ok(())
+    }
+}
+
+fn storage_error(key: &str, error: JsValue) -> CrawlError {
+    CrawlError::InterpreterError {
+        reason: format!("storage failed for {key} ({error:?})"),
+    }
+}
+
+#[wasm_bindgen(js_name = Crawl)]
+pub struct WasmCrawl {
+    crawl: Crawl,
+    output: BufferSink,
+}
+
+#[wasm_bindgen(js_class = Crawl)]
+impl WasmCrawl {
+    /// Tables and persistent facts are read and written through `read` and `write`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(read: Function, write: Function) -> WasmCrawl {
+        let output = BufferSink::new();
+        let crawl = Crawl::new()
+            .with_output(Box::new(output.clone()))
+            .with_storage(Box::new(JsStorage {
+                read: SendWrapper::new(read),
+                write: SendWrapper::new(write),
+            }));
+        WasmCrawl { crawl, output }
+    }
+
+    /// Run `source`, returning everything it output as a JSON array of
+    /// `{"kind": "record" | "reminder" | "warning" | "error", "value": ...}` objects.
+    pub fn execute(&mut self, source: &str) -> Result<String, JsError> {
+        self.crawl.execute(source);
+        Ok(serde_json::to_string(&self.output.take())?)
+    }
+
+    /// The session's facts, tables, and procedures as JSON, to pass back to `restore`.
+    pub fn snapshot(&self) -> Result<String, JsError> {
+        Ok(self.crawl.snapshot().to_json()?)
+    }
+
+    pub fn restore(&mut self, json: &str) -> Result<(), JsError> {
+        let snapshot = StateSnapshot::from_json(json)?;
+        self.crawl.restore(snapshot);
+        Ok(())
+    }
+}
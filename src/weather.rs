@@ -0,0 +1,49 @@
+use crate::tables::Table;
+
+// Weather that follows from the day before's. A weather table's header row names the weather
+// each column follows from, and each column is rolled on to find what the weather turns to:
+//
+//     roll,clear,overcast,rain
+//     1-3,clear,overcast,rain
+//     4-5,overcast,rain,overcast
+//     6,rain,clear,clear
+//
+// Today's weather is kept as a persistent fact, like `weather is rain`. Before there's any, it's
+// the first column's.
+
+/// The table `advance weather` rolls on, unless it's given another.
+pub const WEATHER_TABLE: &str = "weather";
+/// The entity and attribute of the persistent fact the weather is kept in.
+pub const WEATHER_FACT: &str = "weather is";
+
+/// The weather each of `table`'s columns follows from, in order, or nothing if it has no header
+/// row.
+pub fn states(table: &Table) -> Vec<&str> {
+    table
+        .entries()
+        .first()
+        .map(|entry| entry.fields.iter().map(|(name, _)| name.as_str()).collect())
+        .unwrap_or_default()
+}
+
+/// The persistent fact kept for `weather`.
+pub fn fact(weather: &str) -> String {
+    format!("{WEATHER_FACT} {weather}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weather_states() {
+        let table =
+            Table::from_reader("roll,clear,rain\n1-4,clear,rain\n5-6,rain,clear\n".as_bytes())
+                .unwrap();
+        assert_eq!(states(&table), ["clear", "rain"]);
+        assert_eq!(fact("rain"), "weather is rain");
+
+        let table = Table::from_reader("1-4,clear\n5-6,rain\n".as_bytes()).unwrap();
+        assert!(states(&table).is_empty());
+    }
+}